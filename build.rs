@@ -0,0 +1,17 @@
+fn main() {
+    #[cfg(feature = "grpc-gateway")]
+    {
+        if std::env::var_os("PROTOC").is_none() {
+            std::env::set_var(
+                "PROTOC",
+                protoc_bin_vendored::protoc_bin_path().expect("no vendored protoc"),
+            );
+        }
+        println!("cargo:rerun-if-changed=proto/gateway.proto");
+        // Clients are generated by the consuming services
+        tonic_prost_build::configure()
+            .build_client(false)
+            .compile_protos(&["proto/gateway.proto"], &["proto"])
+            .expect("failed to compile protos");
+    }
+}
@@ -11,5 +11,5 @@ async fn main() {
     println!("Account:");
     println!("{:#?}", api.request(GetAccount {}).await.unwrap());
     println!("Positions:");
-    println!("{:#?}", api.request(GetPositions {}).await.unwrap());
+    println!("{:#?}", api.request(GetPositions::new()).await.unwrap());
 }
@@ -0,0 +1,48 @@
+/*
+ * C interface of the ftx crate, built with
+ * `cargo rustc --release --lib --features ffi --crate-type cdylib`.
+ *
+ * Responses are JSON objects of the form {"success": true, "result": ...}
+ * or {"success": false, "error": "..."} and must be released with
+ * ftx_string_free.
+ */
+
+#ifndef FTX_H
+#define FTX_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct FtxClient FtxClient;
+typedef struct FtxSubscription FtxSubscription;
+
+typedef void (*FtxWsCallback)(void *user_data, const char *market, const char *data);
+typedef void (*FtxWsErrorCallback)(void *user_data, const char *error);
+
+/* key, secret and subaccount may be NULL for unauthenticated access. Returns
+ * NULL if the runtime can't be started or the credentials are invalid. */
+FtxClient *ftx_client_new(const char *key, const char *secret, const char *subaccount, bool us);
+void ftx_client_free(FtxClient *client);
+
+/* method is "GET", "POST" or "DELETE"; params is a JSON object or NULL. */
+char *ftx_request(const FtxClient *client, const char *method, const char *path, const char *params);
+char *ftx_place_order(const FtxClient *client, const char *order);
+char *ftx_cancel_order(const FtxClient *client, uint64_t id);
+void ftx_string_free(char *s);
+
+/* channels is a JSON array, e.g. ["trades:BTC-PERP", "orderbook:BTC-PERP", "fills"].
+ * Callbacks run on a thread of the subscription and may call ftx_request. */
+FtxSubscription *ftx_ws_subscribe(const FtxClient *client, const char *channels,
+                                  FtxWsCallback callback, FtxWsErrorCallback on_error,
+                                  void *user_data);
+void ftx_ws_free(FtxSubscription *subscription);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
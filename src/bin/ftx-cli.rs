@@ -0,0 +1,309 @@
+//! Command line client for operating an FTX account, built with the `cli`
+//! feature. Credentials are read from `API_KEY`, `API_SECRET` and the
+//! optional `SUBACCOUNT` environment variables or a `.env` file.
+
+use chrono::{Duration, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use dotenvy::dotenv;
+use ftx::{
+    options::Options,
+    rest::{
+        CancelAllOrder, CancelOrder, GetOpenOrders, GetPositions, GetWalletBalances, Id, OrderType,
+        PlaceOrder, Rest, Side, TimeInForce,
+    },
+    ws::{Channel, Data, Orderbook, Trade, Ws},
+};
+use futures::stream::StreamExt;
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+use std::env::var;
+use std::error::Error;
+use std::fmt::Write;
+use std::time::{Duration as StdDuration, Instant};
+
+/// Minimum time between two redraws of the ladder view.
+const REDRAW_INTERVAL: StdDuration = StdDuration::from_millis(100);
+
+#[derive(Parser)]
+#[command(name = "ftx-cli", version, about = "Command line client for FTX")]
+struct Cli {
+    /// Use ftx.us instead of ftx.com
+    #[arg(long, global = true)]
+    us: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum CliSide {
+    Buy,
+    Sell,
+}
+
+impl From<CliSide> for Side {
+    fn from(side: CliSide) -> Self {
+        match side {
+            CliSide::Buy => Side::Buy,
+            CliSide::Sell => Side::Sell,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Show wallet balances
+    Balances,
+    /// Show open positions
+    Positions,
+    /// List open orders
+    Orders {
+        #[arg(long)]
+        market: Option<String>,
+    },
+    /// Place a limit order, or a market order if no price is given
+    Place {
+        market: String,
+        #[arg(value_enum)]
+        side: CliSide,
+        size: Decimal,
+        #[arg(long)]
+        price: Option<Decimal>,
+        #[arg(long)]
+        post_only: bool,
+        #[arg(long)]
+        reduce_only: bool,
+        #[arg(long)]
+        client_id: Option<String>,
+    },
+    /// Cancel an order by ID
+    Cancel { id: Id },
+    /// Cancel all orders, optionally of a single market
+    CancelAll {
+        #[arg(long)]
+        market: Option<String>,
+    },
+    /// Stream trades and the top of the orderbook of a market
+    Stream { market: String },
+    /// Show a live depth ladder and trade tape of a market
+    Ladder {
+        market: String,
+        /// Number of price levels shown per side
+        #[arg(long, default_value_t = 10)]
+        depth: usize,
+        /// Number of trades shown in the tape
+        #[arg(long, default_value_t = 15)]
+        trades: usize,
+    },
+    /// Download the funding rate history of a perpetual future
+    FundingHistory {
+        future: String,
+        #[arg(long, default_value_t = 7)]
+        days: i64,
+    },
+    /// Download all fills of an order
+    OrderFills { market: String, order_id: Id },
+}
+
+fn options(us: bool) -> Options {
+    let options = if us {
+        Options::us()
+    } else {
+        Options::default()
+    };
+    match (var("API_KEY"), var("API_SECRET")) {
+        (Ok(key), Ok(secret)) => options
+            .authenticate(key, secret)
+            .subaccount_optional(var("SUBACCOUNT").ok()),
+        _ => options,
+    }
+}
+
+async fn stream(options: Options, market: String) -> Result<(), Box<dyn Error>> {
+    let mut websocket = Ws::connect(options).await?;
+    let mut orderbook = Orderbook::new(market.clone());
+    websocket
+        .subscribe(&[
+            Channel::Trades(market.clone()),
+            Channel::Orderbook(market.clone()),
+        ])
+        .await?;
+
+    while let Some(data) = websocket.next().await {
+        match data? {
+            (_, Data::Trade(trade)) => println!(
+                "{} {:?} {} at {}",
+                trade.time, trade.side, trade.size, trade.price
+            ),
+            (_, Data::OrderbookData(orderbook_data)) => {
+                orderbook.update(&orderbook_data)?;
+                if let Some(((bid, bid_size), (ask, ask_size))) = orderbook.best_bid_and_ask() {
+                    println!("{} x {} | {} x {}", bid_size, bid, ask, ask_size);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn render(market: &str, orderbook: &Orderbook, tape: &VecDeque<Trade>, depth: usize) -> String {
+    // Clear the screen and move the cursor to the top left
+    let mut screen = String::from("\x1b[2J\x1b[H");
+    let _ = writeln!(screen, "{}\n", market);
+    let _ = writeln!(
+        screen,
+        "{:>16} {:>16} {:>16}",
+        "bid size", "price", "ask size"
+    );
+    for (price, size) in orderbook.asks.iter().take(depth).rev() {
+        let _ = writeln!(screen, "{:>16} {:>16} {:>16}", "", price, size);
+    }
+    let _ = writeln!(
+        screen,
+        "{:>16} {:>16} {:>16}",
+        "",
+        orderbook
+            .mid_price()
+            .map_or_else(|| "-".to_owned(), |mid| format!("({})", mid)),
+        ""
+    );
+    for (price, size) in orderbook.bids.iter().rev().take(depth) {
+        let _ = writeln!(screen, "{:>16} {:>16} {:>16}", size, price, "");
+    }
+
+    let _ = writeln!(screen, "\n{:>16} {:>16} {:>16}", "time", "price", "size");
+    for trade in tape {
+        let _ = writeln!(
+            screen,
+            "{:>16} {:>16} {:>16} {:?}{}",
+            trade.time.format("%H:%M:%S%.3f"),
+            trade.price,
+            trade.size,
+            trade.side,
+            if trade.liquidation {
+                " (liquidation)"
+            } else {
+                ""
+            }
+        );
+    }
+    screen
+}
+
+async fn ladder(
+    options: Options,
+    market: String,
+    depth: usize,
+    trades: usize,
+) -> Result<(), Box<dyn Error>> {
+    let mut websocket = Ws::connect(options).await?;
+    let mut orderbook = Orderbook::new(market.clone());
+    let mut tape = VecDeque::with_capacity(trades);
+    let mut last_redraw = Instant::now();
+    websocket
+        .subscribe(&[
+            Channel::Trades(market.clone()),
+            Channel::Orderbook(market.clone()),
+        ])
+        .await?;
+
+    while let Some(data) = websocket.next().await {
+        match data? {
+            (_, Data::Trade(trade)) => {
+                tape.push_front(trade);
+                tape.truncate(trades);
+            }
+            (_, Data::OrderbookData(orderbook_data)) => orderbook.update(&orderbook_data)?,
+            _ => {}
+        }
+
+        if last_redraw.elapsed() >= REDRAW_INTERVAL {
+            print!("{}", render(&market, &orderbook, &tape, depth));
+            last_redraw = Instant::now();
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    dotenv().ok();
+    let cli = Cli::parse();
+    let options = options(cli.us);
+    let rest = Rest::new(options.clone());
+
+    match cli.command {
+        Command::Balances => println!("{:#?}", rest.request(GetWalletBalances {}).await?),
+        Command::Positions => println!("{:#?}", rest.request(GetPositions::new()).await?),
+        Command::Orders { market } => println!(
+            "{:#?}",
+            rest.request(GetOpenOrders {
+                market: market.as_deref()
+            })
+            .await?
+        ),
+        Command::Place {
+            market,
+            side,
+            size,
+            price,
+            post_only,
+            reduce_only,
+            client_id,
+        } => {
+            let order = rest
+                .request(PlaceOrder {
+                    market: &market,
+                    side: side.into(),
+                    price,
+                    r#type: if price.is_some() {
+                        OrderType::Limit
+                    } else {
+                        OrderType::Market
+                    },
+                    size,
+                    reduce_only,
+                    time_in_force: if post_only {
+                        TimeInForce::PostOnly
+                    } else {
+                        TimeInForce::Gtc
+                    },
+                    client_id: client_id.as_deref(),
+                    ..Default::default()
+                })
+                .await?;
+            println!("{:#?}", order);
+        }
+        Command::Cancel { id } => println!("{}", rest.request(CancelOrder::new(id)).await?),
+        Command::CancelAll { market } => println!(
+            "{}",
+            rest.request(CancelAllOrder {
+                market: market.as_deref(),
+                ..Default::default()
+            })
+            .await?
+        ),
+        Command::Stream { market } => stream(options, market).await?,
+        Command::Ladder {
+            market,
+            depth,
+            trades,
+        } => ladder(options, market, depth, trades).await?,
+        Command::FundingHistory { future, days } => {
+            let end_time = Utc::now();
+            let rates = rest
+                .get_funding_rate_history(&future, end_time - Duration::days(days), end_time)
+                .await?;
+            for rate in rates {
+                println!("{} {}", rate.time, rate.rate);
+            }
+        }
+        Command::OrderFills { market, order_id } => {
+            println!("{:#?}", rest.get_order_fills(&market, order_id).await?)
+        }
+    }
+
+    Ok(())
+}
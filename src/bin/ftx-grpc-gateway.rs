@@ -0,0 +1,20 @@
+//! gRPC gateway to a single FTX account, built with the `grpc-gateway`
+//! feature. Credentials are read from `API_KEY`, `API_SECRET` and the
+//! optional `SUBACCOUNT` environment variables or a `.env` file; the listen
+//! address from `GATEWAY_ADDR` (default `127.0.0.1:50051`).
+
+use dotenvy::dotenv;
+use ftx::{grpc::Gateway, options::Options};
+use std::env::var;
+use std::error::Error;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    dotenv().ok();
+    let addr = var("GATEWAY_ADDR")
+        .unwrap_or_else(|_| "127.0.0.1:50051".to_owned())
+        .parse()?;
+
+    Gateway::new(Options::from_env()).serve(addr).await?;
+    Ok(())
+}
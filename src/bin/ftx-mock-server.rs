@@ -0,0 +1,27 @@
+//! Mock FTX server for integration tests of other languages or processes,
+//! built with the `mock-server` feature. Serves the REST API on
+//! `MOCK_REST_ADDR` (default `127.0.0.1:8081`) and the websocket API on
+//! `MOCK_WS_ADDR` (default `127.0.0.1:8082`), accepting requests signed with
+//! `MOCK_API_KEY` and `MOCK_API_SECRET` (default `key` and `secret`).
+
+use dotenvy::dotenv;
+use ftx::mock::MockServer;
+use std::env::var;
+use std::error::Error;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    dotenv().ok();
+    let env = |name: &str, default: &str| var(name).unwrap_or_else(|_| default.to_owned());
+    let rest_addr = env("MOCK_REST_ADDR", "127.0.0.1:8081").parse()?;
+    let ws_addr = env("MOCK_WS_ADDR", "127.0.0.1:8082").parse()?;
+
+    let handle = MockServer::new(env("MOCK_API_KEY", "key"), env("MOCK_API_SECRET", "secret"))
+        .with_default_markets()
+        .serve(rest_addr, ws_addr)
+        .await?;
+    println!("REST: http://{}/api", handle.rest_addr);
+    println!("Websocket: ws://{}/ws", handle.ws_addr);
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
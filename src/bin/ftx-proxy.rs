@@ -0,0 +1,34 @@
+//! Local HTTP proxy to a single FTX account, built with the `proxy`
+//! feature. Credentials are read from `API_KEY`, `API_SECRET` and the
+//! optional `SUBACCOUNT` environment variables or a `.env` file; the listen
+//! address from `PROXY_ADDR` (default `127.0.0.1:8080`) and an optional
+//! client token from `PROXY_TOKEN`. If `PROXY_CONFIG` names a TOML file
+//! (see `ftx::config`), client options and the risk limits applied to
+//! orders are read from it instead.
+
+use dotenvy::dotenv;
+use ftx::{config::Config, options::Options, proxy::Proxy, rest::RiskLimits};
+use std::env::var;
+use std::error::Error;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    dotenv().ok();
+    let addr = var("PROXY_ADDR")
+        .unwrap_or_else(|_| "127.0.0.1:8080".to_owned())
+        .parse()?;
+
+    let (options, risk_limits) = match var("PROXY_CONFIG") {
+        Ok(path) => {
+            let config = Config::load(path)?;
+            (config.options()?, config.risk_limits())
+        }
+        Err(_) => (Options::from_env(), RiskLimits::default()),
+    };
+    let mut proxy = Proxy::new(options).risk_limits(risk_limits);
+    if let Ok(token) = var("PROXY_TOKEN") {
+        proxy = proxy.token(token);
+    }
+    proxy.serve(addr).await?;
+    Ok(())
+}
@@ -0,0 +1,96 @@
+//! Blocking REST client, built with the `blocking` feature, for scripts,
+//! backtests and tools which do not run a tokio runtime.
+//!
+//! ```no_run
+//! use ftx::{blocking::Rest, options::Options, rest::GetMarkets};
+//!
+//! let rest = Rest::new(Options::from_env());
+//! let markets = rest.request(GetMarkets {}).unwrap();
+//! ```
+
+use crate::options::Options;
+use crate::rest::{self, Request, Result, SuccessResponse, Transport};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::runtime::{Builder, Runtime};
+
+/// `rest::Rest` running its requests on a runtime of its own.
+///
+/// Must not be used from async code, where blocking on a request panics.
+#[derive(Clone, Debug)]
+pub struct Rest {
+    rest: rest::Rest,
+    runtime: Arc<Runtime>,
+}
+
+impl Rest {
+    /// # Panics
+    ///
+    /// If the runtime cannot be started.
+    pub fn new(options: Options) -> Self {
+        Self::from_async(rest::Rest::new(options))
+    }
+
+    /// Blocking client sending its requests with `rest`.
+    ///
+    /// # Panics
+    ///
+    /// If the runtime cannot be started.
+    pub fn from_async(rest: rest::Rest) -> Self {
+        // A worker thread keeps background tasks such as clock
+        // synchronization running between requests
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("ftx-blocking")
+            .enable_all()
+            .build()
+            .expect("failed to start the runtime of the blocking client");
+        Self {
+            rest,
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    /// See `rest::Rest::with_transport`.
+    #[must_use]
+    pub fn with_transport(self, transport: impl Transport + 'static) -> Self {
+        Self {
+            rest: self.rest.with_transport(transport),
+            ..self
+        }
+    }
+
+    /// See `rest::Rest::for_subaccount`. Shares the runtime.
+    pub fn for_subaccount(&self, nickname: impl Into<String>) -> Self {
+        Self {
+            rest: self.rest.for_subaccount(nickname),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Subaccount requests are sent for, `None` for the main account.
+    pub fn subaccount(&self) -> Option<&str> {
+        self.rest.subaccount()
+    }
+
+    pub fn request<R: Request>(&self, req: R) -> Result<R::Response> {
+        self.runtime.block_on(self.rest.request(req))
+    }
+
+    /// Like `request`, returning the whole response, e.g. to check
+    /// `has_more_data`.
+    pub fn request_envelope<R: Request>(&self, req: R) -> Result<SuccessResponse<R::Response>> {
+        self.runtime.block_on(self.rest.request_envelope(req))
+    }
+
+    /// The async client, e.g. to use its helpers with `block_on`.
+    pub fn as_async(&self) -> &rest::Rest {
+        &self.rest
+    }
+
+    /// Runs `future` to completion on the runtime of the client, e.g.
+    /// `rest.block_on(rest.as_async().place_orders(orders))`.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
@@ -0,0 +1,105 @@
+//! Local order books kept in sync with the `orderbook` websocket channel.
+
+use crate::options::Options;
+use crate::ws::{Channel, Data, Orderbook, ReconnectingWs, Result, Symbol, WsEvent};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+/// Price level of an order book.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Level {
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+/// Best levels of an order book at a point in time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Depth {
+    /// Highest first
+    pub bids: Vec<Level>,
+    /// Lowest first
+    pub asks: Vec<Level>,
+    /// Time of the last update applied
+    pub time: Option<DateTime<Utc>>,
+}
+
+/// Order book of one market, maintained from the partial and update
+/// messages of its own websocket connection.
+///
+/// The checksum of every update is verified. If it does not match, e.g.
+/// after a missed message, the channel is subscribed again to start over
+/// from a new partial. Lost connections are reconnected, also starting over
+/// from a new partial.
+pub struct OrderBook {
+    ws: ReconnectingWs,
+    market: Symbol,
+    time: Option<DateTime<Utc>>,
+}
+
+impl OrderBook {
+    /// Connects, subscribes to the order book of `market` and waits for
+    /// the partial.
+    pub async fn subscribe(options: Options, market: &str) -> Result<Self> {
+        let mut ws = ReconnectingWs::connect(options).await?;
+        ws.subscribe(&[Channel::Orderbook(market.to_owned())])
+            .await?;
+        let mut book = Self {
+            ws,
+            market: market.to_owned(),
+            time: None,
+        };
+        book.next_update().await?;
+        Ok(book)
+    }
+
+    pub fn market(&self) -> &Symbol {
+        &self.market
+    }
+
+    /// Waits for the next message of the channel that applies. After the
+    /// book started over, waits for the new partial.
+    pub async fn next_update(&mut self) -> Result<()> {
+        loop {
+            match self.ws.next_event().await? {
+                WsEvent::Data(_, Data::OrderbookData(data)) => {
+                    self.time = Some(data.time);
+                    return Ok(());
+                }
+                WsEvent::Resubscribed | WsEvent::OrderbookReset(_) => self.time = None,
+                WsEvent::Data(..) | WsEvent::Disconnected(_) => {}
+            }
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<Level> {
+        self.book().best_bid().map(level)
+    }
+
+    pub fn best_ask(&self) -> Option<Level> {
+        self.book().best_ask().map(level)
+    }
+
+    /// The best `levels` bids and asks.
+    pub fn depth(&self, levels: usize) -> Depth {
+        let book = self.book();
+        Depth {
+            bids: book.bids.iter().rev().take(levels).map(level).collect(),
+            asks: book.asks.iter().take(levels).map(level).collect(),
+            time: self.time,
+        }
+    }
+
+    /// The underlying book, e.g. for `Orderbook::quote`.
+    pub fn book(&self) -> &Orderbook {
+        self.ws
+            .orderbook(&self.market)
+            .expect("order book channel is subscribed")
+    }
+}
+
+fn level((price, size): (&Decimal, &Decimal)) -> Level {
+    Level {
+        price: *price,
+        size: *size,
+    }
+}
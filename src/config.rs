@@ -0,0 +1,263 @@
+//! Loading of client, risk and strategy settings from TOML files, built with
+//! the `config` feature.
+//!
+//! ```toml
+//! markets = ["BTC-PERP", "ETH-PERP"]
+//!
+//! [client]
+//! endpoint = "com"
+//! subaccount = "bot"
+//!
+//! [credentials]
+//! source = "env"
+//! key_var = "API_KEY"
+//! secret_var = "API_SECRET"
+//!
+//! [rate_limit]
+//! requests_per_second = 30
+//! orders_per_second = 10
+//!
+//! [risk]
+//! allowed_markets = ["BTC-PERP", "ETH-PERP"]
+//! max_order_notional = 10000
+//! max_order_size = { BTC-PERP = 0.5 }
+//!
+//! [strategy]
+//! spread = 0.001
+//! ```
+//!
+//! Credentials are never stored in the file itself, only a reference to
+//! where they are found. With the `encrypted-credentials` feature they can
+//! also be read from an encrypted file, unlocked through the environment as
+//! described at `credentials::EncryptedFile::from_env`:
+//!
+//! ```toml
+//! [credentials]
+//! source = "encrypted_file"
+//! path = "/etc/ftx/credentials.json"
+//! ```
+
+#[cfg(feature = "encrypted-credentials")]
+use crate::credentials::EncryptedFile;
+use crate::credentials::{CredentialsError, CredentialsProvider, EnvCredentials};
+use crate::options::{Endpoint, Options};
+use crate::rest::{RateLimit, RiskLimits, Symbol};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to read {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error("invalid configuration: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("invalid value for {0}: {1}")]
+    Invalid(&'static str, String),
+
+    #[error(transparent)]
+    Credentials(#[from] CredentialsError),
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Markets the strategy trades or subscribes to
+    #[serde(default)]
+    pub markets: Vec<Symbol>,
+    #[serde(default)]
+    pub client: ClientConfig,
+    pub credentials: Option<CredentialsConfig>,
+    pub rate_limit: Option<RateLimitConfig>,
+    #[serde(default)]
+    pub risk: RiskConfig,
+    /// Free-form settings of the strategy, see `Config::strategy`
+    #[serde(default)]
+    pub strategy: toml::Table,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    #[serde(default)]
+    pub endpoint: Endpoint,
+    pub subaccount: Option<String>,
+    #[serde(default)]
+    pub enforce_address_book: bool,
+    #[serde(default)]
+    pub validate_withdrawals: bool,
+    #[serde(default)]
+    pub detect_schema_drift: bool,
+}
+
+/// Where the API key and secret are read from.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case", deny_unknown_fields)]
+pub enum CredentialsConfig {
+    Env {
+        #[serde(default = "default_key_var")]
+        key_var: String,
+        #[serde(default = "default_secret_var")]
+        secret_var: String,
+    },
+    #[cfg(feature = "encrypted-credentials")]
+    EncryptedFile { path: PathBuf },
+}
+
+fn default_key_var() -> String {
+    "API_KEY".to_owned()
+}
+
+fn default_secret_var() -> String {
+    "API_SECRET".to_owned()
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// FTX allows up to 30 requests per second
+    pub requests_per_second: u32,
+    pub orders_per_second: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RiskConfig {
+    pub allowed_markets: Option<Vec<Symbol>>,
+    #[serde(default)]
+    pub max_order_size: HashMap<Symbol, Decimal>,
+    pub max_order_notional: Option<Decimal>,
+}
+
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    /// Parses and validates a configuration.
+    fn from_str(s: &str) -> Result<Self, ConfigError> {
+        let config: Config = toml::from_str(s)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+impl Config {
+    /// Reads, parses and validates the configuration file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::Io(path.to_owned(), e))?
+            .parse()
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(market) = self.markets.iter().find(|market| market.is_empty()) {
+            return Err(ConfigError::Invalid("markets", format!("{:?}", market)));
+        }
+        if let Some(rate_limit) = &self.rate_limit {
+            if rate_limit.requests_per_second == 0 {
+                return Err(ConfigError::Invalid(
+                    "rate_limit.requests_per_second",
+                    "must be positive".to_owned(),
+                ));
+            }
+            if rate_limit.orders_per_second == Some(0) {
+                return Err(ConfigError::Invalid(
+                    "rate_limit.orders_per_second",
+                    "must be positive".to_owned(),
+                ));
+            }
+        }
+        if let Some((market, size)) = self
+            .risk
+            .max_order_size
+            .iter()
+            .find(|(_, size)| !size.is_sign_positive() || size.is_zero())
+        {
+            return Err(ConfigError::Invalid(
+                "risk.max_order_size",
+                format!("{} for {} must be positive", size, market),
+            ));
+        }
+        if let Some(notional) = self.risk.max_order_notional {
+            if !notional.is_sign_positive() || notional.is_zero() {
+                return Err(ConfigError::Invalid(
+                    "risk.max_order_notional",
+                    format!("{} must be positive", notional),
+                ));
+            }
+        }
+        if let Some(allowed) = &self.risk.allowed_markets {
+            if let Some(market) = self.markets.iter().find(|market| !allowed.contains(market)) {
+                return Err(ConfigError::Invalid(
+                    "markets",
+                    format!("{} is not in risk.allowed_markets", market),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Client options, with credentials resolved from their source.
+    pub fn options(&self) -> Result<Options, ConfigError> {
+        let mut options = Options {
+            endpoint: self.client.endpoint.clone(),
+            subaccount: self.client.subaccount.clone(),
+            enforce_address_book: self.client.enforce_address_book,
+            validate_withdrawals: self.client.validate_withdrawals,
+            detect_schema_drift: self.client.detect_schema_drift,
+            rate_limit: self.rate_limit.as_ref().map(|rate_limit| {
+                let limit = RateLimit::new(rate_limit.requests_per_second);
+                match rate_limit.orders_per_second {
+                    Some(orders_per_second) => limit.orders_per_second(orders_per_second),
+                    None => limit,
+                }
+            }),
+            ..Default::default()
+        };
+        let credentials = match &self.credentials {
+            Some(CredentialsConfig::Env {
+                key_var,
+                secret_var,
+            }) => EnvCredentials {
+                key_var: key_var.clone(),
+                secret_var: secret_var.clone(),
+                ..Default::default()
+            }
+            .credentials()?,
+            #[cfg(feature = "encrypted-credentials")]
+            Some(CredentialsConfig::EncryptedFile { path }) => {
+                EncryptedFile::from_env(path)?.credentials()?
+            }
+            None => return Ok(options),
+        };
+        // The configured subaccount takes precedence
+        let subaccount = options.subaccount.take().or(credentials.subaccount);
+        options = options
+            .authenticate(credentials.key, credentials.secret)
+            .subaccount_optional(subaccount);
+        Ok(options)
+    }
+
+    pub fn risk_limits(&self) -> RiskLimits {
+        RiskLimits {
+            allowed_markets: self
+                .risk
+                .allowed_markets
+                .as_ref()
+                .map(|markets| markets.iter().cloned().collect()),
+            max_order_size: self.risk.max_order_size.clone(),
+            max_order_notional: self.risk.max_order_notional,
+        }
+    }
+
+    /// Deserializes the `[strategy]` table into the settings type of a
+    /// strategy.
+    pub fn strategy<T: serde::de::DeserializeOwned>(&self) -> Result<T, ConfigError> {
+        Ok(toml::Value::Table(self.strategy.clone()).try_into()?)
+    }
+}
@@ -0,0 +1,250 @@
+//! Sources of API credentials.
+//!
+//! With the `encrypted-credentials` feature, credentials can be stored in a
+//! file encrypted with AES-256-GCM, unlocked by a passphrase (stretched with
+//! Argon2id) or a raw 256 bit key, for servers without an OS keyring.
+
+use std::env::var;
+use std::fmt;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CredentialsError {
+    #[error("environment variable {0} is not set")]
+    MissingEnv(String),
+
+    #[error("failed to read {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error("malformed credentials file: {0}")]
+    Format(String),
+
+    #[error("credentials could not be decrypted, wrong passphrase or key?")]
+    Decryption,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "encrypted-credentials",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct Credentials {
+    pub key: String,
+    pub secret: String,
+    pub subaccount: Option<String>,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("key", &self.key)
+            .field("secret", &"<redacted>")
+            .field("subaccount", &self.subaccount)
+            .finish()
+    }
+}
+
+pub trait CredentialsProvider {
+    fn credentials(&self) -> Result<Credentials, CredentialsError>;
+}
+
+/// Reads credentials from environment variables, by default `API_KEY`,
+/// `API_SECRET` and the optional `SUBACCOUNT`.
+#[derive(Clone, Debug)]
+pub struct EnvCredentials {
+    pub key_var: String,
+    pub secret_var: String,
+    pub subaccount_var: String,
+}
+
+impl Default for EnvCredentials {
+    fn default() -> Self {
+        Self {
+            key_var: "API_KEY".to_owned(),
+            secret_var: "API_SECRET".to_owned(),
+            subaccount_var: "SUBACCOUNT".to_owned(),
+        }
+    }
+}
+
+impl CredentialsProvider for EnvCredentials {
+    fn credentials(&self) -> Result<Credentials, CredentialsError> {
+        let read =
+            |name: &String| var(name).map_err(|_| CredentialsError::MissingEnv(name.clone()));
+        Ok(Credentials {
+            key: read(&self.key_var)?,
+            secret: read(&self.secret_var)?,
+            subaccount: var(&self.subaccount_var).ok(),
+        })
+    }
+}
+
+#[cfg(feature = "encrypted-credentials")]
+pub use self::encrypted::*;
+
+#[cfg(feature = "encrypted-credentials")]
+mod encrypted {
+    use super::{Credentials, CredentialsError, CredentialsProvider};
+    use aes_gcm::aead::rand_core::RngCore;
+    use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+    use aes_gcm::{Aes256Gcm, Key, Nonce};
+    use argon2::Argon2;
+    use serde::{Deserialize, Serialize};
+    use std::convert::TryFrom;
+    use std::env::var;
+    use std::path::PathBuf;
+
+    /// Environment variable holding the passphrase for `EncryptedFile::from_env`.
+    pub const PASSPHRASE_VAR: &str = "FTX_CREDENTIALS_PASSPHRASE";
+    /// Environment variable holding the hex encoded key for
+    /// `EncryptedFile::from_env`.
+    pub const KEY_VAR: &str = "FTX_CREDENTIALS_KEY";
+
+    #[derive(Serialize, Deserialize)]
+    struct Envelope {
+        version: u32,
+        /// `"argon2id"` if the key is derived from a passphrase, `"none"` if
+        /// the key is used directly
+        kdf: String,
+        salt: String,
+        nonce: String,
+        ciphertext: String,
+    }
+
+    #[derive(Clone)]
+    enum Secret {
+        Passphrase(String),
+        Key([u8; 32]),
+    }
+
+    /// Credentials file written by `encrypt_credentials` or
+    /// `encrypt_credentials_with_key`.
+    #[derive(Clone)]
+    pub struct EncryptedFile {
+        path: PathBuf,
+        secret: Secret,
+    }
+
+    impl EncryptedFile {
+        pub fn with_passphrase(path: impl Into<PathBuf>, passphrase: impl Into<String>) -> Self {
+            Self {
+                path: path.into(),
+                secret: Secret::Passphrase(passphrase.into()),
+            }
+        }
+
+        pub fn with_key(path: impl Into<PathBuf>, key: [u8; 32]) -> Self {
+            Self {
+                path: path.into(),
+                secret: Secret::Key(key),
+            }
+        }
+
+        /// Unlocks the file with the hex encoded key in `FTX_CREDENTIALS_KEY`
+        /// or else the passphrase in `FTX_CREDENTIALS_PASSPHRASE`.
+        pub fn from_env(path: impl Into<PathBuf>) -> Result<Self, CredentialsError> {
+            if let Ok(key) = var(KEY_VAR) {
+                let key = hex::decode(key.trim())
+                    .ok()
+                    .and_then(|key| <[u8; 32]>::try_from(key).ok())
+                    .ok_or_else(|| {
+                        CredentialsError::Format(format!(
+                            "{} must be 32 hex encoded bytes",
+                            KEY_VAR
+                        ))
+                    })?;
+                return Ok(Self::with_key(path, key));
+            }
+            let passphrase = var(PASSPHRASE_VAR)
+                .map_err(|_| CredentialsError::MissingEnv(PASSPHRASE_VAR.to_owned()))?;
+            Ok(Self::with_passphrase(path, passphrase))
+        }
+    }
+
+    impl CredentialsProvider for EncryptedFile {
+        fn credentials(&self) -> Result<Credentials, CredentialsError> {
+            let contents = std::fs::read_to_string(&self.path)
+                .map_err(|e| CredentialsError::Io(self.path.clone(), e))?;
+            decrypt_credentials(&contents, &self.secret)
+        }
+    }
+
+    /// Encrypts `credentials` with a key derived from `passphrase`, returning
+    /// the contents of a credentials file.
+    pub fn encrypt_credentials(credentials: &Credentials, passphrase: &str) -> String {
+        let mut salt = [0; 16];
+        OsRng.fill_bytes(&mut salt);
+        let key = derive_key(passphrase, &salt).expect("valid salt length");
+        seal(credentials, &key, "argon2id", &salt)
+    }
+
+    /// Encrypts `credentials` with `key`, returning the contents of a
+    /// credentials file.
+    pub fn encrypt_credentials_with_key(credentials: &Credentials, key: &[u8; 32]) -> String {
+        seal(credentials, key, "none", &[])
+    }
+
+    fn seal(credentials: &Credentials, key: &[u8; 32], kdf: &str, salt: &[u8]) -> String {
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let plaintext = serde_json::to_vec(credentials).expect("credentials serialize to JSON");
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_ref())
+            .expect("encryption does not fail");
+        serde_json::to_string_pretty(&Envelope {
+            version: 1,
+            kdf: kdf.to_owned(),
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+        .expect("envelope serializes to JSON")
+    }
+
+    fn decrypt_credentials(
+        contents: &str,
+        secret: &Secret,
+    ) -> Result<Credentials, CredentialsError> {
+        let format = |e: &dyn std::fmt::Display| CredentialsError::Format(e.to_string());
+        let envelope: Envelope = serde_json::from_str(contents).map_err(|e| format(&e))?;
+        if envelope.version != 1 {
+            return Err(format(&format_args!(
+                "unsupported version {}",
+                envelope.version
+            )));
+        }
+        let salt = hex::decode(&envelope.salt).map_err(|e| format(&e))?;
+        let nonce = hex::decode(&envelope.nonce).map_err(|e| format(&e))?;
+        let ciphertext = hex::decode(&envelope.ciphertext).map_err(|e| format(&e))?;
+        if nonce.len() != 12 {
+            return Err(format(&"nonce must be 12 bytes"));
+        }
+
+        let key = match (envelope.kdf.as_str(), secret) {
+            ("argon2id", Secret::Passphrase(passphrase)) => derive_key(passphrase, &salt)?,
+            ("none", Secret::Key(key)) => *key,
+            ("argon2id", Secret::Key(_)) => {
+                return Err(format(&"file is encrypted with a passphrase, not a key"))
+            }
+            ("none", Secret::Passphrase(_)) => {
+                return Err(format(&"file is encrypted with a key, not a passphrase"))
+            }
+            (kdf, _) => return Err(format(&format_args!("unsupported kdf {}", kdf))),
+        };
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| CredentialsError::Decryption)?;
+        serde_json::from_slice(&plaintext).map_err(|e| format(&e))
+    }
+
+    fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], CredentialsError> {
+        let mut key = [0; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| CredentialsError::Format(e.to_string()))?;
+        Ok(key)
+    }
+}
@@ -0,0 +1,601 @@
+//! Backtesting of strategies on recorded websocket journals or downloaded
+//! trades and candles.
+//!
+//! Orders requested by the strategy reach the simulated exchange after the
+//! configured latency. Marketable orders fill against the replayed order
+//! book (or the last price if no book was recorded) as taker, resting
+//! orders fill as maker once the market trades through their price. With
+//! queue position tracking, resting orders also fill from trades at their
+//! price once the volume queued ahead of them when they were placed has
+//! traded or been cancelled. Replays are deterministic: resting orders are
+//! matched in the order they were placed.
+
+use super::{Action, Context, NewOrder, OrderTracker, Strategy};
+use crate::rest::{Candle, Id, OrderInfo, OrderStatus, OrderType, Side, Symbol, Trade};
+use crate::ws::{Data, Fill, Liquidity, Orderbook, OrderbookData, Ticker};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, Write};
+
+#[derive(Clone, Debug)]
+pub enum MarketEvent {
+    Tick(Ticker),
+    Trade(Trade),
+    Book(OrderbookData),
+}
+
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub time: DateTime<Utc>,
+    pub market: Symbol,
+    pub data: MarketEvent,
+}
+
+/// Line of a websocket journal, as written by `JournalWriter`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct JournalEntry {
+    pub time: DateTime<Utc>,
+    pub market: Option<Symbol>,
+    pub data: Data,
+}
+
+/// Records websocket data as JSON lines, for replay with `read_journal`.
+pub struct JournalWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> JournalWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn write(&mut self, market: Option<Symbol>, data: Data) -> io::Result<()> {
+        let entry = JournalEntry {
+            time: Utc::now(),
+            market,
+            data,
+        };
+        serde_json::to_writer(&mut self.writer, &entry)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+/// Reads the market data of a journal written by `JournalWriter`. Fills and
+/// order updates of the recording account are skipped.
+pub fn read_journal(reader: impl BufRead) -> io::Result<Vec<Event>> {
+    let mut events = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: JournalEntry = serde_json::from_str(&line)?;
+        let data = match entry.data {
+            Data::Ticker(ticker) => MarketEvent::Tick(ticker),
+            Data::Trade(trade) => MarketEvent::Trade(trade),
+            Data::OrderbookData(book) => MarketEvent::Book(book),
+            Data::Fill(_) | Data::Order(_) => continue,
+        };
+        events.push(Event {
+            time: entry.time,
+            market: entry.market.unwrap_or_default(),
+            data,
+        });
+    }
+    Ok(events)
+}
+
+/// Events for trades of `market` as returned by `GetTrades`, oldest first.
+pub fn trade_events(market: &str, trades: &[Trade]) -> Vec<Event> {
+    let mut events: Vec<Event> = trades
+        .iter()
+        .map(|trade| Event {
+            time: trade.time,
+            market: market.to_owned(),
+            data: MarketEvent::Trade(*trade),
+        })
+        .collect();
+    events.sort_by_key(|event| event.time);
+    events
+}
+
+/// Events for candles of `market` as returned by `GetHistoricalPrices`,
+/// oldest first. Each candle is replayed as four trades spread over its
+/// `resolution`: the open, the high and low in the order more likely given
+/// the direction of the candle, and the close.
+pub fn candle_events(market: &str, candles: &[Candle], resolution: Duration) -> Vec<Event> {
+    let mut candles = candles.to_vec();
+    candles.sort_by_key(|candle| candle.start_time);
+    candles
+        .iter()
+        .flat_map(|candle| {
+            let path = if candle.close >= candle.open {
+                [candle.open, candle.low, candle.high, candle.close]
+            } else {
+                [candle.open, candle.high, candle.low, candle.close]
+            };
+            let mut previous = candle.open;
+            path.iter()
+                .enumerate()
+                .map(|(i, &price)| {
+                    let side = if price >= previous {
+                        Side::Buy
+                    } else {
+                        Side::Sell
+                    };
+                    previous = price;
+                    let size = if price.is_zero() {
+                        Decimal::ZERO
+                    } else {
+                        candle.volume / price / dec!(4)
+                    };
+                    let time = candle.start_time + resolution * i as i32 / 4;
+                    Event {
+                        time,
+                        market: market.to_owned(),
+                        data: MarketEvent::Trade(Trade {
+                            id: 0,
+                            liquidation: false,
+                            price,
+                            side,
+                            size,
+                            time,
+                        }),
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Fee rates as a fraction of the notional value.
+#[derive(Copy, Clone, Debug)]
+pub struct FeeModel {
+    pub maker: Decimal,
+    pub taker: Decimal,
+}
+
+impl Default for FeeModel {
+    /// Fees of the lowest FTX tier.
+    fn default() -> Self {
+        Self {
+            maker: dec!(0.0002),
+            taker: dec!(0.0007),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulatedFill {
+    pub time: DateTime<Utc>,
+    pub market: Symbol,
+    pub order_id: Id,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub fee: Decimal,
+    pub liquidity: Liquidity,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BacktestReport {
+    pub fills: Vec<SimulatedFill>,
+    /// Net position per market
+    pub positions: HashMap<Symbol, Decimal>,
+    /// Quote currency balance change, including fees
+    pub cash: Decimal,
+    pub fees: Decimal,
+    /// Traded notional value
+    pub volume: Decimal,
+    /// Cost charged on maker fills by the adverse selection model
+    pub adverse_selection: Decimal,
+    /// Cash plus positions valued at the last price, less adverse selection
+    pub pnl: Decimal,
+}
+
+enum Notification {
+    Fill(Fill),
+    Order(OrderInfo),
+}
+
+/// Runs a `Strategy` on historical events.
+pub struct Backtester<S> {
+    strategy: S,
+    latency: Duration,
+    fees: FeeModel,
+    tracker: OrderTracker,
+    books: HashMap<Symbol, Orderbook>,
+    last_prices: HashMap<Symbol, Decimal>,
+    pending: VecDeque<(DateTime<Utc>, Action)>,
+    notifications: VecDeque<Notification>,
+    queue_position: bool,
+    /// Size queued ahead of resting orders at their price
+    queue_ahead: HashMap<Id, Decimal>,
+    adverse_selection: Decimal,
+    next_id: Id,
+    report: BacktestReport,
+}
+
+impl<S: Strategy> Backtester<S> {
+    pub fn new(strategy: S) -> Self {
+        Self {
+            strategy,
+            latency: Duration::zero(),
+            fees: FeeModel::default(),
+            tracker: OrderTracker::new(),
+            books: HashMap::new(),
+            last_prices: HashMap::new(),
+            pending: VecDeque::new(),
+            notifications: VecDeque::new(),
+            queue_position: false,
+            queue_ahead: HashMap::new(),
+            adverse_selection: Decimal::ZERO,
+            next_id: 1,
+            report: BacktestReport::default(),
+        }
+    }
+
+    /// Delay between a strategy requesting an action and the simulated
+    /// exchange processing it.
+    #[must_use]
+    pub fn latency(mut self, latency: std::time::Duration) -> Self {
+        self.latency = Duration::from_std(latency).expect("latency out of range");
+        self
+    }
+
+    #[must_use]
+    pub fn fees(mut self, fees: FeeModel) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Fills resting orders from trades at their price once the size queued
+    /// ahead of them in the replayed book has been consumed. Without a
+    /// recorded book, orders are assumed to be first in the queue.
+    #[must_use]
+    pub fn queue_position(mut self) -> Self {
+        self.queue_position = true;
+        self
+    }
+
+    /// Charges `cost`, as a fraction of the notional value, on every maker
+    /// fill, modelling the price moving against resting orders after they
+    /// are filled.
+    #[must_use]
+    pub fn adverse_selection(mut self, cost: Decimal) -> Self {
+        self.adverse_selection = cost;
+        self
+    }
+
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    /// Replays `events`, which must be ordered by time, and returns the
+    /// report of the simulated trading.
+    pub fn run(&mut self, events: impl IntoIterator<Item = Event>) -> BacktestReport {
+        for event in events {
+            self.process_pending(event.time);
+            self.apply(&event);
+            self.match_resting(&event);
+            self.notify(event.time);
+
+            let mut ctx = Context::new(event.time, &self.tracker);
+            match &event.data {
+                MarketEvent::Tick(ticker) => self.strategy.on_tick(&mut ctx, &event.market, ticker),
+                MarketEvent::Trade(trade) => self.strategy.on_trade(&mut ctx, &event.market, trade),
+                MarketEvent::Book(_) => {
+                    if let Some(book) = self.books.get(&event.market) {
+                        self.strategy.on_book(&mut ctx, &event.market, book);
+                    }
+                }
+            }
+            let actions = ctx.into_actions();
+            self.schedule(event.time, actions);
+        }
+
+        let mut report = std::mem::take(&mut self.report);
+        report.pnl = report.cash - report.adverse_selection
+            + report
+                .positions
+                .iter()
+                .map(|(market, position)| {
+                    position * self.last_prices.get(market).copied().unwrap_or_default()
+                })
+                .sum::<Decimal>();
+        report
+    }
+
+    fn schedule(&mut self, time: DateTime<Utc>, actions: Vec<Action>) {
+        let time = time + self.latency;
+        self.pending
+            .extend(actions.into_iter().map(|action| (time, action)));
+    }
+
+    /// Processes the actions due at `time`.
+    fn process_pending(&mut self, time: DateTime<Utc>) {
+        while self.pending.front().is_some_and(|(due, _)| *due <= time) {
+            let (due, action) = self.pending.pop_front().unwrap();
+            match action {
+                Action::Place(order) => self.place(due, order),
+                Action::Cancel(id) => {
+                    if let Some(order) = self.tracker.get(id) {
+                        let mut order = order.clone();
+                        order.status = OrderStatus::Closed;
+                        self.queue_ahead.remove(&id);
+                        self.tracker.update(&order);
+                        self.notifications.push_back(Notification::Order(order));
+                    }
+                }
+            }
+            self.notify(due);
+        }
+    }
+
+    fn place(&mut self, time: DateTime<Utc>, order: NewOrder) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut info = OrderInfo {
+            id,
+            market: order.market.clone(),
+            future: None,
+            r#type: order.r#type,
+            side: order.side,
+            price: order.price,
+            size: order.size,
+            reduce_only: Some(order.reduce_only),
+            ioc: Some(order.time_in_force.ioc()),
+            post_only: Some(order.time_in_force.post_only()),
+            status: OrderStatus::Open,
+            filled_size: Some(Decimal::ZERO),
+            remaining_size: Some(order.size),
+            avg_fill_price: None,
+            liquidation: Some(false),
+            created_at: time,
+            client_id: order.client_id.clone(),
+            retry_until_filled: None,
+            trigger_price: None,
+            order_price: None,
+            triggered_at: None,
+            order_type: None,
+            order_id: None,
+            trail_value: None,
+            trail_start: None,
+            error: None,
+            raw: Default::default(),
+        };
+
+        let taker_price = self.taker_price(&order);
+        let marketable = match (order.r#type, order.price, taker_price) {
+            (OrderType::Market, _, Some(_)) => true,
+            (_, Some(limit), Some(price)) => match order.side {
+                Side::Buy => price <= limit,
+                Side::Sell => price >= limit,
+            },
+            _ => false,
+        };
+
+        if marketable && !order.time_in_force.post_only() {
+            self.fill(
+                time,
+                &mut info,
+                taker_price.unwrap(),
+                order.size,
+                Liquidity::Taker,
+            );
+        } else if order.r#type == OrderType::Market
+            || order.time_in_force.ioc()
+            || order.time_in_force.post_only() && marketable
+        {
+            info.status = OrderStatus::Closed;
+        }
+        if info.status != OrderStatus::Closed && self.queue_position {
+            let ahead = self.level_size(&info).unwrap_or_default();
+            self.queue_ahead.insert(id, ahead);
+        }
+        self.tracker.update(&info);
+        self.notifications.push_back(Notification::Order(info));
+    }
+
+    /// Price a taker order would fill at, from the book if one was recorded
+    /// and the last price otherwise.
+    fn taker_price(&self, order: &NewOrder) -> Option<Decimal> {
+        self.books
+            .get(&order.market)
+            .filter(|book| book.is_initialized())
+            .and_then(|book| book.quote(order.side, order.size))
+            .or_else(|| self.last_prices.get(&order.market).copied())
+    }
+
+    /// Size in the replayed book at the price of `order`, on its side.
+    fn level_size(&self, order: &OrderInfo) -> Option<Decimal> {
+        let book = self.books.get(&order.market)?;
+        let levels = match order.side {
+            Side::Buy => &book.bids,
+            Side::Sell => &book.asks,
+        };
+        Some(levels.get(&order.price?).copied().unwrap_or_default())
+    }
+
+    fn apply(&mut self, event: &Event) {
+        match &event.data {
+            MarketEvent::Tick(ticker) => {
+                self.last_prices.insert(event.market.clone(), ticker.last);
+            }
+            MarketEvent::Trade(trade) => {
+                self.last_prices.insert(event.market.clone(), trade.price);
+            }
+            MarketEvent::Book(data) => {
+                let book = self
+                    .books
+                    .entry(event.market.clone())
+                    .or_insert_with(|| Orderbook::new(event.market.clone()));
+                if let Err(error) = book.update(data) {
+                    log::warn!("skipping order book update of {}: {}", event.market, error);
+                }
+            }
+        }
+    }
+
+    /// Fills resting orders the market traded through.
+    fn match_resting(&mut self, event: &Event) {
+        let mut orders: Vec<OrderInfo> = self
+            .tracker
+            .open_orders(Some(&event.market))
+            .cloned()
+            .collect();
+        orders.sort_by_key(|order| order.id);
+        for mut order in orders {
+            let (price, remaining) = match (order.price, order.remaining_size) {
+                (Some(price), Some(remaining)) if !remaining.is_zero() => (price, remaining),
+                _ => continue,
+            };
+            let crosses = |other: Decimal, strict: bool| match order.side {
+                Side::Buy => other < price || !strict && other == price,
+                Side::Sell => other > price || !strict && other == price,
+            };
+
+            let size = match &event.data {
+                MarketEvent::Trade(trade) if crosses(trade.price, true) => {
+                    remaining.min(trade.size)
+                }
+                MarketEvent::Trade(trade) if trade.price == price && trade.side != order.side => {
+                    let ahead = match self.queue_ahead.get_mut(&order.id) {
+                        Some(ahead) => ahead,
+                        None => continue,
+                    };
+                    let available = trade.size - *ahead;
+                    *ahead = (*ahead - trade.size).max(Decimal::ZERO);
+                    if available <= Decimal::ZERO {
+                        continue;
+                    }
+                    remaining.min(available)
+                }
+                MarketEvent::Tick(ticker) => {
+                    let opposite = match order.side {
+                        Side::Buy => ticker.ask,
+                        Side::Sell => ticker.bid,
+                    };
+                    if crosses(opposite, false) {
+                        remaining
+                    } else {
+                        continue;
+                    }
+                }
+                MarketEvent::Book(_) => {
+                    // Size ahead that left the level was cancelled
+                    if let Some(size) = self.level_size(&order) {
+                        if let Some(ahead) = self.queue_ahead.get_mut(&order.id) {
+                            *ahead = (*ahead).min(size);
+                        }
+                    }
+                    let opposite = self.books.get(&event.market).and_then(|book| {
+                        match order.side {
+                            Side::Buy => book.ask_price(),
+                            Side::Sell => book.bid_price(),
+                        }
+                        .copied()
+                    });
+                    match opposite {
+                        Some(opposite) if crosses(opposite, false) => remaining,
+                        _ => continue,
+                    }
+                }
+                _ => continue,
+            };
+            self.fill(event.time, &mut order, price, size, Liquidity::Maker);
+            if order.status == OrderStatus::Closed {
+                self.queue_ahead.remove(&order.id);
+            }
+            self.tracker.update(&order);
+            self.notifications.push_back(Notification::Order(order));
+        }
+    }
+
+    fn fill(
+        &mut self,
+        time: DateTime<Utc>,
+        order: &mut OrderInfo,
+        price: Decimal,
+        size: Decimal,
+        liquidity: Liquidity,
+    ) {
+        let filled = order.filled_size.unwrap_or_default();
+        order.avg_fill_price = Some(
+            (order.avg_fill_price.unwrap_or_default() * filled + price * size) / (filled + size),
+        );
+        order.filled_size = Some(filled + size);
+        order.remaining_size = Some(order.size - filled - size);
+        if order.remaining_size == Some(Decimal::ZERO) {
+            order.status = OrderStatus::Closed;
+        }
+
+        let fee_rate = match liquidity {
+            Liquidity::Maker => self.fees.maker,
+            Liquidity::Taker => self.fees.taker,
+        };
+        let notional = price * size;
+        let fee = notional * fee_rate;
+        let signed_size = match order.side {
+            Side::Buy => size,
+            Side::Sell => -size,
+        };
+        *self
+            .report
+            .positions
+            .entry(order.market.clone())
+            .or_default() += signed_size;
+        self.report.cash -= signed_size * price + fee;
+        self.report.fees += fee;
+        if liquidity == Liquidity::Maker {
+            self.report.adverse_selection += notional * self.adverse_selection;
+        }
+        self.report.volume += notional;
+        self.report.fills.push(SimulatedFill {
+            time,
+            market: order.market.clone(),
+            order_id: order.id,
+            side: order.side,
+            price,
+            size,
+            fee,
+            liquidity,
+        });
+
+        self.notifications.push_back(Notification::Fill(Fill {
+            id: self.report.fills.len() as Id,
+            market: Some(order.market.clone()),
+            future: None,
+            base_currency: None,
+            quote_currency: None,
+            r#type: "order".to_owned(),
+            side: order.side,
+            price,
+            size,
+            order_id: Some(order.id),
+            trade_id: None,
+            time,
+            fee,
+            fee_rate,
+            fee_currency: "USD".to_owned(),
+            liquidity,
+            raw: Default::default(),
+        }));
+    }
+
+    /// Delivers queued fills and order updates to the strategy.
+    fn notify(&mut self, time: DateTime<Utc>) {
+        while let Some(notification) = self.notifications.pop_front() {
+            let mut ctx = Context::new(time, &self.tracker);
+            match &notification {
+                Notification::Fill(fill) => self.strategy.on_fill(&mut ctx, fill),
+                Notification::Order(order) => self.strategy.on_order_update(&mut ctx, order),
+            }
+            let actions = ctx.into_actions();
+            self.schedule(time, actions);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Event driven trading strategies, built with the `ws` feature.
+//!
+//! A `Strategy` reacts to market data and account events and requests
+//! orders through its `Context`. `Runner` drives it with live websocket
+//! data, checking requested orders against `RiskLimits` before placing
+//! them.
+
+mod backtest;
+mod runner;
+#[cfg(test)]
+mod tests;
+mod tracker;
+
+pub use backtest::*;
+pub use runner::*;
+pub use tracker::*;
+
+use crate::rest::{
+    Error, Id, OrderInfo, OrderType, PlaceOrder, Result, Side, Symbol, TimeInForce, Trade,
+};
+use crate::ws::{Fill, Orderbook, Ticker};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+/// Callbacks of a trading strategy. All callbacks default to doing nothing.
+#[allow(unused_variables)]
+pub trait Strategy {
+    fn on_tick(&mut self, ctx: &mut Context, market: &str, ticker: &Ticker) {}
+    fn on_trade(&mut self, ctx: &mut Context, market: &str, trade: &Trade) {}
+    /// Called after every update of the order book of `market`.
+    fn on_book(&mut self, ctx: &mut Context, market: &str, book: &Orderbook) {}
+    fn on_fill(&mut self, ctx: &mut Context, fill: &Fill) {}
+    fn on_order_update(&mut self, ctx: &mut Context, order: &OrderInfo) {}
+    /// Called periodically if the runner has a timer interval.
+    fn on_timer(&mut self, ctx: &mut Context) {}
+}
+
+/// Order requested by a strategy.
+#[derive(Clone, Debug, Default)]
+pub struct NewOrder {
+    pub market: Symbol,
+    pub side: Side,
+    pub price: Option<Decimal>,
+    pub r#type: OrderType,
+    pub size: Decimal,
+    pub reduce_only: bool,
+    pub time_in_force: TimeInForce,
+    pub client_id: Option<String>,
+}
+
+impl NewOrder {
+    pub fn limit(market: &str, side: Side, price: Decimal, size: Decimal) -> Self {
+        Self {
+            market: market.to_owned(),
+            side,
+            price: Some(price),
+            r#type: OrderType::Limit,
+            size,
+            ..Default::default()
+        }
+    }
+
+    pub fn market(market: &str, side: Side, size: Decimal) -> Self {
+        Self {
+            market: market.to_owned(),
+            side,
+            r#type: OrderType::Market,
+            size,
+            ..Default::default()
+        }
+    }
+
+    /// Fails with `Error::PostOnlyMarketOrder` for post-only market orders.
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Result<Self> {
+        if time_in_force == TimeInForce::PostOnly && self.r#type == OrderType::Market {
+            return Err(Error::PostOnlyMarketOrder);
+        }
+        self.time_in_force = time_in_force;
+        Ok(self)
+    }
+
+    pub fn as_place_order(&self) -> PlaceOrder<'_> {
+        PlaceOrder {
+            market: &self.market,
+            side: self.side,
+            price: self.price,
+            r#type: self.r#type,
+            size: self.size,
+            reduce_only: self.reduce_only,
+            time_in_force: self.time_in_force,
+            client_id: self.client_id.as_deref(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    Place(NewOrder),
+    Cancel(Id),
+}
+
+/// State visible to a strategy callback and the actions it requested.
+pub struct Context<'a> {
+    now: DateTime<Utc>,
+    orders: &'a OrderTracker,
+    actions: Vec<Action>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(now: DateTime<Utc>, orders: &'a OrderTracker) -> Self {
+        Self {
+            now,
+            orders,
+            actions: Vec::new(),
+        }
+    }
+
+    /// Time of the event being handled.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+
+    pub fn orders(&self) -> &OrderTracker {
+        self.orders
+    }
+
+    pub fn place_order(&mut self, order: NewOrder) {
+        self.actions.push(Action::Place(order));
+    }
+
+    pub fn cancel_order(&mut self, id: Id) {
+        self.actions.push(Action::Cancel(id));
+    }
+
+    /// Actions requested during the callback, in order.
+    pub fn into_actions(self) -> Vec<Action> {
+        self.actions
+    }
+}
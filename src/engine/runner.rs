@@ -0,0 +1,176 @@
+use super::{Action, Context, OrderTracker, Strategy};
+use crate::options::Options;
+use crate::rest::{
+    Alert, AlertSink, CancelOrder, GetOpenOrders, LogSink, PlaceOrder, Rest, Result, RiskLimits,
+    Severity, Symbol,
+};
+use crate::ws::{Channel, Data, Orderbook, Ws};
+use chrono::Utc;
+use futures::StreamExt;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time;
+
+/// Runs a `Strategy` on live websocket data of `markets`, placing the
+/// orders it requests through the REST API.
+pub struct Runner<S> {
+    strategy: S,
+    options: Options,
+    rest: Rest,
+    markets: Vec<Symbol>,
+    risk_limits: RiskLimits,
+    alert_sink: Box<dyn AlertSink>,
+    timer: Option<Duration>,
+    tracker: OrderTracker,
+    books: HashMap<Symbol, Orderbook>,
+    last_prices: HashMap<Symbol, Decimal>,
+}
+
+impl<S: Strategy> Runner<S> {
+    pub fn new(options: Options, markets: Vec<Symbol>, strategy: S) -> Self {
+        Self {
+            strategy,
+            rest: Rest::new(options.clone()),
+            options,
+            markets,
+            risk_limits: RiskLimits::default(),
+            alert_sink: Box::new(LogSink),
+            timer: None,
+            tracker: OrderTracker::new(),
+            books: HashMap::new(),
+            last_prices: HashMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn risk_limits(mut self, risk_limits: RiskLimits) -> Self {
+        self.risk_limits = risk_limits;
+        self
+    }
+
+    /// Where failed and rejected orders are reported, the log by default.
+    #[must_use]
+    pub fn alert_sink(mut self, alert_sink: impl AlertSink + 'static) -> Self {
+        self.alert_sink = Box::new(alert_sink);
+        self
+    }
+
+    /// Calls `Strategy::on_timer` every `interval`.
+    #[must_use]
+    pub fn timer(mut self, interval: Duration) -> Self {
+        self.timer = Some(interval);
+        self
+    }
+
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    pub fn orders(&self) -> &OrderTracker {
+        &self.tracker
+    }
+
+    /// Connects and runs the strategy until the websocket closes or fails.
+    pub async fn run(&mut self) -> Result<()> {
+        for order in self.rest.request(GetOpenOrders::all_market()).await? {
+            self.tracker.update(&order);
+        }
+
+        let mut ws = Ws::connect(self.options.clone()).await.map_err(Box::new)?;
+        let mut channels = vec![Channel::Fills, Channel::Orders];
+        for market in &self.markets {
+            channels.push(Channel::Ticker(market.clone()));
+            channels.push(Channel::Trades(market.clone()));
+            channels.push(Channel::Orderbook(market.clone()));
+            self.books
+                .insert(market.clone(), Orderbook::new(market.clone()));
+        }
+        ws.subscribe(&channels).await.map_err(Box::new)?;
+
+        let mut timer = self.timer.map(time::interval);
+        loop {
+            let tick = async {
+                match &mut timer {
+                    Some(timer) => timer.tick().await,
+                    None => futures::future::pending().await,
+                }
+            };
+            tokio::select! {
+                data = ws.next() => match data {
+                    Some(data) => {
+                        let (symbol, data) = data.map_err(Box::new)?;
+                        self.handle(symbol, data).await?;
+                    }
+                    None => return Ok(()),
+                },
+                _ = tick => {
+                    let mut ctx = Context::new(Utc::now(), &self.tracker);
+                    self.strategy.on_timer(&mut ctx);
+                    let actions = ctx.into_actions();
+                    self.execute(actions).await;
+                }
+            }
+        }
+    }
+
+    async fn handle(&mut self, symbol: Option<Symbol>, data: Data) -> Result<()> {
+        let market = symbol.unwrap_or_default();
+        if let Data::Order(order) = &data {
+            self.tracker.update(order);
+        }
+        if let Data::OrderbookData(data) = &data {
+            if let Some(book) = self.books.get_mut(&market) {
+                book.update(data).map_err(Box::new)?;
+            }
+        }
+
+        let mut ctx = Context::new(Utc::now(), &self.tracker);
+        match &data {
+            Data::Ticker(ticker) => {
+                self.last_prices.insert(market.clone(), ticker.last);
+                self.strategy.on_tick(&mut ctx, &market, ticker);
+            }
+            Data::Trade(trade) => self.strategy.on_trade(&mut ctx, &market, trade),
+            Data::OrderbookData(_) => {
+                if let Some(book) = self.books.get(&market) {
+                    self.strategy.on_book(&mut ctx, &market, book);
+                }
+            }
+            Data::Fill(fill) => self.strategy.on_fill(&mut ctx, fill),
+            Data::Order(order) => self.strategy.on_order_update(&mut ctx, order),
+        }
+        let actions = ctx.into_actions();
+        self.execute(actions).await;
+        Ok(())
+    }
+
+    /// Executes the actions of a strategy callback. Failures are reported to
+    /// the alert sink rather than stopping the strategy.
+    async fn execute(&mut self, actions: Vec<Action>) {
+        for action in actions {
+            let result = match &action {
+                Action::Place(order) => self.place(order.as_place_order()).await,
+                Action::Cancel(id) => self.rest.request(CancelOrder::new(*id)).await.map(drop),
+            };
+            if let Err(error) = result {
+                let alert = Alert::new(
+                    Severity::Warning,
+                    "engine",
+                    format!("{:?} failed: {}", action, error),
+                );
+                if let Err(alert_error) = self.alert_sink.send(&alert).await {
+                    log::warn!("failed to send alert: {}", alert_error);
+                }
+            }
+        }
+    }
+
+    async fn place(&mut self, order: PlaceOrder<'_>) -> Result<()> {
+        let reference_price = self.last_prices.get(order.market).copied();
+        self.risk_limits.check(&order, reference_price)?;
+        let order = self.rest.request(order).await?;
+        self.tracker.update(&order);
+        Ok(())
+    }
+}
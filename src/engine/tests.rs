@@ -0,0 +1,295 @@
+use super::*;
+use crate::rest::{Candle, OrderInfo, OrderStatus, Trade};
+use crate::ws::{Fill, Liquidity, Orderbook, OrderbookAction, OrderbookData};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde_json::json;
+
+fn order(id: Id, market: &str, status: OrderStatus) -> OrderInfo {
+    serde_json::from_value(json!({
+        "id": id,
+        "market": market,
+        "future": null,
+        "type": "limit",
+        "side": "buy",
+        "price": 100.0,
+        "size": 1.0,
+        "status": status,
+        "filledSize": 0.0,
+        "remainingSize": 1.0,
+        "avgFillPrice": null,
+        "createdAt": "2022-01-01T00:00:00.000000+00:00",
+        "clientId": null,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn order_tracker() {
+    let mut tracker = OrderTracker::new();
+    tracker.update(&order(1, "BTC-PERP", OrderStatus::New));
+    tracker.update(&order(2, "ETH-PERP", OrderStatus::Open));
+    tracker.update(&order(1, "BTC-PERP", OrderStatus::Open));
+    assert_eq!(tracker.get(1).unwrap().status, OrderStatus::Open);
+    assert_eq!(tracker.open_orders(None).count(), 2);
+    assert_eq!(tracker.open_orders(Some("ETH-PERP")).count(), 1);
+
+    tracker.update(&order(1, "BTC-PERP", OrderStatus::Closed));
+    assert!(tracker.get(1).is_none());
+    assert_eq!(tracker.open_orders(Some("BTC-PERP")).count(), 0);
+}
+
+#[test]
+fn order_tracker_changes() {
+    let with_client_id = |id, status| OrderInfo {
+        client_id: Some(format!("quote-{}", id)),
+        ..order(id, "BTC-PERP", status)
+    };
+    let mut tracker = OrderTracker::new();
+    assert!(matches!(
+        tracker.update(&with_client_id(1, OrderStatus::New)),
+        Some(OrderChange::Opened(_))
+    ));
+    assert!(matches!(
+        tracker.update(&with_client_id(1, OrderStatus::Open)),
+        Some(OrderChange::Updated(_))
+    ));
+    // Repeated states are no change
+    assert!(tracker
+        .update(&with_client_id(1, OrderStatus::Open))
+        .is_none());
+    assert_eq!(tracker.get_by_client_id("quote-1").unwrap().id, 1);
+    assert_eq!(tracker.status("quote-1"), Some(OrderStatus::Open));
+
+    // Orders missing from a snapshot are reported, new ones recorded
+    let (changes, missing) = tracker.apply_snapshot(&[with_client_id(2, OrderStatus::Open)]);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].order().id, 2);
+    assert_eq!(missing, [1]);
+
+    let change = tracker.update(&with_client_id(1, OrderStatus::Closed));
+    assert!(matches!(change, Some(OrderChange::Closed(_))));
+    assert!(tracker
+        .update(&with_client_id(1, OrderStatus::Closed))
+        .is_none());
+    assert!(tracker.get_by_client_id("quote-1").is_none());
+    assert_eq!(tracker.status("quote-1"), Some(OrderStatus::Closed));
+    assert_eq!(tracker.open_orders_for("BTC-PERP").count(), 1);
+    assert_eq!(tracker.status("unknown"), None);
+}
+
+#[test]
+fn strategy_actions() {
+    // Replaces its resting order on every fill
+    struct Requote;
+
+    impl Strategy for Requote {
+        fn on_order_update(&mut self, ctx: &mut Context, order: &OrderInfo) {
+            let open: Vec<Id> = ctx
+                .orders()
+                .open_orders(Some(&order.market))
+                .map(|o| o.id)
+                .collect();
+            for id in open {
+                ctx.cancel_order(id);
+            }
+            ctx.place_order(NewOrder::limit(
+                &order.market,
+                Side::Sell,
+                dec!(101),
+                dec!(1),
+            ));
+        }
+    }
+
+    let mut tracker = OrderTracker::new();
+    tracker.update(&order(1, "BTC-PERP", OrderStatus::Open));
+    let mut ctx = Context::new(chrono::Utc::now(), &tracker);
+    Requote.on_order_update(&mut ctx, &order(1, "BTC-PERP", OrderStatus::Open));
+    // Callbacks that are not implemented do nothing
+    Requote.on_timer(&mut ctx);
+
+    let actions = ctx.into_actions();
+    assert_eq!(actions.len(), 2);
+    assert!(matches!(actions[0], Action::Cancel(1)));
+    match &actions[1] {
+        Action::Place(order) => {
+            let place = order.as_place_order();
+            assert_eq!(place.market, "BTC-PERP");
+            assert_eq!(place.price, Some(dec!(101)));
+            assert_eq!(place.r#type, OrderType::Limit);
+        }
+        action => panic!("unexpected action {:?}", action),
+    }
+}
+
+fn trade(price: Decimal, size: Decimal, second: u64) -> Trade {
+    Trade {
+        id: second,
+        liquidation: false,
+        price,
+        side: Side::Buy,
+        size,
+        time: chrono::DateTime::parse_from_rfc3339("2022-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+            + chrono::Duration::seconds(second as i64),
+    }
+}
+
+#[test]
+fn backtest_trades() {
+    // Buys at market once, then offers the position one dollar higher
+    #[derive(Default)]
+    struct RoundTrip {
+        bought: bool,
+        fills: usize,
+    }
+
+    impl Strategy for RoundTrip {
+        fn on_trade(&mut self, ctx: &mut Context, market: &str, _trade: &Trade) {
+            if !self.bought {
+                self.bought = true;
+                ctx.place_order(NewOrder::market(market, Side::Buy, dec!(2)));
+            }
+        }
+
+        fn on_fill(&mut self, ctx: &mut Context, fill: &Fill) {
+            self.fills += 1;
+            if fill.side == Side::Buy {
+                let market = fill.market.as_deref().unwrap();
+                ctx.place_order(NewOrder::limit(
+                    market,
+                    Side::Sell,
+                    fill.price + dec!(1),
+                    dec!(2),
+                ));
+            }
+        }
+    }
+
+    let trades = vec![
+        trade(dec!(100), dec!(5), 0),
+        trade(dec!(100.5), dec!(5), 1),
+        // Trades at the limit price do not fill the resting order
+        trade(dec!(101), dec!(5), 2),
+        trade(dec!(101.5), dec!(1), 3),
+        trade(dec!(102), dec!(5), 4),
+    ];
+    let events = trade_events("BTC-PERP", &trades);
+
+    let mut backtester = Backtester::new(RoundTrip::default())
+        .latency(std::time::Duration::from_millis(500))
+        .fees(FeeModel {
+            maker: dec!(0),
+            taker: dec!(0.001),
+        });
+    let report = backtester.run(events);
+
+    // The market order arrives before the second trade
+    let prices: Vec<(Liquidity, Decimal, Decimal)> = report
+        .fills
+        .iter()
+        .map(|fill| (fill.liquidity, fill.price, fill.size))
+        .collect();
+    assert_eq!(
+        prices,
+        vec![
+            (Liquidity::Taker, dec!(100), dec!(2)),
+            (Liquidity::Maker, dec!(101), dec!(1)),
+            (Liquidity::Maker, dec!(101), dec!(1)),
+        ]
+    );
+    assert_eq!(backtester.strategy().fills, 3);
+    assert_eq!(report.positions["BTC-PERP"], dec!(0));
+    assert_eq!(report.fees, dec!(0.2));
+    assert_eq!(report.pnl, dec!(1.8));
+}
+
+#[test]
+fn candle_replay() {
+    let candles: Vec<Candle> = serde_json::from_value(json!([{
+        "close": 95.0,
+        "high": 110.0,
+        "low": 90.0,
+        "open": 100.0,
+        "volume": 4000.0,
+        "startTime": "2022-01-01T00:00:00+00:00",
+        "time": 1640995200000.0,
+    }]))
+    .unwrap();
+    let events = candle_events("BTC-PERP", &candles, chrono::Duration::minutes(1));
+    let prices: Vec<Decimal> = events
+        .iter()
+        .map(|event| match &event.data {
+            MarketEvent::Trade(trade) => trade.price,
+            data => panic!("unexpected event {:?}", data),
+        })
+        .collect();
+    // A falling candle is assumed to reach its high before its low
+    assert_eq!(prices, vec![dec!(100), dec!(110), dec!(90), dec!(95)]);
+    assert_eq!(
+        events[3].time - events[0].time,
+        chrono::Duration::seconds(45)
+    );
+}
+
+#[test]
+fn backtest_queue_position() {
+    // Joins the best bid once
+    #[derive(Default)]
+    struct JoinBid {
+        placed: bool,
+    }
+
+    impl Strategy for JoinBid {
+        fn on_book(&mut self, ctx: &mut Context, market: &str, book: &Orderbook) {
+            if !self.placed {
+                self.placed = true;
+                let bid = *book.bid_price().unwrap();
+                ctx.place_order(NewOrder::limit(market, Side::Buy, bid, dec!(1)));
+            }
+        }
+    }
+
+    let time = trade(dec!(100), dec!(0), 0).time;
+    let book = OrderbookData {
+        action: OrderbookAction::Partial,
+        bids: vec![(dec!(100), dec!(3))],
+        asks: vec![(dec!(101), dec!(2))],
+        checksum: crc32fast::hash(b"100.0:3.0:101.0:2.0"),
+        time,
+    };
+    let sell = |second| Trade {
+        side: Side::Sell,
+        ..trade(dec!(100), dec!(2), second)
+    };
+    let events = || {
+        let mut events = vec![Event {
+            time,
+            market: "BTC-PERP".to_owned(),
+            data: MarketEvent::Book(book.clone()),
+        }];
+        events.extend(trade_events("BTC-PERP", &[sell(1), sell(2)]));
+        events
+    };
+
+    // Trades at the bid do not fill without queue position tracking
+    let report = Backtester::new(JoinBid::default()).run(events());
+    assert!(report.fills.is_empty());
+
+    // The first trade consumes 2 of the 3 queued ahead, the second fills 1
+    let report = Backtester::new(JoinBid::default())
+        .queue_position()
+        .adverse_selection(dec!(0.01))
+        .fees(FeeModel {
+            maker: dec!(0),
+            taker: dec!(0),
+        })
+        .run(events());
+    assert_eq!(report.fills.len(), 1);
+    assert_eq!(report.fills[0].time, sell(2).time);
+    assert_eq!(report.fills[0].size, dec!(1));
+    assert_eq!(report.adverse_selection, dec!(1));
+    assert_eq!(report.pnl, dec!(-1));
+}
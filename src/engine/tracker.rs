@@ -0,0 +1,221 @@
+use crate::options::Options;
+use crate::rest::{GetOpenOrders, GetOrder, Id, OrderInfo, OrderStatus, Rest, Result};
+use crate::ws::{Channel, Data, ReconnectingWs, WsEvent};
+use std::collections::{HashMap, VecDeque};
+
+/// Closed orders remembered for `OrderTracker::status`.
+const CLOSED_HISTORY: usize = 1000;
+
+/// Change of an order recorded by an `OrderTracker`.
+#[derive(Clone, Debug)]
+pub enum OrderChange {
+    /// The order was seen for the first time.
+    Opened(OrderInfo),
+    Updated(OrderInfo),
+    /// The order was filled or cancelled.
+    Closed(OrderInfo),
+}
+
+impl OrderChange {
+    pub fn order(&self) -> &OrderInfo {
+        match self {
+            OrderChange::Opened(order)
+            | OrderChange::Updated(order)
+            | OrderChange::Closed(order) => order,
+        }
+    }
+}
+
+/// Keeps the latest state of open orders from REST responses and websocket
+/// order updates.
+#[derive(Clone, Debug, Default)]
+pub struct OrderTracker {
+    orders: HashMap<Id, OrderInfo>,
+    client_ids: HashMap<String, Id>,
+    /// Most recently closed orders, oldest first
+    closed: VecDeque<OrderInfo>,
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the state of `order`, forgetting it once it is closed.
+    /// Returns the change, or `None` if the state of the order is already
+    /// known.
+    pub fn update(&mut self, order: &OrderInfo) -> Option<OrderChange> {
+        if order.status == OrderStatus::Closed {
+            let known = self.orders.remove(&order.id).is_some();
+            if let Some(client_id) = &order.client_id {
+                self.client_ids.remove(client_id);
+            }
+            if !known && self.closed.iter().any(|closed| closed.id == order.id) {
+                return None;
+            }
+            if self.closed.len() == CLOSED_HISTORY {
+                self.closed.pop_front();
+            }
+            self.closed.push_back(order.clone());
+            Some(OrderChange::Closed(order.clone()))
+        } else {
+            if let Some(client_id) = &order.client_id {
+                self.client_ids.insert(client_id.clone(), order.id);
+            }
+            match self.orders.insert(order.id, order.clone()) {
+                Some(previous) if same_state(&previous, order) => None,
+                Some(_) => Some(OrderChange::Updated(order.clone())),
+                None => Some(OrderChange::Opened(order.clone())),
+            }
+        }
+    }
+
+    /// Records the open orders of a snapshot, e.g. from `GetOpenOrders`.
+    /// Returns the changes and the IDs of tracked orders missing from the
+    /// snapshot, whose final state is unknown.
+    pub fn apply_snapshot(&mut self, orders: &[OrderInfo]) -> (Vec<OrderChange>, Vec<Id>) {
+        let missing = self
+            .orders
+            .keys()
+            .filter(|id| !orders.iter().any(|order| order.id == **id))
+            .copied()
+            .collect();
+        let changes = orders
+            .iter()
+            .filter_map(|order| self.update(order))
+            .collect();
+        (changes, missing)
+    }
+
+    pub fn get(&self, id: Id) -> Option<&OrderInfo> {
+        self.orders.get(&id)
+    }
+
+    /// Open order placed with `client_id`.
+    pub fn get_by_client_id(&self, client_id: &str) -> Option<&OrderInfo> {
+        self.client_ids
+            .get(client_id)
+            .and_then(|id| self.orders.get(id))
+    }
+
+    /// Status of the order placed with `client_id`, if it is open or was
+    /// closed recently.
+    pub fn status(&self, client_id: &str) -> Option<OrderStatus> {
+        self.get_by_client_id(client_id)
+            .or_else(|| {
+                self.closed
+                    .iter()
+                    .rev()
+                    .find(|order| order.client_id.as_deref() == Some(client_id))
+            })
+            .map(|order| order.status)
+    }
+
+    /// Open orders, in `market` only if given.
+    pub fn open_orders<'a>(
+        &'a self,
+        market: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a OrderInfo> + 'a {
+        self.orders
+            .values()
+            .filter(move |order| market.is_none_or(|market| order.market == market))
+    }
+
+    /// Open orders in `market`.
+    pub fn open_orders_for<'a>(
+        &'a self,
+        market: &'a str,
+    ) -> impl Iterator<Item = &'a OrderInfo> + 'a {
+        self.open_orders(Some(market))
+    }
+}
+
+fn same_state(a: &OrderInfo, b: &OrderInfo) -> bool {
+    a.status == b.status
+        && a.price == b.price
+        && a.size == b.size
+        && a.filled_size == b.filled_size
+        && a.remaining_size == b.remaining_size
+}
+
+/// `OrderTracker` kept current from the `orders` websocket channel, seeded
+/// and reconciled with `GetOpenOrders`.
+///
+/// After a lost connection, orders which were closed in the meantime are
+/// looked up with `GetOrder`, so no change is missed.
+pub struct LiveOrderTracker {
+    rest: Rest,
+    ws: ReconnectingWs,
+    tracker: OrderTracker,
+    pending: VecDeque<OrderChange>,
+}
+
+impl LiveOrderTracker {
+    /// Subscribes to the order updates of the account of `options`, then
+    /// records its open orders.
+    pub async fn connect(options: Options) -> Result<Self> {
+        let rest = Rest::try_new(options.clone())?;
+        let mut ws = ReconnectingWs::connect(options).await.map_err(Box::new)?;
+        ws.subscribe(&[Channel::Orders]).await.map_err(Box::new)?;
+        let mut tracker = Self {
+            rest,
+            ws,
+            tracker: OrderTracker::new(),
+            pending: VecDeque::new(),
+        };
+        tracker.reconcile().await?;
+        tracker.pending.clear();
+        Ok(tracker)
+    }
+
+    pub fn orders(&self) -> &OrderTracker {
+        &self.tracker
+    }
+
+    /// Open orders in `market`.
+    pub fn open_orders_for<'a>(
+        &'a self,
+        market: &'a str,
+    ) -> impl Iterator<Item = &'a OrderInfo> + 'a {
+        self.tracker.open_orders_for(market)
+    }
+
+    /// See `OrderTracker::status`.
+    pub fn status(&self, client_id: &str) -> Option<OrderStatus> {
+        self.tracker.status(client_id)
+    }
+
+    /// Waits for the next change of an order and records it.
+    pub async fn next_change(&mut self) -> Result<OrderChange> {
+        loop {
+            if let Some(change) = self.pending.pop_front() {
+                return Ok(change);
+            }
+            match self.ws.next_event().await.map_err(Box::new)? {
+                WsEvent::Data(_, Data::Order(order)) => {
+                    if let Some(change) = self.tracker.update(&order) {
+                        return Ok(change);
+                    }
+                }
+                WsEvent::Data(..) | WsEvent::OrderbookReset(_) => {}
+                WsEvent::Disconnected(error) => {
+                    log::warn!("order updates interrupted: {}", error);
+                }
+                WsEvent::Resubscribed => self.reconcile().await?,
+            }
+        }
+    }
+
+    /// Applies a snapshot of the open orders and looks up the orders which
+    /// were closed since, queueing the changes.
+    async fn reconcile(&mut self) -> Result<()> {
+        let orders = self.rest.request(GetOpenOrders::all_market()).await?;
+        let (changes, missing) = self.tracker.apply_snapshot(&orders);
+        self.pending.extend(changes);
+        for id in missing {
+            let order = self.rest.request(GetOrder::new(id)).await?;
+            self.pending.extend(self.tracker.update(&order));
+        }
+        Ok(())
+    }
+}
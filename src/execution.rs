@@ -0,0 +1,561 @@
+//! TWAP and iceberg execution of large orders, built with the `ws` feature.
+//!
+//! An `Execution` works a parent order through one child limit order at a
+//! time. It follows a schedule releasing the size in equal slices over the
+//! duration, joins the best price on its side of the book and reprices the
+//! child order with `ModifyOrder` as the ticker moves. Sizes are capped by an
+//! optional display size and share of the quoted size. Once the duration of
+//! a TWAP is over, the rest is bought at the ask or sold at the bid.
+//!
+//! `Executor` drives an `Execution` with the ticker and order channels of
+//! the websocket and sends its orders with the REST client.
+
+use crate::options::Options;
+use crate::rest::{
+    CancelOrder, GetMarket, GetOrder, Id, MarketRules, ModifyOrder, OrderInfo, OrderStatus,
+    OrderType, PlaceOrder, Rest, Result, Side, Symbol,
+};
+use crate::ws::{Channel, Data, ReconnectingWs, Ticker, WsEvent};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time;
+
+/// Parent order worked by an `Execution`.
+#[derive(Clone, Debug)]
+pub struct ExecutionParams {
+    pub market: Symbol,
+    pub side: Side,
+    pub size: Decimal,
+    /// Time over which the size is released, zero to release it at once
+    pub duration: Duration,
+    /// Number of equal parts the size is released in
+    pub slices: u32,
+    /// Largest size of a child order, shown in the book
+    pub display_size: Option<Decimal>,
+    /// Largest share of the size quoted at the best price on the side of
+    /// the order a child order may have, e.g. 0.1 for 10%
+    pub participation: Option<Decimal>,
+    /// Highest price to buy or lowest price to sell at
+    pub limit_price: Option<Decimal>,
+}
+
+impl ExecutionParams {
+    /// Releases `size` in `slices` equal parts over `duration`.
+    pub fn twap(market: &str, side: Side, size: Decimal, duration: Duration, slices: u32) -> Self {
+        Self {
+            market: market.to_owned(),
+            side,
+            size,
+            duration,
+            slices: slices.max(1),
+            display_size: None,
+            participation: None,
+            limit_price: None,
+        }
+    }
+
+    /// Releases `size` at once, showing at most `display_size` of it.
+    pub fn iceberg(market: &str, side: Side, size: Decimal, display_size: Decimal) -> Self {
+        Self::twap(market, side, size, Duration::ZERO, 1).display_size(display_size)
+    }
+
+    #[must_use]
+    pub fn display_size(mut self, display_size: Decimal) -> Self {
+        self.display_size = Some(display_size);
+        self
+    }
+
+    #[must_use]
+    pub fn participation(mut self, participation: Decimal) -> Self {
+        self.participation = Some(participation);
+        self
+    }
+
+    #[must_use]
+    pub fn limit_price(mut self, limit_price: Decimal) -> Self {
+        self.limit_price = Some(limit_price);
+        self
+    }
+}
+
+/// Order requested by an `Execution`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecutionAction {
+    Place {
+        price: Decimal,
+        size: Decimal,
+    },
+    /// Replace the child order `id` with `ModifyOrder`. The size is capped
+    /// so that the parent order is not overfilled if the replaced order
+    /// fills in full, cancelling the child order instead if nothing is left.
+    Modify {
+        id: Id,
+        price: Decimal,
+        size: Decimal,
+    },
+    Cancel(Id),
+}
+
+/// Fill progress of an `Execution`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExecutionProgress {
+    pub filled: Decimal,
+    pub size: Decimal,
+    /// Size released by the schedule so far
+    pub scheduled: Decimal,
+    /// Average price of the fills, `None` before the first fill
+    pub average_price: Option<Decimal>,
+}
+
+impl ExecutionProgress {
+    pub fn remaining(&self) -> Decimal {
+        self.size - self.filled
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.filled >= self.size
+    }
+}
+
+/// Child order being worked.
+#[derive(Clone, Copy, Debug)]
+struct Child {
+    id: Id,
+    price: Decimal,
+    remaining: Decimal,
+}
+
+/// State of a TWAP or iceberg execution, deciding the child orders from
+/// ticker and order updates. See the module documentation.
+#[derive(Clone, Debug)]
+pub struct Execution {
+    params: ExecutionParams,
+    rules: MarketRules,
+    start: DateTime<Utc>,
+    child: Option<Child>,
+    /// Filled size and average price per child order
+    fills: HashMap<Id, (Decimal, Decimal)>,
+}
+
+impl Execution {
+    pub fn new(params: ExecutionParams, rules: MarketRules, start: DateTime<Utc>) -> Self {
+        Self {
+            params,
+            rules,
+            start,
+            child: None,
+            fills: HashMap::new(),
+        }
+    }
+
+    pub fn params(&self) -> &ExecutionParams {
+        &self.params
+    }
+
+    /// ID of the child order being worked.
+    pub fn child_order(&self) -> Option<Id> {
+        self.child.map(|child| child.id)
+    }
+
+    pub fn progress(&self, now: DateTime<Utc>) -> ExecutionProgress {
+        let filled: Decimal = self.fills.values().map(|(size, _)| size).sum();
+        let notional: Decimal = self.fills.values().map(|(size, price)| size * price).sum();
+        ExecutionProgress {
+            filled,
+            size: self.params.size,
+            scheduled: self.scheduled(now),
+            average_price: (!filled.is_zero()).then(|| notional / filled),
+        }
+    }
+
+    /// Size released by the schedule at `now`, including the current slice.
+    fn scheduled(&self, now: DateTime<Utc>) -> Decimal {
+        let elapsed = (now - self.start).to_std().unwrap_or_default();
+        if elapsed >= self.params.duration {
+            return self.params.size;
+        }
+        let slice = self.params.duration / self.params.slices;
+        let slices = (elapsed.as_nanos() / slice.as_nanos().max(1)) as u32 + 1;
+        self.params.size * Decimal::from(slices.min(self.params.slices))
+            / Decimal::from(self.params.slices)
+    }
+
+    /// Records an update of a child order, e.g. from the `orders` channel.
+    /// Updates of other orders are ignored.
+    pub fn on_order(&mut self, order: &OrderInfo) {
+        let filled = order.filled_size.unwrap_or_default();
+        let fill = match self.fills.get_mut(&order.id) {
+            Some(fill) => fill,
+            None => return,
+        };
+        *fill = (filled.max(fill.0), order.avg_fill_price.unwrap_or(fill.1));
+        if let Some(child) = &mut self.child {
+            if child.id == order.id {
+                if order.status == OrderStatus::Closed {
+                    self.child = None;
+                } else {
+                    child.remaining = order.size - filled;
+                }
+            }
+        }
+    }
+
+    /// Records the child order placed or modified for the last action.
+    pub fn on_placed(&mut self, order: &OrderInfo) {
+        self.fills.entry(order.id).or_default();
+        self.child = Some(Child {
+            id: order.id,
+            price: order.price.unwrap_or_default(),
+            remaining: order.size,
+        });
+        self.on_order(order);
+    }
+
+    /// Forgets the child order, e.g. after it could not be modified. Its
+    /// fills are still recorded through `on_order`.
+    pub fn clear_child(&mut self) {
+        self.child = None;
+    }
+
+    /// Next order to send after the ticker moved to `ticker` or time passed,
+    /// if any.
+    pub fn next_action(&self, now: DateTime<Utc>, ticker: &Ticker) -> Option<ExecutionAction> {
+        let progress = self.progress(now);
+        if progress.is_done() {
+            return self.child.map(|child| ExecutionAction::Cancel(child.id));
+        }
+
+        // Icebergs without a duration stay passive
+        let elapsed = (now - self.start).to_std().unwrap_or_default();
+        let late = !self.params.duration.is_zero() && elapsed >= self.params.duration;
+        let (best, quoted) = match (self.params.side, late) {
+            (Side::Buy, false) => (ticker.bid, ticker.bid_size),
+            (Side::Sell, false) => (ticker.ask, ticker.ask_size),
+            (Side::Buy, true) => (ticker.ask, ticker.ask_size),
+            (Side::Sell, true) => (ticker.bid, ticker.bid_size),
+        };
+        let price = match (self.params.side, self.params.limit_price) {
+            (Side::Buy, Some(limit)) => best.min(limit),
+            (Side::Sell, Some(limit)) => best.max(limit),
+            (_, None) => best,
+        };
+        let price = self.rules.round_price_for(self.params.side, price);
+
+        let mut size = progress.scheduled - progress.filled;
+        if let Some(display_size) = self.params.display_size {
+            size = size.min(display_size);
+        }
+        if let Some(participation) = self.params.participation {
+            size = size.min(quoted * participation);
+        }
+        let size = self.rules.round_size(size);
+
+        match self.child {
+            Some(child) if price != child.price || size > child.remaining => {
+                // The replaced order may still fill before it is cancelled
+                let unreserved = self.params.size - progress.filled - child.remaining;
+                let size = size
+                    .max(child.remaining)
+                    .min(self.rules.round_size(unreserved));
+                if size.is_zero() {
+                    Some(ExecutionAction::Cancel(child.id))
+                } else {
+                    Some(ExecutionAction::Modify {
+                        id: child.id,
+                        price,
+                        size,
+                    })
+                }
+            }
+            Some(_) => None,
+            None if size.is_zero() => None,
+            None => Some(ExecutionAction::Place { price, size }),
+        }
+    }
+}
+
+/// Runs an `Execution` with live data, see the module documentation.
+pub struct Executor {
+    rest: Rest,
+    ws: ReconnectingWs,
+    execution: Execution,
+    ticker: Option<Ticker>,
+    /// Interval of checking the schedule without ticker updates
+    interval: Duration,
+}
+
+impl Executor {
+    /// Subscribes to the ticker of the market and the order updates of the
+    /// account, and starts the execution.
+    pub async fn start(options: Options, params: ExecutionParams) -> Result<Self> {
+        let rest = Rest::try_new(options.clone())?;
+        let rules = rest.request(GetMarket::new(&params.market)).await?.rules();
+        let mut ws = ReconnectingWs::connect(options).await.map_err(Box::new)?;
+        ws.subscribe(&[Channel::Ticker(params.market.clone()), Channel::Orders])
+            .await
+            .map_err(Box::new)?;
+        let interval = (params.duration / params.slices / 10)
+            .clamp(Duration::from_millis(100), Duration::from_secs(1));
+        Ok(Self {
+            rest,
+            ws,
+            execution: Execution::new(params, rules, Utc::now()),
+            ticker: None,
+            interval,
+        })
+    }
+
+    pub fn execution(&self) -> &Execution {
+        &self.execution
+    }
+
+    /// Handles the next ticker or order update, sending the orders the
+    /// execution requests, and returns the progress.
+    pub async fn next_progress(&mut self) -> Result<ExecutionProgress> {
+        if let Ok(event) = time::timeout(self.interval, self.ws.next_event()).await {
+            match event.map_err(Box::new)? {
+                WsEvent::Data(_, Data::Ticker(ticker)) => self.ticker = Some(ticker),
+                WsEvent::Data(_, Data::Order(order)) => self.execution.on_order(&order),
+                WsEvent::Data(..) | WsEvent::OrderbookReset(_) => {}
+                WsEvent::Disconnected(error) => {
+                    log::warn!("execution updates interrupted: {}", error);
+                }
+                WsEvent::Resubscribed => {
+                    // Fills may have been missed while disconnected
+                    if let Some(id) = self.execution.child_order() {
+                        let order = self.rest.request(GetOrder::new(id)).await?;
+                        self.execution.on_order(&order);
+                    }
+                }
+            }
+        }
+
+        if let Some(ticker) = self.ticker {
+            if let Some(action) = self.execution.next_action(Utc::now(), &ticker) {
+                self.send(action).await?;
+            }
+        }
+        Ok(self.execution.progress(Utc::now()))
+    }
+
+    /// Works the order until it is filled, returning the final progress.
+    pub async fn run(mut self) -> Result<ExecutionProgress> {
+        loop {
+            let progress = self.next_progress().await?;
+            if progress.is_done() && self.execution.child_order().is_none() {
+                return Ok(progress);
+            }
+        }
+    }
+
+    /// Stops the execution, cancelling the child order.
+    pub async fn cancel(self) -> Result<ExecutionProgress> {
+        if let Some(id) = self.execution.child_order() {
+            self.rest.request(CancelOrder::new(id)).await?;
+        }
+        Ok(self.execution.progress(Utc::now()))
+    }
+
+    async fn send(&mut self, action: ExecutionAction) -> Result<()> {
+        let params = self.execution.params();
+        match action {
+            ExecutionAction::Place { price, size } => {
+                let order = self
+                    .rest
+                    .request(PlaceOrder {
+                        market: &params.market,
+                        side: params.side,
+                        price: Some(price),
+                        r#type: OrderType::Limit,
+                        size,
+                        ..Default::default()
+                    })
+                    .await?;
+                self.execution.on_placed(&order);
+            }
+            ExecutionAction::Modify { id, price, size } => {
+                let modified = self
+                    .rest
+                    .request(ModifyOrder {
+                        id,
+                        price: Some(price),
+                        size: Some(size),
+                        client_id: None,
+                    })
+                    .await;
+                match modified {
+                    Ok(order) => self.execution.on_placed(&order),
+                    // Most likely filled or cancelled in the meantime. Kept
+                    // as the child order while still open.
+                    Err(error) => {
+                        log::warn!("failed to modify child order {}: {}", id, error);
+                        let order = self.rest.request(GetOrder::new(id)).await?;
+                        self.execution.on_order(&order);
+                    }
+                }
+            }
+            ExecutionAction::Cancel(id) => {
+                if let Err(error) = self.rest.request(CancelOrder::new(id)).await {
+                    log::warn!("failed to cancel child order {}: {}", id, error);
+                }
+                self.execution.clear_child();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    #[test]
+    fn twap_execution() {
+        use chrono::{Duration, TimeZone};
+
+        let order = |id: u64, price: f64, size: f64, filled: f64, status: &str| -> OrderInfo {
+            serde_json::from_value(json!({
+                "id": id,
+                "market": "BTC-PERP",
+                "future": "BTC-PERP",
+                "type": "limit",
+                "side": "buy",
+                "price": price,
+                "size": size,
+                "status": status,
+                "filledSize": filled,
+                "remainingSize": size - filled,
+                "avgFillPrice": (filled > 0.0).then_some(price),
+                "createdAt": "2022-01-01T00:00:00.000000+00:00",
+                "clientId": null,
+            }))
+            .unwrap()
+        };
+        let ticker = |bid, ask| Ticker {
+            bid,
+            ask,
+            bid_size: dec!(100),
+            ask_size: dec!(100),
+            last: bid,
+            time: Utc::now(),
+        };
+        let rules = MarketRules {
+            price_increment: dec!(0.5),
+            size_increment: dec!(0.001),
+            min_provide_size: dec!(0.001),
+        };
+        let start = Utc.timestamp_opt(1_640_995_200, 0).unwrap();
+        let at = |seconds| start + Duration::seconds(seconds);
+
+        // 10 over 100 seconds in 4 slices, joining the bid
+        let params = ExecutionParams::twap(
+            "BTC-PERP",
+            Side::Buy,
+            dec!(10),
+            std::time::Duration::from_secs(100),
+            4,
+        );
+        let mut execution = Execution::new(params, rules, start);
+        assert_eq!(
+            execution.next_action(at(0), &ticker(dec!(100), dec!(101))),
+            Some(ExecutionAction::Place {
+                price: dec!(100),
+                size: dec!(2.5)
+            })
+        );
+        execution.on_placed(&order(1, 100.0, 2.5, 0.0, "open"));
+        assert_eq!(
+            execution.next_action(at(1), &ticker(dec!(100), dec!(101))),
+            None
+        );
+
+        // Repriced as the bid moves, keeping the fills of the replaced order
+        assert_eq!(
+            execution.next_action(at(2), &ticker(dec!(100.5), dec!(101))),
+            Some(ExecutionAction::Modify {
+                id: 1,
+                price: dec!(100.5),
+                size: dec!(2.5)
+            })
+        );
+        execution.on_placed(&order(2, 100.5, 2.5, 0.0, "open"));
+        execution.on_order(&order(1, 100.0, 2.5, 1.0, "closed"));
+        assert_eq!(execution.child_order(), Some(2));
+        assert_eq!(execution.progress(at(2)).filled, dec!(1));
+
+        // The next slice grows the child order
+        assert_eq!(
+            execution.next_action(at(30), &ticker(dec!(100.5), dec!(101))),
+            Some(ExecutionAction::Modify {
+                id: 2,
+                price: dec!(100.5),
+                size: dec!(4)
+            })
+        );
+        execution.on_placed(&order(3, 100.5, 4.0, 0.0, "open"));
+        execution.on_order(&order(3, 100.5, 4.0, 4.0, "closed"));
+        assert_eq!(execution.child_order(), None);
+
+        // Late, the rest crosses the spread up to the limit price
+        let progress = execution.progress(at(100));
+        assert_eq!(progress.filled, dec!(5));
+        assert_eq!(progress.scheduled, dec!(10));
+        assert_eq!(progress.average_price, Some(dec!(100.4)));
+        assert_eq!(
+            execution.next_action(at(100), &ticker(dec!(101), dec!(102))),
+            Some(ExecutionAction::Place {
+                price: dec!(102),
+                size: dec!(5)
+            })
+        );
+        let capped = Execution::new(
+            execution.params().clone().limit_price(dec!(101.7)),
+            rules,
+            start,
+        );
+        assert_eq!(
+            capped.next_action(at(100), &ticker(dec!(101), dec!(102))),
+            Some(ExecutionAction::Place {
+                price: dec!(101.5),
+                size: dec!(10)
+            })
+        );
+        execution.on_placed(&order(4, 102.0, 5.0, 5.0, "open"));
+        assert!(execution.progress(at(101)).is_done());
+        assert_eq!(
+            execution.next_action(at(101), &ticker(dec!(101), dec!(102))),
+            Some(ExecutionAction::Cancel(4))
+        );
+
+        // Cancelled instead of replaced once the child order holds the rest
+        let mut execution = Execution::new(execution.params().clone(), rules, start);
+        execution.on_placed(&order(5, 102.0, 10.0, 0.0, "open"));
+        assert_eq!(
+            execution.next_action(at(100), &ticker(dec!(102), dec!(103))),
+            Some(ExecutionAction::Cancel(5))
+        );
+
+        // Icebergs show at most the display size, and a share of the bid
+        let iceberg = ExecutionParams::iceberg("BTC-PERP", Side::Buy, dec!(10), dec!(3));
+        let execution = Execution::new(iceberg.clone(), rules, start);
+        assert_eq!(
+            execution.next_action(at(0), &ticker(dec!(100), dec!(101))),
+            Some(ExecutionAction::Place {
+                price: dec!(100),
+                size: dec!(3)
+            })
+        );
+        let execution = Execution::new(iceberg.participation(dec!(0.01)), rules, start);
+        assert_eq!(
+            execution.next_action(at(0), &ticker(dec!(100), dec!(101))),
+            Some(ExecutionAction::Place {
+                price: dec!(100),
+                size: dec!(1)
+            })
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! Export of account history to CSV, or Parquet with the `parquet` feature,
+//! e.g. for tax reporting or research.
+//!
+//! The `export_*` functions download all rows of a time range, paginating as
+//! needed, and stream them newest first to a `RowWriter`. Decimals are
+//! written as sent by the exchange, without a round trip through floats, and
+//! timestamps in RFC 3339. Columns are named like the fields of the JSON
+//! responses; missing values are left empty, or null in Parquet.
+
+use crate::rest::{
+    self, Fill, FundingPayment, GetFills, GetFundingPayments, GetOrderHistory, OrderInfo, Rest,
+};
+use chrono::{DateTime, Utc};
+use futures::{pin_mut, Stream, TryStreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use std::io::{self, Write};
+use thiserror::Error;
+
+/// Number of fills requested per `GetFills` page.
+const FILLS_PAGE_SIZE: usize = 5000;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error(transparent)]
+    Rest(#[from] rest::Error),
+
+    #[error("failed to write export: {0}")]
+    Io(#[from] io::Error),
+
+    #[cfg(feature = "parquet")]
+    #[error("failed to write parquet: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+}
+
+/// Model which can be exported.
+pub trait ExportRow: Serialize {
+    /// Fields written, by their names in the JSON responses
+    const COLUMNS: &'static [&'static str];
+}
+
+impl ExportRow for Fill {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "time",
+        "market",
+        "future",
+        "baseCurrency",
+        "quoteCurrency",
+        "type",
+        "side",
+        "price",
+        "size",
+        "orderId",
+        "tradeId",
+        "fee",
+        "feeRate",
+        "feeCurrency",
+        "liquidity",
+    ];
+}
+
+impl ExportRow for OrderInfo {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "createdAt",
+        "market",
+        "type",
+        "side",
+        "price",
+        "size",
+        "status",
+        "filledSize",
+        "remainingSize",
+        "avgFillPrice",
+        "reduceOnly",
+        "ioc",
+        "postOnly",
+        "liquidation",
+        "clientId",
+    ];
+}
+
+impl ExportRow for FundingPayment {
+    const COLUMNS: &'static [&'static str] = &["id", "time", "future", "payment", "rate"];
+}
+
+/// Destination of exported rows, see `CsvWriter` and `ParquetWriter`.
+pub trait RowWriter<T: ExportRow> {
+    fn write_row(&mut self, row: &T) -> Result<(), ExportError>;
+
+    /// Writes anything still buffered. Rows written after an error, or
+    /// without calling `finish`, may be incomplete.
+    fn finish(self) -> Result<(), ExportError>;
+}
+
+/// Values of the `ExportRow::COLUMNS` of `row`, `None` if missing.
+fn fields<T: ExportRow>(row: &T) -> Result<impl Iterator<Item = Option<String>>, ExportError> {
+    let value = serde_json::to_value(row).map_err(rest::Error::from)?;
+    Ok(T::COLUMNS.iter().map(move |column| match &value[column] {
+        Value::Null => None,
+        Value::String(string) => Some(string.clone()),
+        value => Some(value.to_string()),
+    }))
+}
+
+/// Writes rows as CSV with a header line.
+#[derive(Debug)]
+pub struct CsvWriter<W: Write> {
+    writer: W,
+    header: bool,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            header: false,
+        }
+    }
+
+    fn write_header<T: ExportRow>(&mut self) -> io::Result<()> {
+        if !self.header {
+            self.header = true;
+            write_record(
+                &mut self.writer,
+                T::COLUMNS.iter().map(|column| column.to_string()),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: ExportRow, W: Write> RowWriter<T> for CsvWriter<W> {
+    fn write_row(&mut self, row: &T) -> Result<(), ExportError> {
+        self.write_header::<T>()?;
+        write_record(
+            &mut self.writer,
+            fields(row)?.map(Option::unwrap_or_default),
+        )?;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), ExportError> {
+        self.write_header::<T>()?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes `rows` as CSV with a header line.
+pub fn write_csv<T: ExportRow>(rows: &[T], writer: impl Write) -> Result<(), ExportError> {
+    let mut csv = CsvWriter::new(writer);
+    for row in rows {
+        csv.write_row(row)?;
+    }
+    RowWriter::<T>::finish(csv)
+}
+
+fn write_record(writer: &mut impl Write, fields: impl Iterator<Item = String>) -> io::Result<()> {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        if field.contains(['"', ',', '\n', '\r']) {
+            write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(field.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\r\n")
+}
+
+/// Writes rows to a Parquet file, with a nullable string column per
+/// `ExportRow::COLUMNS` entry to keep decimals exact. Rows are written in
+/// row groups of `PARQUET_BATCH_ROWS`.
+#[cfg(feature = "parquet")]
+pub struct ParquetWriter<T, W: Write + Send> {
+    writer: parquet::arrow::ArrowWriter<W>,
+    schema: arrow_schema::SchemaRef,
+    columns: Vec<arrow_array::builder::StringBuilder>,
+    rows: usize,
+    row: std::marker::PhantomData<fn(&T)>,
+}
+
+/// Rows buffered by a `ParquetWriter` before they are written.
+#[cfg(feature = "parquet")]
+pub const PARQUET_BATCH_ROWS: usize = 8192;
+
+#[cfg(feature = "parquet")]
+impl<T: ExportRow, W: Write + Send> ParquetWriter<T, W> {
+    pub fn new(writer: W) -> Result<Self, ExportError> {
+        use arrow_schema::{DataType, Field, Schema};
+
+        let schema = std::sync::Arc::new(Schema::new(
+            T::COLUMNS
+                .iter()
+                .map(|column| Field::new(*column, DataType::Utf8, true))
+                .collect::<Vec<_>>(),
+        ));
+        Ok(Self {
+            writer: parquet::arrow::ArrowWriter::try_new(writer, schema.clone(), None)?,
+            schema,
+            columns: T::COLUMNS.iter().map(|_| Default::default()).collect(),
+            rows: 0,
+            row: std::marker::PhantomData,
+        })
+    }
+
+    fn write_batch(&mut self) -> Result<(), ExportError> {
+        use arrow_array::{ArrayRef, RecordBatch};
+
+        let columns = self
+            .columns
+            .iter_mut()
+            .map(|column| std::sync::Arc::new(column.finish()) as ArrayRef)
+            .collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)
+            .map_err(parquet::errors::ParquetError::from)?;
+        self.writer.write(&batch)?;
+        self.rows = 0;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl<T: ExportRow, W: Write + Send> RowWriter<T> for ParquetWriter<T, W> {
+    fn write_row(&mut self, row: &T) -> Result<(), ExportError> {
+        for (column, field) in self.columns.iter_mut().zip(fields(row)?) {
+            column.append_option(field);
+        }
+        self.rows += 1;
+        if self.rows == PARQUET_BATCH_ROWS {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), ExportError> {
+        if self.rows > 0 {
+            self.write_batch()?;
+        }
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+/// Writes `rows` to `output` as they arrive. Returns the number of rows.
+async fn export<T: ExportRow>(
+    rows: impl Stream<Item = rest::Result<T>>,
+    mut output: impl RowWriter<T>,
+) -> Result<usize, ExportError> {
+    pin_mut!(rows);
+    let mut count = 0;
+    while let Some(row) = rows.try_next().await? {
+        output.write_row(&row)?;
+        count += 1;
+    }
+    output.finish()?;
+    Ok(count)
+}
+
+/// Writes the fills between `start_time` and `end_time`, in `market` only
+/// if given. Returns the number of fills.
+pub async fn export_fills(
+    rest: &Rest,
+    market: Option<&str>,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    output: impl RowWriter<Fill>,
+) -> Result<usize, ExportError> {
+    let fills = rest.paginate(GetFills {
+        market_name: market,
+        start_time: Some(start_time),
+        end_time: Some(end_time),
+        limit: Some(FILLS_PAGE_SIZE),
+        ..Default::default()
+    });
+    export(fills, output).await
+}
+
+/// Writes the orders created between `start_time` and `end_time`, in
+/// `market` only if given. Returns the number of orders.
+pub async fn export_orders(
+    rest: &Rest,
+    market: Option<&str>,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    output: impl RowWriter<OrderInfo>,
+) -> Result<usize, ExportError> {
+    let orders = rest.paginate(GetOrderHistory {
+        market,
+        start_time: Some(start_time),
+        end_time: Some(end_time),
+        ..Default::default()
+    });
+    export(orders, output).await
+}
+
+/// Writes the funding payments between `start_time` and `end_time`, of
+/// `future` only if given. Returns the number of payments.
+pub async fn export_funding_payments(
+    rest: &Rest,
+    future: Option<&str>,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    output: impl RowWriter<FundingPayment>,
+) -> Result<usize, ExportError> {
+    let payments = rest.paginate(GetFundingPayments {
+        future,
+        start_time: Some(start_time),
+        end_time: Some(end_time),
+    });
+    export(payments, output).await
+}
@@ -0,0 +1,410 @@
+//! C ABI, built with the `ffi` feature, for embedding the client in non-Rust
+//! systems. See `include/ftx.h` for the declarations.
+//!
+//! Requests and responses are passed as JSON. Every response is an object of
+//! the form `{"success": true, "result": ...}` or `{"success": false,
+//! "error": "..."}` and must be released with `ftx_string_free`.
+//!
+//! Websocket callbacks run on a thread of their own, outside of the runtime
+//! of the client, so they may make requests. Panics are caught at the
+//! boundary instead of unwinding into the caller.
+
+use crate::options::Options;
+use crate::rest::{RawDelete, RawGet, RawPost, Request, Rest};
+use crate::ws::{Channel, Ws};
+use futures::StreamExt;
+use serde_json::{json, Map, Value};
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use tokio::runtime::{Handle, Runtime};
+use tokio::task::JoinHandle;
+
+/// Opaque client handle.
+pub struct FtxClient {
+    options: Options,
+    rest: Rest,
+    runtime: Runtime,
+}
+
+/// Opaque websocket subscription handle.
+pub struct FtxSubscription {
+    task: JoinHandle<()>,
+    /// Set when freed, so that queued messages are no longer passed on
+    closed: Arc<AtomicBool>,
+}
+
+/// Message passed from the websocket task to the callback thread.
+enum Event {
+    Data(Option<CString>, CString),
+    Error(CString),
+}
+
+/// Called for every websocket message with the market (or null) and the
+/// message data as JSON. Both strings are only valid during the call.
+pub type FtxWsCallback =
+    extern "C" fn(user_data: *mut c_void, market: *const c_char, data: *const c_char);
+
+/// Called once when a websocket subscription ends, with the error as JSON.
+pub type FtxWsErrorCallback = extern "C" fn(user_data: *mut c_void, error: *const c_char);
+
+#[derive(Clone, Copy)]
+struct UserData(*mut c_void);
+
+// The caller guarantees that user data may be used from any thread.
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        None
+    } else {
+        CStr::from_ptr(s).to_str().ok()
+    }
+}
+
+fn to_c_string(value: &Value) -> *mut c_char {
+    CString::new(value.to_string())
+        .map(CString::into_raw)
+        .unwrap_or(ptr::null_mut())
+}
+
+fn error(error: impl ToString) -> *mut c_char {
+    to_c_string(&json!({ "success": false, "error": error.to_string() }))
+}
+
+/// Runs `f`, or `on_panic` if it panics, since unwinding into the caller is
+/// undefined behavior.
+fn catch_panic<T>(f: impl FnOnce() -> T, on_panic: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| on_panic())
+}
+
+fn request<R: Request<Response = Value>>(client: &FtxClient, req: R) -> *mut c_char {
+    // Blocking on the runtime from one of its threads would panic
+    if Handle::try_current().is_ok() {
+        return error("requests cannot be made from within an async runtime");
+    }
+    match client.runtime.block_on(client.rest.request(req)) {
+        Ok(result) => to_c_string(&json!({ "success": true, "result": result })),
+        Err(e) => error(e),
+    }
+}
+
+unsafe fn parse_params(params: *const c_char) -> Result<Map<String, Value>, *mut c_char> {
+    match to_str(params) {
+        None => Ok(Map::new()),
+        Some(params) => serde_json::from_str(params).map_err(error),
+    }
+}
+
+/// Creates a client. `key`, `secret` and `subaccount` may be null for
+/// unauthenticated access. Returns null if the runtime can't be started
+/// or the credentials are invalid.
+///
+/// # Safety
+///
+/// String arguments must be null or valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn ftx_client_new(
+    key: *const c_char,
+    secret: *const c_char,
+    subaccount: *const c_char,
+    us: bool,
+) -> *mut FtxClient {
+    catch_panic(|| client_new(key, secret, subaccount, us), ptr::null_mut)
+}
+
+unsafe fn client_new(
+    key: *const c_char,
+    secret: *const c_char,
+    subaccount: *const c_char,
+    us: bool,
+) -> *mut FtxClient {
+    let mut options = if us {
+        Options::us()
+    } else {
+        Options::default()
+    };
+    if let (Some(key), Some(secret)) = (to_str(key), to_str(secret)) {
+        options = options.authenticate(key.to_owned(), secret.to_owned());
+    }
+    let options = options.subaccount_optional(to_str(subaccount).map(str::to_owned));
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build();
+    match (runtime, Rest::try_new(options.clone())) {
+        (Ok(runtime), Ok(rest)) => Box::into_raw(Box::new(FtxClient {
+            rest,
+            options,
+            runtime,
+        })),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Destroys a client created by `ftx_client_new`.
+///
+/// # Safety
+///
+/// `client` must be null or a handle returned by `ftx_client_new` that has
+/// not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ftx_client_free(client: *mut FtxClient) {
+    if !client.is_null() {
+        let client = Box::from_raw(client);
+        // Dropping the runtime from within another runtime would panic
+        if Handle::try_current().is_ok() {
+            thread::spawn(move || drop(client));
+        } else {
+            catch_panic(|| drop(client), || ());
+        }
+    }
+}
+
+/// Sends a request to `path`, e.g. `"/markets"`. `method` is `"GET"`,
+/// `"POST"` or `"DELETE"`; `params` is a JSON object of query parameters or
+/// the request body, or null.
+///
+/// # Safety
+///
+/// `client` must be a valid handle and the strings null or valid
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn ftx_request(
+    client: *const FtxClient,
+    method: *const c_char,
+    path: *const c_char,
+    params: *const c_char,
+) -> *mut c_char {
+    catch_panic(
+        || raw_request(client, method, path, params),
+        || error("request panicked"),
+    )
+}
+
+unsafe fn raw_request(
+    client: *const FtxClient,
+    method: *const c_char,
+    path: *const c_char,
+    params: *const c_char,
+) -> *mut c_char {
+    let client = match client.as_ref() {
+        Some(client) => client,
+        None => return error("client is null"),
+    };
+    let path = match to_str(path) {
+        Some(path) => path,
+        None => return error("path is null"),
+    };
+    let params = match parse_params(params) {
+        Ok(params) => params,
+        Err(e) => return e,
+    };
+
+    match to_str(method) {
+        Some("GET") => request(client, RawGet::new(path, params)),
+        Some("POST") => request(client, RawPost::new(path, params)),
+        Some("DELETE") => request(client, RawDelete::new(path, params)),
+        _ => error("method must be GET, POST or DELETE"),
+    }
+}
+
+/// Places an order described by the JSON object `order`, using the fields
+/// of the FTX place order endpoint.
+///
+/// # Safety
+///
+/// See `ftx_request`.
+#[no_mangle]
+pub unsafe extern "C" fn ftx_place_order(
+    client: *const FtxClient,
+    order: *const c_char,
+) -> *mut c_char {
+    ftx_request(
+        client,
+        b"POST\0".as_ptr().cast(),
+        b"/orders\0".as_ptr().cast(),
+        order,
+    )
+}
+
+/// Cancels the order with the given ID.
+///
+/// # Safety
+///
+/// `client` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn ftx_cancel_order(client: *const FtxClient, id: u64) -> *mut c_char {
+    let cancel = || match client.as_ref() {
+        Some(client) => request(
+            client,
+            RawDelete::new(format!("/orders/{}", id), Map::new()),
+        ),
+        None => error("client is null"),
+    };
+    catch_panic(cancel, || error("request panicked"))
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library that has not been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ftx_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        catch_panic(|| drop(CString::from_raw(s)), || ());
+    }
+}
+
+/// Subscribes to the websocket channels in the JSON array `channels`, e.g.
+/// `["trades:BTC-PERP", "orderbook:BTC-PERP", "fills"]`. Messages are passed
+/// to `callback` from a thread of the subscription until it is freed or
+/// fails, in which case `on_error` is called once. Returns null if
+/// `channels` is invalid.
+///
+/// # Safety
+///
+/// `client` must be a valid handle that outlives the subscription, and
+/// `user_data` must be usable from any thread.
+#[no_mangle]
+pub unsafe extern "C" fn ftx_ws_subscribe(
+    client: *const FtxClient,
+    channels: *const c_char,
+    callback: FtxWsCallback,
+    on_error: FtxWsErrorCallback,
+    user_data: *mut c_void,
+) -> *mut FtxSubscription {
+    catch_panic(
+        || ws_subscribe(client, channels, callback, on_error, user_data),
+        ptr::null_mut,
+    )
+}
+
+unsafe fn ws_subscribe(
+    client: *const FtxClient,
+    channels: *const c_char,
+    callback: FtxWsCallback,
+    on_error: FtxWsErrorCallback,
+    user_data: *mut c_void,
+) -> *mut FtxSubscription {
+    let client = match client.as_ref() {
+        Some(client) => client,
+        None => return ptr::null_mut(),
+    };
+    let channels: Option<Vec<Channel>> = to_str(channels)
+        .and_then(|channels| serde_json::from_str::<Vec<String>>(channels).ok())
+        .and_then(|channels| channels.iter().map(|c| c.parse().ok()).collect());
+    let channels = match channels {
+        Some(channels) => channels,
+        None => return ptr::null_mut(),
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let closed = Arc::new(AtomicBool::new(false));
+    let user_data = UserData(user_data);
+    let dispatch = {
+        let closed = closed.clone();
+        move || {
+            // Ends once the websocket task is done and drops the sender
+            for event in receiver {
+                if closed.load(Ordering::Acquire) {
+                    break;
+                }
+                match event {
+                    Event::Data(market, data) => callback(
+                        user_data.0,
+                        market
+                            .as_ref()
+                            .map_or(ptr::null(), |market| market.as_ptr()),
+                        data.as_ptr(),
+                    ),
+                    Event::Error(error) => on_error(user_data.0, error.as_ptr()),
+                }
+            }
+        }
+    };
+    if thread::Builder::new()
+        .name("ftx-ws-callbacks".to_owned())
+        .spawn(dispatch)
+        .is_err()
+    {
+        return ptr::null_mut();
+    }
+
+    let options = client.options.clone();
+    let task = client.runtime.spawn(async move {
+        let result = async {
+            let mut ws = Ws::connect(options).await?;
+            ws.subscribe(&channels).await?;
+            while let Some((market, data)) = ws.next().await.transpose()? {
+                let market = market.and_then(|market| CString::new(market).ok());
+                let data = serde_json::to_string(&data)?;
+                if let Ok(data) = CString::new(data) {
+                    if sender.send(Event::Data(market, data)).is_err() {
+                        break;
+                    }
+                }
+            }
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+        };
+        let message = match result.await {
+            Ok(()) => "websocket closed".to_owned(),
+            Err(e) => e.to_string(),
+        };
+        let error = json!({ "success": false, "error": message }).to_string();
+        if let Ok(error) = CString::new(error) {
+            let _ = sender.send(Event::Error(error));
+        }
+    });
+
+    Box::into_raw(Box::new(FtxSubscription { task, closed }))
+}
+
+/// Ends and releases a websocket subscription.
+///
+/// # Safety
+///
+/// `subscription` must be null or a handle returned by `ftx_ws_subscribe`
+/// that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ftx_ws_free(subscription: *mut FtxSubscription) {
+    if !subscription.is_null() {
+        catch_panic(
+            || {
+                let subscription = Box::from_raw(subscription);
+                subscription.closed.store(true, Ordering::Release);
+                subscription.task.abort();
+            },
+            || (),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn request_within_runtime() {
+        unsafe {
+            let client = ftx_client_new(ptr::null(), ptr::null(), ptr::null(), false);
+            assert!(!client.is_null());
+            let response = ftx_request(
+                client,
+                b"GET\0".as_ptr().cast(),
+                b"/markets\0".as_ptr().cast(),
+                ptr::null(),
+            );
+            let json: Value = serde_json::from_str(to_str(response).unwrap()).unwrap();
+            assert_eq!(json["success"], false);
+            ftx_string_free(response);
+            ftx_client_free(client);
+        }
+    }
+}
@@ -0,0 +1,441 @@
+//! gRPC gateway, built with the `grpc-gateway` feature, exposing orders,
+//! balances, positions and market data streams of a single account to other
+//! services. See `proto/gateway.proto` for the API.
+
+use crate::options::Options;
+use crate::rest::{
+    ApiError, CancelOrder, GetOpenOrders, GetPositions, GetWalletBalances, OrderInfo, OrderType,
+    PlaceOrder, Rest, Side, TimeInForce,
+};
+use crate::ws::{Channel, Data, Orderbook, ReconnectingWs, Symbol, WsEvent};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, oneshot, OnceCell};
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tonic::{Request, Response, Status};
+
+/// Generated protobuf types and service definitions.
+pub mod proto {
+    tonic::include_proto!("ftx.gateway");
+}
+
+use proto::gateway_server::{Gateway as GatewayService, GatewayServer};
+
+/// Number of price levels sent per side in orderbook updates.
+const ORDERBOOK_DEPTH: usize = 25;
+
+/// Number of stream messages buffered per client.
+const STREAM_BUFFER: usize = 1024;
+
+/// Number of pending stream subscriptions.
+const SUBSCRIPTION_BUFFER: usize = 64;
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+fn to_status(error: crate::rest::Error) -> Status {
+    match error {
+        crate::rest::Error::Config(message) => Status::invalid_argument(message),
+        crate::rest::Error::Exchange { error, message, .. } => match error {
+            ApiError::InvalidSignature | ApiError::InvalidApiKey | ApiError::NotLoggedIn => {
+                Status::unauthenticated(message)
+            }
+            ApiError::ReadOnlyPermissions => Status::permission_denied(message),
+            ApiError::RateLimited => Status::resource_exhausted(message),
+            _ => Status::failed_precondition(message),
+        },
+        error => Status::unavailable(error.to_string()),
+    }
+}
+
+fn parse_decimal(field: &str, value: &str) -> Result<Decimal, Status> {
+    Decimal::from_str(value)
+        .map_err(|_| Status::invalid_argument(format!("{} is not a decimal", field)))
+}
+
+fn side_to_proto(side: Side) -> i32 {
+    match side {
+        Side::Buy => proto::Side::Buy as i32,
+        Side::Sell => proto::Side::Sell as i32,
+    }
+}
+
+fn order_to_proto(order: OrderInfo) -> proto::Order {
+    proto::Order {
+        id: order.id,
+        market: order.market,
+        side: side_to_proto(order.side),
+        price: order.price.map(|price| price.to_string()),
+        size: order.size.to_string(),
+        filled_size: order.filled_size.unwrap_or_default().to_string(),
+        remaining_size: order.remaining_size.unwrap_or_default().to_string(),
+        status: format!("{:?}", order.status),
+        client_id: order.client_id,
+        created_at_millis: order.created_at.timestamp_millis(),
+    }
+}
+
+/// Message of a subscribed channel, sent to every stream of the channel.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug)]
+enum Update {
+    Data(Data),
+    /// Best levels of the order book after an update
+    Orderbook(proto::OrderbookUpdate),
+}
+
+/// Request of a stream for the messages of a channel.
+struct Subscription {
+    channel: Channel,
+    reply: oneshot::Sender<Result<broadcast::Receiver<Update>, Status>>,
+}
+
+fn orderbook_to_proto(orderbook: &Orderbook, time_millis: i64) -> proto::OrderbookUpdate {
+    let level = |(price, size): (&Decimal, &Decimal)| proto::PriceLevel {
+        price: price.to_string(),
+        size: size.to_string(),
+    };
+    proto::OrderbookUpdate {
+        bids: orderbook
+            .bids
+            .iter()
+            .rev()
+            .take(ORDERBOOK_DEPTH)
+            .map(level)
+            .collect(),
+        asks: orderbook
+            .asks
+            .iter()
+            .take(ORDERBOOK_DEPTH)
+            .map(level)
+            .collect(),
+        time_millis,
+    }
+}
+
+/// Channel a message was received on.
+fn channel_of(market: Option<Symbol>, data: &Data) -> Option<Channel> {
+    Some(match data {
+        Data::Ticker(_) => Channel::Ticker(market?),
+        Data::Trade(_) => Channel::Trades(market?),
+        Data::OrderbookData(_) => Channel::Orderbook(market?),
+        Data::Fill(_) => Channel::Fills,
+        Data::Order(_) => Channel::Orders,
+    })
+}
+
+/// Owns the websocket shared by all streams. Subscribes to a channel for
+/// its first stream and unsubscribes once no stream is left, which is
+/// noticed with the next message of the channel.
+///
+/// Order books out of sync are subscribed again by `ReconnectingWs`, and
+/// their streams continue with the new partial.
+async fn run_hub(mut ws: ReconnectingWs, mut subscriptions: mpsc::Receiver<Subscription>) {
+    let mut channels: HashMap<Channel, broadcast::Sender<Update>> = HashMap::new();
+    loop {
+        tokio::select! {
+            subscription = subscriptions.recv() => {
+                // The gateway was dropped
+                let Subscription { channel, reply } = match subscription {
+                    Some(subscription) => subscription,
+                    None => return,
+                };
+                let receiver = match channels.get(&channel) {
+                    Some(sender) => Ok(sender.subscribe()),
+                    None => match ws.subscribe(std::slice::from_ref(&channel)).await {
+                        Ok(()) => {
+                            let (sender, receiver) = broadcast::channel(STREAM_BUFFER);
+                            channels.insert(channel, sender);
+                            Ok(receiver)
+                        }
+                        Err(e) => Err(Status::unavailable(e.to_string())),
+                    },
+                };
+                let _ = reply.send(receiver);
+            }
+            event = ws.next_event() => {
+                let (market, data) = match event {
+                    Ok(WsEvent::Data(market, data)) => (market, data),
+                    Ok(WsEvent::Disconnected(error)) => {
+                        log::warn!("gateway streams interrupted: {}", error);
+                        continue;
+                    }
+                    Ok(_) => continue,
+                    Err(error) => {
+                        log::warn!("skipping websocket message: {}", error);
+                        continue;
+                    }
+                };
+                let channel = match channel_of(market, &data) {
+                    Some(channel) => channel,
+                    None => continue,
+                };
+                let sender = match channels.get(&channel) {
+                    Some(sender) => sender,
+                    None => continue,
+                };
+                let update = match (&channel, &data) {
+                    (Channel::Orderbook(market), Data::OrderbookData(update)) => {
+                        match ws.orderbook(market) {
+                            Some(orderbook) => Update::Orderbook(orderbook_to_proto(
+                                orderbook,
+                                update.time.timestamp_millis(),
+                            )),
+                            None => continue,
+                        }
+                    }
+                    _ => Update::Data(data),
+                };
+                if sender.send(update).is_err() {
+                    // No stream left
+                    channels.remove(&channel);
+                    if let Err(error) = ws.unsubscribe(std::slice::from_ref(&channel)).await {
+                        log::warn!("failed to unsubscribe from {:?}: {}", channel, error);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Gateway service owning the connections to FTX.
+///
+/// Streams share one websocket, connected with the first stream.
+#[derive(Debug, Clone)]
+pub struct Gateway {
+    options: Options,
+    rest: Rest,
+    hub: Arc<OnceCell<mpsc::Sender<Subscription>>>,
+}
+
+impl Gateway {
+    pub fn new(options: Options) -> Self {
+        Self {
+            rest: Rest::new(options.clone()),
+            options,
+            hub: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// Serves the gateway on `addr` until the process is stopped.
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+        tonic::transport::Server::builder()
+            .add_service(GatewayServer::new(self))
+            .serve(addr)
+            .await
+    }
+
+    /// Subscribes the shared websocket to `channel` and forwards the
+    /// messages mapped by `map` to the returned stream until the client
+    /// disconnects. Ends with `data_loss` if the client falls too far
+    /// behind.
+    async fn stream<T, F>(&self, channel: Channel, mut map: F) -> Result<ResponseStream<T>, Status>
+    where
+        T: Send + 'static,
+        F: FnMut(Update) -> Option<T> + Send + 'static,
+    {
+        let hub = self
+            .hub
+            .get_or_try_init(|| async {
+                let ws = ReconnectingWs::connect(self.options.clone())
+                    .await
+                    .map_err(|e| Status::unavailable(e.to_string()))?;
+                let (sender, receiver) = mpsc::channel(SUBSCRIPTION_BUFFER);
+                tokio::spawn(run_hub(ws, receiver));
+                Ok::<_, Status>(sender)
+            })
+            .await?;
+        let (reply, subscribed) = oneshot::channel();
+        hub.send(Subscription { channel, reply })
+            .await
+            .map_err(|_| Status::unavailable("websocket closed"))?;
+        let mut updates = subscribed
+            .await
+            .map_err(|_| Status::unavailable("websocket closed"))??;
+
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        tokio::spawn(async move {
+            loop {
+                let item = match updates.recv().await {
+                    Ok(update) => match map(update) {
+                        Some(item) => Ok(item),
+                        None => continue,
+                    },
+                    Err(broadcast::error::RecvError::Lagged(missed)) => Err(Status::data_loss(
+                        format!("stream fell behind by {} messages", missed),
+                    )),
+                    Err(broadcast::error::RecvError::Closed) => {
+                        Err(Status::unavailable("websocket closed"))
+                    }
+                };
+                let failed = item.is_err();
+                if tx.send(item).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(Box::pin(ReceiverStream::new(rx)))
+    }
+}
+
+#[tonic::async_trait]
+impl GatewayService for Gateway {
+    async fn place_order(
+        &self,
+        request: Request<proto::PlaceOrderRequest>,
+    ) -> Result<Response<proto::Order>, Status> {
+        let request = request.into_inner();
+        let side = match proto::Side::try_from(request.side) {
+            Ok(proto::Side::Buy) => Side::Buy,
+            Ok(proto::Side::Sell) => Side::Sell,
+            Err(_) => return Err(Status::invalid_argument("unknown side")),
+        };
+        let price = request
+            .price
+            .as_deref()
+            .map(|price| parse_decimal("price", price))
+            .transpose()?;
+
+        let order = self
+            .rest
+            .request(PlaceOrder {
+                market: &request.market,
+                side,
+                price,
+                r#type: if price.is_some() {
+                    OrderType::Limit
+                } else {
+                    OrderType::Market
+                },
+                size: parse_decimal("size", &request.size)?,
+                reduce_only: request.reduce_only,
+                time_in_force: TimeInForce::from_flags(request.ioc, request.post_only)
+                    .map_err(to_status)?,
+                client_id: request.client_id.as_deref(),
+                ..Default::default()
+            })
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(order_to_proto(order)))
+    }
+
+    async fn cancel_order(
+        &self,
+        request: Request<proto::CancelOrderRequest>,
+    ) -> Result<Response<proto::CancelOrderResponse>, Status> {
+        let message = self
+            .rest
+            .request(CancelOrder::new(request.into_inner().id))
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(proto::CancelOrderResponse { message }))
+    }
+
+    async fn get_open_orders(
+        &self,
+        request: Request<proto::GetOpenOrdersRequest>,
+    ) -> Result<Response<proto::Orders>, Status> {
+        let request = request.into_inner();
+        let orders = self
+            .rest
+            .request(GetOpenOrders {
+                market: request.market.as_deref(),
+            })
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(proto::Orders {
+            orders: orders.into_iter().map(order_to_proto).collect(),
+        }))
+    }
+
+    async fn get_balances(
+        &self,
+        _request: Request<proto::GetBalancesRequest>,
+    ) -> Result<Response<proto::Balances>, Status> {
+        let balances = self
+            .rest
+            .request(GetWalletBalances {})
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(proto::Balances {
+            balances: balances
+                .into_iter()
+                .map(|balance| proto::Balance {
+                    coin: balance.coin,
+                    free: balance.free.to_string(),
+                    total: balance.total.to_string(),
+                    usd_value: balance.usd_value.map(|value| value.to_string()),
+                })
+                .collect(),
+        }))
+    }
+
+    async fn get_positions(
+        &self,
+        _request: Request<proto::GetPositionsRequest>,
+    ) -> Result<Response<proto::Positions>, Status> {
+        let positions = self
+            .rest
+            .request(GetPositions::new())
+            .await
+            .map_err(to_status)?;
+        Ok(Response::new(proto::Positions {
+            positions: positions
+                .into_iter()
+                .map(|position| proto::Position {
+                    future: position.future,
+                    side: side_to_proto(position.side),
+                    size: position.size.to_string(),
+                    net_size: position.net_size.to_string(),
+                    entry_price: position.entry_price.map(|price| price.to_string()),
+                    unrealized_pnl: position.unrealized_pnl.to_string(),
+                })
+                .collect(),
+        }))
+    }
+
+    type StreamTradesStream = ResponseStream<proto::Trade>;
+
+    async fn stream_trades(
+        &self,
+        request: Request<proto::StreamRequest>,
+    ) -> Result<Response<Self::StreamTradesStream>, Status> {
+        let market = request.into_inner().market;
+        let stream = self
+            .stream(Channel::Trades(market), |update| match update {
+                Update::Data(Data::Trade(trade)) => Some(proto::Trade {
+                    id: trade.id,
+                    price: trade.price.to_string(),
+                    size: trade.size.to_string(),
+                    side: side_to_proto(trade.side),
+                    liquidation: trade.liquidation,
+                    time_millis: trade.time.timestamp_millis(),
+                }),
+                _ => None,
+            })
+            .await?;
+        Ok(Response::new(stream))
+    }
+
+    type StreamOrderbookStream = ResponseStream<proto::OrderbookUpdate>;
+
+    async fn stream_orderbook(
+        &self,
+        request: Request<proto::StreamRequest>,
+    ) -> Result<Response<Self::StreamOrderbookStream>, Status> {
+        let market = request.into_inner().market;
+        let stream = self
+            .stream(Channel::Orderbook(market), |update| match update {
+                Update::Orderbook(update) => Some(update),
+                _ => None,
+            })
+            .await?;
+        Ok(Response::new(stream))
+    }
+}
@@ -1,4 +1,30 @@
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "ws")]
+pub mod book;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod credentials;
+#[cfg(feature = "ws")]
+pub mod engine;
+#[cfg(feature = "ws")]
+pub mod execution;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "grpc-gateway")]
+pub mod grpc;
+#[cfg(feature = "mock-server")]
+pub mod mock;
 pub mod options;
+#[cfg(feature = "proxy")]
+pub mod proxy;
+#[cfg(feature = "python")]
+mod python;
 pub mod rest;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(feature = "ws")]
+pub mod webhook;
 #[cfg(feature = "ws")]
 pub mod ws;
@@ -0,0 +1,425 @@
+use crate::rest::{
+    Id, MarketType, OrderInfo, OrderStatus, OrderType, Position, Side, Symbol, Trade,
+};
+use crate::ws::{Fill, Liquidity, Orderbook, OrderbookAction, OrderbookData, Ticker};
+use chrono::Utc;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Taker fee of the mock account, maker orders trade without fees.
+pub(super) const TAKER_FEE: Decimal = dec!(0.0007);
+
+/// Update published to websocket subscribers.
+#[derive(Clone, Debug)]
+pub(super) enum Event {
+    Trades(Symbol, Vec<Trade>),
+    Orderbook(Symbol, OrderbookData),
+    Ticker(Symbol, Ticker),
+    Fill(Fill),
+    Order(OrderInfo),
+}
+
+pub(super) struct MarketState {
+    pub market_type: MarketType,
+    pub book: Orderbook,
+    pub trades: Vec<Trade>,
+    pub last: Option<Decimal>,
+}
+
+impl MarketState {
+    pub fn ticker(&self) -> Ticker {
+        let (bid, bid_size) = self
+            .book
+            .best_bid()
+            .map_or((Decimal::ZERO, Decimal::ZERO), |(p, s)| (*p, *s));
+        let (ask, ask_size) = self
+            .book
+            .best_ask()
+            .map_or((Decimal::ZERO, Decimal::ZERO), |(p, s)| (*p, *s));
+        Ticker {
+            bid,
+            ask,
+            bid_size,
+            ask_size,
+            last: self.last.unwrap_or(bid),
+            time: Utc::now(),
+        }
+    }
+
+    pub fn partial(&self) -> OrderbookData {
+        OrderbookData {
+            action: OrderbookAction::Partial,
+            bids: self.book.bids.iter().rev().map(|(p, s)| (*p, *s)).collect(),
+            asks: self.book.asks.iter().map(|(p, s)| (*p, *s)).collect(),
+            checksum: self.book.checksum(),
+            time: Utc::now(),
+        }
+    }
+}
+
+/// Order as accepted by `POST /api/orders`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct NewOrder {
+    pub market: Symbol,
+    pub side: Side,
+    pub price: Option<Decimal>,
+    pub r#type: OrderType,
+    pub size: Decimal,
+    #[serde(default)]
+    pub reduce_only: bool,
+    #[serde(default)]
+    pub ioc: bool,
+    #[serde(default)]
+    pub post_only: bool,
+    pub client_id: Option<String>,
+}
+
+pub(super) struct Exchange {
+    pub markets: BTreeMap<Symbol, MarketState>,
+    pub balances: BTreeMap<String, Decimal>,
+    pub positions: BTreeMap<Symbol, Decimal>,
+    /// All orders placed, open or closed
+    pub orders: HashMap<Id, OrderInfo>,
+    pub fills: Vec<Fill>,
+    next_id: Id,
+}
+
+impl Exchange {
+    pub fn new() -> Self {
+        Self {
+            markets: BTreeMap::new(),
+            balances: BTreeMap::new(),
+            positions: BTreeMap::new(),
+            orders: HashMap::new(),
+            fills: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    fn next_id(&mut self) -> Id {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Places `order`, matching it against the book. Returns the order and
+    /// the events it caused, or the error message FTX would return.
+    pub fn place(&mut self, order: NewOrder) -> Result<(OrderInfo, Vec<Event>), String> {
+        if !self.markets.contains_key(&order.market) {
+            return Err("No such market".to_owned());
+        }
+        if order.size <= Decimal::ZERO {
+            return Err("Invalid size".to_owned());
+        }
+        let limit = match (order.r#type, order.price) {
+            (OrderType::Limit, Some(price)) if price > Decimal::ZERO => Some(price),
+            (OrderType::Limit, _) => return Err("Invalid price".to_owned()),
+            (OrderType::Market, _) => None,
+            _ => return Err("Unsupported order type".to_owned()),
+        };
+
+        let id = self.next_id();
+        let now = Utc::now();
+        let mut info = OrderInfo {
+            id,
+            market: order.market.clone(),
+            future: None,
+            r#type: order.r#type,
+            side: order.side,
+            price: limit,
+            size: order.size,
+            reduce_only: Some(order.reduce_only),
+            ioc: Some(order.ioc),
+            post_only: Some(order.post_only),
+            status: OrderStatus::New,
+            filled_size: Some(Decimal::ZERO),
+            remaining_size: Some(order.size),
+            avg_fill_price: None,
+            liquidation: Some(false),
+            created_at: now,
+            client_id: order.client_id.clone(),
+            retry_until_filled: None,
+            trigger_price: None,
+            order_price: None,
+            triggered_at: None,
+            order_type: None,
+            order_id: None,
+            trail_value: None,
+            trail_start: None,
+            error: None,
+            raw: Default::default(),
+        };
+
+        let state = self.markets.get_mut(&order.market).unwrap();
+        let crosses = |price: &Decimal| match (order.side, limit) {
+            (_, None) => true,
+            (Side::Buy, Some(limit)) => *price <= limit,
+            (Side::Sell, Some(limit)) => *price >= limit,
+        };
+        let opposite = match order.side {
+            Side::Buy => state.book.asks.iter().next(),
+            Side::Sell => state.book.bids.iter().next_back(),
+        };
+        if order.post_only && opposite.is_some_and(|(price, _)| crosses(price)) {
+            // Post only orders that would take are cancelled, not rejected
+            info.status = OrderStatus::Closed;
+            self.orders.insert(id, info.clone());
+            let events = vec![Event::Order(info.clone())];
+            return Ok((info, events));
+        }
+
+        // Take liquidity level by level
+        let mut matched: Vec<(Decimal, Decimal)> = Vec::new();
+        let mut remaining = order.size;
+        while remaining > Decimal::ZERO {
+            let level = match order.side {
+                Side::Buy => state.book.asks.iter_mut().next(),
+                Side::Sell => state.book.bids.iter_mut().next_back(),
+            };
+            let (price, size) = match level {
+                Some((price, size)) if crosses(price) => (*price, size),
+                _ => break,
+            };
+            let taken = remaining.min(*size);
+            *size -= taken;
+            remaining -= taken;
+            matched.push((price, taken));
+            let levels = match order.side {
+                Side::Buy => &mut state.book.asks,
+                Side::Sell => &mut state.book.bids,
+            };
+            if levels[&price].is_zero() {
+                levels.remove(&price);
+            }
+        }
+
+        let mut events = Vec::new();
+        let mut trades = Vec::new();
+        let mut changed = Vec::new();
+        for (price, size) in &matched {
+            trades.push(Trade {
+                id: id * 1000 + trades.len() as Id,
+                liquidation: false,
+                price: *price,
+                side: order.side,
+                size: *size,
+                time: now,
+            });
+            changed.push(*price);
+        }
+
+        let filled = order.size - remaining;
+        if !filled.is_zero() {
+            let notional: Decimal = matched.iter().map(|(price, size)| price * size).sum();
+            info.filled_size = Some(filled);
+            info.avg_fill_price = Some(notional / filled);
+            state.last = matched.last().map(|(price, _)| *price);
+            state.trades.extend(trades.iter().copied());
+        }
+        let market_type = state.market_type;
+
+        // Rest the remainder of limit orders
+        if let Some(price) = limit.filter(|_| remaining > Decimal::ZERO && !order.ioc) {
+            let levels = match order.side {
+                Side::Buy => &mut state.book.bids,
+                Side::Sell => &mut state.book.asks,
+            };
+            *levels.entry(price).or_default() += remaining;
+            changed.push(price);
+            info.status = OrderStatus::Open;
+            info.remaining_size = Some(remaining);
+        } else {
+            info.status = OrderStatus::Closed;
+            info.remaining_size = Some(Decimal::ZERO);
+        }
+        self.orders.insert(id, info.clone());
+
+        for (price, size) in matched {
+            let fill = self.settle(&info, market_type, price, size, Liquidity::Taker);
+            events.push(Event::Fill(fill));
+        }
+        if !trades.is_empty() {
+            events.push(Event::Trades(order.market.clone(), trades));
+        }
+        events.extend(self.book_events(&order.market, &changed));
+        events.push(Event::Order(info.clone()));
+        Ok((info, events))
+    }
+
+    /// Cancels the open order `id`.
+    pub fn cancel(&mut self, id: Id) -> Result<Vec<Event>, String> {
+        let order = match self.orders.get_mut(&id) {
+            Some(order) if order.status == OrderStatus::Open => order,
+            Some(_) => return Err("Order already closed".to_owned()),
+            None => return Err("Order not found".to_owned()),
+        };
+        order.status = OrderStatus::Closed;
+        let order = order.clone();
+        let price = order.price.unwrap_or_default();
+        let state = self.markets.get_mut(&order.market).unwrap();
+        let levels = match order.side {
+            Side::Buy => &mut state.book.bids,
+            Side::Sell => &mut state.book.asks,
+        };
+        if let Some(size) = levels.get_mut(&price) {
+            *size -= order.remaining_size.unwrap_or_default();
+            if *size <= Decimal::ZERO {
+                levels.remove(&price);
+            }
+        }
+        let mut events = self.book_events(&order.market, &[price]);
+        events.push(Event::Order(order));
+        Ok(events)
+    }
+
+    /// Cancels all open orders, in `market` only if given.
+    pub fn cancel_all(&mut self, market: Option<&str>) -> Vec<Event> {
+        let mut ids: Vec<Id> = self
+            .orders
+            .values()
+            .filter(|order| order.status == OrderStatus::Open)
+            .filter(|order| market.is_none_or(|market| order.market == market))
+            .map(|order| order.id)
+            .collect();
+        ids.sort_unstable();
+        ids.into_iter()
+            .flat_map(|id| self.cancel(id).unwrap_or_default())
+            .collect()
+    }
+
+    /// Books a fill of `order` to the account.
+    fn settle(
+        &mut self,
+        order: &OrderInfo,
+        market_type: MarketType,
+        price: Decimal,
+        size: Decimal,
+        liquidity: Liquidity,
+    ) -> Fill {
+        let fee_rate = match liquidity {
+            Liquidity::Maker => Decimal::ZERO,
+            Liquidity::Taker => TAKER_FEE,
+        };
+        let fee = price * size * fee_rate;
+        let signed = match order.side {
+            Side::Buy => size,
+            Side::Sell => -size,
+        };
+
+        let (base, quote) = match market_type {
+            MarketType::Spot => {
+                let mut parts = order.market.splitn(2, '/');
+                let base = parts.next().unwrap_or_default().to_owned();
+                let quote = parts.next().unwrap_or("USD").to_owned();
+                *self.balances.entry(base.clone()).or_default() += signed;
+                *self.balances.entry(quote.clone()).or_default() -= signed * price + fee;
+                (Some(base), Some(quote))
+            }
+            MarketType::Future => {
+                *self.positions.entry(order.market.clone()).or_default() += signed;
+                *self.balances.entry("USD".to_owned()).or_default() -= fee;
+                (None, None)
+            }
+        };
+
+        let fill = Fill {
+            id: self.fills.len() as Id + 1,
+            market: Some(order.market.clone()),
+            future: (market_type == MarketType::Future).then(|| order.market.clone()),
+            base_currency: base,
+            quote_currency: quote,
+            r#type: "order".to_owned(),
+            side: order.side,
+            price,
+            size,
+            order_id: Some(order.id),
+            trade_id: None,
+            time: Utc::now(),
+            fee,
+            fee_rate,
+            fee_currency: "USD".to_owned(),
+            liquidity,
+            raw: Default::default(),
+        };
+        self.fills.push(fill.clone());
+        fill
+    }
+
+    /// Order book update and ticker for the levels at `prices` of `market`.
+    fn book_events(&self, market: &str, prices: &[Decimal]) -> Vec<Event> {
+        if prices.is_empty() {
+            return Vec::new();
+        }
+        let state = &self.markets[market];
+        let mut data = OrderbookData {
+            action: OrderbookAction::Update,
+            bids: Vec::new(),
+            asks: Vec::new(),
+            checksum: state.book.checksum(),
+            time: Utc::now(),
+        };
+        for price in prices {
+            let bid = state.book.bids.get(price);
+            let ask = state.book.asks.get(price);
+            // A level no longer on one side was removed from it
+            data.bids.push((*price, bid.copied().unwrap_or_default()));
+            data.asks.push((*price, ask.copied().unwrap_or_default()));
+        }
+        data.bids.dedup();
+        data.asks.dedup();
+        vec![
+            Event::Orderbook(market.to_owned(), data),
+            Event::Ticker(market.to_owned(), state.ticker()),
+        ]
+    }
+
+    pub fn positions(&self) -> Vec<Position> {
+        self.positions
+            .iter()
+            .map(|(future, net_size)| {
+                let mark = self.markets[future].last.unwrap_or_default();
+                let long_orders = self.open_size(future, Side::Buy);
+                let short_orders = self.open_size(future, Side::Sell);
+                Position {
+                    cost: net_size * mark,
+                    entry_price: None,
+                    estimated_liquidation_price: None,
+                    future: future.clone(),
+                    initial_margin_requirement: dec!(0.05),
+                    long_order_size: long_orders,
+                    maintenance_margin_requirement: dec!(0.03),
+                    net_size: *net_size,
+                    open_size: net_size.abs() + long_orders.max(short_orders),
+                    realized_pnl: Decimal::ZERO,
+                    short_order_size: short_orders,
+                    side: if net_size.is_sign_negative() {
+                        Side::Sell
+                    } else {
+                        Side::Buy
+                    },
+                    size: net_size.abs(),
+                    unrealized_pnl: Decimal::ZERO,
+                    collateral_used: net_size.abs() * mark * dec!(0.05),
+                    recent_average_open_price: None,
+                    recent_pnl: None,
+                    recent_break_even_price: None,
+                    cumulative_buy_size: None,
+                    cumulative_sell_size: None,
+                    raw: Default::default(),
+                }
+            })
+            .collect()
+    }
+
+    fn open_size(&self, market: &str, side: Side) -> Decimal {
+        self.orders
+            .values()
+            .filter(|order| order.status == OrderStatus::Open)
+            .filter(|order| order.market == market && order.side == side)
+            .map(|order| order.remaining_size.unwrap_or_default())
+            .sum()
+    }
+}
@@ -0,0 +1,331 @@
+use super::exchange::{Exchange, NewOrder};
+use super::Shared;
+use crate::rest::{Market, MarketType, OrderStatus};
+use hmac_sha256::HMAC;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+type Reply = (StatusCode, Result<Value, String>);
+
+pub(super) async fn handle(shared: Shared, req: Request<Body>) -> Response<Body> {
+    let (parts, body) = req.into_parts();
+    let body = match hyper::body::to_bytes(body).await {
+        Ok(body) => body,
+        Err(e) => return respond((StatusCode::BAD_REQUEST, Err(e.to_string()))),
+    };
+    let path = parts.uri.path().to_owned();
+    let query = parse_query(parts.uri.query().unwrap_or_default());
+
+    let path = match path.strip_prefix("/api") {
+        Some(path) => path.to_owned(),
+        None => return respond(not_found()),
+    };
+
+    if let Some(reply) = public(&shared, &parts.method, &path, &query) {
+        return respond(reply);
+    }
+
+    // Private routes require a request signed like the FTX API requires
+    let header = |name: &str| {
+        parts
+            .headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+    };
+    let payload = format!(
+        "{}{}{}{}",
+        header("FTX-TS"),
+        parts.method,
+        parts
+            .uri
+            .path_and_query()
+            .map(|p| p.as_str())
+            .unwrap_or_default(),
+        String::from_utf8_lossy(&body)
+    );
+    let sign = hex::encode(HMAC::mac(payload.as_bytes(), shared.secret.as_bytes()));
+    if header("FTX-KEY") != shared.key || header("FTX-SIGN") != sign {
+        return respond((StatusCode::UNAUTHORIZED, Err("Not logged in".to_owned())));
+    }
+
+    respond(private(&shared, &parts.method, &path, &query, &body))
+}
+
+fn public(
+    shared: &Shared,
+    method: &Method,
+    path: &str,
+    query: &HashMap<String, String>,
+) -> Option<Reply> {
+    if method != Method::GET {
+        return None;
+    }
+    let exchange = shared.exchange.lock().unwrap();
+    if path == "/markets" {
+        let markets: Vec<Market> = exchange
+            .markets
+            .keys()
+            .map(|name| market(&exchange, name))
+            .collect();
+        return Some(ok(json!(markets)));
+    }
+
+    let name = path.strip_prefix("/markets/")?;
+    let (name, resource) = if let Some(name) = name.strip_suffix("/orderbook") {
+        (name, "orderbook")
+    } else if let Some(name) = name.strip_suffix("/trades") {
+        (name, "trades")
+    } else {
+        (name, "")
+    };
+    let state = match exchange.markets.get(name) {
+        Some(state) => state,
+        None => return Some((StatusCode::NOT_FOUND, Err("No such market".to_owned()))),
+    };
+
+    Some(match resource {
+        "orderbook" => {
+            let depth = query
+                .get("depth")
+                .and_then(|depth| depth.parse().ok())
+                .unwrap_or(20);
+            let bids: Vec<_> = state.book.bids.iter().rev().take(depth).collect();
+            let asks: Vec<_> = state.book.asks.iter().take(depth).collect();
+            ok(json!({ "bids": bids, "asks": asks }))
+        }
+        "trades" => {
+            let trades: Vec<_> = state.trades.iter().rev().take(100).collect();
+            ok(json!(trades))
+        }
+        _ => ok(json!(market(&exchange, name))),
+    })
+}
+
+fn private(
+    shared: &Shared,
+    method: &Method,
+    path: &str,
+    query: &HashMap<String, String>,
+    body: &[u8],
+) -> Reply {
+    let mut exchange = shared.exchange.lock().unwrap();
+    let (reply, events) = match (method.clone(), path) {
+        (Method::GET, "/account") => {
+            let collateral = exchange.balances.get("USD").copied().unwrap_or_default();
+            let positions = exchange.positions();
+            let reply = ok(json!({
+                "backstopProvider": false,
+                "chargeInterestOnNegativeUsd": false,
+                "collateral": collateral,
+                "freeCollateral": collateral,
+                "initialMarginRequirement": 0.05,
+                "liquidating": false,
+                "maintenanceMarginRequirement": 0.03,
+                "makerFee": 0.0,
+                "marginFraction": null,
+                "openMarginFraction": null,
+                "positionLimit": null,
+                "positionLimitUsed": null,
+                "takerFee": super::exchange::TAKER_FEE,
+                "totalAccountValue": collateral,
+                "totalPositionSize": positions.iter().map(|p| p.cost.abs()).sum::<Decimal>(),
+                "useFttCollateral": false,
+                "username": "mock",
+                "leverage": 20,
+                "positions": positions,
+                "spotLendingEnabled": false,
+                "spotMarginEnabled": false,
+            }));
+            (reply, Vec::new())
+        }
+        (Method::GET, "/positions") => (ok(json!(exchange.positions())), Vec::new()),
+        (Method::GET, "/wallet/balances") => {
+            let balances: Vec<Value> = exchange
+                .balances
+                .iter()
+                .map(|(coin, total)| {
+                    json!({
+                        "coin": coin,
+                        "free": total,
+                        "total": total,
+                        "usdValue": null,
+                        "spotBorrow": 0,
+                        "availableWithoutBorrow": total,
+                    })
+                })
+                .collect();
+            (ok(json!(balances)), Vec::new())
+        }
+        (Method::GET, "/fills") => {
+            let fills: Vec<_> = exchange
+                .fills
+                .iter()
+                .rev()
+                .filter(|fill| {
+                    query
+                        .get("market")
+                        .is_none_or(|m| fill.market.as_ref() == Some(m))
+                })
+                .collect();
+            (ok(json!(fills)), Vec::new())
+        }
+        (Method::GET, "/orders") => {
+            let mut orders: Vec<_> = exchange
+                .orders
+                .values()
+                .filter(|order| order.status == OrderStatus::Open)
+                .filter(|order| query.get("market").is_none_or(|m| &order.market == m))
+                .collect();
+            orders.sort_by_key(|order| order.id);
+            (ok(json!(orders)), Vec::new())
+        }
+        (Method::POST, "/orders") => match serde_json::from_slice::<NewOrder>(body) {
+            Ok(order) => match exchange.place(order) {
+                Ok((order, events)) => (ok(json!(order)), events),
+                Err(error) => ((StatusCode::BAD_REQUEST, Err(error)), Vec::new()),
+            },
+            Err(error) => (
+                (StatusCode::BAD_REQUEST, Err(error.to_string())),
+                Vec::new(),
+            ),
+        },
+        (Method::DELETE, "/orders") => {
+            let market: Option<String> = serde_json::from_slice::<Value>(body)
+                .ok()
+                .and_then(|body| body.get("market")?.as_str().map(str::to_owned));
+            let events = exchange.cancel_all(market.as_deref());
+            (ok(json!("Orders queued for cancellation")), events)
+        }
+        (Method::GET, path) if path.starts_with("/orders/") => {
+            match path["/orders/".len()..]
+                .parse()
+                .ok()
+                .and_then(|id| exchange.orders.get(&id))
+            {
+                Some(order) => (ok(json!(order)), Vec::new()),
+                None => (
+                    (StatusCode::NOT_FOUND, Err("Order not found".to_owned())),
+                    Vec::new(),
+                ),
+            }
+        }
+        (Method::DELETE, path) if path.starts_with("/orders/") => {
+            let cancelled = path["/orders/".len()..]
+                .parse()
+                .map_err(|_| "Invalid order id".to_owned())
+                .and_then(|id| exchange.cancel(id));
+            match cancelled {
+                Ok(events) => (ok(json!("Order queued for cancellation")), events),
+                Err(error) => ((StatusCode::BAD_REQUEST, Err(error)), Vec::new()),
+            }
+        }
+        _ => (not_found(), Vec::new()),
+    };
+    drop(exchange);
+
+    for event in events {
+        // Sending only fails if no websocket is connected
+        let _ = shared.events.send(event);
+    }
+    reply
+}
+
+fn market(exchange: &Exchange, name: &str) -> Market {
+    let state = &exchange.markets[name];
+    let ticker = state.ticker();
+    let (market_type, base, quote, underlying) = match state.market_type {
+        MarketType::Spot => {
+            let mut parts = name.splitn(2, '/');
+            let base = parts.next().map(str::to_owned);
+            let quote = parts.next().map(str::to_owned);
+            (MarketType::Spot, base, quote, None)
+        }
+        MarketType::Future => {
+            let underlying = name.split('-').next().map(str::to_owned);
+            (MarketType::Future, None, None, underlying)
+        }
+    };
+    let price = state.last.or_else(|| state.book.mid_price());
+    Market {
+        market_type,
+        name: name.to_owned(),
+        underlying,
+        base_currency: base,
+        quote_currency: quote,
+        enabled: true,
+        ask: state.book.ask_price().copied(),
+        bid: state.book.bid_price().copied(),
+        last: state.last.or(Some(ticker.last)),
+        post_only: false,
+        price_increment: Decimal::new(1, 2),
+        size_increment: Decimal::new(1, 4),
+        restricted: false,
+        min_provide_size: Decimal::new(1, 4),
+        price,
+        high_leverage_fee_exempt: false,
+        change1h: Decimal::ZERO,
+        change24h: Decimal::ZERO,
+        change_bod: Decimal::ZERO,
+        quote_volume24h: Decimal::ZERO,
+        volume_usd24h: Decimal::ZERO,
+        raw: Default::default(),
+    }
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            Some((percent_decode(key), percent_decode(value)))
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn ok(result: Value) -> Reply {
+    (StatusCode::OK, Ok(result))
+}
+
+fn not_found() -> Reply {
+    (StatusCode::NOT_FOUND, Err("Not found".to_owned()))
+}
+
+fn respond((status, result): Reply) -> Response<Body> {
+    let body = match result {
+        Ok(result) => json!({ "success": true, "result": result }),
+        Err(error) => json!({ "success": false, "error": error }),
+    };
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
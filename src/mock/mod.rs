@@ -0,0 +1,213 @@
+//! In-process mock of the FTX REST and websocket APIs, built with the
+//! `mock-server` feature, for integration tests that must not touch the real
+//! exchange.
+//!
+//! Orders placed through `POST /api/orders` are matched against the
+//! configured order books, and the resulting fills, order updates, trades and
+//! book changes are published on the websocket channels. Private routes and
+//! websocket logins are checked against the configured key and secret.
+//!
+//! ```no_run
+//! # async fn example() -> std::io::Result<()> {
+//! use ftx::mock::MockServer;
+//! use ftx::rest::{GetMarkets, Rest};
+//!
+//! let handle = MockServer::new("key", "secret")
+//!     .with_default_markets()
+//!     .start()
+//!     .await?;
+//! let api = Rest::new(handle.options());
+//! let markets = api.request(GetMarkets {}).await.unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+mod exchange;
+mod http;
+mod websocket;
+
+use crate::options::{Endpoint, Options};
+use crate::rest::MarketType;
+use crate::ws::Orderbook;
+use exchange::{Event, Exchange, MarketState};
+use hyper::server::conn::AddrIncoming;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::Server;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::convert::Infallible;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+
+/// State shared by the REST and websocket servers.
+#[derive(Clone)]
+struct Shared {
+    exchange: Arc<Mutex<Exchange>>,
+    events: broadcast::Sender<Event>,
+    key: String,
+    secret: String,
+}
+
+pub struct MockServer {
+    exchange: Exchange,
+    key: String,
+    secret: String,
+}
+
+/// Addresses of a started `MockServer`.
+#[derive(Clone, Debug)]
+pub struct MockHandle {
+    pub rest_addr: SocketAddr,
+    pub ws_addr: SocketAddr,
+    key: String,
+    secret: String,
+}
+
+impl MockServer {
+    /// Mock accepting requests signed with `key` and `secret`.
+    pub fn new(key: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self {
+            exchange: Exchange::new(),
+            key: key.into(),
+            secret: secret.into(),
+        }
+    }
+
+    /// Adds a market with the given `(price, size)` levels. Names containing
+    /// a `/` are spot markets, all others futures.
+    #[must_use]
+    pub fn market(
+        mut self,
+        name: &str,
+        bids: &[(Decimal, Decimal)],
+        asks: &[(Decimal, Decimal)],
+    ) -> Self {
+        let mut book = Orderbook::new(name.to_owned());
+        book.bids.extend(bids.iter().copied());
+        book.asks.extend(asks.iter().copied());
+        let market_type = if name.contains('/') {
+            MarketType::Spot
+        } else {
+            MarketType::Future
+        };
+        self.exchange.markets.insert(
+            name.to_owned(),
+            MarketState {
+                market_type,
+                book,
+                trades: Vec::new(),
+                last: None,
+            },
+        );
+        self
+    }
+
+    #[must_use]
+    pub fn balance(mut self, coin: &str, amount: Decimal) -> Self {
+        self.exchange.balances.insert(coin.to_owned(), amount);
+        self
+    }
+
+    /// Adds BTC-PERP, ETH-PERP and BTC/USD with a few levels on each side,
+    /// and a balance of 100,000 USD.
+    #[must_use]
+    pub fn with_default_markets(self) -> Self {
+        let levels = |mid: Decimal, step: Decimal, size: Decimal| {
+            let bids: Vec<_> = (1..=5)
+                .map(|i| (mid - step * Decimal::from(i), size * Decimal::from(i)))
+                .collect();
+            let asks: Vec<_> = (1..=5)
+                .map(|i| (mid + step * Decimal::from(i), size * Decimal::from(i)))
+                .collect();
+            (bids, asks)
+        };
+        let (btc_bids, btc_asks) = levels(dec!(40000), dec!(1), dec!(0.5));
+        let (eth_bids, eth_asks) = levels(dec!(3000), dec!(0.1), dec!(5));
+        self.market("BTC-PERP", &btc_bids, &btc_asks)
+            .market("ETH-PERP", &eth_bids, &eth_asks)
+            .market("BTC/USD", &btc_bids, &btc_asks)
+            .balance("USD", dec!(100000))
+    }
+
+    /// Starts serving on free ports of the loopback interface.
+    pub async fn start(self) -> io::Result<MockHandle> {
+        let rest = TcpListener::bind("127.0.0.1:0").await?;
+        let ws = TcpListener::bind("127.0.0.1:0").await?;
+        self.spawn(rest, ws)
+    }
+
+    /// Starts serving REST requests on `rest_addr` and websocket connections
+    /// on `ws_addr`.
+    pub async fn serve(self, rest_addr: SocketAddr, ws_addr: SocketAddr) -> io::Result<MockHandle> {
+        let rest = TcpListener::bind(rest_addr).await?;
+        let ws = TcpListener::bind(ws_addr).await?;
+        self.spawn(rest, ws)
+    }
+
+    fn spawn(self, rest: TcpListener, ws: TcpListener) -> io::Result<MockHandle> {
+        let handle = MockHandle {
+            rest_addr: rest.local_addr()?,
+            ws_addr: ws.local_addr()?,
+            key: self.key.clone(),
+            secret: self.secret.clone(),
+        };
+        let shared = Shared {
+            exchange: Arc::new(Mutex::new(self.exchange)),
+            events: broadcast::channel(1024).0,
+            key: self.key,
+            secret: self.secret,
+        };
+
+        let rest_shared = shared.clone();
+        let make_service = make_service_fn(move |_| {
+            let shared = rest_shared.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let shared = shared.clone();
+                    async move { Ok::<_, Infallible>(http::handle(shared, req).await) }
+                }))
+            }
+        });
+        let server = Server::builder(AddrIncoming::from_listener(rest).map_err(io_error)?)
+            .serve(make_service);
+        tokio::spawn(async move {
+            if let Err(error) = server.await {
+                log::error!("mock REST server failed: {}", error);
+            }
+        });
+
+        tokio::spawn(async move {
+            loop {
+                match ws.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(websocket::accept(shared.clone(), stream));
+                    }
+                    Err(error) => log::warn!("mock websocket server failed to accept: {}", error),
+                }
+            }
+        });
+
+        Ok(handle)
+    }
+}
+
+impl MockHandle {
+    /// Options connecting to the mock with its credentials.
+    pub fn options(&self) -> Options {
+        Options {
+            endpoint: Endpoint::Custom {
+                rest: format!("http://{}/api", self.rest_addr),
+                ws: format!("ws://{}/ws", self.ws_addr),
+            },
+            ..Default::default()
+        }
+        .authenticate(self.key.clone(), self.secret.clone())
+    }
+}
+
+fn io_error(error: hyper::Error) -> io::Error {
+    io::Error::other(error)
+}
@@ -0,0 +1,155 @@
+use super::exchange::Event;
+use super::Shared;
+use futures::{SinkExt, StreamExt};
+use hmac_sha256::HMAC;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use tokio::net::TcpStream;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{accept_async, WebSocketStream};
+
+pub(super) async fn accept(shared: Shared, stream: TcpStream) {
+    let result = match accept_async(stream).await {
+        Ok(ws) => connection(shared, ws).await,
+        Err(error) => Err(error),
+    };
+    if let Err(error) = result {
+        log::debug!("mock websocket connection closed: {}", error);
+    }
+}
+
+/// Channel and market of a subscription, the market is empty for `fills`
+/// and `orders`.
+type Subscription = (String, String);
+
+async fn connection(
+    shared: Shared,
+    mut ws: WebSocketStream<TcpStream>,
+) -> Result<(), tungstenite::Error> {
+    let mut events = shared.events.subscribe();
+    let mut logged_in = false;
+    let mut subscriptions = HashSet::new();
+    loop {
+        tokio::select! {
+            message = ws.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(error)) => return Err(error),
+                };
+                let replies = match serde_json::from_str(&text) {
+                    Ok(request) => handle(&shared, &request, &mut logged_in, &mut subscriptions),
+                    Err(_) => vec![error("Invalid JSON")],
+                };
+                for reply in replies {
+                    ws.send(Message::Text(reply.to_string())).await?;
+                }
+            }
+            event = events.recv() => match event {
+                Ok(event) => {
+                    if let Some(message) = route(&event, &subscriptions) {
+                        ws.send(Message::Text(message.to_string())).await?;
+                    }
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+}
+
+fn handle(
+    shared: &Shared,
+    request: &Value,
+    logged_in: &mut bool,
+    subscriptions: &mut HashSet<Subscription>,
+) -> Vec<Value> {
+    let op = request["op"].as_str().unwrap_or_default();
+    let channel = request["channel"].as_str().unwrap_or_default().to_owned();
+    let market = request["market"].as_str().unwrap_or_default().to_owned();
+
+    match op {
+        "ping" => vec![json!({ "type": "pong" })],
+        "login" => {
+            let args = &request["args"];
+            let payload = format!("{}websocket_login", args["time"]);
+            let sign = hex::encode(HMAC::mac(payload.as_bytes(), shared.secret.as_bytes()));
+            if args["key"] == shared.key.as_str() && args["sign"] == sign.as_str() {
+                *logged_in = true;
+                Vec::new()
+            } else {
+                vec![error("Invalid login credentials")]
+            }
+        }
+        "subscribe" => {
+            let exchange = shared.exchange.lock().unwrap();
+            let mut replies = vec![json!({
+                "type": "subscribed",
+                "channel": channel,
+                "market": market,
+            })];
+            match channel.as_str() {
+                "fills" | "orders" if !*logged_in => return vec![error("Not logged in")],
+                "fills" | "orders" => {
+                    subscriptions.insert((channel, String::new()));
+                }
+                "orderbook" | "trades" | "ticker" => {
+                    let state = match exchange.markets.get(&market) {
+                        Some(state) => state,
+                        None => return vec![error("No such market")],
+                    };
+                    if channel == "orderbook" {
+                        replies.push(json!({
+                            "channel": "orderbook",
+                            "market": market,
+                            "type": "partial",
+                            "data": state.partial(),
+                        }));
+                    }
+                    subscriptions.insert((channel, market));
+                }
+                _ => return vec![error("Invalid channel")],
+            }
+            replies
+        }
+        "unsubscribe" => {
+            let key = match channel.as_str() {
+                "fills" | "orders" => (channel.clone(), String::new()),
+                _ => (channel.clone(), market.clone()),
+            };
+            if subscriptions.remove(&key) {
+                vec![json!({ "type": "unsubscribed", "channel": channel, "market": market })]
+            } else {
+                vec![error("Not subscribed")]
+            }
+        }
+        _ => vec![error("Invalid op")],
+    }
+}
+
+/// Message for `event` if it is on a subscribed channel.
+fn route(event: &Event, subscriptions: &HashSet<Subscription>) -> Option<Value> {
+    let (channel, market, data) = match event {
+        Event::Trades(market, trades) => ("trades", market.as_str(), json!(trades)),
+        Event::Orderbook(market, data) => ("orderbook", market.as_str(), json!(data)),
+        Event::Ticker(market, ticker) => ("ticker", market.as_str(), json!(ticker)),
+        Event::Fill(fill) => ("fills", "", json!(fill)),
+        Event::Order(order) => ("orders", "", json!(order)),
+    };
+    subscriptions
+        .contains(&(channel.to_owned(), market.to_owned()))
+        .then(|| {
+            json!({
+                "channel": channel,
+                "market": (!market.is_empty()).then_some(market),
+                "type": "update",
+                "data": data,
+            })
+        })
+}
+
+fn error(msg: &str) -> Value {
+    json!({ "type": "error", "code": 400, "msg": msg })
+}
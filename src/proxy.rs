@@ -0,0 +1,236 @@
+//! Local HTTP proxy, built with the `proxy` feature, letting several
+//! applications share one set of credentials and one rate limit budget.
+//!
+//! Public market data routes are cached for a configurable time. Order
+//! routes check orders against `RiskLimits` before forwarding them. Paths
+//! and response envelopes mirror the FTX REST API.
+
+use crate::options::Options;
+use crate::rest::{
+    Alert, AlertSink, CancelOrder, Error, LogSink, OrderType, PlaceOrder, RawGet, Rest, Result,
+    RiskLimits, Severity, Side, TimeInForce,
+};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Path prefixes of public routes, which are cached.
+const PUBLIC_PREFIXES: [&str; 2] = ["/markets", "/futures"];
+
+/// Path prefixes of authenticated read-only routes, which are forwarded.
+const PRIVATE_PREFIXES: [&str; 4] = ["/account", "/orders", "/positions", "/wallet/balances"];
+
+/// Order as accepted by `POST /api/orders`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OrderRequest {
+    market: String,
+    side: Side,
+    price: Option<Decimal>,
+    r#type: OrderType,
+    size: Decimal,
+    #[serde(default)]
+    reduce_only: bool,
+    #[serde(default)]
+    ioc: bool,
+    #[serde(default)]
+    post_only: bool,
+    client_id: Option<String>,
+}
+
+pub struct Proxy {
+    rest: Rest,
+    risk_limits: RiskLimits,
+    alert_sink: Box<dyn AlertSink>,
+    cache_ttl: Duration,
+    cache: Mutex<HashMap<String, (Instant, Value)>>,
+    token: Option<String>,
+}
+
+impl Proxy {
+    pub fn new(options: Options) -> Self {
+        Self {
+            rest: Rest::new(options),
+            risk_limits: RiskLimits::default(),
+            alert_sink: Box::new(LogSink),
+            cache_ttl: Duration::from_secs(1),
+            cache: Mutex::new(HashMap::new()),
+            token: None,
+        }
+    }
+
+    #[must_use]
+    pub fn risk_limits(mut self, risk_limits: RiskLimits) -> Self {
+        self.risk_limits = risk_limits;
+        self
+    }
+
+    /// Where orders rejected by the risk limits are reported, the log by
+    /// default.
+    #[must_use]
+    pub fn alert_sink(mut self, alert_sink: impl AlertSink + 'static) -> Self {
+        self.alert_sink = Box::new(alert_sink);
+        self
+    }
+
+    #[must_use]
+    pub fn cache_ttl(mut self, cache_ttl: Duration) -> Self {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    /// Requires clients to send `Authorization: Bearer <token>`.
+    #[must_use]
+    pub fn token(mut self, token: String) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Serves the proxy on `addr` until the process is stopped.
+    pub async fn serve(self, addr: SocketAddr) -> std::result::Result<(), hyper::Error> {
+        let proxy = Arc::new(self);
+        let make_service = make_service_fn(move |_| {
+            let proxy = proxy.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let proxy = proxy.clone();
+                    async move { Ok::<_, Infallible>(proxy.handle(req).await) }
+                }))
+            }
+        });
+        Server::bind(&addr).serve(make_service).await
+    }
+
+    async fn handle(&self, req: Request<Body>) -> Response<Body> {
+        if let Some(token) = &self.token {
+            let authorized = req
+                .headers()
+                .get(hyper::header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+                == Some(token.as_str());
+            if !authorized {
+                return respond(StatusCode::UNAUTHORIZED, Err("unauthorized".to_owned()));
+            }
+        }
+
+        let path = match req.uri().path().strip_prefix("/api") {
+            Some(path) => path.to_owned(),
+            None => return respond(StatusCode::NOT_FOUND, Err("not found".to_owned())),
+        };
+        let path_and_query = match req.uri().query() {
+            Some(query) => format!("{}?{}", path, query),
+            None => path.clone(),
+        };
+        let starts_with = |prefixes: &[&str]| {
+            prefixes
+                .iter()
+                .any(|prefix| path == *prefix || path.starts_with(&format!("{}/", prefix)))
+        };
+
+        let result = match *req.method() {
+            Method::GET if starts_with(&PUBLIC_PREFIXES) => self.cached_get(&path_and_query).await,
+            Method::GET if starts_with(&PRIVATE_PREFIXES) => {
+                self.rest
+                    .request(RawGet::new(path_and_query, Map::new()))
+                    .await
+            }
+            Method::POST if path == "/orders" => self.place_order(req.into_body()).await,
+            Method::DELETE if path.starts_with("/orders/") => {
+                match path["/orders/".len()..].parse() {
+                    Ok(id) => self
+                        .rest
+                        .request(CancelOrder::new(id))
+                        .await
+                        .map(Value::String),
+                    Err(_) => {
+                        return respond(StatusCode::BAD_REQUEST, Err("invalid order id".to_owned()))
+                    }
+                }
+            }
+            _ => return respond(StatusCode::NOT_FOUND, Err("not found".to_owned())),
+        };
+
+        match result {
+            Ok(result) => respond(StatusCode::OK, Ok(result)),
+            Err(Error::Exchange {
+                status, message, ..
+            }) => respond(status, Err(message)),
+            Err(error @ Error::Config(_))
+            | Err(error @ Error::RiskLimitExceeded(_))
+            | Err(error @ Error::Json(_)) => {
+                respond(StatusCode::BAD_REQUEST, Err(error.to_string()))
+            }
+            Err(error) => respond(StatusCode::BAD_GATEWAY, Err(error.to_string())),
+        }
+    }
+
+    async fn cached_get(&self, path: &str) -> Result<Value> {
+        if let Some((time, value)) = self.cache.lock().unwrap().get(path) {
+            if time.elapsed() < self.cache_ttl {
+                return Ok(value.clone());
+            }
+        }
+
+        let value = self.rest.request(RawGet::new(path, Map::new())).await?;
+        let mut cache = self.cache.lock().unwrap();
+        cache.retain(|_, (time, _)| time.elapsed() < self.cache_ttl);
+        cache.insert(path.to_owned(), (Instant::now(), value.clone()));
+        Ok(value)
+    }
+
+    async fn place_order(&self, body: Body) -> Result<Value> {
+        let body = hyper::body::to_bytes(body)
+            .await
+            .map_err(std::io::Error::other)?;
+        let order: OrderRequest = serde_json::from_slice(&body)?;
+        let order = PlaceOrder {
+            market: &order.market,
+            side: order.side,
+            price: order.price,
+            r#type: order.r#type,
+            size: order.size,
+            reduce_only: order.reduce_only,
+            time_in_force: TimeInForce::from_flags(order.ioc, order.post_only)?,
+            client_id: order.client_id.as_deref(),
+            ..Default::default()
+        };
+
+        let reference_price = match order.price {
+            Some(_) => None,
+            None => self
+                .cached_get(&format!("/markets/{}", order.market))
+                .await?
+                .get("price")
+                .and_then(|price| serde_json::from_value(price.clone()).ok()),
+        };
+        if let Err(error) = self.risk_limits.check(&order, reference_price) {
+            let alert = Alert::new(Severity::Warning, "risk", error.to_string());
+            if let Err(alert_error) = self.alert_sink.send(&alert).await {
+                log::warn!("failed to send alert: {}", alert_error);
+            }
+            return Err(error);
+        }
+
+        Ok(serde_json::to_value(self.rest.request(order).await?)?)
+    }
+}
+
+fn respond(status: StatusCode, result: std::result::Result<Value, String>) -> Response<Body> {
+    let body = match result {
+        Ok(result) => json!({ "success": true, "result": result }),
+        Err(error) => json!({ "success": false, "error": error }),
+    };
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
@@ -0,0 +1,245 @@
+//! Python bindings, built with the `python` feature, e.g. using
+//! `maturin develop --features python`.
+//!
+//! Responses are converted to Python objects through JSON. Calls block the
+//! calling thread but release the GIL while waiting on FTX.
+
+use crate::options::Options;
+use crate::rest::{
+    CancelOrder, OrderType, PlaceOrder, RawDelete, RawGet, RawPost, Request, Rest, Side,
+    TimeInForce,
+};
+use crate::ws::{Channel, Ws};
+use futures::StreamExt;
+use pyo3::exceptions::{PyRuntimeError, PyStopIteration, PyValueError};
+use pyo3::prelude::*;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::runtime::Runtime;
+
+fn runtime_error(error: impl ToString) -> PyErr {
+    PyRuntimeError::new_err(error.to_string())
+}
+
+fn value_error(error: impl ToString) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+fn to_python(py: Python<'_>, value: &impl Serialize) -> PyResult<Py<PyAny>> {
+    let json = serde_json::to_string(value).map_err(runtime_error)?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+fn from_python(py: Python<'_>, params: Option<Bound<'_, PyAny>>) -> PyResult<Map<String, Value>> {
+    match params {
+        None => Ok(Map::new()),
+        Some(params) => {
+            let json: String = py
+                .import("json")?
+                .call_method1("dumps", (params,))?
+                .extract()?;
+            serde_json::from_str(&json).map_err(value_error)
+        }
+    }
+}
+
+fn parse_decimal(value: &str) -> PyResult<Decimal> {
+    Decimal::from_str(value).map_err(value_error)
+}
+
+/// FTX REST client.
+#[pyclass(name = "Client")]
+struct PyClient {
+    options: Options,
+    rest: Rest,
+    runtime: Arc<Runtime>,
+}
+
+impl PyClient {
+    fn request<R>(&self, py: Python<'_>, req: R) -> PyResult<Py<PyAny>>
+    where
+        R: Request + Send,
+        R::Response: Serialize + Send,
+    {
+        let response = py
+            .detach(|| self.runtime.block_on(self.rest.request(req)))
+            .map_err(runtime_error)?;
+        to_python(py, &response)
+    }
+}
+
+#[pymethods]
+impl PyClient {
+    #[new]
+    #[pyo3(signature = (key=None, secret=None, subaccount=None, us=false))]
+    fn new(
+        key: Option<String>,
+        secret: Option<String>,
+        subaccount: Option<String>,
+        us: bool,
+    ) -> PyResult<Self> {
+        let mut options = if us {
+            Options::us()
+        } else {
+            Options::default()
+        };
+        if let (Some(key), Some(secret)) = (key, secret) {
+            options = options.authenticate(key, secret);
+        }
+        let options = options.subaccount_optional(subaccount);
+        let rest = Rest::try_new(options.clone()).map_err(value_error)?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(runtime_error)?;
+
+        Ok(Self {
+            rest,
+            options,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Sends a GET request to `path`, e.g. `"/markets"`.
+    #[pyo3(signature = (path, params=None))]
+    fn get(
+        &self,
+        py: Python<'_>,
+        path: String,
+        params: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        let params = from_python(py, params)?;
+        self.request(py, RawGet::new(path, params))
+    }
+
+    /// Sends a POST request with a JSON body to `path`.
+    #[pyo3(signature = (path, body=None))]
+    fn post(
+        &self,
+        py: Python<'_>,
+        path: String,
+        body: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        let body = from_python(py, body)?;
+        self.request(py, RawPost::new(path, body))
+    }
+
+    /// Sends a DELETE request with a JSON body to `path`.
+    #[pyo3(signature = (path, body=None))]
+    fn delete(
+        &self,
+        py: Python<'_>,
+        path: String,
+        body: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        let body = from_python(py, body)?;
+        self.request(py, RawDelete::new(path, body))
+    }
+
+    /// Places an order. Sizes and prices are passed as strings to avoid
+    /// floating point rounding. Orders without a price are market orders.
+    #[pyo3(signature = (market, side, size, price=None, reduce_only=false, post_only=false, ioc=false, client_id=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn place_order(
+        &self,
+        py: Python<'_>,
+        market: &str,
+        side: &str,
+        size: &str,
+        price: Option<&str>,
+        reduce_only: bool,
+        post_only: bool,
+        ioc: bool,
+        client_id: Option<&str>,
+    ) -> PyResult<Py<PyAny>> {
+        let side = match side {
+            "buy" => Side::Buy,
+            "sell" => Side::Sell,
+            _ => return Err(value_error(format!("unknown side {}", side))),
+        };
+        let price = price.map(parse_decimal).transpose()?;
+        self.request(
+            py,
+            PlaceOrder {
+                market,
+                side,
+                price,
+                r#type: if price.is_some() {
+                    OrderType::Limit
+                } else {
+                    OrderType::Market
+                },
+                size: parse_decimal(size)?,
+                reduce_only,
+                time_in_force: TimeInForce::from_flags(ioc, post_only).map_err(value_error)?,
+                client_id,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn cancel_order(&self, py: Python<'_>, id: u64) -> PyResult<Py<PyAny>> {
+        self.request(py, CancelOrder::new(id))
+    }
+
+    /// Subscribes to websocket `channels` and returns an iterator over
+    /// `(market, data)` tuples.
+    fn websocket(&self, py: Python<'_>, channels: Vec<String>) -> PyResult<PyWebsocket> {
+        let channels = channels
+            .iter()
+            .map(|channel| channel.parse().map_err(value_error))
+            .collect::<PyResult<Vec<Channel>>>()?;
+        let options = self.options.clone();
+        let ws = py.detach(|| {
+            self.runtime
+                .block_on(async {
+                    let mut ws = Ws::connect(options).await?;
+                    ws.subscribe(&channels).await?;
+                    Ok::<_, crate::ws::Error>(ws)
+                })
+                .map_err(runtime_error)
+        })?;
+
+        Ok(PyWebsocket {
+            ws: Mutex::new(ws),
+            runtime: self.runtime.clone(),
+        })
+    }
+}
+
+/// Iterator over websocket messages.
+#[pyclass(name = "Websocket")]
+struct PyWebsocket {
+    ws: Mutex<Ws>,
+    runtime: Arc<Runtime>,
+}
+
+#[pymethods]
+impl PyWebsocket {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<(Option<String>, Py<PyAny>)> {
+        let next = py.detach(|| {
+            let mut ws = self.ws.lock().map_err(runtime_error)?;
+            Ok::<_, PyErr>(self.runtime.block_on(ws.next()))
+        })?;
+        match next {
+            Some(Ok((market, data))) => Ok((market, to_python(py, &data)?)),
+            Some(Err(error)) => Err(runtime_error(error)),
+            None => Err(PyStopIteration::new_err(())),
+        }
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "ftx")]
+fn python_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyClient>()?;
+    m.add_class::<PyWebsocket>()?;
+    Ok(())
+}
@@ -0,0 +1,119 @@
+//! Offline sanity checks of withdrawal addresses, so that obviously
+//! malformed addresses are rejected before they reach FTX.
+
+use hmac_sha256::Hash;
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BECH32_ALPHABET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Checks `address` against the format of the withdrawal `method` (e.g.
+/// `"erc20"`, `"trx"`, `"sol"`), as listed in `CoinInfo::methods`.
+///
+/// Returns `None` for methods whose format is unknown. EVM addresses are
+/// checked for length and hex digits only, their EIP-55 checksum is not
+/// verified.
+pub fn is_valid_address(method: &str, address: &str) -> Option<bool> {
+    let valid = match method {
+        "erc20" | "eth" | "bsc" | "bep20" | "heco" | "matic" | "ftm" | "avax" | "arbitrum"
+        | "optimism" => is_evm_address(address),
+        "trx" => {
+            base58check(address).is_some_and(|payload| payload.len() == 21 && payload[0] == 0x41)
+        }
+        "sol" | "spl" => base58(address).is_some_and(|bytes| bytes.len() == 32),
+        "btc" => {
+            base58check(address)
+                .is_some_and(|payload| payload.len() == 21 && matches!(payload[0], 0x00 | 0x05))
+                || bech32_hrp(address).as_deref() == Some("bc")
+        }
+        "bep2" => bech32_hrp(address).as_deref() == Some("bnb"),
+        _ => return None,
+    };
+    Some(valid)
+}
+
+fn is_evm_address(address: &str) -> bool {
+    address
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Decodes a base58 string.
+fn base58(s: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in s.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in bytes.iter_mut().rev() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = s.bytes().take_while(|&c| c == b'1').count();
+    let mut decoded = vec![0; zeros];
+    decoded.extend(bytes);
+    Some(decoded)
+}
+
+/// Decodes a base58 string with a 4 byte double SHA-256 checksum, returning
+/// the payload if the checksum matches.
+fn base58check(s: &str) -> Option<Vec<u8>> {
+    let mut bytes = base58(s)?;
+    if bytes.len() < 4 {
+        return None;
+    }
+    let checksum = bytes.split_off(bytes.len() - 4);
+    (Hash::hash(&Hash::hash(&bytes))[..4] == checksum[..]).then_some(bytes)
+}
+
+/// Returns the human readable part of a bech32 or bech32m string if its
+/// checksum matches.
+fn bech32_hrp(s: &str) -> Option<String> {
+    if s.len() > 90 || (s.to_lowercase() != s && s.to_uppercase() != s) {
+        return None;
+    }
+    let s = s.to_lowercase();
+    let (hrp, data) = s.rsplit_once('1')?;
+    if hrp.is_empty() || data.len() < 6 {
+        return None;
+    }
+    let data = data
+        .bytes()
+        .map(|c| {
+            BECH32_ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .map(|v| v as u8)
+        })
+        .collect::<Option<Vec<u8>>>()?;
+
+    let mut values: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|c| c & 31));
+    values.extend(data);
+
+    // bech32 and bech32m constants
+    matches!(bech32_polymod(&values), 1 | 0x2bc8_30a3).then(|| hrp.to_owned())
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    values.iter().fold(1, |chk, &value| {
+        let top = chk >> 25;
+        let chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(value);
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(chk, |chk, (_, gen)| chk ^ gen)
+    })
+}
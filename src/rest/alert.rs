@@ -0,0 +1,156 @@
+//! Operational alerts, e.g. orders rejected by `RiskLimits`.
+
+use super::Result;
+use async_trait::async_trait;
+#[cfg(any(feature = "slack", feature = "telegram"))]
+use reqwest::Client;
+#[cfg(any(feature = "slack", feature = "telegram"))]
+use serde_json::json;
+use std::fmt;
+use std::sync::Arc;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Clone, Debug)]
+pub struct Alert {
+    pub severity: Severity,
+    /// Component raising the alert, e.g. `"risk"`
+    pub source: String,
+    pub message: String,
+}
+
+impl Alert {
+    pub fn new(severity: Severity, source: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            source: source.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{:?}] {}: {}", self.severity, self.source, self.message)
+    }
+}
+
+/// Destination of operational alerts.
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    async fn send(&self, alert: &Alert) -> Result<()>;
+}
+
+#[async_trait]
+impl<T: AlertSink + ?Sized> AlertSink for Arc<T> {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        (**self).send(alert).await
+    }
+}
+
+/// Sends alerts to every sink, returning the first error after trying all.
+#[async_trait]
+impl<T: AlertSink> AlertSink for Vec<T> {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        let mut result = Ok(());
+        for sink in self {
+            if let Err(error) = sink.send(alert).await {
+                result = result.and(Err(error));
+            }
+        }
+        result
+    }
+}
+
+/// Writes alerts to the `log` crate, warnings and above as errors.
+#[derive(Clone, Debug, Default)]
+pub struct LogSink;
+
+#[async_trait]
+impl AlertSink for LogSink {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        match alert.severity {
+            Severity::Info => log::info!("{}", alert),
+            Severity::Warning | Severity::Critical => log::error!("{}", alert),
+        }
+        Ok(())
+    }
+}
+
+/// Posts alerts to a Slack incoming webhook. Errors omit the webhook URL,
+/// which is a secret.
+#[cfg(feature = "slack")]
+#[derive(Clone, Debug)]
+pub struct SlackSink {
+    client: Client,
+    webhook_url: String,
+}
+
+#[cfg(feature = "slack")]
+impl SlackSink {
+    pub fn new(webhook_url: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            webhook_url: webhook_url.into(),
+        }
+    }
+}
+
+#[cfg(feature = "slack")]
+#[async_trait]
+impl AlertSink for SlackSink {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        self.client
+            .post(&self.webhook_url)
+            .json(&json!({ "text": alert.to_string() }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.without_url())?;
+        Ok(())
+    }
+}
+
+/// Sends alerts to a Telegram chat through a bot. Errors omit the request
+/// URL, which contains the bot token.
+#[cfg(feature = "telegram")]
+#[derive(Clone, Debug)]
+pub struct TelegramSink {
+    client: Client,
+    bot_token: String,
+    chat_id: String,
+}
+
+#[cfg(feature = "telegram")]
+impl TelegramSink {
+    pub fn new(bot_token: impl Into<String>, chat_id: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            bot_token: bot_token.into(),
+            chat_id: chat_id.into(),
+        }
+    }
+}
+
+#[cfg(feature = "telegram")]
+#[async_trait]
+impl AlertSink for TelegramSink {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        self.client
+            .post(format!(
+                "https://api.telegram.org/bot{}/sendMessage",
+                self.bot_token
+            ))
+            .json(&json!({ "chat_id": self.chat_id, "text": alert.to_string() }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.without_url())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use super::{Error, HttpResponse, Result};
+use chrono::{DateTime, Utc};
+use hmac_sha256::Hash;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Fields of request bodies which are never written to the log.
+const REDACTED_FIELDS: &[&str] = &["password", "code"];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditKind {
+    /// Request about to be sent, with its body
+    Request,
+    /// Response received, with its body
+    Response,
+    /// No response was received, with the error
+    Failure,
+}
+
+/// Line of the audit log.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub time: DateTime<Utc>,
+    /// Shared by a request and its response
+    pub correlation_id: String,
+    pub kind: AuditKind,
+    pub method: String,
+    pub path: String,
+    pub body: Value,
+    /// Hash of the previous entry, empty for the first one
+    pub prev_hash: String,
+    /// SHA-256 of `prev_hash` and this entry with an empty `hash`
+    pub hash: String,
+}
+
+impl AuditEntry {
+    fn compute_hash(&self) -> String {
+        let unhashed = AuditEntry {
+            hash: String::new(),
+            ..self.clone()
+        };
+        let json = serde_json::to_string(&unhashed).expect("entries serialize");
+        let mut hash = Hash::new();
+        hash.update(self.prev_hash.as_bytes());
+        hash.update(json.as_bytes());
+        hex::encode(hash.finalize())
+    }
+}
+
+#[derive(Debug)]
+struct Chain {
+    file: File,
+    seq: u64,
+    last_hash: String,
+}
+
+/// Append-only log of all requests changing the account (orders, cancels,
+/// withdrawals, transfers...) and their responses, enabled with
+/// `Options::audit_log`.
+///
+/// Every entry contains the hash of the previous one, so editing or
+/// removing entries other than the last breaks the chain, which
+/// `AuditLog::verify` detects. Requests are logged before they are sent;
+/// a request which cannot be logged is not sent. Withdrawal passwords and
+/// 2FA codes are redacted.
+///
+/// Entries are written and synced on the blocking thread pool of tokio.
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    chain: Arc<Mutex<Chain>>,
+    /// Time the log was opened in milliseconds, prefix of correlation IDs
+    session: i64,
+    requests: AtomicU64,
+}
+
+impl AuditLog {
+    /// Opens or creates the log at `path`, verifying the existing entries.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let (seq, last_hash) = match File::open(&path) {
+            Ok(file) => verify_chain(file)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (0, String::new()),
+            Err(e) => return Err(io_error(&path, e)),
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| io_error(&path, e))?;
+        Ok(Self {
+            path,
+            chain: Arc::new(Mutex::new(Chain {
+                file,
+                seq,
+                last_hash,
+            })),
+            session: Utc::now().timestamp_millis(),
+            requests: AtomicU64::new(0),
+        })
+    }
+
+    /// Checks the hash chain of the log at `path`, returning the number of
+    /// entries, or `Error::AuditLogTampered` with the first entry which
+    /// does not match.
+    pub fn verify(path: impl AsRef<Path>) -> Result<u64> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| io_error(path, e))?;
+        Ok(verify_chain(file)?.0)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Logs a request with `body` about to be sent, returning the
+    /// correlation ID for its response.
+    pub(super) async fn request(
+        &self,
+        method: &Method,
+        path: &str,
+        body: Option<&str>,
+    ) -> Result<String> {
+        let mut body = body
+            .map(serde_json::from_str)
+            .transpose()?
+            .unwrap_or(Value::Null);
+        if let Value::Object(fields) = &mut body {
+            for field in REDACTED_FIELDS {
+                if let Some(value) = fields.get_mut(*field) {
+                    *value = Value::String("[redacted]".to_owned());
+                }
+            }
+        }
+        let correlation_id = format!(
+            "{}-{}",
+            self.session,
+            self.requests.fetch_add(1, Ordering::Relaxed)
+        );
+        self.append(&correlation_id, AuditKind::Request, method, path, body)
+            .await?;
+        Ok(correlation_id)
+    }
+
+    /// Logs the response to the request logged as `correlation_id`.
+    pub(super) async fn response(
+        &self,
+        correlation_id: &str,
+        method: &Method,
+        path: &str,
+        response: &Result<HttpResponse>,
+    ) -> Result<()> {
+        let (kind, body) = match response {
+            Ok(response) => (
+                AuditKind::Response,
+                serde_json::from_slice(&response.body).unwrap_or_else(|_| {
+                    Value::String(String::from_utf8_lossy(&response.body).into_owned())
+                }),
+            ),
+            Err(error) => (AuditKind::Failure, Value::String(error.to_string())),
+        };
+        self.append(correlation_id, kind, method, path, body).await
+    }
+
+    async fn append(
+        &self,
+        correlation_id: &str,
+        kind: AuditKind,
+        method: &Method,
+        path: &str,
+        body: Value,
+    ) -> Result<()> {
+        let chain = self.chain.clone();
+        let log_path = self.path.clone();
+        let mut entry = AuditEntry {
+            seq: 0,
+            time: Utc::now(),
+            correlation_id: correlation_id.to_owned(),
+            kind,
+            method: method.to_string(),
+            path: path.to_owned(),
+            body,
+            prev_hash: String::new(),
+            hash: String::new(),
+        };
+        tokio::task::spawn_blocking(move || {
+            let mut chain = chain.lock().unwrap();
+            entry.seq = chain.seq;
+            entry.prev_hash = chain.last_hash.clone();
+            entry.hash = entry.compute_hash();
+            let mut line = serde_json::to_vec(&entry)?;
+            line.push(b'\n');
+            chain
+                .file
+                .write_all(&line)
+                .and_then(|_| chain.file.sync_data())
+                .map_err(|e| io_error(&log_path, e))?;
+            chain.seq += 1;
+            chain.last_hash = entry.hash;
+            Ok(())
+        })
+        .await
+        .map_err(|e| Error::Io(std::io::Error::other(e)))?
+    }
+}
+
+/// Returns the number of entries and the hash of the last one.
+fn verify_chain(file: File) -> Result<(u64, String)> {
+    let mut seq = 0;
+    let mut last_hash = String::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        let entry: AuditEntry =
+            serde_json::from_str(&line).map_err(|_| Error::AuditLogTampered(seq))?;
+        if entry.seq != seq || entry.prev_hash != last_hash || entry.hash != entry.compute_hash() {
+            return Err(Error::AuditLogTampered(seq));
+        }
+        seq += 1;
+        last_hash = entry.hash;
+    }
+    Ok((seq, last_hash))
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> Error {
+    let message = format!("failed to write audit log {:?}: {}", path, error);
+    Error::Io(std::io::Error::new(error.kind(), message))
+}
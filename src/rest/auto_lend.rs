@@ -0,0 +1,129 @@
+use super::{
+    Coin, GetLendingInfo, GetWalletBalances, LendingInfo, Rest, Result, SubmitLendingOffer,
+    WalletBalance,
+};
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::time::Duration;
+
+/// Number of decimal places lending offer sizes are rounded down to.
+const SIZE_DECIMALS: u32 = 8;
+
+#[derive(Debug, Clone)]
+pub struct AutoLendConfig {
+    /// Coins to lend, e.g. `USD` and `USDT`
+    pub coins: Vec<Coin>,
+    /// Fraction of the idle balance to lend, between 0 and 1
+    pub fraction: Decimal,
+    /// Minimum hourly lending rate
+    pub min_rate: Decimal,
+    /// Amount of each coin that is never lent, e.g. to fund pending orders
+    pub reserve: Decimal,
+    /// Offers are only resized if the size changes by more than this
+    /// fraction, to avoid resubmitting on every small balance change
+    pub min_change: Decimal,
+}
+
+impl AutoLendConfig {
+    pub fn new(coins: Vec<Coin>, fraction: Decimal, min_rate: Decimal) -> Self {
+        Self {
+            coins,
+            fraction,
+            min_rate,
+            reserve: Decimal::ZERO,
+            min_change: Decimal::new(1, 2),
+        }
+    }
+
+    #[must_use]
+    pub fn reserve(mut self, reserve: Decimal) -> Self {
+        self.reserve = reserve;
+        self
+    }
+
+    #[must_use]
+    pub fn min_change(mut self, min_change: Decimal) -> Self {
+        self.min_change = min_change;
+        self
+    }
+
+    /// Returns the offer size for a coin. Funds locked by open orders are
+    /// excluded, as is the configured reserve.
+    pub fn target_size(&self, info: &LendingInfo, balance: Option<&WalletBalance>) -> Decimal {
+        let available = balance.map_or(info.lendable, |balance| info.lendable.min(balance.free));
+        ((available - self.reserve).max(Decimal::ZERO) * self.fraction)
+            .round_dp_with_strategy(SIZE_DECIMALS, RoundingStrategy::ToZero)
+    }
+
+    /// Returns whether the current offer in `info` should be replaced by an
+    /// offer of `target` size.
+    pub fn needs_update(&self, info: &LendingInfo, target: Decimal) -> bool {
+        if info.min_rate != Some(self.min_rate) && !target.is_zero() {
+            return true;
+        }
+        let change = (target - info.offered).abs();
+        if info.offered.is_zero() {
+            !change.is_zero()
+        } else {
+            change > info.offered * self.min_change
+        }
+    }
+}
+
+/// Keeps a configured fraction of idle balances lent out at a minimum rate,
+/// resizing the lending offers as balances change.
+#[derive(Debug, Clone)]
+pub struct AutoLender {
+    rest: Rest,
+    config: AutoLendConfig,
+}
+
+impl AutoLender {
+    pub fn new(rest: Rest, config: AutoLendConfig) -> Self {
+        Self { rest, config }
+    }
+
+    pub fn config(&self) -> &AutoLendConfig {
+        &self.config
+    }
+
+    /// Resizes the lending offers of all configured coins once. Returns the
+    /// coins whose offers were changed, with their new size.
+    pub async fn rebalance(&self) -> Result<Vec<(Coin, Decimal)>> {
+        let infos = self.rest.request(GetLendingInfo {}).await?;
+        let balances = self.rest.request(GetWalletBalances {}).await?;
+
+        let mut changed = Vec::new();
+        for coin in &self.config.coins {
+            let info = match infos.iter().find(|info| &info.coin == coin) {
+                Some(info) => info,
+                None => continue,
+            };
+            let balance = balances.iter().find(|balance| &balance.coin == coin);
+            let target = self.config.target_size(info, balance);
+            if !self.config.needs_update(info, target) {
+                continue;
+            }
+
+            self.rest
+                .request(SubmitLendingOffer {
+                    coin,
+                    size: target,
+                    rate: self.config.min_rate,
+                })
+                .await?;
+            changed.push((coin.clone(), target));
+        }
+
+        Ok(changed)
+    }
+
+    /// Rebalances `count` times, `interval` apart.
+    pub async fn run(&self, interval: Duration, count: usize) -> Result<()> {
+        let mut interval = tokio::time::interval(interval);
+        for _ in 0..count {
+            interval.tick().await;
+            self.rebalance().await?;
+        }
+        Ok(())
+    }
+}
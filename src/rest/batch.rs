@@ -0,0 +1,84 @@
+use super::{CancelOrder, Error, Id, OrderInfo, PlaceOrder, Rest, Result};
+use futures::stream::{self, StreamExt};
+
+/// Outcome of a batch of requests, holding one entry per submitted item in
+/// the same order as the input.
+#[derive(Debug)]
+pub struct BatchReport<T, R> {
+    pub results: Vec<(T, Result<R>)>,
+}
+
+/// Outcome of `Rest::place_orders`.
+pub type PlaceOrdersReport<'a> = BatchReport<PlaceOrder<'a>, OrderInfo>;
+
+/// Outcome of `Rest::cancel_orders`.
+pub type CancelOrdersReport = BatchReport<Id, String>;
+
+impl<T, R> BatchReport<T, R> {
+    /// Returns true if every request succeeded.
+    pub fn is_all_ok(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    /// Returns the accepted items with their responses.
+    pub fn succeeded(&self) -> impl Iterator<Item = (&T, &R)> {
+        self.results
+            .iter()
+            .filter_map(|(item, result)| result.as_ref().ok().map(|response| (item, response)))
+    }
+
+    /// Returns the rejected items together with the error returned for them.
+    pub fn failed(&self) -> impl Iterator<Item = (&T, &Error)> {
+        self.results
+            .iter()
+            .filter_map(|(item, result)| result.as_ref().err().map(|err| (item, err)))
+    }
+}
+
+impl Rest {
+    /// Submits all orders concurrently. A failing order does not abort the
+    /// others; each order's outcome is reported individually.
+    pub async fn place_orders<'a>(&self, orders: Vec<PlaceOrder<'a>>) -> PlaceOrdersReport<'a> {
+        let concurrency = orders.len();
+        self.place_orders_with_concurrency(orders, concurrency)
+            .await
+    }
+
+    /// Like `place_orders`, with at most `concurrency` requests in flight.
+    pub async fn place_orders_with_concurrency<'a>(
+        &self,
+        orders: Vec<PlaceOrder<'a>>,
+        concurrency: usize,
+    ) -> PlaceOrdersReport<'a> {
+        let results: Vec<_> = stream::iter(orders.iter().cloned().map(|order| self.request(order)))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+
+        BatchReport {
+            results: orders.into_iter().zip(results).collect(),
+        }
+    }
+
+    /// Cancels all orders concurrently, reporting each outcome individually.
+    pub async fn cancel_orders(&self, ids: &[Id]) -> CancelOrdersReport {
+        self.cancel_orders_with_concurrency(ids, ids.len()).await
+    }
+
+    /// Like `cancel_orders`, with at most `concurrency` requests in flight.
+    pub async fn cancel_orders_with_concurrency(
+        &self,
+        ids: &[Id],
+        concurrency: usize,
+    ) -> CancelOrdersReport {
+        let results: Vec<_> =
+            stream::iter(ids.iter().map(|id| self.request(CancelOrder::new(*id))))
+                .buffered(concurrency.max(1))
+                .collect()
+                .await;
+
+        BatchReport {
+            results: ids.iter().copied().zip(results).collect(),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use super::{
+    parse_response, GetOpenOrders, GetOrder, HttpResponse, OrderInfoRef, PlaceOrder, Request, Rest,
+    Result,
+};
+use bytes::Bytes;
+use serde::{de::IgnoredAny, Deserialize};
+use std::marker::PhantomData;
+
+/// Request with a response type borrowing from the response body, see
+/// `Rest::request_borrowed`.
+pub trait BorrowedRequest: Request {
+    type ResponseRef<'a>: Deserialize<'a>;
+}
+
+impl BorrowedRequest for PlaceOrder<'_> {
+    type ResponseRef<'a> = OrderInfoRef<'a>;
+}
+
+impl BorrowedRequest for GetOrder {
+    type ResponseRef<'a> = OrderInfoRef<'a>;
+}
+
+impl BorrowedRequest for GetOpenOrders<'_> {
+    type ResponseRef<'a> = Vec<OrderInfoRef<'a>>;
+}
+
+/// Successful response kept as received, to deserialize views borrowing
+/// from it.
+#[derive(Clone, Debug)]
+pub struct ResponseBuffer<R> {
+    response: HttpResponse,
+    request: PhantomData<fn() -> R>,
+}
+
+impl<R: BorrowedRequest> ResponseBuffer<R> {
+    /// Deserializes the result without copying its strings.
+    pub fn parse(&self) -> Result<R::ResponseRef<'_>> {
+        parse_response(&self.response).map(|response| response.result)
+    }
+
+    pub fn body(&self) -> &Bytes {
+        &self.response.body
+    }
+}
+
+impl Rest {
+    /// Like `request`, returning the response body for `ResponseBuffer::parse`
+    /// to deserialize types such as `OrderInfoRef`, which borrow their
+    /// strings instead of allocating them.
+    ///
+    /// Error responses fail here. Responses are not recorded for
+    /// `Options::detect_schema_drift`.
+    pub async fn request_borrowed<R: BorrowedRequest>(&self, req: R) -> Result<ResponseBuffer<R>> {
+        let response = self.send_request(&req).await?;
+        if !response.status.is_success() {
+            parse_response::<IgnoredAny>(&response)?;
+        }
+        Ok(ResponseBuffer {
+            response,
+            request: PhantomData,
+        })
+    }
+}
@@ -0,0 +1,195 @@
+use super::{
+    GetFundingRates, GetFuture, GetFutures, GetMarket, GetMarkets, HttpResponse, Request, Rest,
+    Result, SuccessResponse,
+};
+use bytes::Bytes;
+use reqwest::Method;
+use serde::de::IgnoredAny;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long responses of public requests are cached, set with
+/// `Options::cache`.
+///
+/// Responses are fresh for the TTL of their endpoint, keyed by
+/// `Request::PATH`. For the `stale_while_revalidate` period after that, the
+/// stale response is still returned while it is refreshed in the
+/// background. Authenticated requests are never cached.
+#[derive(Clone, Debug)]
+pub struct CachePolicy {
+    ttls: HashMap<&'static str, Duration>,
+    stale_while_revalidate: Duration,
+}
+
+impl Default for CachePolicy {
+    /// Caches markets and futures for a second and funding rates for a
+    /// minute, serving stale responses for up to 5 seconds while refreshing.
+    fn default() -> Self {
+        Self::new()
+            .ttl(GetMarkets::PATH, Duration::from_secs(1))
+            .ttl(GetMarket::PATH, Duration::from_secs(1))
+            .ttl(GetFutures::PATH, Duration::from_secs(1))
+            .ttl(GetFuture::PATH, Duration::from_secs(1))
+            .ttl(GetFundingRates::PATH, Duration::from_secs(60))
+            .stale_while_revalidate(Duration::from_secs(5))
+    }
+}
+
+impl CachePolicy {
+    /// Policy caching nothing, add endpoints with `ttl`.
+    pub fn new() -> Self {
+        Self {
+            ttls: HashMap::new(),
+            stale_while_revalidate: Duration::ZERO,
+        }
+    }
+
+    /// Caches responses of the public GET endpoint `path`, e.g.
+    /// `GetMarkets::PATH`, for `ttl`.
+    #[must_use]
+    pub fn ttl(mut self, path: &'static str, ttl: Duration) -> Self {
+        self.ttls.insert(path, ttl);
+        self
+    }
+
+    #[must_use]
+    pub fn stale_while_revalidate(mut self, period: Duration) -> Self {
+        self.stale_while_revalidate = period;
+        self
+    }
+}
+
+struct Entry {
+    body: Bytes,
+    fresh_until: Instant,
+    stale_until: Instant,
+    refreshing: bool,
+}
+
+enum Lookup {
+    Fresh(Bytes),
+    /// Stale response, and whether the caller should refresh it
+    Stale(Bytes, bool),
+    Miss,
+}
+
+pub(super) struct ResponseCache {
+    policy: CachePolicy,
+    /// Responses by path and query
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl std::fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseCache")
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ResponseCache {
+    pub fn new(policy: CachePolicy) -> Self {
+        Self {
+            policy,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// TTL of the endpoint `path`, if it is cached.
+    pub fn ttl(&self, path: &str) -> Option<Duration> {
+        self.policy.ttls.get(path).copied()
+    }
+
+    fn lookup(&self, key: &str) -> Lookup {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get_mut(key) {
+            Some(entry) if now < entry.fresh_until => Lookup::Fresh(entry.body.clone()),
+            Some(entry) if now < entry.stale_until => {
+                let refresh = !entry.refreshing;
+                entry.refreshing = true;
+                Lookup::Stale(entry.body.clone(), refresh)
+            }
+            _ => Lookup::Miss,
+        }
+    }
+
+    /// Stores `body` if it is a successful response.
+    fn insert(&self, key: String, body: &Bytes, ttl: Duration) {
+        if serde_json::from_slice::<SuccessResponse<IgnoredAny>>(body).is_err() {
+            self.refresh_failed(&key);
+            return;
+        }
+        let now = Instant::now();
+        let stale_while_revalidate = self.policy.stale_while_revalidate;
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| now < entry.stale_until);
+        entries.insert(
+            key,
+            Entry {
+                body: body.clone(),
+                fresh_until: now + ttl,
+                stale_until: now + ttl + stale_while_revalidate,
+                refreshing: false,
+            },
+        );
+    }
+
+    /// Lets the next request for a stale `key` try to refresh it again.
+    fn refresh_failed(&self, key: &str) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(key) {
+            entry.refreshing = false;
+        }
+    }
+
+    /// Forgets all responses.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+impl Rest {
+    /// Sends the public GET request for `path`, answering from and filling
+    /// the cache.
+    pub(super) async fn cached_send(
+        &self,
+        cache: &Arc<ResponseCache>,
+        path: String,
+        optimized_access: bool,
+        ttl: Duration,
+    ) -> Result<HttpResponse> {
+        match cache.lookup(&path) {
+            Lookup::Fresh(body) | Lookup::Stale(body, false) => Ok(HttpResponse::ok(body)),
+            Lookup::Stale(body, true) => {
+                let rest = self.clone();
+                let cache = cache.clone();
+                tokio::spawn(async move {
+                    match rest
+                        .send(Method::GET, &path, None, false, optimized_access)
+                        .await
+                    {
+                        Ok(response) => cache.insert(path, &response.body, ttl),
+                        Err(error) => {
+                            log::warn!("failed to refresh cached {}: {}", path, error);
+                            cache.refresh_failed(&path);
+                        }
+                    }
+                });
+                Ok(HttpResponse::ok(body))
+            }
+            Lookup::Miss => {
+                let response = self.coalesced_send(path.clone(), optimized_access).await?;
+                cache.insert(path, &response.body, ttl);
+                Ok(response)
+            }
+        }
+    }
+
+    /// Drops all cached responses, e.g. after listing a new market.
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+}
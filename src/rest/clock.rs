@@ -0,0 +1,104 @@
+use super::{GetTime, Rest, Result};
+use chrono::{Duration, Utc};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Requests sent by `Rest::sync_time`, of which the one with the shortest
+/// round trip is used.
+const SYNC_SAMPLES: usize = 3;
+
+/// Offset of the exchange clock to the local one, applied to the timestamps
+/// of signed requests.
+#[derive(Debug, Default)]
+pub(super) struct Clock {
+    offset_millis: AtomicI64,
+    /// Interval of resyncing in the background, see `Options::sync_time`
+    resync_interval: Option<std::time::Duration>,
+    resyncing: AtomicBool,
+}
+
+impl Clock {
+    pub(super) fn new(resync_interval: Option<std::time::Duration>) -> Self {
+        Self {
+            resync_interval,
+            ..Default::default()
+        }
+    }
+
+    /// Milliseconds since the epoch on the exchange clock.
+    pub(super) fn timestamp(&self) -> Result<u128> {
+        let local = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i128;
+        let offset = self.offset_millis.load(Ordering::Relaxed) as i128;
+        Ok((local + offset).max(0) as u128)
+    }
+}
+
+impl Rest {
+    /// Measures the offset of the exchange clock to the local one and
+    /// applies it to the timestamps of signed requests, so they are not
+    /// rejected because of a drifting local clock. Returns the offset,
+    /// positive if the exchange clock is ahead.
+    ///
+    /// The offset is shared with clients derived with `for_subaccount`.
+    pub async fn sync_time(&self) -> Result<Duration> {
+        let mut best: Option<(Duration, Duration)> = None;
+        for _ in 0..SYNC_SAMPLES {
+            let sent = Utc::now();
+            let server_time = self.request(GetTime {}).await?;
+            let received = Utc::now();
+            let round_trip = received - sent;
+            let offset = server_time - (sent + round_trip / 2);
+            if best.is_none_or(|(shortest, _)| round_trip < shortest) {
+                best = Some((round_trip, offset));
+            }
+        }
+        let (_, offset) = best.expect("at least one sample");
+        self.clock
+            .offset_millis
+            .store(offset.num_milliseconds(), Ordering::Relaxed);
+        log::debug!("exchange clock offset: {}ms", offset.num_milliseconds());
+        Ok(offset)
+    }
+
+    /// Offset applied to the timestamps of signed requests, zero until
+    /// `sync_time` succeeded.
+    pub fn clock_offset(&self) -> Duration {
+        Duration::milliseconds(self.clock.offset_millis.load(Ordering::Relaxed))
+    }
+
+    /// Starts resyncing the clock periodically, if configured and not yet
+    /// started.
+    pub(super) fn spawn_time_sync(&self) {
+        let interval = match self.clock.resync_interval {
+            Some(interval) => interval,
+            None => return,
+        };
+        if self.clock.resyncing.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        // Only a weak reference to the clock, so the task ends with the
+        // last client. Requests are sent by a client with its own clock.
+        let clock = Arc::downgrade(&self.clock);
+        let rest = Rest {
+            clock: Arc::new(Clock::default()),
+            ..self.clone()
+        };
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(interval);
+            loop {
+                timer.tick().await;
+                let clock = match clock.upgrade() {
+                    Some(clock) => clock,
+                    None => break,
+                };
+                match rest.sync_time().await {
+                    Ok(offset) => clock
+                        .offset_millis
+                        .store(offset.num_milliseconds(), Ordering::Relaxed),
+                    Err(error) => log::warn!("failed to sync time: {}", error),
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,62 @@
+use super::{Error, HttpResponse, Rest, Result};
+use futures::future::{BoxFuture, FutureExt, Shared};
+use reqwest::Method;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+type SharedResponse = Shared<BoxFuture<'static, std::result::Result<HttpResponse, Arc<Error>>>>;
+
+/// Public requests in flight, by path and query.
+#[derive(Clone, Default)]
+pub(super) struct InFlight(Arc<Mutex<HashMap<String, SharedResponse>>>);
+
+impl std::fmt::Debug for InFlight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let paths: Vec<String> = self.0.lock().unwrap().keys().cloned().collect();
+        f.debug_tuple("InFlight").field(&paths).finish()
+    }
+}
+
+impl Rest {
+    /// Sends the public GET request for `path`, sharing the response with
+    /// identical requests sent at the same time.
+    ///
+    /// Only responses are shared, including error responses of the API. If
+    /// no response arrives at all, each caller sends its own request.
+    pub(super) async fn coalesced_send(
+        &self,
+        path: String,
+        optimized_access: bool,
+    ) -> Result<HttpResponse> {
+        let response = {
+            let mut in_flight = self.in_flight.0.lock().unwrap();
+            match in_flight.get(&path) {
+                Some(response) => response.clone(),
+                None => {
+                    let rest = self.clone();
+                    let request_path = path.clone();
+                    let response = async move {
+                        let response = rest
+                            .send(Method::GET, &request_path, None, false, optimized_access)
+                            .await;
+                        rest.in_flight.0.lock().unwrap().remove(&request_path);
+                        response.map_err(Arc::new)
+                    }
+                    .boxed()
+                    .shared();
+                    in_flight.insert(path.clone(), response.clone());
+                    response
+                }
+            }
+        };
+
+        match response.await {
+            Ok(response) => Ok(response),
+            Err(error) => {
+                log::debug!("coalesced request for {} failed: {}", path, error);
+                self.send(Method::GET, &path, None, false, optimized_access)
+                    .await
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use super::{AcceptQuote, Error, GetQuote, Quote, RequestQuote, Rest, Result};
+use chrono::{Duration, Utc};
+use rust_decimal::Decimal;
+
+/// Number of times `Rest::convert` requests a new quote before giving up.
+pub const MAX_REQUOTES: usize = 4;
+
+/// Quotes expiring sooner than this are re-quoted instead of accepted, since
+/// the accept request would likely arrive too late.
+const ACCEPT_MARGIN_MILLIS: i64 = 500;
+
+/// Decision on a quote offered by `Rest::convert`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum QuoteDecision {
+    Accept,
+    /// Discard the quote and request a new one
+    Requote,
+    /// Abort the conversion
+    Reject,
+}
+
+impl Rest {
+    /// Converts `size` of `from` into `to` via an OTC quote.
+    ///
+    /// Each quote is passed to `decide`. Accepted quotes that expire within
+    /// the next 500ms are re-quoted automatically, up to `MAX_REQUOTES`
+    /// times in total. Returns the filled quote, `None` if `decide` rejected
+    /// a quote or asked for more requotes, or
+    /// `Error::QuoteExpiredAfterRequotes` if the last quote expired.
+    pub async fn convert(
+        &self,
+        from: &str,
+        to: &str,
+        size: Decimal,
+        mut decide: impl FnMut(&Quote) -> QuoteDecision,
+    ) -> Result<Option<Quote>> {
+        for requotes in 0..=MAX_REQUOTES {
+            let quote_id = self
+                .request(RequestQuote::new(from, to, size))
+                .await?
+                .quote_id;
+            let quote = self.request(GetQuote::new(quote_id)).await?;
+
+            match decide(&quote) {
+                QuoteDecision::Reject => return Ok(None),
+                QuoteDecision::Requote => continue,
+                QuoteDecision::Accept => {
+                    let deadline = quote.expiry - Duration::milliseconds(ACCEPT_MARGIN_MILLIS);
+                    if quote.expired || Utc::now() >= deadline {
+                        if requotes == MAX_REQUOTES {
+                            return Err(Error::QuoteExpiredAfterRequotes(requotes));
+                        }
+                        continue;
+                    }
+                    self.request(AcceptQuote::new(quote_id)).await?;
+                    return Ok(Some(self.request(GetQuote::new(quote_id)).await?));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
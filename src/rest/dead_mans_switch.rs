@@ -0,0 +1,84 @@
+use super::{Alert, AlertSink, CancelAllAfter, Error, Rest, Result, Severity};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time;
+
+/// Keeps refreshing the cancel-all timer of the exchange, so open orders
+/// are cancelled once the process stops or loses its connection.
+///
+/// Dropping the switch stops the refreshes without disabling the timer, so
+/// the orders are cancelled when it expires. Use `disarm` to keep them.
+#[derive(Debug)]
+pub struct DeadMansSwitch {
+    rest: Rest,
+    task: JoinHandle<()>,
+}
+
+impl DeadMansSwitch {
+    /// Stops refreshing and disables the timer.
+    pub async fn disarm(self) -> Result<()> {
+        self.task.abort();
+        self.rest.request(CancelAllAfter::disable()).await
+    }
+}
+
+impl Drop for DeadMansSwitch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Rest {
+    /// Arms the cancel-all timer with `timeout` and spawns a task resetting
+    /// it every `interval`, which must be shorter than `timeout`.
+    ///
+    /// Fails if the timer cannot be armed. Failed refreshes are logged,
+    /// reported to `alert_sink` as critical once until a refresh succeeds
+    /// again, and retried on the next tick.
+    pub async fn spawn_dead_mans_switch(
+        &self,
+        timeout: Duration,
+        interval: Duration,
+        alert_sink: Option<Arc<dyn AlertSink>>,
+    ) -> Result<DeadMansSwitch> {
+        if interval >= timeout {
+            return Err(Error::Config(format!(
+                "dead man's switch interval {:?} is not shorter than its timeout {:?}",
+                interval, timeout
+            )));
+        }
+        self.request(CancelAllAfter::new(timeout)).await?;
+
+        let rest = self.clone();
+        let task = tokio::spawn(async move {
+            let mut timer = time::interval_at(time::Instant::now() + interval, interval);
+            let mut failing = false;
+            loop {
+                timer.tick().await;
+                match rest.request(CancelAllAfter::new(timeout)).await {
+                    Ok(()) => failing = false,
+                    Err(error) => {
+                        log::warn!("failed to refresh dead man's switch: {}", error);
+                        if let Some(alert_sink) = alert_sink.as_ref().filter(|_| !failing) {
+                            let alert = Alert::new(
+                                Severity::Critical,
+                                "dead_mans_switch",
+                                format!("failed to refresh dead man's switch: {}", error),
+                            );
+                            if let Err(error) = alert_sink.send(&alert).await {
+                                log::error!("failed to send alert: {}", error);
+                            }
+                        }
+                        failing = true;
+                    }
+                }
+            }
+        });
+
+        Ok(DeadMansSwitch {
+            rest: self.clone(),
+            task,
+        })
+    }
+}
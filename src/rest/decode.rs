@@ -0,0 +1,378 @@
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+use serde::forward_to_deserialize_any;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+/// How responses which do not match the models are handled, see
+/// `Options::strictness`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Strictness {
+    /// Fail the request.
+    #[default]
+    Strict,
+    /// Ignore optional fields which are missing or cannot be parsed, e.g.
+    /// enums with a variant added by the exchange, and fail only if a
+    /// required field is affected.
+    Lenient,
+}
+
+thread_local! {
+    /// JSON objects being deserialized by `decode`, innermost last
+    static CAPTURING: RefCell<Vec<Arc<Value>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// JSON object a model was deserialized from, kept if enabled with
+/// `Options::capture_raw`, to read fields the crate does not model yet.
+#[derive(Clone, Debug, Default)]
+pub struct Raw(Option<Arc<Value>>);
+
+impl Raw {
+    /// The object being deserialized, if captured.
+    pub(crate) fn captured() -> Self {
+        Self(CAPTURING.with(|capturing| capturing.borrow().last().cloned()))
+    }
+
+    pub fn get(&self) -> Option<&Value> {
+        self.0.as_deref()
+    }
+
+    /// Field `name` of the object, as sent by the exchange.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        self.get()?.get(name)
+    }
+}
+
+/// Pops the object pushed onto `CAPTURING` when dropped.
+struct Capture;
+
+impl Capture {
+    fn push(value: &Value) -> Self {
+        CAPTURING.with(|capturing| capturing.borrow_mut().push(Arc::new(value.clone())));
+        Capture
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        CAPTURING.with(|capturing| capturing.borrow_mut().pop());
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+struct Path<'a>(&'a [Segment]);
+
+impl fmt::Display for Path<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "result");
+        }
+        for segment in self.0 {
+            match segment {
+                Segment::Key(key) => write!(f, ".{}", key)?,
+                Segment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes `value`, keeping the objects models are deserialized from
+/// if `capture` is set. If lenient, failing fields are set to null or
+/// dropped until the value deserializes, or a required field fails.
+pub(super) fn decode<T: DeserializeOwned>(
+    value: &Value,
+    strictness: Strictness,
+    capture: bool,
+) -> serde_json::Result<T> {
+    let original = value;
+    let mut value = Cow::Borrowed(value);
+    let mut repaired = HashSet::new();
+    let mut first_error = None;
+    loop {
+        let mut state = State {
+            original: capture.then_some(original),
+            failed_at: None,
+        };
+        let result = T::deserialize(Decoder {
+            value: &value,
+            path: Vec::new(),
+            state: &mut state,
+        });
+        let error = match result {
+            Ok(result) => return Ok(result),
+            Err(error) if strictness == Strictness::Strict => return Err(error),
+            Err(error) => error,
+        };
+        let repair = state
+            .failed_at
+            .and_then(|path| repair(value.to_mut(), path, &error, &mut repaired));
+        match repair {
+            Some(repair) => {
+                log::warn!("{} of response: {}", repair, error);
+                first_error.get_or_insert(error);
+            }
+            None => return Err(first_error.unwrap_or(error)),
+        }
+    }
+}
+
+/// Sets a missing field to null, or drops the field containing `path`,
+/// unless it was repaired before. Returns what was done.
+fn repair(
+    value: &mut Value,
+    mut path: Vec<Segment>,
+    error: &serde_json::Error,
+    repaired: &mut HashSet<Vec<Segment>>,
+) -> Option<String> {
+    let missing = error
+        .to_string()
+        .strip_prefix("missing field `")
+        .and_then(|field| Some(field.split_once('`')?.0.to_owned()));
+    if let Some(field) = missing {
+        let mut field_path = path.clone();
+        field_path.push(Segment::Key(field.clone()));
+        if !repaired.contains(&field_path) {
+            let object = get_mut(value, &path)?.as_object_mut()?;
+            object.insert(field, Value::Null);
+            let repair = format!("setting missing {} to null", Path(&field_path));
+            repaired.insert(field_path);
+            return Some(repair);
+        }
+    }
+
+    // A repaired field which still fails is required, so its parent goes
+    while repaired.contains(&path) {
+        path.pop();
+    }
+    let key = match path.pop()? {
+        Segment::Key(key) => key,
+        Segment::Index(_) => return None,
+    };
+    get_mut(value, &path)?.as_object_mut()?.remove(&key);
+    path.push(Segment::Key(key));
+    let repair = format!("ignoring {}", Path(&path));
+    repaired.insert(path);
+    Some(repair)
+}
+
+fn get<'a>(mut value: &'a Value, path: &[Segment]) -> Option<&'a Value> {
+    for segment in path {
+        value = match segment {
+            Segment::Key(key) => value.get(key.as_str())?,
+            Segment::Index(index) => value.get(*index)?,
+        };
+    }
+    Some(value)
+}
+
+fn get_mut<'a>(mut value: &'a mut Value, path: &[Segment]) -> Option<&'a mut Value> {
+    for segment in path {
+        value = match segment {
+            Segment::Key(key) => value.get_mut(key.as_str())?,
+            Segment::Index(index) => value.get_mut(*index)?,
+        };
+    }
+    Some(value)
+}
+
+struct State<'v> {
+    /// Value as received, if capturing the objects of structs
+    original: Option<&'v Value>,
+    /// Innermost position where deserializing failed
+    failed_at: Option<Vec<Segment>>,
+}
+
+impl State<'_> {
+    fn failed<T>(
+        &mut self,
+        path: Vec<Segment>,
+        result: serde_json::Result<T>,
+    ) -> serde_json::Result<T> {
+        if result.is_err() && self.failed_at.is_none() {
+            self.failed_at = Some(path);
+        }
+        result
+    }
+}
+
+/// Deserializer of a `serde_json::Value` which remembers where it failed
+/// and captures the objects of structs.
+struct Decoder<'a, 'de> {
+    value: &'de Value,
+    path: Vec<Segment>,
+    state: &'a mut State<'de>,
+}
+
+impl<'de> Decoder<'_, 'de> {
+    fn visit_map<V: Visitor<'de>>(
+        value: &'de Map<String, Value>,
+        path: &[Segment],
+        state: &mut State<'de>,
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        visitor.visit_map(Entries {
+            entries: value.iter(),
+            value: None,
+            path,
+            state,
+        })
+    }
+}
+
+impl<'de> Deserializer<'de> for Decoder<'_, 'de> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        let Decoder { value, path, state } = self;
+        let result = match value {
+            Value::Array(items) => visitor.visit_seq(Items {
+                items: items.iter().enumerate(),
+                path: &path,
+                state,
+            }),
+            Value::Object(object) => Self::visit_map(object, &path, state, visitor),
+            value => value.deserialize_any(visitor),
+        };
+        state.failed(path, result)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        let Decoder { value, path, state } = self;
+        let result = match value {
+            Value::Object(object) => {
+                // Fields dropped by lenient decoding are kept
+                let _capture = state
+                    .original
+                    .map(|original| Capture::push(get(original, &path).unwrap_or(value)));
+                Self::visit_map(object, &path, state, visitor)
+            }
+            value => value.deserialize_any(visitor),
+        };
+        state.failed(path, result)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        let result = match self.value {
+            Value::String(variant) => visitor.visit_enum(variant.as_str().into_deserializer()),
+            value => value.deserialize_enum(name, variants, visitor),
+        };
+        self.state.failed(self.path, result)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier
+        ignored_any
+    }
+}
+
+struct Items<'a, 'de> {
+    items: std::iter::Enumerate<std::slice::Iter<'de, Value>>,
+    path: &'a [Segment],
+    state: &'a mut State<'de>,
+}
+
+impl<'de> SeqAccess<'de> for Items<'_, 'de> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> serde_json::Result<Option<T::Value>> {
+        match self.items.next() {
+            Some((index, value)) => {
+                let mut path = self.path.to_vec();
+                path.push(Segment::Index(index));
+                seed.deserialize(Decoder {
+                    value,
+                    path,
+                    state: self.state,
+                })
+                .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct Entries<'a, 'de> {
+    entries: serde_json::map::Iter<'de>,
+    value: Option<(&'de String, &'de Value)>,
+    path: &'a [Segment],
+    state: &'a mut State<'de>,
+}
+
+impl<'de> MapAccess<'de> for Entries<'_, 'de> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> serde_json::Result<Option<K::Value>> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some((key, value));
+                seed.deserialize(key.as_str().into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> serde_json::Result<V::Value> {
+        let (key, value) = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before key"))?;
+        let mut path = self.path.to_vec();
+        path.push(Segment::Key(key.clone()));
+        seed.deserialize(Decoder {
+            value,
+            path,
+            state: self.state,
+        })
+    }
+}
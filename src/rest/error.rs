@@ -1,18 +1,71 @@
+use reqwest::StatusCode;
+use rust_decimal::Decimal;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, Error)]
 pub enum Error {
-    #[error("Api error: {0}")]
-    Api(String),
+    /// Invalid configuration or arguments, detected before anything is sent
+    #[error("invalid configuration: {0}")]
+    Config(String),
+
+    /// Error response of the exchange, displayed with its original message
+    #[error("Api error: {message}")]
+    Exchange {
+        status: StatusCode,
+        error: ApiError,
+        message: String,
+    },
 
     #[error("placing limit order requires price")]
     PlacingLimitOrderRequiresPrice,
 
+    #[error("orders cannot be both immediate-or-cancel and post-only")]
+    ConflictingTimeInForce,
+
+    #[error("market orders cannot be post-only")]
+    PostOnlyMarketOrder,
+
+    #[error("trail value must be negative for sell and positive for buy orders")]
+    TrailValueSignMismatch,
+
+    #[error("retry until filled is only supported by market trigger orders")]
+    RetryUntilFilledRequiresMarketTrigger,
+
+    #[error("withdrawal address {0} is not a saved address")]
+    AddressNotSaved(String),
+
+    #[error("invalid {1} address {0}")]
+    InvalidAddress(String, String),
+
+    #[error("withdrawals of {0} require a tag")]
+    TagRequired(String),
+
+    #[error("size {0} is below the minimum of {1}")]
+    SizeBelowMinimum(Decimal, Decimal),
+
+    #[error("size {0} exceeds the available {1}")]
+    SizeExceedsLimit(Decimal, Decimal),
+
+    #[error("risk limit exceeded: {0}")]
+    RiskLimitExceeded(String),
+
     #[error("endpoint requires auth but no secret configured")]
     NoSecretConfigured,
 
+    #[error("timed out")]
+    Timeout,
+
+    #[error("quote expired again after {0} requotes")]
+    QuoteExpiredAfterRequotes(usize),
+
+    #[error("audit log entry {0} was modified or removed")]
+    AuditLogTampered(u64),
+
+    #[error("job failed: {0}")]
+    JobFailed(String),
+
     #[error(transparent)]
     SerdeQs(#[from] serde_qs::Error),
 
@@ -22,6 +75,123 @@ pub enum Error {
     #[error(transparent)]
     Json(#[from] serde_json::Error),
 
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
     #[error(transparent)]
     SystemTime(#[from] std::time::SystemTimeError),
+
+    #[cfg(feature = "ws")]
+    #[error(transparent)]
+    Ws(#[from] Box<crate::ws::Error>),
+}
+
+/// Error messages of the exchange, parsed with `ApiError::from_message`.
+///
+/// Variants are displayed with a generic description; the message as sent,
+/// e.g. with the actual rate limit, is kept in `Error::Exchange`.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum ApiError {
+    #[error("Not logged in: Invalid signature")]
+    InvalidSignature,
+
+    #[error("Not logged in: Invalid API key")]
+    InvalidApiKey,
+
+    #[error("Not logged in")]
+    NotLoggedIn,
+
+    #[error("Not allowed with read-only permissions")]
+    ReadOnlyPermissions,
+
+    #[error("Do not send more requests than the rate limit allows")]
+    RateLimited,
+
+    #[error("Not enough balances")]
+    InsufficientBalance,
+
+    #[error("Account does not have enough margin for order")]
+    InsufficientMargin,
+
+    #[error("No such market")]
+    NoSuchMarket,
+
+    #[error("Order not found")]
+    OrderNotFound,
+
+    #[error("Order already closed")]
+    OrderAlreadyClosed,
+
+    #[error("Order already queued for cancellation")]
+    OrderAlreadyQueuedForCancellation,
+
+    #[error("Duplicate client order ID")]
+    DuplicateClientId,
+
+    #[error("Invalid reduce-only order")]
+    InvalidReduceOnlyOrder,
+
+    #[error("Invalid size")]
+    InvalidSize,
+
+    #[error("Size too small")]
+    SizeTooSmall,
+
+    #[error("Invalid price")]
+    InvalidPrice,
+
+    #[error("Price too high")]
+    PriceTooHigh,
+
+    #[error("Price too low")]
+    PriceTooLow,
+
+    #[error("{0}")]
+    Unknown(String),
+}
+
+impl ApiError {
+    pub fn from_message(message: &str) -> Self {
+        let lowercase = message.to_lowercase();
+        let starts_with = |prefix: &str| lowercase.starts_with(prefix);
+        if starts_with("not logged in: invalid signature") {
+            ApiError::InvalidSignature
+        } else if starts_with("not logged in: invalid api key") {
+            ApiError::InvalidApiKey
+        } else if starts_with("not logged in") {
+            ApiError::NotLoggedIn
+        } else if starts_with("not allowed with read-only permissions") {
+            ApiError::ReadOnlyPermissions
+        } else if starts_with("do not send more than") || starts_with("please retry request") {
+            ApiError::RateLimited
+        } else if starts_with("not enough balances") {
+            ApiError::InsufficientBalance
+        } else if starts_with("account does not have enough margin") {
+            ApiError::InsufficientMargin
+        } else if starts_with("no such market") {
+            ApiError::NoSuchMarket
+        } else if starts_with("order not found") {
+            ApiError::OrderNotFound
+        } else if starts_with("order already closed") {
+            ApiError::OrderAlreadyClosed
+        } else if starts_with("order already queued for cancellation") {
+            ApiError::OrderAlreadyQueuedForCancellation
+        } else if starts_with("duplicate client order id") {
+            ApiError::DuplicateClientId
+        } else if starts_with("invalid reduce-only order") {
+            ApiError::InvalidReduceOnlyOrder
+        } else if starts_with("invalid size") {
+            ApiError::InvalidSize
+        } else if starts_with("size too small") {
+            ApiError::SizeTooSmall
+        } else if starts_with("invalid price") {
+            ApiError::InvalidPrice
+        } else if lowercase.contains("price too high") {
+            ApiError::PriceTooHigh
+        } else if lowercase.contains("price too low") {
+            ApiError::PriceTooLow
+        } else {
+            ApiError::Unknown(message.to_owned())
+        }
+    }
 }
@@ -0,0 +1,117 @@
+use super::{Future, FutureType, GetFutures, Rest, Result, Symbol};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeMap;
+
+/// A future with a fixed expiry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatedFuture {
+    pub name: Symbol,
+    pub underlying: Symbol,
+    pub expiry: DateTime<Utc>,
+}
+
+impl DatedFuture {
+    /// Returns the fractional number of days until expiry, negative once
+    /// expired.
+    pub fn days_to_expiry(&self, now: DateTime<Utc>) -> f64 {
+        (self.expiry - now).num_seconds() as f64 / 86_400.0
+    }
+}
+
+/// Dated futures per underlying, ordered by expiry.
+///
+/// Positions are rolled out of a future `roll_period` before it expires,
+/// so front and back month queries skip futures within their roll period.
+#[derive(Clone, Debug, Default)]
+pub struct ExpiryCalendar {
+    futures: BTreeMap<Symbol, Vec<DatedFuture>>,
+    roll_period: Duration,
+}
+
+impl ExpiryCalendar {
+    /// Builds a calendar of the dated futures in `futures`; perpetual, MOVE
+    /// and prediction futures are ignored.
+    pub fn new<'a>(futures: impl IntoIterator<Item = &'a Future>) -> Self {
+        let mut calendar = Self::default();
+        for future in futures {
+            let expiry = match future.expiry {
+                Some(expiry) if future.market_type == FutureType::Future => expiry,
+                _ => continue,
+            };
+            calendar
+                .futures
+                .entry(future.underlying.clone())
+                .or_default()
+                .push(DatedFuture {
+                    name: future.name.clone(),
+                    underlying: future.underlying.clone(),
+                    expiry,
+                });
+        }
+        for futures in calendar.futures.values_mut() {
+            futures.sort_by_key(|future| future.expiry);
+        }
+        calendar
+    }
+
+    #[must_use]
+    pub fn roll_period(mut self, roll_period: Duration) -> Self {
+        self.roll_period = roll_period;
+        self
+    }
+
+    /// Returns the date positions in `future` should be rolled.
+    pub fn roll_date(&self, future: &DatedFuture) -> DateTime<Utc> {
+        future.expiry - self.roll_period
+    }
+
+    pub fn underlyings(&self) -> impl Iterator<Item = &str> {
+        self.futures.keys().map(String::as_str)
+    }
+
+    /// Returns all dated futures of `underlying`, ordered by expiry.
+    pub fn futures(&self, underlying: &str) -> &[DatedFuture] {
+        self.futures
+            .get(underlying)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns the futures of `underlying` not yet due to be rolled at `now`.
+    pub fn active(&self, underlying: &str, now: DateTime<Utc>) -> &[DatedFuture] {
+        let futures = self.futures(underlying);
+        let first = futures.partition_point(|future| self.roll_date(future) <= now);
+        &futures[first..]
+    }
+
+    /// Returns the nearest active future of `underlying`.
+    pub fn front(&self, underlying: &str, now: DateTime<Utc>) -> Option<&DatedFuture> {
+        self.active(underlying, now).first()
+    }
+
+    /// Returns the second nearest active future of `underlying`.
+    pub fn back(&self, underlying: &str, now: DateTime<Utc>) -> Option<&DatedFuture> {
+        self.active(underlying, now).get(1)
+    }
+
+    /// Returns all distinct expiries, ordered from nearest to furthest.
+    pub fn expiries(&self) -> Vec<DateTime<Utc>> {
+        let mut expiries: Vec<_> = self
+            .futures
+            .values()
+            .flatten()
+            .map(|future| future.expiry)
+            .collect();
+        expiries.sort();
+        expiries.dedup();
+        expiries
+    }
+}
+
+impl Rest {
+    /// Fetches the listed futures and builds their expiry calendar.
+    pub async fn get_expiry_calendar(&self) -> Result<ExpiryCalendar> {
+        let futures = self.request(GetFutures {}).await?;
+        Ok(ExpiryCalendar::new(&futures))
+    }
+}
@@ -0,0 +1,411 @@
+use super::{Error, HttpRequest, HttpResponse, Rest, Result, Transport};
+use crate::options::Endpoint;
+use reqwest::{header::HeaderMap, Method, StatusCode};
+use std::io::ErrorKind;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Backup endpoints used while the main one fails, set with
+/// `Options::failover`.
+///
+/// An endpoint is considered down after `failure_threshold` consecutive
+/// requests to it failed to connect, timed out or got a 5xx response from
+/// the edge. Requests then go to the first healthy endpoint in the order
+/// configured, the main endpoint first. Endpoints which are down are probed
+/// with a public GET of `probe_path` every `probe_interval`, and used again
+/// once a probe succeeds, so traffic fails back to the main endpoint.
+///
+/// Requests which failed to connect are retried on the next endpoint.
+/// Requests which may have reached the exchange are only retried if they
+/// are GET requests, so orders are never sent twice.
+///
+/// With `route_by_latency`, the round trip time to every endpoint is also
+/// measured, and orders and other requests changing state go to the
+/// fastest healthy endpoint, while GET requests such as history downloads
+/// keep the configured order.
+#[derive(Clone, Debug)]
+pub struct Failover {
+    endpoints: Vec<Endpoint>,
+    failure_threshold: u32,
+    probe_interval: Duration,
+    probe_path: String,
+    latency_interval: Option<Duration>,
+}
+
+impl Failover {
+    /// Fails over to `endpoints`, in this order, e.g. `Endpoint::Custom`
+    /// backup or regional URLs of the same exchange.
+    pub fn new(endpoints: Vec<Endpoint>) -> Self {
+        Self {
+            endpoints,
+            failure_threshold: 2,
+            probe_interval: Duration::from_secs(10),
+            probe_path: "/markets/BTC-PERP".to_owned(),
+            latency_interval: None,
+        }
+    }
+
+    #[must_use]
+    pub fn failure_threshold(mut self, failures: u32) -> Self {
+        self.failure_threshold = failures.max(1);
+        self
+    }
+
+    #[must_use]
+    pub fn probe_interval(mut self, interval: Duration) -> Self {
+        self.probe_interval = interval;
+        self
+    }
+
+    /// Public path probed to check if an endpoint is back, relative to the
+    /// `/api` root like `Request::PATH`.
+    #[must_use]
+    pub fn probe_path(mut self, path: impl Into<String>) -> Self {
+        self.probe_path = path.into();
+        self
+    }
+
+    /// Measures the round trip time to all endpoints with a GET of
+    /// `probe_path` when the first request is sent and every `interval`
+    /// after that, to send requests which are not GET requests to the
+    /// fastest endpoint.
+    #[must_use]
+    pub fn route_by_latency(mut self, interval: Duration) -> Self {
+        self.latency_interval = Some(interval);
+        self
+    }
+
+    /// The backup endpoints, in the order they are tried.
+    pub fn endpoints(&self) -> &[Endpoint] {
+        &self.endpoints
+    }
+}
+
+/// Health and latency of one endpoint, see `Rest::endpoint_status`.
+#[derive(Clone, Debug)]
+pub struct EndpointStatus {
+    pub endpoint: Endpoint,
+    /// Whether requests are sent to the endpoint, false while it is down
+    pub healthy: bool,
+    /// Smoothed round trip time of the latency probes, if measured
+    pub latency: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct EndpointState {
+    endpoint: Endpoint,
+    failures: AtomicU32,
+    healthy: AtomicBool,
+    /// Smoothed round trip time in microseconds, 0 until measured
+    rtt: AtomicU64,
+}
+
+/// Endpoints of a client and their health.
+#[derive(Debug)]
+pub(super) struct EndpointPool {
+    endpoints: Vec<EndpointState>,
+    failure_threshold: u32,
+    probe_interval: Duration,
+    probe_path: String,
+    probing: AtomicBool,
+    latency_interval: Option<Duration>,
+    measuring: AtomicBool,
+}
+
+impl EndpointPool {
+    pub fn new(main: Endpoint, failover: Option<Failover>) -> Self {
+        let failover = failover.unwrap_or_else(|| Failover::new(Vec::new()));
+        let endpoints = std::iter::once(main)
+            .chain(failover.endpoints)
+            .map(|endpoint| EndpointState {
+                endpoint,
+                failures: AtomicU32::new(0),
+                healthy: AtomicBool::new(true),
+                rtt: AtomicU64::new(0),
+            })
+            .collect();
+        Self {
+            endpoints,
+            failure_threshold: failover.failure_threshold,
+            probe_interval: failover.probe_interval,
+            probe_path: failover.probe_path,
+            probing: AtomicBool::new(false),
+            latency_interval: failover.latency_interval,
+            measuring: AtomicBool::new(false),
+        }
+    }
+
+    /// The main endpoint, whose header names are used for all requests.
+    pub fn main(&self) -> &Endpoint {
+        &self.endpoints[0].endpoint
+    }
+
+    /// Indices of the endpoints to try in order: healthy ones by priority,
+    /// or by latency if `latency_critical` and latencies are measured, then
+    /// the others as a last resort.
+    fn candidates(&self, latency_critical: bool) -> Vec<usize> {
+        let (mut healthy, unhealthy): (Vec<usize>, Vec<usize>) = (0..self.endpoints.len())
+            .partition(|&i| self.endpoints[i].healthy.load(Ordering::Relaxed));
+        if latency_critical && self.latency_interval.is_some() {
+            // Unmeasured endpoints last, the sort keeps the priority on ties
+            healthy.sort_by_key(|&i| match self.endpoints[i].rtt.load(Ordering::Relaxed) {
+                0 => u64::MAX,
+                rtt => rtt,
+            });
+        }
+        healthy.extend(unhealthy);
+        healthy
+    }
+
+    fn succeeded(&self, index: usize) {
+        let state = &self.endpoints[index];
+        state.failures.store(0, Ordering::Relaxed);
+        if !state.healthy.swap(true, Ordering::Relaxed) {
+            log::info!("endpoint {} is back up", state.endpoint.rest());
+        }
+    }
+
+    /// Records a failure, returning whether the endpoint is now down.
+    fn failed(&self, index: usize) -> bool {
+        let state = &self.endpoints[index];
+        let failures = state.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= self.failure_threshold && state.healthy.swap(false, Ordering::Relaxed) {
+            log::warn!("endpoint {} is down, failing over", state.endpoint.rest());
+            return true;
+        }
+        false
+    }
+
+    /// Records a measured round trip time, smoothed with earlier ones.
+    fn measured(&self, index: usize, rtt: Duration) {
+        let sample = (rtt.as_micros() as u64).max(1);
+        let rtt = &self.endpoints[index].rtt;
+        let smoothed = match rtt.load(Ordering::Relaxed) {
+            0 => sample,
+            previous => (previous * 3 + sample) / 4,
+        };
+        rtt.store(smoothed, Ordering::Relaxed);
+    }
+
+    fn status(&self) -> Vec<EndpointStatus> {
+        self.endpoints
+            .iter()
+            .map(|state| EndpointStatus {
+                endpoint: state.endpoint.clone(),
+                healthy: state.healthy.load(Ordering::Relaxed),
+                latency: match state.rtt.load(Ordering::Relaxed) {
+                    0 => None,
+                    rtt => Some(Duration::from_micros(rtt)),
+                },
+            })
+            .collect()
+    }
+
+    /// Whether all endpoints are healthy.
+    fn all_healthy(&self) -> bool {
+        self.endpoints
+            .iter()
+            .all(|state| state.healthy.load(Ordering::Relaxed))
+    }
+
+    /// The currently preferred endpoint.
+    pub fn active(&self) -> &Endpoint {
+        &self.endpoints[self.candidates(false)[0]].endpoint
+    }
+}
+
+/// Base URL of REST requests to `endpoint`.
+fn rest_url(endpoint: &Endpoint, optimized_access: bool) -> &str {
+    #[cfg(feature = "optimized-access")]
+    if optimized_access {
+        return endpoint.optimized_access_rest();
+    }
+    #[cfg(not(feature = "optimized-access"))]
+    let _ = optimized_access;
+    endpoint.rest()
+}
+
+/// Whether `status` is sent by the edge of the exchange when it cannot
+/// reach the API.
+pub(super) fn edge_error(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Whether `error` means the endpoint could not be reached, and whether the
+/// request may have been received anyway. Transports other than reqwest
+/// report these as `Error::Io`.
+pub(super) fn unreachable(error: &Error) -> Option<bool> {
+    match error {
+        Error::Reqwest(e) if e.is_connect() => Some(false),
+        Error::Reqwest(e) if e.is_timeout() || e.is_request() => Some(true),
+        Error::Io(e) => match e.kind() {
+            ErrorKind::ConnectionRefused => Some(false),
+            ErrorKind::TimedOut | ErrorKind::ConnectionReset => Some(true),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl Rest {
+    /// Sends a request for `path`, signed if `auth` is set, to the first
+    /// endpoint which responds, and returns the response.
+    pub(super) async fn failover_send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<String>,
+        auth: bool,
+        optimized_access: bool,
+    ) -> Result<HttpResponse> {
+        if self.endpoints.latency_interval.is_some() {
+            self.spawn_latency_probes();
+        }
+        let candidates = self.endpoints.candidates(method != Method::GET);
+        let mut result = Err(Error::Config("no endpoint configured".to_owned()));
+        for (attempt, &index) in candidates.iter().enumerate() {
+            let endpoint = &self.endpoints.endpoints[index].endpoint;
+            let url = format!("{}{}", rest_url(endpoint, optimized_access), path);
+            let response = self
+                .send_within_budget(method.clone(), url, path, body.clone(), auth)
+                .await;
+            let may_have_arrived = match &response {
+                Ok(response) if edge_error(response.status) => true,
+                Ok(_) => {
+                    self.endpoints.succeeded(index);
+                    return response;
+                }
+                Err(error) => match unreachable(error) {
+                    Some(may_have_arrived) => may_have_arrived,
+                    None => return response,
+                },
+            };
+
+            if self.endpoints.failed(index) {
+                self.spawn_probes();
+            }
+            result = response;
+            if attempt + 1 == candidates.len() || (may_have_arrived && method != Method::GET) {
+                break;
+            }
+            log::debug!("retrying {} {} on the next endpoint", method, path);
+        }
+        result
+    }
+
+    /// Probes the endpoints which are down until all are back up.
+    fn spawn_probes(&self) {
+        if self.endpoints.probing.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        // Only a weak reference, so the task ends with the last client
+        let pool = Arc::downgrade(&self.endpoints);
+        let transport = self.transport.clone();
+        tokio::spawn(async move {
+            loop {
+                let probe_interval = match pool.upgrade() {
+                    Some(pool) if !pool.all_healthy() => pool.probe_interval,
+                    Some(pool) => {
+                        pool.probing.store(false, Ordering::Relaxed);
+                        break;
+                    }
+                    None => break,
+                };
+                tokio::time::sleep(probe_interval).await;
+                let pool = match pool.upgrade() {
+                    Some(pool) => pool,
+                    None => break,
+                };
+                for (index, state) in pool.endpoints.iter().enumerate() {
+                    if state.healthy.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    match probe(&*transport, &state.endpoint, &pool.probe_path).await {
+                        Ok(response) if response.status.is_success() => pool.succeeded(index),
+                        Ok(response) => {
+                            log::debug!(
+                                "probe of {} failed: {}",
+                                state.endpoint.rest(),
+                                response.status
+                            )
+                        }
+                        Err(error) => {
+                            log::debug!("probe of {} failed: {}", state.endpoint.rest(), error)
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Measures the latency of all endpoints now and periodically, for as
+    /// long as the client is in use.
+    fn spawn_latency_probes(&self) {
+        if self.endpoints.measuring.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let interval = match self.endpoints.latency_interval {
+            Some(interval) => interval,
+            None => return,
+        };
+        // Only a weak reference, so the task ends with the last client
+        let pool = Arc::downgrade(&self.endpoints);
+        let transport = self.transport.clone();
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(interval);
+            loop {
+                timer.tick().await;
+                let pool = match pool.upgrade() {
+                    Some(pool) => pool,
+                    None => break,
+                };
+                for (index, state) in pool.endpoints.iter().enumerate() {
+                    let start = Instant::now();
+                    match probe(&*transport, &state.endpoint, &pool.probe_path).await {
+                        Ok(response) if response.status.is_success() => {
+                            pool.measured(index, start.elapsed())
+                        }
+                        Ok(response) => log::debug!(
+                            "latency probe of {} failed: {}",
+                            state.endpoint.rest(),
+                            response.status
+                        ),
+                        Err(error) => log::debug!(
+                            "latency probe of {} failed: {}",
+                            state.endpoint.rest(),
+                            error
+                        ),
+                    }
+                }
+            }
+        });
+    }
+
+    /// The endpoint GET requests are currently sent to.
+    pub fn active_endpoint(&self) -> &Endpoint {
+        self.endpoints.active()
+    }
+
+    /// Health and latency of the main and backup endpoints, in the
+    /// configured order.
+    pub fn endpoint_status(&self) -> Vec<EndpointStatus> {
+        self.endpoints.status()
+    }
+}
+
+/// Sends the public GET of `path` to `endpoint`.
+async fn probe(transport: &dyn Transport, endpoint: &Endpoint, path: &str) -> Result<HttpResponse> {
+    transport
+        .send(HttpRequest {
+            method: Method::GET,
+            url: format!("{}{}", endpoint.rest(), path),
+            path: path.to_owned(),
+            headers: HeaderMap::new(),
+            body: None,
+        })
+        .await
+}
@@ -0,0 +1,154 @@
+use super::{ApiError, CancelOrder, Error, Id, OrderInfo, PlaceOrder, Rest, Result};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time;
+
+/// Emulates good-till-date orders, which FTX does not support, by
+/// cancelling orders once their expiry has passed.
+///
+/// Expiries are kept in memory and, with `persist_to`, in a JSON file, so a
+/// restarted process still cancels the orders it placed before. Failed
+/// cancellations, e.g. while the connection is down, are retried on the
+/// next check.
+#[derive(Debug, Clone)]
+pub struct GtdScheduler {
+    rest: Rest,
+    expiries: Arc<Mutex<BTreeMap<Id, DateTime<Utc>>>>,
+    path: Option<PathBuf>,
+}
+
+impl GtdScheduler {
+    pub fn new(rest: Rest) -> Self {
+        Self {
+            rest,
+            expiries: Default::default(),
+            path: None,
+        }
+    }
+
+    /// Persists the expiries to `path`, continuing with the ones stored
+    /// there if the file exists.
+    pub fn persist_to(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        if let Some(expiries) = read_state(&path)? {
+            *self.expiries.lock().unwrap() = expiries;
+        }
+        self.path = Some(path);
+        Ok(self)
+    }
+
+    /// Places `order` and cancels it at `expires_at`.
+    pub async fn place(
+        &self,
+        order: PlaceOrder<'_>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<OrderInfo> {
+        let order = self.rest.request(order).await?;
+        self.schedule(order.id, expires_at)?;
+        Ok(order)
+    }
+
+    /// Cancels the already placed order `id` at `expires_at`.
+    pub fn schedule(&self, id: Id, expires_at: DateTime<Utc>) -> Result<()> {
+        let mut expiries = self.expiries.lock().unwrap();
+        expiries.insert(id, expires_at);
+        self.save(&expiries)
+    }
+
+    /// Stops tracking the expiry of order `id`, e.g. once it was filled.
+    pub fn unschedule(&self, id: Id) -> Result<()> {
+        let mut expiries = self.expiries.lock().unwrap();
+        if expiries.remove(&id).is_some() {
+            self.save(&expiries)?;
+        }
+        Ok(())
+    }
+
+    /// Orders with their expiries.
+    pub fn expiries(&self) -> BTreeMap<Id, DateTime<Utc>> {
+        self.expiries.lock().unwrap().clone()
+    }
+
+    /// Cancels the orders whose expiry has passed, returning their IDs.
+    ///
+    /// Orders the exchange refuses to cancel, because they were already
+    /// filled or cancelled, are forgotten as well. Orders which could not
+    /// be cancelled for other reasons are kept to be retried.
+    pub async fn cancel_expired(&self) -> Result<Vec<Id>> {
+        let now = Utc::now();
+        let expired: Vec<Id> = self
+            .expiries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, expires_at)| **expires_at <= now)
+            .map(|(id, _)| *id)
+            .collect();
+
+        let mut done = Vec::new();
+        for id in expired {
+            match self.rest.request(CancelOrder::new(id)).await {
+                Ok(_) => done.push(id),
+                Err(Error::Exchange { error, .. }) if error != ApiError::RateLimited => {
+                    log::info!("order {} expired but was not cancelled: {}", id, error);
+                    done.push(id);
+                }
+                Err(error) => log::warn!("failed to cancel expired order {}: {}", id, error),
+            }
+        }
+
+        if !done.is_empty() {
+            let mut expiries = self.expiries.lock().unwrap();
+            for id in &done {
+                expiries.remove(id);
+            }
+            self.save(&expiries)?;
+        }
+        Ok(done)
+    }
+
+    /// Cancels expired orders every `interval`, forever.
+    pub async fn run(&self, interval: Duration) -> Result<()> {
+        let mut interval = time::interval(interval);
+        loop {
+            interval.tick().await;
+            self.cancel_expired().await?;
+        }
+    }
+
+    fn save(&self, expiries: &BTreeMap<Id, DateTime<Utc>>) -> Result<()> {
+        match &self.path {
+            Some(path) => write_state(path, expiries),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Reads state persisted with `write_state`, if the file exists.
+pub(super) fn read_state<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    match std::fs::read(path) {
+        Ok(json) => Ok(Some(serde_json::from_slice(&json)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(io_error(e, "read", path)),
+    }
+}
+
+/// Writes `state` to `path` as JSON.
+pub(super) fn write_state<T: Serialize>(path: &Path, state: &T) -> Result<()> {
+    // Written next to the file and renamed, so a crash never leaves a
+    // truncated file behind
+    let temporary = path.with_extension("tmp");
+    std::fs::write(&temporary, serde_json::to_vec(state)?)
+        .and_then(|_| std::fs::rename(&temporary, path))
+        .map_err(|e| io_error(e, "write", path))
+}
+
+/// Adds the path to `error`, keeping its kind.
+fn io_error(error: std::io::Error, operation: &str, path: &Path) -> Error {
+    let message = format!("failed to {} {:?}: {}", operation, path, error);
+    Error::Io(std::io::Error::new(error.kind(), message))
+}
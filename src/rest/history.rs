@@ -0,0 +1,87 @@
+use super::{
+    Candle, Fill, FundingRate, GetFills, GetFundingRates, GetHistoricalPrices, Id, Resolution,
+    Rest, Result,
+};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+
+/// Number of candles requested per `GetHistoricalPrices` page, the most
+/// FTX returns without a limit.
+const CANDLES_PAGE_SIZE: u32 = 1501;
+
+/// Number of fills requested per `GetFills` page.
+const FILLS_PAGE_SIZE: usize = 5000;
+
+impl Rest {
+    /// Downloads all funding rates of `future` between `start_time` and
+    /// `end_time`, ordered from oldest to newest.
+    pub async fn get_funding_rate_history(
+        &self,
+        future: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<FundingRate>> {
+        let mut rates: Vec<FundingRate> = self
+            .paginate(GetFundingRates::new_paged(
+                Some(future.to_owned()),
+                Some(start_time),
+                Some(end_time),
+            ))
+            .try_collect()
+            .await?;
+        rates.sort_by_key(|rate| rate.time);
+        Ok(rates)
+    }
+
+    /// Downloads all candles of `market` starting between `start_time` and
+    /// `end_time`, ordered from oldest to newest, in as many requests as
+    /// the range needs.
+    pub async fn get_candle_history(
+        &self,
+        market: &str,
+        resolution: Resolution,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<Candle>> {
+        let mut candles: Vec<Candle> = self
+            .paginate(GetHistoricalPrices::new_paged(
+                market,
+                resolution,
+                Some(CANDLES_PAGE_SIZE),
+                Some(start_time),
+                Some(end_time),
+            ))
+            .try_collect()
+            .await?;
+        candles.sort_by_key(|candle| candle.start_time);
+        Ok(candles)
+    }
+
+    /// Downloads all fills of a single order, ordered from oldest to newest,
+    /// even if they span multiple pages.
+    pub async fn get_order_fills(&self, market: &str, order_id: Id) -> Result<Vec<Fill>> {
+        let mut fills: Vec<Fill> = self
+            .paginate(GetFills {
+                market_name: Some(market),
+                order_id: Some(order_id),
+                limit: Some(FILLS_PAGE_SIZE),
+                ..Default::default()
+            })
+            .try_collect()
+            .await?;
+        fills.sort_by_key(|fill| (fill.time, fill.id));
+        Ok(fills)
+    }
+}
+
+/// Returns the size-weighted average price of `fills`, or `None` if the
+/// total size is zero.
+pub fn average_fill_price(fills: &[Fill]) -> Option<Decimal> {
+    let size: Decimal = fills.iter().map(|fill| fill.size).sum();
+    if size.is_zero() {
+        return None;
+    }
+    let notional: Decimal = fills.iter().map(|fill| fill.price * fill.size).sum();
+    Some(notional / size)
+}
@@ -0,0 +1,129 @@
+use super::failover::{edge_error, unreachable};
+use super::{ApiError, Error, GetOrderByClientId, OrderInfo, PlaceOrder, Rest, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time;
+
+/// Times an order is sent again after it was verified not to have arrived.
+const MAX_RESUBMITS: usize = 2;
+
+/// Delays before each lookup of an order that may have arrived. A request
+/// delayed at the edge can still reach the matching engine after the first
+/// lookup, so the order is only resubmitted if no lookup finds it.
+const LOOKUP_DELAYS: [Duration; 2] = [Duration::from_millis(500), Duration::from_secs(2)];
+
+/// Generates client order IDs unique across processes and restarts, e.g.
+/// `bot-l2x0k9a1-1`.
+#[derive(Debug)]
+pub struct ClientIdGenerator {
+    prefix: String,
+    /// Start time of the generator and process ID, in base 36
+    seed: String,
+    counter: AtomicU64,
+}
+
+impl ClientIdGenerator {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+        Self {
+            prefix: prefix.into(),
+            seed: base36(nanos ^ u64::from(std::process::id()) << 48),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns a new ID.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&self) -> String {
+        let n = self.counter.fetch_add(1, Ordering::Relaxed) + 1;
+        format!("{}-{}-{}", self.prefix, self.seed, base36(n))
+    }
+}
+
+impl Default for ClientIdGenerator {
+    fn default() -> Self {
+        Self::new("ftx")
+    }
+}
+
+fn base36(mut n: u64) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut digits = Vec::new();
+    loop {
+        digits.push(DIGITS[(n % 36) as usize]);
+        n /= 36;
+        if n == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+    String::from_utf8(digits).expect("ASCII digits")
+}
+
+/// Whether the request that failed with `error` may have been received by
+/// the exchange anyway.
+fn may_have_arrived(error: &Error) -> bool {
+    match error {
+        Error::Exchange { status, .. } => edge_error(*status),
+        error => unreachable(error) == Some(true),
+    }
+}
+
+impl Rest {
+    /// Places `order` at most once, with a client ID from `ids` unless it
+    /// already has one.
+    ///
+    /// If it is unclear whether the order arrived, e.g. after a timeout or
+    /// a gateway error, it is looked up by its client ID after a delay, and
+    /// again after a longer one, and only sent again if neither lookup found
+    /// it. If FTX reports the client ID as a duplicate, the order placed
+    /// before is returned.
+    pub async fn place_order_idempotent(
+        &self,
+        order: PlaceOrder<'_>,
+        ids: &ClientIdGenerator,
+    ) -> Result<OrderInfo> {
+        let generated;
+        let client_id = match order.client_id {
+            Some(client_id) => client_id,
+            None => {
+                generated = ids.next();
+                &generated
+            }
+        };
+        let order = PlaceOrder {
+            client_id: Some(client_id),
+            ..order
+        };
+
+        let mut resubmits = 0;
+        loop {
+            match self.request(order.clone()).await {
+                Ok(placed) => return Ok(placed),
+                Err(Error::Exchange {
+                    error: ApiError::DuplicateClientId,
+                    ..
+                }) => return self.request(GetOrderByClientId::new(client_id)).await,
+                Err(error) if may_have_arrived(&error) && resubmits < MAX_RESUBMITS => {
+                    log::warn!("placing order {} failed: {}", client_id, error);
+                    for delay in LOOKUP_DELAYS {
+                        time::sleep(delay).await;
+                        match self.request(GetOrderByClientId::new(client_id)).await {
+                            Ok(placed) => return Ok(placed),
+                            Err(Error::Exchange {
+                                error: ApiError::OrderNotFound,
+                                ..
+                            }) => {}
+                            // Resubmitting could place the order twice
+                            Err(_) => return Err(error),
+                        }
+                    }
+                    resubmits += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
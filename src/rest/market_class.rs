@@ -0,0 +1,111 @@
+use super::{Coin, Market, MarketType};
+
+/// Leveraged token families by their suffix, e.g. `ETHBULL`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LeveragedTokenKind {
+    /// 3x long
+    Bull,
+    /// 3x short
+    Bear,
+    /// 1x short
+    Hedge,
+    /// 0.5x long
+    Half,
+}
+
+/// What a coin represents.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CoinClass {
+    /// A regular coin or token
+    Coin,
+    Leveraged {
+        underlying: Coin,
+        kind: LeveragedTokenKind,
+    },
+    /// A volatility token tracking the implied volatility of `underlying`,
+    /// e.g. `BVOL`, or its inverse, e.g. `IBVOL`
+    Volatility { underlying: Coin, inverse: bool },
+}
+
+impl CoinClass {
+    /// Returns the coin whose price drives the value of this token, if
+    /// any. Tokens must still be valued by their own market price.
+    pub fn underlying(&self) -> Option<&str> {
+        match self {
+            CoinClass::Coin => None,
+            CoinClass::Leveraged { underlying, .. } | CoinClass::Volatility { underlying, .. } => {
+                Some(underlying)
+            }
+        }
+    }
+}
+
+/// Tokens without a prefix refer to BTC, e.g. `BULL` and `BVOL`.
+fn prefix_underlying(prefix: &str) -> Coin {
+    if prefix.is_empty() {
+        "BTC".to_owned()
+    } else {
+        prefix.to_owned()
+    }
+}
+
+/// Classifies a coin by its name.
+pub fn classify_coin(coin: &str) -> CoinClass {
+    // IBVOL has to be checked first, as it also ends with BVOL
+    if let Some(prefix) = coin.strip_suffix("IBVOL") {
+        return CoinClass::Volatility {
+            underlying: prefix_underlying(prefix),
+            inverse: true,
+        };
+    }
+    if let Some(prefix) = coin.strip_suffix("BVOL") {
+        return CoinClass::Volatility {
+            underlying: prefix_underlying(prefix),
+            inverse: false,
+        };
+    }
+
+    let kinds = [
+        ("BULL", LeveragedTokenKind::Bull),
+        ("BEAR", LeveragedTokenKind::Bear),
+        ("HEDGE", LeveragedTokenKind::Hedge),
+        ("HALF", LeveragedTokenKind::Half),
+    ];
+    for (suffix, kind) in kinds.iter() {
+        if let Some(prefix) = coin.strip_suffix(suffix) {
+            return CoinClass::Leveraged {
+                underlying: prefix_underlying(prefix),
+                kind: *kind,
+            };
+        }
+    }
+
+    CoinClass::Coin
+}
+
+/// What a market trades.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MarketClass {
+    /// A spot market of the classified base currency
+    Spot(CoinClass),
+    Perpetual,
+    DatedFuture,
+    Move,
+    Prediction,
+}
+
+/// Classifies a market by its type and name.
+pub fn classify_market(market: &Market) -> MarketClass {
+    match market.market_type {
+        MarketType::Spot => MarketClass::Spot(
+            market
+                .base_currency
+                .as_deref()
+                .map_or(CoinClass::Coin, classify_coin),
+        ),
+        MarketType::Future if market.name.ends_with("-PERP") => MarketClass::Perpetual,
+        MarketType::Future if market.name.contains("-MOVE-") => MarketClass::Move,
+        MarketType::Future if market.name.contains('-') => MarketClass::DatedFuture,
+        MarketType::Future => MarketClass::Prediction,
+    }
+}
@@ -0,0 +1,66 @@
+use reqwest::{Method, StatusCode};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// REST request passed to a `Middleware`.
+#[derive(Clone, Copy, Debug)]
+pub struct RequestInfo<'a> {
+    pub method: &'a Method,
+    /// `Request::endpoint`, e.g. `/markets/{}`, to group metrics by endpoint
+    pub endpoint: &'a str,
+    /// Path with parameters and query, e.g. `/markets/BTC-PERP`
+    pub path: &'a str,
+}
+
+/// Outcome of a REST request passed to `Middleware::after`.
+#[derive(Clone, Copy, Debug)]
+pub struct ResponseInfo {
+    /// Time from sending the request until the response was read, including
+    /// retries, rate limit delays and failovers
+    pub latency: Duration,
+    /// `None` if no response was received, e.g. after a connection error
+    pub status: Option<StatusCode>,
+    /// `success` flag of the response body, `None` if the body is not an
+    /// FTX response
+    pub success: Option<bool>,
+}
+
+/// Observes every REST request, e.g. to record latency histograms per
+/// endpoint or to count failures, see `Options::middleware`.
+///
+/// Hooks run on the task sending the request and should return quickly.
+pub trait Middleware: Send + Sync + fmt::Debug {
+    /// Called before the request is sent.
+    fn before(&self, _request: &RequestInfo<'_>) {}
+
+    /// Called once the request completed or failed.
+    fn after(&self, _request: &RequestInfo<'_>, _response: &ResponseInfo) {}
+}
+
+impl<T: Middleware + ?Sized> Middleware for Arc<T> {
+    fn before(&self, request: &RequestInfo<'_>) {
+        (**self).before(request)
+    }
+
+    fn after(&self, request: &RequestInfo<'_>, response: &ResponseInfo) {
+        (**self).after(request, response)
+    }
+}
+
+/// `success` flag of the response `body`, read from its leading key so the
+/// body is not parsed twice. FTX responses start with it.
+pub(super) fn success_flag(body: &[u8]) -> Option<bool> {
+    fn token<'a>(body: &'a [u8], token: &[u8]) -> Option<&'a [u8]> {
+        body.trim_ascii_start().strip_prefix(token)
+    }
+
+    let value = token(token(token(body, b"{")?, b"\"success\"")?, b":")?;
+    if token(value, b"true").is_some() {
+        Some(true)
+    } else if token(value, b"false").is_some() {
+        Some(false)
+    } else {
+        None
+    }
+}
@@ -1,26 +1,95 @@
 //! This module is used to interact with the REST API.
 
+mod address;
+mod alert;
+mod audit;
+mod auto_lend;
+mod batch;
+#[cfg(feature = "optimized-access")]
+mod borrowed;
+mod cache;
+mod clock;
+mod coalesce;
+mod convert;
+mod dead_mans_switch;
+mod decode;
 mod error;
+mod expiry_calendar;
+mod failover;
+mod gtd;
+mod history;
+mod idempotent;
+mod market_class;
+mod middleware;
 mod model;
+mod modify;
+mod move_contracts;
+mod open_interest;
+mod options_chain;
+mod paginate;
+mod poll;
+mod query;
+mod rate_limit;
+mod retry;
+mod risk;
+mod schedule;
+mod schema_drift;
+mod signer;
+mod stablecoins;
 #[cfg(test)]
 pub(crate) mod tests;
-
+mod transport;
+mod volume;
+mod wait;
+mod withdraw;
+
+pub use address::*;
+pub use alert::*;
+pub use audit::*;
+pub use auto_lend::*;
+pub use batch::*;
 use boolinator::Boolinator;
+#[cfg(feature = "optimized-access")]
+pub use borrowed::*;
+pub use cache::CachePolicy;
+pub use convert::*;
+pub use dead_mans_switch::*;
+pub use decode::{Raw, Strictness};
 pub use error::*;
+pub use expiry_calendar::*;
+pub use failover::{EndpointStatus, Failover};
+pub use gtd::*;
+pub use history::average_fill_price;
+pub use idempotent::ClientIdGenerator;
+pub use market_class::*;
+pub use middleware::{Middleware, RequestInfo, ResponseInfo};
 pub use model::*;
-
-use crate::options::{Endpoint, Options};
+pub use modify::*;
+pub use move_contracts::*;
+pub use open_interest::*;
+pub use options_chain::*;
+pub use paginate::*;
+pub use poll::*;
+pub use query::*;
+pub use rate_limit::*;
+pub use retry::*;
+pub use risk::*;
+pub use schedule::*;
+pub use schema_drift::*;
+pub use signer::*;
+pub use stablecoins::*;
+pub use transport::*;
+pub use volume::*;
+pub use withdraw::validate_withdrawal;
+
+use crate::options::Options;
 use chrono::{DateTime, Utc};
-use hmac_sha256::HMAC;
 use reqwest::{
     header::{HeaderMap, HeaderName, HeaderValue},
-    Client, ClientBuilder, Method,
+    ClientBuilder, Method, Proxy,
 };
 use rust_decimal::prelude::*;
-use std::{
-    ops::Not,
-    time::{SystemTime, UNIX_EPOCH},
-};
+use std::{ops::Not, sync::Arc, time::Instant};
 
 macro_rules! deprecate_msg {
     () => {
@@ -29,56 +98,203 @@ macro_rules! deprecate_msg {
 }
 #[derive(Debug, Clone)]
 pub struct Rest {
-    secret: Option<String>,
-    client: Client,
+    key: Option<String>,
+    signer: Option<Arc<dyn Signer>>,
     subaccount: Option<String>,
-    endpoint: Endpoint,
+    endpoints: Arc<failover::EndpointPool>,
+    enforce_address_book: bool,
+    validate_withdrawals: bool,
+    schema_drift: Option<Arc<SchemaDrift>>,
+    cache: Option<Arc<cache::ResponseCache>>,
+    in_flight: coalesce::InFlight,
+    audit_log: Option<Arc<AuditLog>>,
+    rate_limit: Option<RateLimit>,
+    retry_policy: Option<Arc<dyn RetryPolicy>>,
+    transport: Arc<dyn Transport>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    clock: Arc<clock::Clock>,
+    strictness: Strictness,
+    capture_raw: bool,
+    #[cfg(feature = "ws")]
+    ws_options: wait::WsOptions,
 }
 
 impl Rest {
-    // TODO: this should return Result<> if it can fail
-    pub fn new(
+    /// See `Rest::try_new`.
+    ///
+    /// # Panics
+    ///
+    /// If the options are invalid.
+    pub fn new(options: Options) -> Self {
+        Self::try_new(options).expect("invalid options")
+    }
+
+    /// Fails with `Error::Config` if the credentials cannot be sent as
+    /// headers or the proxy is not supported.
+    pub fn try_new(
         Options {
             endpoint,
             key,
             secret,
             subaccount,
+            enforce_address_book,
+            validate_withdrawals,
+            detect_schema_drift,
+            cache,
+            failover,
+            audit_log,
+            rate_limit,
+            retry,
+            proxy,
+            timeout,
+            connect_timeout,
+            middleware,
+            sync_time,
+            strictness,
+            capture_raw,
         }: Options,
-    ) -> Self {
-        // Set default headers.
-        let headers = [
+    ) -> Result<Self> {
+        // The key and subaccount are sent with each request, check them up
+        // front.
+        for (hdr_val, hdr_key) in [
             (&key, endpoint.key_header()),
             (&subaccount, endpoint.subaccount_header()),
-        ]
-        .iter()
-        .flat_map(|(hdr_val, hdr_ident)| hdr_val.as_ref().map(|v| (v, hdr_ident)))
-        .map(|(hdr_val, hdr_key)| {
-            (
-                HeaderName::from_str(hdr_key).unwrap(),
-                HeaderValue::from_str(hdr_val).unwrap(),
-            )
+        ] {
+            if let Some(hdr_val) = hdr_val {
+                HeaderValue::from_str(hdr_val)
+                    .map_err(|_| Error::Config(format!("invalid {} header", hdr_key)))?;
+            }
+        }
+
+        #[cfg(feature = "ws")]
+        let ws_options = wait::WsOptions(Options {
+            endpoint: endpoint.clone(),
+            key: key.clone(),
+            secret: secret.clone(),
+            subaccount: subaccount.clone(),
+            failover: failover.clone(),
+            proxy: proxy.clone(),
+            connect_timeout,
+            ..Default::default()
+        });
+
+        let mut builder = ClientBuilder::new();
+        if let Some(proxy) = proxy {
+            crate::options::check_proxy(&proxy)?;
+            builder = builder.proxy(
+                Proxy::all(proxy.clone())
+                    .map_err(|e| Error::Config(format!("unsupported proxy {}: {}", proxy, e)))?,
+            );
+        }
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        let client = builder.build()?;
+
+        Ok(Self {
+            key,
+            signer: secret.map(|secret| Arc::new(SecretSigner::new(secret)) as Arc<dyn Signer>),
+            transport: Arc::new(client),
+            subaccount,
+            endpoints: Arc::new(failover::EndpointPool::new(endpoint, failover)),
+            enforce_address_book,
+            validate_withdrawals,
+            schema_drift: detect_schema_drift.as_some_from(|| Arc::new(SchemaDrift::new())),
+            cache: cache.map(|policy| Arc::new(cache::ResponseCache::new(policy))),
+            in_flight: Default::default(),
+            audit_log,
+            rate_limit,
+            retry_policy: retry,
+            middleware,
+            clock: Arc::new(clock::Clock::new(sync_time)),
+            strictness,
+            capture_raw,
+            #[cfg(feature = "ws")]
+            ws_options,
         })
-        .collect();
+    }
 
-        let client = ClientBuilder::new()
-            .default_headers(headers)
-            .build()
-            .unwrap();
+    /// Fields of the responses so far compared to the models, if enabled with
+    /// `Options::detect_schema_drift`.
+    pub fn schema_drift(&self) -> Option<&SchemaDrift> {
+        self.schema_drift.as_deref()
+    }
 
+    /// Client for the subaccount `nickname`, e.g. one created with
+    /// `CreateSubaccount`, using the credentials of this client and sharing
+    /// its connections, cache and rate limit.
+    pub fn for_subaccount(&self, nickname: impl Into<String>) -> Self {
+        let nickname = nickname.into();
+        #[cfg(feature = "ws")]
+        let ws_options = wait::WsOptions(Options {
+            subaccount: Some(nickname.clone()),
+            ..self.ws_options.0.clone()
+        });
         Self {
-            secret,
-            client,
-            subaccount,
-            endpoint,
+            subaccount: Some(nickname),
+            #[cfg(feature = "ws")]
+            ws_options,
+            ..self.clone()
         }
     }
 
+    /// Subaccount requests are sent for, `None` for the main account.
+    pub fn subaccount(&self) -> Option<&str> {
+        self.subaccount.as_deref()
+    }
+
     pub async fn request<R: Request>(&self, req: R) -> Result<R::Response> {
+        self.request_envelope(req).await.map(|res| res.result)
+    }
+
+    /// Like `request`, returning the whole response, e.g. to check
+    /// `has_more_data`.
+    pub async fn request_envelope<R: Request>(
+        &self,
+        req: R,
+    ) -> Result<SuccessResponse<R::Response>> {
+        let response = self.send_request(&req).await?;
+
+        let decode = self.strictness != Strictness::Strict || self.capture_raw;
+        if self.schema_drift.is_some() || decode {
+            if let Ok(res) =
+                serde_json::from_slice::<SuccessResponse<serde_json::Value>>(&response.body)
+            {
+                let recorded = self
+                    .schema_drift
+                    .as_ref()
+                    .map(|schema_drift| schema_drift.record(&req.endpoint(), &res.result));
+                let result = match recorded {
+                    Some(recorded) if !decode => recorded?,
+                    _ => decode::decode(&res.result, self.strictness, self.capture_raw)?,
+                };
+                return Ok(SuccessResponse {
+                    success: res.success,
+                    result,
+                    has_more_data: res.has_more_data,
+                });
+            }
+        }
+
+        parse_response(&response)
+    }
+
+    /// Validates and sends `req` through the cache, audit log and
+    /// middleware, returning the response as received.
+    async fn send_request<R: Request>(&self, req: &R) -> Result<HttpResponse> {
+        req.validate()?;
         let params = matches!(R::METHOD, Method::GET).as_some(serde_qs::to_string(&req)?);
         let body = matches!(R::METHOD, Method::GET)
             .not()
             .as_some(serde_json::to_string(&req)?);
 
+        if let Some(withdrawal) = req.withdrawal() {
+            self.check_withdrawal(withdrawal).await?;
+        }
+
         let mut path = req.path().into_owned();
         if let Some(params) = params {
             if !params.is_empty() {
@@ -87,21 +303,154 @@ impl Rest {
             }
         }
         #[cfg(feature = "optimized-access")]
-        let url = if R::OPTIMIZED_ACCESS_SUPPORTED {
-            format!("{}{}", self.endpoint.optimized_access_rest(), path)
-        } else {
-            format!("{}{}", self.endpoint.rest(), path)
-        };
+        let optimized_access = R::OPTIMIZED_ACCESS_SUPPORTED;
         #[cfg(not(feature = "optimized-access"))]
-        let url = format!("{}{}", self.endpoint.rest(), path);
+        let optimized_access = false;
+
+        let cache_ttl = self
+            .cache
+            .as_ref()
+            .filter(|_| R::METHOD == Method::GET && !R::AUTH)
+            .and_then(|cache| cache.ttl(R::PATH));
+        let endpoint = req.endpoint();
+        let request = RequestInfo {
+            method: &R::METHOD,
+            endpoint: &endpoint,
+            path: &path,
+        };
+        let send = async {
+            match (&self.cache, cache_ttl) {
+                (Some(cache), Some(ttl)) => {
+                    self.cached_send(cache, path.clone(), optimized_access, ttl)
+                        .await
+                }
+                _ if R::METHOD == Method::GET && !R::AUTH => {
+                    self.coalesced_send(path.clone(), optimized_access).await
+                }
+                _ if R::METHOD == Method::GET => {
+                    self.send(R::METHOD, &path, body, R::AUTH, optimized_access)
+                        .await
+                }
+                _ => self.audited_send::<R>(&path, body, optimized_access).await,
+            }
+        };
+        self.observed(&request, send).await
+    }
 
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    /// Runs `send` between the hooks of the middleware, and in a span with
+    /// the `tracing` feature.
+    async fn observed(
+        &self,
+        request: &RequestInfo<'_>,
+        send: impl std::future::Future<Output = Result<HttpResponse>>,
+    ) -> Result<HttpResponse> {
+        if self.middleware.is_empty() && !cfg!(feature = "tracing") {
+            return send.await;
+        }
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "ftx_request",
+            method = %request.method,
+            endpoint = request.endpoint,
+            path = request.path,
+            status = tracing::field::Empty,
+            success = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+        );
+        #[cfg(feature = "tracing")]
+        let send = tracing::Instrument::instrument(send, span.clone());
+
+        for middleware in &self.middleware {
+            middleware.before(request);
+        }
+        let start = Instant::now();
+        let response = send.await;
+        let info = ResponseInfo {
+            latency: start.elapsed(),
+            status: response.as_ref().ok().map(|response| response.status),
+            success: response
+                .as_ref()
+                .ok()
+                .and_then(|response| middleware::success_flag(&response.body)),
+        };
+
+        #[cfg(feature = "tracing")]
+        {
+            span.record("latency_ms", info.latency.as_millis() as u64);
+            if let Some(status) = info.status {
+                span.record("status", status.as_u16());
+            }
+            if let Some(success) = info.success {
+                span.record("success", success);
+            }
+        }
+        for middleware in &self.middleware {
+            middleware.after(request, &info);
+        }
+        response
+    }
+
+    /// Sends a request changing the account, writing it and its response to
+    /// the audit log if enabled.
+    async fn audited_send<R: Request>(
+        &self,
+        path: &str,
+        body: Option<String>,
+        optimized_access: bool,
+    ) -> Result<HttpResponse> {
+        let audit_log = match &self.audit_log {
+            Some(audit_log) => audit_log,
+            None => {
+                return self
+                    .send(R::METHOD, path, body, R::AUTH, optimized_access)
+                    .await
+            }
+        };
+        let correlation_id = audit_log.request(&R::METHOD, path, body.as_deref()).await?;
+        let response = self
+            .send(R::METHOD, path, body, R::AUTH, optimized_access)
+            .await;
+        if let Err(error) = audit_log
+            .response(&correlation_id, &R::METHOD, path, &response)
+            .await
+        {
+            log::error!("failed to log response to {}: {}", correlation_id, error);
+        }
+        response
+    }
+
+    /// Sends a request for `path` to `url`, signed if `auth` is set, and
+    /// returns the response status and body.
+    async fn send_to(
+        &self,
+        method: Method,
+        url: String,
+        path: &str,
+        body: Option<String>,
+        auth: bool,
+    ) -> Result<HttpResponse> {
+        self.spawn_time_sync();
+        let timestamp = self.clock.timestamp()?;
 
         log::trace!("timestamp: {}", timestamp);
-        log::trace!("method: {}", R::METHOD);
+        log::trace!("method: {}", method);
         log::trace!("path: {}", path);
         log::trace!("body: {:?}", body);
 
+        let sign = match &self.signer {
+            Some(signer) if auth => {
+                let sign_payload = format!(
+                    "{}{}/api{}{}",
+                    timestamp,
+                    method,
+                    path,
+                    body.as_deref().unwrap_or("")
+                );
+                Some(signer.sign(sign_payload.as_bytes()).await?)
+            }
+            _ => None,
+        };
+
         let headers: HeaderMap = IntoIterator::into_iter([
             // Always include content_type header
             Some((
@@ -110,34 +459,29 @@ impl Rest {
             )),
             // Always include timestamp in header
             Some((
-                HeaderName::from_str(self.endpoint.timestamp_header())
-                    .map_err(|e| Error::Api(format!("invalid header {:?}", e)))?,
+                HeaderName::from_str(self.endpoints.main().timestamp_header())
+                    .map_err(|e| Error::Config(format!("invalid header {:?}", e)))?,
                 HeaderValue::from_str(&format!("{}", timestamp))
-                    .map_err(|e| Error::Api(format!("invalid header {:?}", e)))?,
+                    .map_err(|e| Error::Config(format!("invalid header {:?}", e)))?,
             )),
+            // If a key is set, include it
+            self.key.as_ref().and_then(|key| {
+                Some((
+                    HeaderName::from_str(self.endpoints.main().key_header()).ok()?,
+                    HeaderValue::from_str(key).ok()?,
+                ))
+            }),
             // If requires auth, include a sig
-            R::AUTH.as_option().and_then(|_| {
-                let secret = self.secret.as_ref().ok_or(Error::NoSecretConfigured).ok()?;
-
-                let sign_payload = format!(
-                    "{}{}/api{}{}",
-                    timestamp,
-                    R::METHOD,
-                    path,
-                    body.as_deref().unwrap_or("")
-                );
-
-                let sign = HMAC::mac(sign_payload.as_bytes(), secret.as_bytes());
-                let sign = hex::encode(sign);
+            sign.and_then(|sign| {
                 Some((
-                    HeaderName::from_str(self.endpoint.sign_header()).ok()?,
+                    HeaderName::from_str(self.endpoints.main().sign_header()).ok()?,
                     HeaderValue::from_str(&sign).ok()?,
                 ))
             }),
             // If subaccount is set, include it
             self.subaccount.as_ref().and_then(|subaccount| {
                 Some((
-                    HeaderName::from_str(self.endpoint.subaccount_header()).ok()?,
+                    HeaderName::from_str(self.endpoints.main().subaccount_header()).ok()?,
                     HeaderValue::from_str(subaccount).ok()?,
                 ))
             }),
@@ -145,25 +489,15 @@ impl Rest {
         .flatten()
         .collect();
 
-        let builder = self.client.request(R::METHOD, url).headers(headers);
-        let builder = if let Some(body) = body {
-            builder.body(body)
-        } else {
-            builder
-        };
-
-        let resp_body = builder.send().await?.bytes().await?;
-
-        serde_json::from_reader(&*resp_body)
-            .map(|res: SuccessResponse<R::Response>| res.result)
-            .map_err(|_| {
-                // try to parse the error response
-                serde_json::from_reader(&*resp_body)
-                    .map(|res: ErrorResponse| Error::Api(res.error))
-                    // otherwise return the raw response
-                    .unwrap_or_else(Into::into)
+        self.transport
+            .send(HttpRequest {
+                method,
+                url,
+                path: path.to_owned(),
+                headers,
+                body,
             })
-            .map_err(Into::into)
+            .await
     }
 
     #[deprecated=deprecate_msg!()]
@@ -307,7 +641,7 @@ impl Rest {
 
     #[deprecated=deprecate_msg!()]
     pub async fn get_positions(&self) -> Result<<GetPositions as Request>::Response> {
-        self.request(GetPositions {}).await
+        self.request(GetPositions::new()).await
     }
 
     #[deprecated=deprecate_msg!()]
@@ -394,11 +728,6 @@ impl Rest {
         post_only: Option<bool>,
         client_id: Option<&str>,
     ) -> Result<<PlaceOrder as Request>::Response> {
-        // Limit orders should have price specified
-        if matches!(r#type, OrderType::Limit) && price.is_none() {
-            return Err(Error::PlacingLimitOrderRequiresPrice);
-        }
-
         let req = PlaceOrder {
             market,
             side,
@@ -406,8 +735,10 @@ impl Rest {
             r#type,
             size,
             reduce_only: reduce_only.unwrap_or_default(),
-            ioc: ioc.unwrap_or_default(),
-            post_only: post_only.unwrap_or_default(),
+            time_in_force: TimeInForce::from_flags(
+                ioc.unwrap_or_default(),
+                post_only.unwrap_or_default(),
+            )?,
             client_id,
             reject_on_price_band: false,
         };
@@ -429,7 +760,7 @@ impl Rest {
         order_price: Option<Decimal>,
         trail_value: Option<Decimal>,
     ) -> Result<OrderInfo> {
-        self.request(PlaceTriggerOrder {
+        let req = PlaceTriggerOrder {
             market,
             side,
             size,
@@ -439,8 +770,9 @@ impl Rest {
             retry_until_filled,
             order_price,
             trail_value,
-        })
-        .await
+        };
+
+        self.request(req).await
     }
 
     #[deprecated=deprecate_msg!()]
@@ -454,6 +786,7 @@ impl Rest {
             client_id,
             price,
             size,
+            new_client_id: None,
         })
         .await
     }
@@ -518,3 +851,20 @@ impl Rest {
         self.request(CancelOrderByClientId::new(client_id)).await
     }
 }
+
+/// Deserializes the result of `response`, or the error FTX responded with.
+fn parse_response<'a, T: serde::Deserialize<'a>>(
+    response: &'a HttpResponse,
+) -> Result<SuccessResponse<T>> {
+    serde_json::from_slice(&response.body).map_err(|_| {
+        // try to parse the error response
+        serde_json::from_slice(&response.body)
+            .map(|res: ErrorResponse| Error::Exchange {
+                status: response.status,
+                error: ApiError::from_message(&res.error),
+                message: res.error,
+            })
+            // otherwise return the raw response
+            .unwrap_or_else(Into::into)
+    })
+}
@@ -1,5 +1,6 @@
 use super::common::Position;
 use super::Request;
+use crate::rest::Raw;
 use http::Method;
 use rust_decimal::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -30,6 +31,17 @@ pub struct Account {
     pub positions: Vec<Position>,
     pub spot_lending_enabled: bool,
     pub spot_margin_enabled: bool,
+    /// JSON object of the response, see `Options::capture_raw`
+    #[serde(skip, default = "Raw::captured")]
+    pub raw: Raw,
+}
+
+impl Account {
+    /// JSON object this was deserialized from, if captured, see
+    /// `Options::capture_raw`.
+    pub fn raw(&self) -> Option<&serde_json::Value> {
+        self.raw.get()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
@@ -1,3 +1,4 @@
+use crate::rest::Raw;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
@@ -60,6 +61,11 @@ pub enum OrderStatus {
     Open,
     /// Rest: filled or cancelled; Ws: filled, rejected, or cancelled
     Closed,
+    /// Trigger orders only: cancelled before triggering
+    Cancelled,
+    /// Trigger orders only: triggered, see `OrderInfo::order_id` for the
+    /// order placed as a result
+    Triggered,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -75,6 +81,26 @@ impl Default for Side {
     }
 }
 
+impl Side {
+    /// Returns the side that closes a position on this side.
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
+}
+
+/// Order in which paginated results are returned.
+#[derive(Copy, Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SortOrder {
+    /// Oldest first
+    Asc,
+    /// Newest first
+    Desc,
+}
+
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum FutureType {
@@ -122,6 +148,23 @@ pub struct Position {
     pub size: Decimal,
     pub unrealized_pnl: Decimal,
     pub collateral_used: Decimal,
+    /// Only set for `GetPositions::with_avg_price`
+    pub recent_average_open_price: Option<Decimal>,
+    pub recent_pnl: Option<Decimal>,
+    pub recent_break_even_price: Option<Decimal>,
+    pub cumulative_buy_size: Option<Decimal>,
+    pub cumulative_sell_size: Option<Decimal>,
+    /// JSON object of the response, see `Options::capture_raw`
+    #[serde(skip, default = "Raw::captured")]
+    pub raw: Raw,
+}
+
+impl Position {
+    /// JSON object this was deserialized from, if captured, see
+    /// `Options::capture_raw`.
+    pub fn raw(&self) -> Option<&serde_json::Value> {
+        self.raw.get()
+    }
 }
 
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
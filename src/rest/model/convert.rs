@@ -0,0 +1,123 @@
+use super::common::{Coin, Id, Side};
+use super::Request;
+use chrono::{DateTime, Utc};
+use http::Method;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, TimestampSecondsWithFrac};
+use std::borrow::Cow;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteId {
+    pub quote_id: Id,
+}
+
+/// Requests a quote to convert `size` of `from_coin` into `to_coin`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestQuote<'a> {
+    pub from_coin: &'a str,
+    pub to_coin: &'a str,
+    pub size: Decimal,
+}
+
+impl<'a> RequestQuote<'a> {
+    pub fn new(from_coin: &'a str, to_coin: &'a str, size: Decimal) -> Self {
+        Self {
+            from_coin,
+            to_coin,
+            size,
+        }
+    }
+}
+
+impl Request for RequestQuote<'_> {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/otc/quotes";
+    const AUTH: bool = true;
+
+    type Response = QuoteId;
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quote {
+    pub id: Id,
+    pub base_coin: Coin,
+    pub quote_coin: Coin,
+    pub from_coin: Coin,
+    pub to_coin: Coin,
+    pub side: Side,
+    pub price: Decimal,
+    /// Amount of `from_coin` paid
+    pub cost: Decimal,
+    /// Amount of `to_coin` received
+    pub proceeds: Decimal,
+    #[serde_as(as = "TimestampSecondsWithFrac<f64>")]
+    pub expiry: DateTime<Utc>,
+    pub expired: bool,
+    pub filled: bool,
+}
+
+/// Alias of `Quote`, as named in the FTX docs.
+pub type QuoteInfo = Quote;
+
+impl Quote {
+    /// Time left until the quote expires, zero once it has expired.
+    pub fn expires_in(&self) -> chrono::Duration {
+        if self.expired {
+            return chrono::Duration::zero();
+        }
+        (self.expiry - Utc::now()).max(chrono::Duration::zero())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetQuote {
+    #[serde(skip_serializing)]
+    pub quote_id: Id,
+}
+
+impl GetQuote {
+    pub fn new(quote_id: Id) -> Self {
+        Self { quote_id }
+    }
+}
+
+impl Request for GetQuote {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/otc/quotes/{}";
+    const AUTH: bool = true;
+
+    type Response = Quote;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/otc/quotes/{}", self.quote_id))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct AcceptQuote {
+    #[serde(skip_serializing)]
+    pub quote_id: Id,
+}
+
+impl AcceptQuote {
+    pub fn new(quote_id: Id) -> Self {
+        Self { quote_id }
+    }
+}
+
+impl Request for AcceptQuote {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/otc/quotes/{}/accept";
+    const AUTH: bool = true;
+
+    type Response = ();
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/otc/quotes/{}/accept", self.quote_id))
+    }
+}
@@ -0,0 +1,95 @@
+use super::common::Coin;
+use super::wallet::WalletWithdrawal;
+use super::Request;
+use chrono::{DateTime, Utc};
+use http::Method;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Bank details to wire fiat to in order to credit the account.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FiatDepositInstructions {
+    pub coin: Coin,
+    pub bank_name: Option<String>,
+    pub bank_address: Option<String>,
+    pub account_name: Option<String>,
+    pub account_number: Option<String>,
+    pub routing_number: Option<String>,
+    pub swift_code: Option<String>,
+    pub iban: Option<String>,
+    /// Must be included in the wire so the deposit can be attributed
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetFiatDepositInstructions<'a> {
+    #[serde(skip_serializing)]
+    pub coin: &'a str,
+}
+
+impl<'a> GetFiatDepositInstructions<'a> {
+    pub fn new(coin: &'a str) -> Self {
+        Self { coin }
+    }
+}
+
+impl Request for GetFiatDepositInstructions<'_> {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/wallet/fiat/deposit_instructions/{}";
+    const AUTH: bool = true;
+
+    type Response = FiatDepositInstructions;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/wallet/fiat/deposit_instructions/{}", self.coin))
+    }
+}
+
+/// Wires `size` of a fiat `coin` to a saved bank account, see
+/// `GetSavedAddresses`.
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestFiatWithdrawal {
+    pub coin: String,
+    pub size: Decimal,
+    pub saved_address_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl Request for RequestFiatWithdrawal {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/wallet/fiat/withdrawals";
+    const AUTH: bool = true;
+
+    type Response = WalletWithdrawal;
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GetFiatWithdrawals {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl Request for GetFiatWithdrawals {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/wallet/fiat/withdrawals";
+    const AUTH: bool = true;
+
+    type Response = Vec<WalletWithdrawal>;
+}
@@ -1,14 +1,18 @@
-use super::{common::Id, Request};
-use crate::ws::Fill;
+use super::{
+    common::{Coin, Id, Side, SortOrder, Symbol},
+    Request,
+};
+use crate::rest::Raw;
 use chrono::{DateTime, Utc};
 use http::Method;
-use serde::Serialize;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 
+/// Fills of the account, of all markets unless `market_name` is set.
 #[derive(Debug, Clone, Serialize, Default)]
-
 pub struct GetFills<'a> {
-    #[serde(rename = "market")]
-    pub market_name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "market")]
+    pub market_name: Option<&'a str>,
     #[serde(
         skip_serializing_if = "Option::is_none",
         serialize_with = "super::serialize_as_timestamp"
@@ -23,15 +27,21 @@ pub struct GetFills<'a> {
     pub order_id: Option<Id>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<SortOrder>,
 }
 
 impl<'a> GetFills<'a> {
     pub fn new(market_name: &'a str) -> Self {
         Self {
-            market_name,
+            market_name: Some(market_name),
             ..Self::default()
         }
     }
+
+    pub fn all_markets() -> Self {
+        Self::default()
+    }
 }
 
 impl Request for GetFills<'_> {
@@ -42,3 +52,42 @@ impl Request for GetFills<'_> {
     const OPTIMIZED_ACCESS_SUPPORTED: bool = true;
     type Response = Vec<Fill>;
 }
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fill {
+    pub id: Id,
+    pub market: Option<Symbol>,
+    pub future: Option<Symbol>,
+    pub base_currency: Option<Coin>,
+    pub quote_currency: Option<Coin>,
+    pub r#type: String, // e.g. "order"
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub order_id: Option<Id>,
+    pub trade_id: Option<Id>,
+    pub time: DateTime<Utc>,
+    pub fee: Decimal,
+    pub fee_rate: Decimal,
+    pub fee_currency: Coin,
+    pub liquidity: Liquidity,
+    /// JSON object of the response, see `Options::capture_raw`
+    #[serde(skip, default = "Raw::captured")]
+    pub raw: Raw,
+}
+
+impl Fill {
+    /// JSON object this was deserialized from, if captured, see
+    /// `Options::capture_raw`.
+    pub fn raw(&self) -> Option<&serde_json::Value> {
+        self.raw.get()
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
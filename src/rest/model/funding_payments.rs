@@ -5,13 +5,16 @@ use http::Method;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FundingPayment {
     pub id: Id,
     pub future: String,
+    /// Positive if paid, negative if received
     pub payment: Decimal,
     pub time: DateTime<Utc>,
+    /// Funding rate the payment was computed with
+    pub rate: Option<Decimal>,
 }
 
 type FundingPayments = Vec<FundingPayment>;
@@ -32,6 +35,19 @@ pub struct GetFundingPayments<'a> {
     pub end_time: Option<DateTime<Utc>>,
 }
 
+impl<'a> GetFundingPayments<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_future(future: &'a str) -> Self {
+        Self {
+            future: Some(future),
+            ..Self::default()
+        }
+    }
+}
+
 impl Request for GetFundingPayments<'_> {
     const METHOD: Method = Method::GET;
     const PATH: &'static str = "/funding_payments";
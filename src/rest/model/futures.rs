@@ -1,5 +1,6 @@
 use super::common::{FutureType, Symbol};
 use super::{Request, Resolution};
+use crate::rest::Raw;
 use chrono::{DateTime, Utc};
 use http::Method;
 use rust_decimal::Decimal;
@@ -26,6 +27,9 @@ pub struct Future {
     pub lower_bound: Decimal,
     pub mark: Option<Decimal>,
     pub name: Symbol,
+    /// Open interest in contracts
+    pub open_interest: Option<Decimal>,
+    pub open_interest_usd: Option<Decimal>,
     pub perpetual: bool,
     pub position_limit_weight: Decimal,
     pub post_only: bool,
@@ -35,6 +39,17 @@ pub struct Future {
     pub upper_bound: Decimal,
     #[serde(rename = "type")]
     pub market_type: FutureType,
+    /// JSON object of the response, see `Options::capture_raw`
+    #[serde(skip, default = "Raw::captured")]
+    pub raw: Raw,
+}
+
+impl Future {
+    /// JSON object this was deserialized from, if captured, see
+    /// `Options::capture_raw`.
+    pub fn raw(&self) -> Option<&serde_json::Value> {
+        self.raw.get()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
@@ -151,6 +166,14 @@ pub struct GetFutureStats {
     pub future_name: String,
 }
 
+impl GetFutureStats {
+    pub fn new(future_name: &str) -> Self {
+        Self {
+            future_name: future_name.into(),
+        }
+    }
+}
+
 impl Request for GetFutureStats {
     const METHOD: Method = Method::GET;
     const PATH: &'static str = "/futures/{}/stats";
@@ -0,0 +1,95 @@
+use super::common::{Coin, Id};
+use super::Request;
+use chrono::{DateTime, Utc};
+use http::Method;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, TimestampSeconds, TimestampSecondsWithFrac};
+use std::borrow::Cow;
+
+/// Requests a snapshot of the balances of `accounts` at `end_time`. FTX
+/// processes the request asynchronously, poll it with `GetHistoricalBalances`.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestHistoricalBalances {
+    /// Account nicknames, "main" for the main account or "all" for all accounts
+    pub accounts: Vec<String>,
+    #[serde_as(as = "TimestampSeconds<i64>")]
+    pub end_time: DateTime<Utc>,
+}
+
+impl Request for RequestHistoricalBalances {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/historical_balances/requests";
+    const AUTH: bool = true;
+
+    type Response = Id;
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoricalBalancesStatus {
+    Requested,
+    Processing,
+    Complete,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoricalBalance {
+    pub account: String,
+    pub ticker: Coin,
+    pub size: Decimal,
+    pub price: Option<Decimal>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoricalBalances {
+    pub id: Id,
+    pub accounts: Vec<String>,
+    #[serde_as(as = "TimestampSecondsWithFrac<f64>")]
+    pub time: DateTime<Utc>,
+    #[serde_as(as = "TimestampSecondsWithFrac<f64>")]
+    pub end_time: DateTime<Utc>,
+    pub status: HistoricalBalancesStatus,
+    pub error: bool,
+    pub results: Option<Vec<HistoricalBalance>>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetHistoricalBalances {
+    #[serde(skip_serializing)]
+    pub id: Id,
+}
+
+impl GetHistoricalBalances {
+    pub fn new(id: Id) -> Self {
+        Self { id }
+    }
+}
+
+impl Request for GetHistoricalBalances {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/historical_balances/requests/{}";
+    const AUTH: bool = true;
+
+    type Response = HistoricalBalances;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/historical_balances/requests/{}", self.id))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetAllHistoricalBalances {}
+
+impl Request for GetAllHistoricalBalances {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/historical_balances/requests";
+    const AUTH: bool = true;
+
+    type Response = Vec<HistoricalBalances>;
+}
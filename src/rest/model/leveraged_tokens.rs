@@ -0,0 +1,213 @@
+use super::common::{Coin, Id};
+use super::Request;
+use crate::rest::{Error, Result};
+use chrono::{DateTime, Utc};
+use http::Method;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeveragedToken {
+    pub name: Coin,
+    pub description: String,
+    pub underlying: String,
+    pub leverage: Decimal,
+    pub outstanding: Decimal,
+    pub price_per_share: Decimal,
+    pub position_per_share: Decimal,
+    pub underlying_mark: Decimal,
+    pub contract_address: Option<String>,
+    pub change_1h: Decimal,
+    pub change_24h: Decimal,
+}
+
+fn validate_size(size: Decimal) -> Result<()> {
+    if size <= Decimal::ZERO {
+        return Err(Error::SizeBelowMinimum(size, Decimal::ZERO));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetLeveragedTokens {}
+
+impl Request for GetLeveragedTokens {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/lt/tokens";
+    const AUTH: bool = false;
+
+    type Response = Vec<LeveragedToken>;
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetLeveragedToken<'a> {
+    #[serde(skip_serializing)]
+    pub token: &'a str,
+}
+
+impl<'a> GetLeveragedToken<'a> {
+    pub fn new(token: &'a str) -> Self {
+        Self { token }
+    }
+}
+
+impl Request for GetLeveragedToken<'_> {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/lt/{}";
+    const AUTH: bool = false;
+
+    type Response = LeveragedToken;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/lt/{}", self.token))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeveragedTokenBalance {
+    pub token: Coin,
+    pub balance: Decimal,
+}
+
+impl LeveragedTokenBalance {
+    /// Checks that `size` tokens can be redeemed from the balance.
+    pub fn validate_redemption(&self, size: Decimal) -> Result<()> {
+        validate_size(size)?;
+        if size > self.balance {
+            return Err(Error::SizeExceedsLimit(size, self.balance));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetLeveragedTokenBalances {}
+
+impl Request for GetLeveragedTokenBalances {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/lt/balances";
+    const AUTH: bool = true;
+
+    type Response = Vec<LeveragedTokenBalance>;
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeveragedTokenCreation {
+    pub id: Id,
+    pub token: Coin,
+    pub requested_size: Decimal,
+    pub pending: bool,
+    pub created_size: Option<Decimal>,
+    pub price: Option<Decimal>,
+    pub cost: Decimal,
+    pub fee: Option<Decimal>,
+    pub requested_at: DateTime<Utc>,
+    pub fulfilled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetLeveragedTokenCreations {}
+
+impl Request for GetLeveragedTokenCreations {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/lt/creations";
+    const AUTH: bool = true;
+
+    type Response = Vec<LeveragedTokenCreation>;
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct RequestLeveragedTokenCreation<'a> {
+    #[serde(skip_serializing)]
+    pub token: &'a str,
+    pub size: Decimal,
+}
+
+impl<'a> RequestLeveragedTokenCreation<'a> {
+    pub fn new(token: &'a str, size: Decimal) -> Self {
+        Self { token, size }
+    }
+
+    /// Rejects sizes which are not positive before creating the request,
+    /// since the errors returned by FTX are not descriptive. FTX does not
+    /// publish further creation limits.
+    pub fn validated(token: &'a str, size: Decimal) -> Result<Self> {
+        validate_size(size)?;
+        Ok(Self::new(token, size))
+    }
+}
+
+impl Request for RequestLeveragedTokenCreation<'_> {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/lt/{}/create";
+    const AUTH: bool = true;
+
+    type Response = LeveragedTokenCreation;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/lt/{}/create", self.token))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeveragedTokenRedemption {
+    pub id: Id,
+    pub token: Coin,
+    pub size: Decimal,
+    pub pending: bool,
+    pub price: Option<Decimal>,
+    pub proceeds: Option<Decimal>,
+    pub projected_proceeds: Option<Decimal>,
+    pub fee: Option<Decimal>,
+    pub requested_at: DateTime<Utc>,
+    pub fulfilled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetLeveragedTokenRedemptions {}
+
+impl Request for GetLeveragedTokenRedemptions {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/lt/redemptions";
+    const AUTH: bool = true;
+
+    type Response = Vec<LeveragedTokenRedemption>;
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct RequestLeveragedTokenRedemption<'a> {
+    #[serde(skip_serializing)]
+    pub token: &'a str,
+    pub size: Decimal,
+}
+
+impl<'a> RequestLeveragedTokenRedemption<'a> {
+    pub fn new(token: &'a str, size: Decimal) -> Self {
+        Self { token, size }
+    }
+
+    /// Validates `size` against the `balance` of the token, from
+    /// `GetLeveragedTokenBalances`, before creating the request, since the
+    /// errors returned by FTX are not descriptive.
+    pub fn validated(balance: &'a LeveragedTokenBalance, size: Decimal) -> Result<Self> {
+        balance.validate_redemption(size)?;
+        Ok(Self::new(&balance.token, size))
+    }
+}
+
+impl Request for RequestLeveragedTokenRedemption<'_> {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/lt/{}/redeem";
+    const AUTH: bool = true;
+
+    type Response = LeveragedTokenRedemption;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/lt/{}/redeem", self.token))
+    }
+}
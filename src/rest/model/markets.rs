@@ -1,8 +1,9 @@
 use super::common::{Coin, Id, MarketType, Resolution, Side, Symbol};
 use super::Request;
+use crate::rest::Raw;
 use chrono::{DateTime, Utc};
 use http::Method;
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 
@@ -31,6 +32,85 @@ pub struct Market {
     pub change_bod: Decimal,
     pub quote_volume24h: Decimal,
     pub volume_usd24h: Decimal,
+    /// JSON object of the response, see `Options::capture_raw`
+    #[serde(skip, default = "Raw::captured")]
+    pub raw: Raw,
+}
+
+impl Market {
+    /// JSON object this was deserialized from, if captured, see
+    /// `Options::capture_raw`.
+    pub fn raw(&self) -> Option<&serde_json::Value> {
+        self.raw.get()
+    }
+
+    pub fn rules(&self) -> MarketRules {
+        MarketRules {
+            price_increment: self.price_increment,
+            size_increment: self.size_increment,
+            min_provide_size: self.min_provide_size,
+        }
+    }
+
+    /// See `MarketRules::round_price`.
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        self.rules().round_price(price)
+    }
+
+    /// See `MarketRules::round_size`.
+    pub fn round_size(&self, size: Decimal) -> Decimal {
+        self.rules().round_size(size)
+    }
+}
+
+/// Tick and lot sizes of a market, which prices and sizes of orders have to
+/// be multiples of. See `PlaceOrder::normalized`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MarketRules {
+    pub price_increment: Decimal,
+    pub size_increment: Decimal,
+    /// Minimum size of orders adding liquidity
+    pub min_provide_size: Decimal,
+}
+
+impl From<&Market> for MarketRules {
+    fn from(market: &Market) -> Self {
+        market.rules()
+    }
+}
+
+impl MarketRules {
+    /// Rounds `price` to the nearest multiple of `price_increment`.
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        round_to(
+            price,
+            self.price_increment,
+            RoundingStrategy::MidpointAwayFromZero,
+        )
+    }
+
+    /// Rounds `price` to a multiple of `price_increment` that is not worse
+    /// for `side`, i.e. down for buys and up for sells.
+    pub fn round_price_for(&self, side: Side, price: Decimal) -> Decimal {
+        let strategy = match side {
+            Side::Buy => RoundingStrategy::ToNegativeInfinity,
+            Side::Sell => RoundingStrategy::ToPositiveInfinity,
+        };
+        round_to(price, self.price_increment, strategy)
+    }
+
+    /// Rounds `size` down to a multiple of `size_increment`, so that orders
+    /// never exceed the intended size.
+    pub fn round_size(&self, size: Decimal) -> Decimal {
+        round_to(size, self.size_increment, RoundingStrategy::ToZero)
+    }
+}
+
+fn round_to(value: Decimal, increment: Decimal, strategy: RoundingStrategy) -> Decimal {
+    if increment.is_zero() {
+        return value;
+    }
+    (value / increment).round_dp_with_strategy(0, strategy) * increment
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
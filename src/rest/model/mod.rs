@@ -1,25 +1,41 @@
 mod account;
 mod common;
+mod convert;
+mod fiat;
 mod fills;
 mod funding_payments;
 mod futures;
+mod historical_balances;
+mod leveraged_tokens;
 mod markets;
+mod options;
 mod orders;
 mod positions;
+mod raw;
 mod spot_margin;
+mod staking;
 mod subaccounts;
+mod time;
 mod wallet;
 
 pub use self::account::*;
 pub use self::common::*;
+pub use self::convert::*;
+pub use self::fiat::*;
 pub use self::fills::*;
 pub use self::funding_payments::*;
 pub use self::futures::*;
+pub use self::historical_balances::*;
+pub use self::leveraged_tokens::*;
 pub use self::markets::*;
+pub use self::options::*;
 pub use self::orders::*;
 pub use self::positions::*;
+pub use self::raw::*;
 pub use self::spot_margin::*;
+pub use self::staking::*;
 pub use self::subaccounts::*;
+pub use self::time::*;
 pub use self::wallet::*;
 
 use chrono::{DateTime, Utc};
@@ -39,12 +55,34 @@ pub trait Request: Serialize {
     fn path(&self) -> Cow<'_, str> {
         Cow::Borrowed(Self::PATH)
     }
+
+    /// Endpoint the request is grouped under, e.g. in `RequestInfo` and
+    /// schema drift reports. `PATH`, unless it is only known at runtime.
+    fn endpoint(&self) -> Cow<'_, str> {
+        Cow::Borrowed(Self::PATH)
+    }
+
+    /// Checks for field combinations that FTX rejects, before the request
+    /// is sent.
+    fn validate(&self) -> crate::rest::Result<()> {
+        Ok(())
+    }
+
+    /// Withdrawal made by the request, checked against the address book
+    /// and address formats before it is sent if enabled in `Options`.
+    fn withdrawal(&self) -> Option<&RequestWithdrawal> {
+        None
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct SuccessResponse<T> {
     pub success: bool,
     pub result: T,
+    /// Set by paginated endpoints such as `/orders/history` if rows before
+    /// the oldest returned one are left
+    #[serde(default, rename = "hasMoreData")]
+    pub has_more_data: Option<bool>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -0,0 +1,418 @@
+use super::common::{Id, Side};
+use super::{Liquidity, Request};
+use chrono::{DateTime, Utc};
+use http::Method;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, TimestampSeconds};
+use std::borrow::Cow;
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionContract {
+    pub underlying: String,
+    pub r#type: OptionType,
+    pub strike: Decimal,
+    pub expiry: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OptionQuoteRequestStatus {
+    Open,
+    Filled,
+    Cancelled,
+}
+
+/// Public request for quotes on an option.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionQuoteRequest {
+    pub id: Id,
+    pub option: OptionContract,
+    pub side: Side,
+    pub size: Decimal,
+    pub time: DateTime<Utc>,
+    pub request_expiry: DateTime<Utc>,
+    pub status: OptionQuoteRequestStatus,
+    pub limit_price: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetOptionQuoteRequests {}
+
+impl Request for GetOptionQuoteRequests {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/options/requests";
+    const AUTH: bool = false;
+
+    type Response = Vec<OptionQuoteRequest>;
+}
+
+/// Quote request of the account, with the quotes received for it.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MyOptionQuoteRequest {
+    #[serde(flatten)]
+    pub request: OptionQuoteRequest,
+    #[serde(default)]
+    pub hide_limit_price: bool,
+    #[serde(default)]
+    pub quotes: Vec<OptionQuote>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetMyOptionQuoteRequests {}
+
+impl Request for GetMyOptionQuoteRequests {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/options/my_requests";
+    const AUTH: bool = true;
+
+    type Response = Vec<MyOptionQuoteRequest>;
+}
+
+/// Requests quotes to trade `size` of an option.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOptionQuoteRequest<'a> {
+    pub underlying: &'a str,
+    pub r#type: OptionType,
+    pub strike: Decimal,
+    #[serde_as(as = "TimestampSeconds<i64>")]
+    pub expiry: DateTime<Utc>,
+    pub side: Side,
+    pub size: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_price: Option<Decimal>,
+    pub hide_limit_price: bool,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub request_expiry: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counterparty_id: Option<Id>,
+}
+
+impl<'a> CreateOptionQuoteRequest<'a> {
+    pub fn new(option: &'a OptionContract, side: Side, size: Decimal) -> Self {
+        Self {
+            underlying: &option.underlying,
+            r#type: option.r#type,
+            strike: option.strike,
+            expiry: option.expiry,
+            side,
+            size,
+            limit_price: None,
+            hide_limit_price: true,
+            request_expiry: None,
+            counterparty_id: None,
+        }
+    }
+
+    /// Only accept quotes at `price` or better.
+    #[must_use]
+    pub fn limit_price(mut self, price: Decimal) -> Self {
+        self.limit_price = Some(price);
+        self
+    }
+
+    /// Expire the request at `expiry` instead of after 5 minutes.
+    #[must_use]
+    pub fn request_expiry(mut self, expiry: DateTime<Utc>) -> Self {
+        self.request_expiry = Some(expiry);
+        self
+    }
+}
+
+impl Request for CreateOptionQuoteRequest<'_> {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/options/requests";
+    const AUTH: bool = true;
+
+    type Response = OptionQuoteRequest;
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct CancelOptionQuoteRequest {
+    #[serde(skip_serializing)]
+    pub request_id: Id,
+}
+
+impl CancelOptionQuoteRequest {
+    pub fn new(request_id: Id) -> Self {
+        Self { request_id }
+    }
+}
+
+impl Request for CancelOptionQuoteRequest {
+    const METHOD: Method = Method::DELETE;
+    const PATH: &'static str = "/options/requests/{}";
+    const AUTH: bool = true;
+
+    type Response = OptionQuoteRequest;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/options/requests/{}", self.request_id))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OptionQuoteStatus {
+    Open,
+    Filled,
+    Cancelled,
+}
+
+/// Quote on an option quote request.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionQuote {
+    pub id: Id,
+    pub request_id: Id,
+    pub option: Option<OptionContract>,
+    pub price: Decimal,
+    pub size: Option<Decimal>,
+    /// Collateral locked by the quoter
+    pub collateral: Option<Decimal>,
+    pub quoter_side: Option<Side>,
+    pub request_side: Option<Side>,
+    pub quote_expiry: Option<DateTime<Utc>>,
+    pub status: OptionQuoteStatus,
+    pub time: DateTime<Utc>,
+}
+
+/// Quotes received for a quote request of the account.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetOptionQuotes {
+    #[serde(skip_serializing)]
+    pub request_id: Id,
+}
+
+impl GetOptionQuotes {
+    pub fn new(request_id: Id) -> Self {
+        Self { request_id }
+    }
+}
+
+impl Request for GetOptionQuotes {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/options/requests/{}/quotes";
+    const AUTH: bool = true;
+
+    type Response = Vec<OptionQuote>;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/options/requests/{}/quotes", self.request_id))
+    }
+}
+
+/// Quotes `price` on the quote request `request_id` of another account.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct CreateOptionQuote {
+    #[serde(skip_serializing)]
+    pub request_id: Id,
+    pub price: Decimal,
+}
+
+impl CreateOptionQuote {
+    pub fn new(request_id: Id, price: Decimal) -> Self {
+        Self { request_id, price }
+    }
+}
+
+impl Request for CreateOptionQuote {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/options/requests/{}/quotes";
+    const AUTH: bool = true;
+
+    type Response = OptionQuote;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/options/requests/{}/quotes", self.request_id))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetMyOptionQuotes {}
+
+impl Request for GetMyOptionQuotes {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/options/my_quotes";
+    const AUTH: bool = true;
+
+    type Response = Vec<OptionQuote>;
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct CancelOptionQuote {
+    #[serde(skip_serializing)]
+    pub quote_id: Id,
+}
+
+impl CancelOptionQuote {
+    pub fn new(quote_id: Id) -> Self {
+        Self { quote_id }
+    }
+}
+
+impl Request for CancelOptionQuote {
+    const METHOD: Method = Method::DELETE;
+    const PATH: &'static str = "/options/quotes/{}";
+    const AUTH: bool = true;
+
+    type Response = OptionQuote;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/options/quotes/{}", self.quote_id))
+    }
+}
+
+/// Accepts a quote received for a quote request of the account.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct AcceptOptionQuote {
+    #[serde(skip_serializing)]
+    pub quote_id: Id,
+}
+
+impl AcceptOptionQuote {
+    pub fn new(quote_id: Id) -> Self {
+        Self { quote_id }
+    }
+}
+
+impl Request for AcceptOptionQuote {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/options/quotes/{}/accept";
+    const AUTH: bool = true;
+
+    type Response = OptionQuote;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/options/quotes/{}/accept", self.quote_id))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionsAccountInfo {
+    pub usd_balance: Decimal,
+    pub liquidation_price: Option<Decimal>,
+    pub liquidating: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetOptionsAccountInfo {}
+
+impl Request for GetOptionsAccountInfo {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/options/account_info";
+    const AUTH: bool = true;
+
+    type Response = OptionsAccountInfo;
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionPosition {
+    pub option: OptionContract,
+    pub side: Side,
+    pub size: Decimal,
+    /// Size signed by side, negative for short positions
+    pub net_size: Decimal,
+    pub entry_price: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetOptionPositions {}
+
+impl Request for GetOptionPositions {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/options/positions";
+    const AUTH: bool = true;
+
+    type Response = Vec<OptionPosition>;
+}
+
+/// Trade of the account on an option.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionFill {
+    pub id: Id,
+    pub option: OptionContract,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub fee: Decimal,
+    pub fee_rate: Decimal,
+    pub liquidity: Liquidity,
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GetOptionFills {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl Request for GetOptionFills {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/options/fills";
+    const AUTH: bool = true;
+
+    type Response = Vec<OptionFill>;
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionTrade {
+    pub id: Id,
+    pub option: OptionContract,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GetOptionTrades {
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl Request for GetOptionTrades {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/options/trades";
+    const AUTH: bool = false;
+
+    type Response = Vec<OptionTrade>;
+}
@@ -1,5 +1,7 @@
 use super::common::{Id, OrderStatus, OrderType, Side};
-use super::Request;
+use super::{MarketRules, Request};
+use crate::rest::Raw;
+use crate::rest::{Error, Result};
 use chrono::{DateTime, Utc};
 use http::Method;
 use rust_decimal::Decimal;
@@ -30,7 +32,104 @@ pub struct OrderInfo {
     pub trigger_price: Option<Decimal>,
     pub order_price: Option<Decimal>,
     pub triggered_at: Option<String>,
+    /// Type of the order placed once a trigger order triggers
+    pub order_type: Option<OrderType>,
+    /// ID of the order placed once a trigger order triggers
+    pub order_id: Option<Id>,
+    pub trail_value: Option<Decimal>,
+    pub trail_start: Option<Decimal>,
     pub error: Option<String>,
+    /// JSON object of the response, see `Options::capture_raw`
+    #[serde(skip, default = "Raw::captured")]
+    pub raw: Raw,
+}
+
+/// `OrderInfo` borrowing its strings from the response body, see
+/// `Rest::request_borrowed`. Strings containing escapes are copied.
+#[cfg(feature = "optimized-access")]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderInfoRef<'a> {
+    pub id: Id,
+    pub market: &'a str,
+    pub future: Option<&'a str>,
+    pub r#type: OrderType,
+    pub side: Side,
+    pub price: Option<Decimal>,
+    pub size: Decimal,
+    pub reduce_only: Option<bool>,
+    pub ioc: Option<bool>,
+    pub post_only: Option<bool>,
+    pub status: OrderStatus,
+    pub filled_size: Option<Decimal>,
+    pub remaining_size: Option<Decimal>,
+    pub avg_fill_price: Option<Decimal>,
+    pub liquidation: Option<bool>,
+    pub created_at: DateTime<Utc>,
+    #[serde(borrow)]
+    pub client_id: Option<Cow<'a, str>>,
+    pub retry_until_filled: Option<bool>,
+    pub trigger_price: Option<Decimal>,
+    pub order_price: Option<Decimal>,
+    pub triggered_at: Option<&'a str>,
+    pub order_type: Option<OrderType>,
+    pub order_id: Option<Id>,
+    pub trail_value: Option<Decimal>,
+    pub trail_start: Option<Decimal>,
+    #[serde(borrow)]
+    pub error: Option<Cow<'a, str>>,
+}
+
+#[cfg(feature = "optimized-access")]
+impl OrderInfoRef<'_> {
+    pub fn into_owned(self) -> OrderInfo {
+        OrderInfo {
+            id: self.id,
+            market: self.market.to_owned(),
+            future: self.future.map(str::to_owned),
+            r#type: self.r#type,
+            side: self.side,
+            price: self.price,
+            size: self.size,
+            reduce_only: self.reduce_only,
+            ioc: self.ioc,
+            post_only: self.post_only,
+            status: self.status,
+            filled_size: self.filled_size,
+            remaining_size: self.remaining_size,
+            avg_fill_price: self.avg_fill_price,
+            liquidation: self.liquidation,
+            created_at: self.created_at,
+            client_id: self.client_id.map(Cow::into_owned),
+            retry_until_filled: self.retry_until_filled,
+            trigger_price: self.trigger_price,
+            order_price: self.order_price,
+            triggered_at: self.triggered_at.map(str::to_owned),
+            order_type: self.order_type,
+            order_id: self.order_id,
+            trail_value: self.trail_value,
+            trail_start: self.trail_start,
+            error: self.error.map(Cow::into_owned),
+            raw: Default::default(),
+        }
+    }
+}
+
+impl OrderInfo {
+    /// JSON object this was deserialized from, if captured, see
+    /// `Options::capture_raw`.
+    pub fn raw(&self) -> Option<&serde_json::Value> {
+        self.raw.get()
+    }
+
+    /// Time in force of the order, from its `ioc` and `post_only` flags.
+    pub fn time_in_force(&self) -> TimeInForce {
+        match (self.ioc, self.post_only) {
+            (_, Some(true)) => TimeInForce::PostOnly,
+            (Some(true), _) => TimeInForce::Ioc,
+            _ => TimeInForce::Gtc,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
@@ -60,6 +159,72 @@ impl Request for GetOpenOrders<'_> {
     type Response = Vec<OrderInfo>;
 }
 
+/// How long an order stays on the book, replacing the `ioc` and `postOnly`
+/// flags of the API, which cannot both be set.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum TimeInForce {
+    /// Good till cancelled
+    #[default]
+    Gtc,
+    /// Immediate or cancel, whatever is not filled at once is cancelled
+    Ioc,
+    /// Rejected instead of taking liquidity
+    PostOnly,
+}
+
+impl TimeInForce {
+    /// From the `ioc` and `post_only` flags of the API, which contradict
+    /// each other if both are set.
+    pub fn from_flags(ioc: bool, post_only: bool) -> Result<Self> {
+        match (ioc, post_only) {
+            (false, false) => Ok(Self::Gtc),
+            (true, false) => Ok(Self::Ioc),
+            (false, true) => Ok(Self::PostOnly),
+            (true, true) => Err(Error::ConflictingTimeInForce),
+        }
+    }
+
+    pub fn ioc(self) -> bool {
+        self == Self::Ioc
+    }
+
+    pub fn post_only(self) -> bool {
+        self == Self::PostOnly
+    }
+}
+
+impl Serialize for TimeInForce {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut flags = serializer.serialize_struct("TimeInForce", 2)?;
+        flags.serialize_field("ioc", &self.ioc())?;
+        flags.serialize_field("postOnly", &self.post_only())?;
+        flags.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeInForce {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Flags {
+            #[serde(default)]
+            ioc: bool,
+            #[serde(default)]
+            post_only: bool,
+        }
+
+        let flags = Flags::deserialize(deserializer)?;
+        Self::from_flags(flags.ioc, flags.post_only).map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PlaceOrder<'a> {
@@ -71,13 +236,98 @@ pub struct PlaceOrder<'a> {
     pub r#type: OrderType,
     pub size: Decimal,
     pub reduce_only: bool,
-    pub ioc: bool,
-    pub post_only: bool,
+    /// Sent as the `ioc` and `postOnly` flags
+    #[serde(flatten)]
+    pub time_in_force: TimeInForce,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_id: Option<&'a str>,
     pub reject_on_price_band: bool,
 }
 
+impl<'a> PlaceOrder<'a> {
+    pub fn limit(market: &'a str, side: Side, price: Decimal, size: Decimal) -> Self {
+        Self {
+            market,
+            side,
+            price: Some(price),
+            r#type: OrderType::Limit,
+            size,
+            ..Default::default()
+        }
+    }
+
+    pub fn market(market: &'a str, side: Side, size: Decimal) -> Self {
+        Self {
+            market,
+            side,
+            r#type: OrderType::Market,
+            size,
+            ..Default::default()
+        }
+    }
+
+    /// Fails with `Error::PostOnlyMarketOrder` for post-only market orders,
+    /// which would always be rejected.
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Result<Self> {
+        if time_in_force == TimeInForce::PostOnly && self.r#type == OrderType::Market {
+            return Err(Error::PostOnlyMarketOrder);
+        }
+        self.time_in_force = time_in_force;
+        Ok(self)
+    }
+
+    /// Cancels whatever is not filled at once.
+    #[must_use]
+    pub fn ioc(mut self) -> Self {
+        self.time_in_force = TimeInForce::Ioc;
+        self
+    }
+
+    /// Fails with `Error::PostOnlyMarketOrder` for market orders.
+    pub fn post_only(self) -> Result<Self> {
+        self.time_in_force(TimeInForce::PostOnly)
+    }
+
+    #[must_use]
+    pub fn reduce_only(mut self) -> Self {
+        self.reduce_only = true;
+        self
+    }
+
+    #[must_use]
+    pub fn client_id(mut self, client_id: &'a str) -> Self {
+        self.client_id = Some(client_id);
+        self
+    }
+
+    #[must_use]
+    pub fn reject_on_price_band(mut self) -> Self {
+        self.reject_on_price_band = true;
+        self
+    }
+
+    /// Snaps the price to the tick size of the market, rounding towards the
+    /// passive side, and the size down to its lot size.
+    ///
+    /// Fails with `Error::SizeBelowMinimum` if nothing is left of the size,
+    /// or if it is below the minimum size for providing liquidity of a
+    /// post-only order.
+    pub fn normalized(mut self, rules: &MarketRules) -> Result<Self> {
+        self.price = self
+            .price
+            .map(|price| rules.round_price_for(self.side, price));
+        let size = rules.round_size(self.size);
+        if size.is_zero() {
+            return Err(Error::SizeBelowMinimum(self.size, rules.size_increment));
+        }
+        if self.time_in_force == TimeInForce::PostOnly && size < rules.min_provide_size {
+            return Err(Error::SizeBelowMinimum(size, rules.min_provide_size));
+        }
+        self.size = size;
+        Ok(self)
+    }
+}
+
 impl Request for PlaceOrder<'_> {
     const METHOD: Method = Method::POST;
     const PATH: &'static str = "/orders";
@@ -85,6 +335,18 @@ impl Request for PlaceOrder<'_> {
     #[cfg(feature = "optimized-access")]
     const OPTIMIZED_ACCESS_SUPPORTED: bool = true;
     type Response = OrderInfo;
+
+    /// Catches orders built field by field rather than with `limit` and
+    /// `market`.
+    fn validate(&self) -> Result<()> {
+        match self.r#type {
+            OrderType::Limit if self.price.is_none() => Err(Error::PlacingLimitOrderRequiresPrice),
+            OrderType::Market if self.time_in_force == TimeInForce::PostOnly => {
+                Err(Error::PostOnlyMarketOrder)
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
@@ -96,6 +358,7 @@ pub struct ModifyOrder<'a> {
     pub price: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<Decimal>,
+    /// Client ID assigned to the replacement order
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_id: Option<&'a str>,
 }
@@ -213,6 +476,38 @@ impl Request for CancelAllOrder<'_> {
     type Response = String;
 }
 
+/// Cancels all open orders once `timeout` seconds pass without another
+/// `CancelAllAfter`, see `Rest::spawn_dead_mans_switch`.
+///
+/// Unverified: FTX never documented `/orders/cancel_all_after`, so the
+/// endpoint and its `timeout` field may not exist on the exchange.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct CancelAllAfter {
+    pub timeout: u64,
+}
+
+impl CancelAllAfter {
+    /// Timeouts are rounded up to whole seconds.
+    pub fn new(timeout: std::time::Duration) -> Self {
+        Self {
+            timeout: timeout.as_secs() + u64::from(timeout.subsec_nanos() > 0),
+        }
+    }
+
+    /// Disables the timer, keeping open orders.
+    pub fn disable() -> Self {
+        Self { timeout: 0 }
+    }
+}
+
+impl Request for CancelAllAfter {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/orders/cancel_all_after";
+    const AUTH: bool = true;
+
+    type Response = ();
+}
+
 #[derive(Debug, Clone, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct CancelOrderByClientId<'a> {
@@ -292,6 +587,8 @@ impl Request for GetOrderHistory<'_> {
     type Response = Vec<OrderInfo>;
 }
 
+/// Stop or take profit order. A market order is placed once the price
+/// crosses `trigger_price`, or a limit order at `order_price` if it is set.
 #[derive(Debug, Clone, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PlaceTriggerOrder<'a> {
@@ -310,12 +607,134 @@ pub struct PlaceTriggerOrder<'a> {
     pub trail_value: Option<Decimal>,
 }
 
+impl<'a> PlaceTriggerOrder<'a> {
+    /// Reduce-only stop market order that closes `size` of a position on
+    /// `position_side` once the price moves against it to `trigger_price`.
+    pub fn stop_loss(
+        market: &'a str,
+        position_side: Side,
+        size: Decimal,
+        trigger_price: Decimal,
+    ) -> Self {
+        Self {
+            market,
+            side: position_side.opposite(),
+            size,
+            r#type: OrderType::Stop,
+            trigger_price,
+            reduce_only: Some(true),
+            ..Default::default()
+        }
+    }
+
+    /// Reduce-only take profit market order that closes `size` of a position
+    /// on `position_side` once the price moves in its favor to `trigger_price`.
+    pub fn take_profit(
+        market: &'a str,
+        position_side: Side,
+        size: Decimal,
+        trigger_price: Decimal,
+    ) -> Self {
+        Self {
+            market,
+            side: position_side.opposite(),
+            size,
+            r#type: OrderType::TakeProfit,
+            trigger_price,
+            reduce_only: Some(true),
+            ..Default::default()
+        }
+    }
+
+    /// Places a limit order at `order_price` once triggered instead of a
+    /// market order. Cannot be combined with `retry_until_filled`.
+    #[must_use]
+    pub fn order_price(mut self, order_price: Decimal) -> Self {
+        self.order_price = Some(order_price);
+        self
+    }
+
+    /// Whether FTX keeps re-triggering the market order until it is filled,
+    /// e.g. after it was rejected because of price bands. FTX defaults to
+    /// `true` for market trigger orders. Only valid without `order_price`.
+    #[must_use]
+    pub fn retry_until_filled(mut self, retry_until_filled: bool) -> Self {
+        self.retry_until_filled = Some(retry_until_filled);
+        self
+    }
+}
+
 impl Request for PlaceTriggerOrder<'_> {
     const METHOD: Method = Method::POST;
     const PATH: &'static str = "/conditional_orders";
     const AUTH: bool = true;
 
     type Response = OrderInfo;
+
+    /// Also catches combinations that FTX silently ignores.
+    fn validate(&self) -> Result<()> {
+        if self.retry_until_filled == Some(true) && self.order_price.is_some() {
+            return Err(Error::RetryUntilFilledRequiresMarketTrigger);
+        }
+        Ok(())
+    }
+}
+
+/// Trailing stop order. Unlike `PlaceTriggerOrder`, the trigger price is
+/// derived by FTX from `trail_value`, whose sign has to match the side:
+/// negative for sell and positive for buy trailing stops.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaceTrailingStop<'a> {
+    pub market: &'a str,
+    pub side: Side,
+    pub size: Decimal,
+    r#type: OrderType,
+    trail_value: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reduce_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_until_filled: Option<bool>,
+}
+
+impl<'a> PlaceTrailingStop<'a> {
+    pub fn new(market: &'a str, side: Side, size: Decimal, trail_value: Decimal) -> Result<Self> {
+        let sign_matches = match side {
+            Side::Buy => trail_value.is_sign_positive() && !trail_value.is_zero(),
+            Side::Sell => trail_value.is_sign_negative() && !trail_value.is_zero(),
+        };
+        if !sign_matches {
+            return Err(Error::TrailValueSignMismatch);
+        }
+
+        Ok(Self {
+            market,
+            side,
+            size,
+            r#type: OrderType::TrailingStop,
+            trail_value,
+            reduce_only: None,
+            retry_until_filled: None,
+        })
+    }
+
+    #[must_use]
+    pub fn reduce_only(mut self) -> Self {
+        self.reduce_only = Some(true);
+        self
+    }
+
+    pub fn trail_value(&self) -> Decimal {
+        self.trail_value
+    }
+}
+
+impl Request for PlaceTrailingStop<'_> {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/conditional_orders";
+    const AUTH: bool = true;
+
+    type Response = OrderInfo;
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
@@ -327,6 +746,9 @@ pub struct ModifyOrderByClientId<'a> {
     pub price: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size: Option<Decimal>,
+    /// Client ID assigned to the replacement order
+    #[serde(rename = "clientId", skip_serializing_if = "Option::is_none")]
+    pub new_client_id: Option<&'a str>,
 }
 
 impl Request for ModifyOrderByClientId<'_> {
@@ -340,3 +762,174 @@ impl Request for ModifyOrderByClientId<'_> {
         Cow::Owned(format!("/orders/by_client_id/{}/modify", self.client_id))
     }
 }
+
+/// Kind of a trigger order, `trailing_stop` and `take_profit` in the
+/// `type` filters of `GetOpenTriggerOrders` and `GetTriggerOrderHistory`.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerOrderType {
+    Stop,
+    #[serde(alias = "trailingStop")]
+    TrailingStop,
+    #[serde(alias = "takeProfit")]
+    TakeProfit,
+}
+
+/// Trigger order, as returned by the `/conditional_orders` endpoints.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerOrderInfo {
+    pub id: Id,
+    pub market: String,
+    pub future: Option<String>,
+    pub r#type: TriggerOrderType,
+    /// Type of the order placed once triggered, market or limit
+    pub order_type: OrderType,
+    pub side: Side,
+    pub size: Decimal,
+    pub status: OrderStatus,
+    /// Derived from `trail_value` for trailing stops
+    pub trigger_price: Option<Decimal>,
+    /// Price of the limit order placed once triggered
+    pub order_price: Option<Decimal>,
+    pub trail_value: Option<Decimal>,
+    pub trail_start: Option<Decimal>,
+    pub filled_size: Option<Decimal>,
+    pub avg_fill_price: Option<Decimal>,
+    pub reduce_only: bool,
+    pub retry_until_filled: bool,
+    pub created_at: DateTime<Utc>,
+    pub triggered_at: Option<DateTime<Utc>>,
+    /// ID of the order placed once triggered
+    pub order_id: Option<Id>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetOpenTriggerOrders<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<TriggerOrderType>,
+}
+
+impl<'a> GetOpenTriggerOrders<'a> {
+    pub fn all_markets() -> Self {
+        Self::default()
+    }
+
+    pub fn with_market(market: &'a str) -> Self {
+        Self {
+            market: Some(market),
+            ..Default::default()
+        }
+    }
+}
+
+impl Request for GetOpenTriggerOrders<'_> {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/conditional_orders";
+    const AUTH: bool = true;
+
+    type Response = Vec<TriggerOrderInfo>;
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTriggerOrderHistory<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub side: Option<Side>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<TriggerOrderType>,
+    /// Market or limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_type: Option<OrderType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(
+        rename = "start_time",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(
+        rename = "end_time",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl Request for GetTriggerOrderHistory<'_> {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/conditional_orders/history";
+    const AUTH: bool = true;
+
+    type Response = Vec<TriggerOrderInfo>;
+}
+
+/// Time a trigger order triggered and the order it placed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerEvent {
+    pub time: DateTime<Utc>,
+    pub order_size: Option<Decimal>,
+    pub filled_size: Option<Decimal>,
+    pub order_id: Option<Id>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetTriggerOrderTriggers {
+    #[serde(skip_serializing)]
+    pub id: Id,
+}
+
+impl GetTriggerOrderTriggers {
+    pub fn new(order_id: Id) -> Self {
+        Self { id: order_id }
+    }
+}
+
+impl Request for GetTriggerOrderTriggers {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/conditional_orders/{}/triggers";
+    const AUTH: bool = true;
+
+    type Response = Vec<TriggerEvent>;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/conditional_orders/{}/triggers", self.id))
+    }
+}
+
+/// Replaces a trigger order by a new one with a new ID. `trigger_price` and
+/// `order_price` apply to stop and take profit orders, `trail_value` to
+/// trailing stops.
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ModifyTriggerOrder {
+    #[serde(skip_serializing)]
+    pub id: Id,
+    pub size: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger_price: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_price: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trail_value: Option<Decimal>,
+}
+
+impl Request for ModifyTriggerOrder {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/conditional_orders/{}/modify";
+    const AUTH: bool = true;
+
+    type Response = TriggerOrderInfo;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/conditional_orders/{}/modify", self.id))
+    }
+}
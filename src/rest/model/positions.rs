@@ -5,7 +5,23 @@ use serde::Serialize;
 
 #[derive(Debug, Clone, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
-pub struct GetPositions {}
+pub struct GetPositions {
+    /// Also returns the `recent_*` and `cumulative_*` fields of `Position`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_avg_price: Option<bool>,
+}
+
+impl GetPositions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_avg_price() -> Self {
+        Self {
+            show_avg_price: Some(true),
+        }
+    }
+}
 
 impl Request for GetPositions {
     const METHOD: Method = Method::GET;
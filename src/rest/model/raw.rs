@@ -0,0 +1,52 @@
+use super::Request;
+use http::Method;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+
+macro_rules! raw_request {
+    ($name:ident, $method:expr, $doc:literal) => {
+        #[doc = $doc]
+        ///
+        /// Untyped request to an arbitrary path, for language bindings that
+        /// pass JSON through. Requests are signed if a secret is configured.
+        /// `PATH` is empty; the endpoint is the path without its query.
+        #[derive(Debug, Clone, Serialize, Default)]
+        pub struct $name {
+            #[serde(skip_serializing)]
+            pub path: String,
+            /// Query parameters or JSON body
+            #[serde(flatten)]
+            pub params: Map<String, Value>,
+        }
+
+        impl $name {
+            pub fn new(path: impl Into<String>, params: Map<String, Value>) -> Self {
+                Self {
+                    path: path.into(),
+                    params,
+                }
+            }
+        }
+
+        impl Request for $name {
+            const METHOD: Method = $method;
+            const PATH: &'static str = "";
+            const AUTH: bool = true;
+
+            type Response = Value;
+
+            fn path(&self) -> Cow<'_, str> {
+                Cow::Borrowed(&self.path)
+            }
+
+            fn endpoint(&self) -> Cow<'_, str> {
+                Cow::Borrowed(self.path.split('?').next().unwrap_or_default())
+            }
+        }
+    };
+}
+
+raw_request!(RawGet, Method::GET, "GET request.");
+raw_request!(RawPost, Method::POST, "POST request.");
+raw_request!(RawDelete, Method::DELETE, "DELETE request.");
@@ -25,6 +25,59 @@ impl Request for GetLendingRates {
     type Response = Vec<LendingRate>;
 }
 
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetBorrowRates {}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BorrowRate {
+    pub coin: String,
+    pub estimate: Decimal, // estimated hourly borrow rate for the next spot margin cycle
+    pub previous: Option<Decimal>, // hourly borrow rate in the previous spot margin cycle
+}
+
+impl Request for GetBorrowRates {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/spot_margin/borrow_rates";
+    const AUTH: bool = true;
+
+    type Response = Vec<BorrowRate>;
+}
+
+/// Public lending history of all coins, or of `coin`.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetLendingHistory<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coin: Option<&'a str>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LendingHistory {
+    pub coin: String,
+    pub time: DateTime<Utc>,
+    pub rate: Decimal,
+    pub size: Decimal,
+}
+
+impl Request for GetLendingHistory<'_> {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/spot_margin/history";
+    const AUTH: bool = false;
+
+    type Response = Vec<LendingHistory>;
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MyLendingHistory {
@@ -57,6 +110,39 @@ impl Request for GetMyLendingHistory {
     type Response = Vec<MyLendingHistory>;
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MyBorrowHistory {
+    pub coin: String,
+    /// Interest paid for the hour
+    pub cost: Decimal,
+    pub rate: Decimal,
+    pub size: Decimal,
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetMyBorrowHistory {
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl Request for GetMyBorrowHistory {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/spot_margin/borrow_history";
+    const AUTH: bool = true;
+
+    type Response = Vec<MyBorrowHistory>;
+}
+
 #[derive(Debug, Clone, Serialize, Default)]
 pub struct GetLendingInfo {}
 
@@ -85,6 +171,14 @@ pub struct SubmitLendingOffer<'a> {
     pub rate: Decimal,
 }
 
+impl<'a> SubmitLendingOffer<'a> {
+    /// Offers `size` of `coin` at a minimum hourly `rate`, replacing the
+    /// current offer of `coin`.
+    pub fn new(coin: &'a str, size: Decimal, rate: Decimal) -> Self {
+        Self { coin, size, rate }
+    }
+}
+
 impl Request for SubmitLendingOffer<'_> {
     const METHOD: Method = Method::POST;
     const PATH: &'static str = "/spot_margin/offers";
@@ -92,3 +186,29 @@ impl Request for SubmitLendingOffer<'_> {
 
     type Response = ();
 }
+
+/// Withdraws the lending offer of `coin`, by offering a size of zero.
+#[derive(Debug, Clone, Serialize)]
+pub struct CancelLendingOffer<'a> {
+    coin: &'a str,
+    size: Decimal,
+    rate: Decimal,
+}
+
+impl<'a> CancelLendingOffer<'a> {
+    pub fn new(coin: &'a str) -> Self {
+        Self {
+            coin,
+            size: Decimal::ZERO,
+            rate: Decimal::ZERO,
+        }
+    }
+}
+
+impl Request for CancelLendingOffer<'_> {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/spot_margin/offers";
+    const AUTH: bool = true;
+
+    type Response = ();
+}
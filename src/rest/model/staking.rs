@@ -0,0 +1,169 @@
+use super::common::Id;
+use super::Request;
+use chrono::{DateTime, Utc};
+use http::Method;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Coins staked with a `Stake` request.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeInfo {
+    pub id: Id,
+    pub coin: String,
+    pub size: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetStakes {}
+
+impl Request for GetStakes {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/staking/stakes";
+    const AUTH: bool = true;
+
+    type Response = Vec<StakeInfo>;
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeBalance {
+    pub coin: String,
+    pub staked: Decimal,
+    /// Part of `staked` with a pending unstake request
+    pub scheduled_to_unstake: Decimal,
+    pub lifetime_rewards: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetStakeBalances {}
+
+impl Request for GetStakeBalances {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/staking/balances";
+    const AUTH: bool = true;
+
+    type Response = Vec<StakeBalance>;
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum UnstakeRequestStatus {
+    Pending,
+    Cancelled,
+    Processed,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnstakeRequest {
+    pub id: Id,
+    pub coin: String,
+    pub size: Decimal,
+    pub status: UnstakeRequestStatus,
+    pub created_at: DateTime<Utc>,
+    /// Time the coins become available
+    pub unlock_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetUnstakeRequests {}
+
+impl Request for GetUnstakeRequests {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/staking/unstake_requests";
+    const AUTH: bool = true;
+
+    type Response = Vec<UnstakeRequest>;
+}
+
+/// Requests to unstake `size` of `coin`, available once the unstaking
+/// period is over.
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateUnstakeRequest<'a> {
+    pub coin: &'a str,
+    pub size: Decimal,
+}
+
+impl<'a> CreateUnstakeRequest<'a> {
+    pub fn new(coin: &'a str, size: Decimal) -> Self {
+        Self { coin, size }
+    }
+}
+
+impl Request for CreateUnstakeRequest<'_> {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/staking/unstake_requests";
+    const AUTH: bool = true;
+
+    type Response = UnstakeRequest;
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct CancelUnstakeRequest {
+    #[serde(skip_serializing)]
+    pub request_id: Id,
+}
+
+impl CancelUnstakeRequest {
+    pub fn new(request_id: Id) -> Self {
+        Self { request_id }
+    }
+}
+
+impl Request for CancelUnstakeRequest {
+    const METHOD: Method = Method::DELETE;
+    const PATH: &'static str = "/staking/unstake_requests/{}";
+    const AUTH: bool = true;
+
+    type Response = String;
+
+    fn path(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("/staking/unstake_requests/{}", self.request_id))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakingReward {
+    pub id: Id,
+    pub coin: String,
+    pub size: Decimal,
+    /// e.g. `complete`
+    pub status: String,
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetStakingRewards {}
+
+impl Request for GetStakingRewards {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/staking/staking_rewards";
+    const AUTH: bool = true;
+
+    type Response = Vec<StakingReward>;
+}
+
+/// Stakes `size` of `coin`, e.g. SRM.
+#[derive(Debug, Clone, Serialize)]
+pub struct Stake<'a> {
+    pub coin: &'a str,
+    pub size: Decimal,
+}
+
+impl<'a> Stake<'a> {
+    pub fn new(coin: &'a str, size: Decimal) -> Self {
+        Self { coin, size }
+    }
+}
+
+impl Request for Stake<'_> {
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/srm_stakes/stakes";
+    const AUTH: bool = true;
+
+    type Response = StakeInfo;
+}
@@ -0,0 +1,16 @@
+use super::Request;
+use chrono::{DateTime, Utc};
+use http::Method;
+use serde::Serialize;
+
+/// Current time of the exchange, see `Rest::sync_time`.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GetTime {}
+
+impl Request for GetTime {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/time";
+    const AUTH: bool = false;
+
+    type Response = DateTime<Utc>;
+}
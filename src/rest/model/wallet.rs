@@ -112,6 +112,41 @@ impl Request for GetWalletDepositAddress<'_> {
     }
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Airdrop {
+    pub id: Id,
+    pub coin: Coin,
+    pub size: Decimal,
+    pub time: DateTime<Utc>,
+    pub status: DepositStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAirdrops {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::serialize_as_timestamp"
+    )]
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl Request for GetAirdrops {
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/wallet/airdrops";
+    const AUTH: bool = true;
+
+    type Response = Vec<Airdrop>;
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CoinInfo {
@@ -199,18 +234,64 @@ pub struct RequestWithdrawal {
     pub coin: String,
     pub size: Decimal,
     pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tag: Option<String>,
+    /// Blockchain, e.g. `"erc20"`, for coins on several chains
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub method: Option<String>,
+    /// Withdrawal password, if enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub password: Option<String>,
+    /// 2FA code, if enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub code: Option<String>,
 }
 
+impl RequestWithdrawal {
+    pub fn new(coin: impl Into<String>, size: Decimal, address: impl Into<String>) -> Self {
+        Self {
+            coin: coin.into(),
+            size,
+            address: address.into(),
+            ..Default::default()
+        }
+    }
+
+    #[must_use]
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    #[must_use]
+    pub fn method(mut self, method: impl Into<String>) -> Self {
+        self.method = Some(method.into());
+        self
+    }
+
+    #[must_use]
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    #[must_use]
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+}
+
 impl Request for RequestWithdrawal {
     const METHOD: Method = Method::POST;
     const PATH: &'static str = "/wallet/withdrawals";
     const AUTH: bool = true;
 
     type Response = WalletWithdrawal;
+
+    fn withdrawal(&self) -> Option<&RequestWithdrawal> {
+        Some(self)
+    }
 }
 
 /// Request data for create saved-address.
@@ -0,0 +1,54 @@
+use super::{Id, ModifyOrder, ModifyOrderByClientId, OrderInfo, Rest, Result};
+
+/// Links a modified order to the order that replaced it.
+///
+/// FTX implements modify as cancelling the order and placing a new one, so
+/// the replacement has a new order ID and optionally a new client ID.
+#[derive(Clone, Debug)]
+pub struct OrderReplacement {
+    /// ID of the replaced order, if it was modified by ID
+    pub replaced_id: Option<Id>,
+    /// Client ID of the replaced order, if it was modified by client ID
+    pub replaced_client_id: Option<String>,
+    /// The replacement order
+    pub order: OrderInfo,
+}
+
+impl OrderReplacement {
+    pub fn new_id(&self) -> Id {
+        self.order.id
+    }
+
+    pub fn new_client_id(&self) -> Option<&str> {
+        self.order.client_id.as_deref()
+    }
+}
+
+impl Rest {
+    /// Modifies an order by ID and returns the mapping to its replacement.
+    pub async fn replace_order(&self, req: ModifyOrder<'_>) -> Result<OrderReplacement> {
+        let replaced_id = req.id;
+        let order = self.request(req).await?;
+
+        Ok(OrderReplacement {
+            replaced_id: Some(replaced_id),
+            replaced_client_id: None,
+            order,
+        })
+    }
+
+    /// Modifies an order by client ID and returns the mapping to its replacement.
+    pub async fn replace_order_by_client_id(
+        &self,
+        req: ModifyOrderByClientId<'_>,
+    ) -> Result<OrderReplacement> {
+        let replaced_client_id = req.client_id.to_owned();
+        let order = self.request(req).await?;
+
+        Ok(OrderReplacement {
+            replaced_id: None,
+            replaced_client_id: Some(replaced_client_id),
+            order,
+        })
+    }
+}
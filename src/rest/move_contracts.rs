@@ -0,0 +1,133 @@
+use super::{Future, FutureType, GetFutures, Rest, Result, Symbol};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+
+/// Period covered by a MOVE contract.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MovePeriod {
+    /// e.g. `BTC-MOVE-1025`
+    Daily,
+    /// e.g. `BTC-MOVE-WK-1029`
+    Weekly,
+    /// e.g. `BTC-MOVE-2021Q4`
+    Quarterly,
+}
+
+/// A MOVE contract, which settles to the absolute change of its underlying
+/// index between `strike_time` and `expiry`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveContract {
+    pub name: Symbol,
+    pub underlying: Symbol,
+    pub period: MovePeriod,
+    /// Time the strike is set to the index price
+    pub strike_time: DateTime<Utc>,
+    pub expiry: DateTime<Utc>,
+}
+
+impl MoveContract {
+    /// Parses a MOVE contract name. Daily and weekly names omit the year,
+    /// which is taken to be the one placing the contract closest to
+    /// `reference`.
+    ///
+    /// Names denote the first day of the period, strikes are set at 00:00
+    /// UTC of that day.
+    pub fn parse(name: &str, reference: DateTime<Utc>) -> Option<Self> {
+        let (underlying, rest) = name.split_once("-MOVE-")?;
+        let (period, strike_date) = if let Some(date) = rest.strip_prefix("WK-") {
+            (MovePeriod::Weekly, parse_month_day(date, reference)?)
+        } else if let Some((year, quarter)) = rest.split_once('Q') {
+            let month = match quarter {
+                "1" => 1,
+                "2" => 4,
+                "3" => 7,
+                "4" => 10,
+                _ => return None,
+            };
+            (
+                MovePeriod::Quarterly,
+                NaiveDate::from_ymd_opt(year.parse().ok()?, month, 1)?,
+            )
+        } else {
+            (MovePeriod::Daily, parse_month_day(rest, reference)?)
+        };
+
+        let strike_time = Utc.from_utc_datetime(&strike_date.and_hms_opt(0, 0, 0)?);
+        let expiry = match period {
+            MovePeriod::Daily => strike_time + Duration::days(1),
+            MovePeriod::Weekly => strike_time + Duration::weeks(1),
+            MovePeriod::Quarterly => {
+                let (year, month) = match strike_date.month() {
+                    10 => (strike_date.year() + 1, 1),
+                    month => (strike_date.year(), month + 3),
+                };
+                Utc.from_utc_datetime(
+                    &NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?,
+                )
+            }
+        };
+
+        Some(Self {
+            name: name.to_owned(),
+            underlying: underlying.to_owned(),
+            period,
+            strike_time,
+            expiry,
+        })
+    }
+
+    /// Parses a listed MOVE future, resolving the year from its expiry.
+    pub fn from_future(future: &Future) -> Option<Self> {
+        if future.market_type != FutureType::Move {
+            return None;
+        }
+        Self::parse(&future.name, future.expiry?)
+    }
+
+    /// Returns whether the strike is set and the contract not yet expired.
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.strike_time <= now && now < self.expiry
+    }
+}
+
+fn parse_month_day(date: &str, reference: DateTime<Utc>) -> Option<NaiveDate> {
+    if date.len() != 4 {
+        return None;
+    }
+    let month = date[..2].parse().ok()?;
+    let day = date[2..].parse().ok()?;
+    let reference = reference.date_naive();
+    [reference.year() - 1, reference.year(), reference.year() + 1]
+        .iter()
+        .filter_map(|&year| NaiveDate::from_ymd_opt(year, month, day))
+        .min_by_key(|date| (*date - reference).num_days().abs())
+}
+
+/// Returns the MOVE contract of `underlying` and `period` that is active at
+/// `now`, if any is listed in `futures`.
+pub fn active_move<'a>(
+    futures: impl IntoIterator<Item = &'a Future>,
+    underlying: &str,
+    period: MovePeriod,
+    now: DateTime<Utc>,
+) -> Option<MoveContract> {
+    futures
+        .into_iter()
+        .filter_map(MoveContract::from_future)
+        .find(|contract| {
+            contract.underlying == underlying
+                && contract.period == period
+                && contract.is_active(now)
+        })
+}
+
+impl Rest {
+    /// Returns the currently active MOVE contract of `underlying` and `period`.
+    pub async fn get_active_move(
+        &self,
+        underlying: &str,
+        period: MovePeriod,
+    ) -> Result<Option<MoveContract>> {
+        let futures = self.request(GetFutures {}).await?;
+        Ok(active_move(&futures, underlying, period, Utc::now()))
+    }
+}
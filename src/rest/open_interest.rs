@@ -0,0 +1,101 @@
+use super::{FutureStats, GetFutureStats, Rest, Result, Symbol};
+use chrono::{DateTime, Utc};
+use futures::future::try_join_all;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Open interest of a future at a point in time.
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenInterestSample {
+    pub time: DateTime<Utc>,
+    pub open_interest: Decimal,
+    pub volume: Decimal,
+}
+
+impl OpenInterestSample {
+    pub fn new(time: DateTime<Utc>, stats: &FutureStats) -> Self {
+        Self {
+            time,
+            open_interest: stats.open_interest,
+            volume: stats.volume,
+        }
+    }
+}
+
+/// Builds open interest time series by periodically sampling
+/// `GetFutureStats`, since FTX does not provide historical open interest.
+///
+/// Samples are serializable, so the series can be persisted and restored
+/// with `OpenInterestSampler::with_series`.
+#[derive(Debug, Clone)]
+pub struct OpenInterestSampler {
+    rest: Rest,
+    futures: Vec<Symbol>,
+    series: HashMap<Symbol, Vec<OpenInterestSample>>,
+}
+
+impl OpenInterestSampler {
+    pub fn new(rest: Rest, futures: Vec<Symbol>) -> Self {
+        Self::with_series(rest, futures, HashMap::new())
+    }
+
+    /// Continues previously collected series.
+    pub fn with_series(
+        rest: Rest,
+        futures: Vec<Symbol>,
+        series: HashMap<Symbol, Vec<OpenInterestSample>>,
+    ) -> Self {
+        Self {
+            rest,
+            futures,
+            series,
+        }
+    }
+
+    /// Takes one sample of every future.
+    pub async fn sample(&mut self) -> Result<()> {
+        let rest = &self.rest;
+        let stats = try_join_all(
+            self.futures
+                .iter()
+                .map(|future| rest.request(GetFutureStats::new(future))),
+        )
+        .await?;
+
+        let time = Utc::now();
+        for (future, stats) in self.futures.iter().zip(stats) {
+            self.series
+                .entry(future.clone())
+                .or_default()
+                .push(OpenInterestSample::new(time, &stats));
+        }
+
+        Ok(())
+    }
+
+    /// Takes `count` samples, `interval` apart.
+    pub async fn run(&mut self, interval: Duration, count: usize) -> Result<()> {
+        let mut interval = tokio::time::interval(interval);
+        for _ in 0..count {
+            interval.tick().await;
+            self.sample().await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the samples collected for `future`, ordered from oldest to newest.
+    pub fn series(&self, future: &str) -> &[OpenInterestSample] {
+        self.series
+            .get(future)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns all collected series, keyed by future.
+    pub fn all_series(&self) -> &HashMap<Symbol, Vec<OpenInterestSample>> {
+        &self.series
+    }
+}
@@ -0,0 +1,140 @@
+use super::{
+    GetOptionQuoteRequests, GetOptionTrades, OptionContract, OptionTrade, OptionType, Rest, Result,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// Option greeks. FTX does not publish them, they are left for the caller
+/// to fill in from its own pricing model.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Greeks {
+    pub implied_volatility: Option<Decimal>,
+    pub delta: Option<Decimal>,
+    pub gamma: Option<Decimal>,
+    pub theta: Option<Decimal>,
+    pub vega: Option<Decimal>,
+}
+
+/// A single option of a chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainOption {
+    pub contract: OptionContract,
+    pub last_price: Option<Decimal>,
+    pub last_trade_time: Option<DateTime<Utc>>,
+    pub greeks: Greeks,
+}
+
+/// The call and put of a strike.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Strike {
+    pub call: Option<ChainOption>,
+    pub put: Option<ChainOption>,
+}
+
+impl Strike {
+    pub fn get(&self, r#type: OptionType) -> Option<&ChainOption> {
+        match r#type {
+            OptionType::Call => self.call.as_ref(),
+            OptionType::Put => self.put.as_ref(),
+        }
+    }
+
+    fn get_mut(&mut self, r#type: OptionType) -> &mut Option<ChainOption> {
+        match r#type {
+            OptionType::Call => &mut self.call,
+            OptionType::Put => &mut self.put,
+        }
+    }
+}
+
+/// Strikes of an expiry, ordered by strike price.
+pub type Expiry = BTreeMap<Decimal, Strike>;
+
+/// Options grouped by underlying, expiry and strike.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OptionsChain {
+    underlyings: BTreeMap<String, BTreeMap<DateTime<Utc>, Expiry>>,
+}
+
+impl OptionsChain {
+    /// Builds a chain of the given contracts.
+    pub fn new(contracts: impl IntoIterator<Item = OptionContract>) -> Self {
+        let mut chain = Self::default();
+        for contract in contracts {
+            chain.entry(contract);
+        }
+        chain
+    }
+
+    fn entry(&mut self, contract: OptionContract) -> &mut ChainOption {
+        let strike = self
+            .underlyings
+            .entry(contract.underlying.clone())
+            .or_default()
+            .entry(contract.expiry)
+            .or_default()
+            .entry(contract.strike)
+            .or_default();
+        strike.get_mut(contract.r#type).get_or_insert(ChainOption {
+            contract,
+            last_price: None,
+            last_trade_time: None,
+            greeks: Greeks::default(),
+        })
+    }
+
+    /// Records the prices of `trades`, adding their contracts to the chain.
+    pub fn apply_trades<'a>(&mut self, trades: impl IntoIterator<Item = &'a OptionTrade>) {
+        for trade in trades {
+            let option = self.entry(trade.option.clone());
+            if option.last_trade_time.is_none_or(|time| time <= trade.time) {
+                option.last_price = Some(trade.price);
+                option.last_trade_time = Some(trade.time);
+            }
+        }
+    }
+
+    /// Returns the underlyings in the chain.
+    pub fn underlyings(&self) -> impl Iterator<Item = &str> {
+        self.underlyings.keys().map(String::as_str)
+    }
+
+    /// Returns the expiries of `underlying`, ordered from nearest to furthest.
+    pub fn expiries(&self, underlying: &str) -> impl Iterator<Item = (&DateTime<Utc>, &Expiry)> {
+        self.underlyings.get(underlying).into_iter().flatten()
+    }
+
+    /// Returns the strikes of `underlying` expiring at `expiry`.
+    pub fn strikes(&self, underlying: &str, expiry: DateTime<Utc>) -> Option<&Expiry> {
+        self.underlyings.get(underlying)?.get(&expiry)
+    }
+
+    pub fn get(&self, contract: &OptionContract) -> Option<&ChainOption> {
+        self.strikes(&contract.underlying, contract.expiry)?
+            .get(&contract.strike)?
+            .get(contract.r#type)
+    }
+
+    pub fn get_mut(&mut self, contract: &OptionContract) -> Option<&mut ChainOption> {
+        self.underlyings
+            .get_mut(&contract.underlying)?
+            .get_mut(&contract.expiry)?
+            .get_mut(&contract.strike)?
+            .get_mut(contract.r#type)
+            .as_mut()
+    }
+}
+
+impl Rest {
+    /// Builds an options chain from the open quote requests and recent
+    /// trades, since FTX does not list option contracts directly.
+    pub async fn get_options_chain(&self) -> Result<OptionsChain> {
+        let requests = self.request(GetOptionQuoteRequests {}).await?;
+        let trades = self.request(GetOptionTrades::default()).await?;
+
+        let mut chain = OptionsChain::new(requests.into_iter().map(|request| request.option));
+        chain.apply_trades(&trades);
+        Ok(chain)
+    }
+}
@@ -0,0 +1,216 @@
+use super::{
+    Candle, Fill, FundingPayment, FundingRate, GetFills, GetFundingPayments, GetFundingRates,
+    GetHistoricalPrices, GetOrderHistory, GetTriggerOrderHistory, Id, OrderInfo, Request, Rest,
+    Result, Symbol, TriggerOrderInfo,
+};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream, TryStreamExt};
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// Maximum number of rows returned by a single `GetFundingRates` request.
+const FUNDING_RATES_PAGE_SIZE: usize = 500;
+
+/// Request for the rows of a time range, newest first, which `Rest::paginate`
+/// continues with requests ending at the oldest row received so far.
+pub trait Paginated: Request + Clone {
+    /// Sets the end of the range of the next page.
+    fn set_end_time(&mut self, end_time: DateTime<Utc>);
+
+    /// Rows of a full page, to detect further pages of endpoints which do
+    /// not send `hasMoreData`.
+    fn limit(&self) -> Option<usize>;
+
+    /// Whether a page of `rows` rows without `hasMoreData` may be followed
+    /// by another, by default if it is full.
+    fn more_pages(&self, rows: usize) -> bool {
+        self.limit() == Some(rows)
+    }
+}
+
+/// Row of a paginated response.
+pub trait PaginatedRow {
+    /// Identifies the row to drop it from overlapping pages, e.g. its ID.
+    type Key: Eq + Hash;
+
+    fn key(&self) -> Self::Key;
+    fn time(&self) -> DateTime<Utc>;
+}
+
+impl Paginated for GetOrderHistory<'_> {
+    fn set_end_time(&mut self, end_time: DateTime<Utc>) {
+        self.end_time = Some(end_time);
+    }
+
+    fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+}
+
+impl PaginatedRow for OrderInfo {
+    type Key = Id;
+
+    fn key(&self) -> Id {
+        self.id
+    }
+
+    fn time(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+}
+
+impl Paginated for GetFills<'_> {
+    fn set_end_time(&mut self, end_time: DateTime<Utc>) {
+        self.end_time = Some(end_time);
+    }
+
+    fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+}
+
+impl PaginatedRow for Fill {
+    type Key = Id;
+
+    fn key(&self) -> Id {
+        self.id
+    }
+
+    fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
+}
+
+impl Paginated for GetTriggerOrderHistory<'_> {
+    fn set_end_time(&mut self, end_time: DateTime<Utc>) {
+        self.end_time = Some(end_time);
+    }
+
+    fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+}
+
+impl PaginatedRow for TriggerOrderInfo {
+    type Key = Id;
+
+    fn key(&self) -> Id {
+        self.id
+    }
+
+    fn time(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+}
+
+impl Paginated for GetFundingRates {
+    fn set_end_time(&mut self, end_time: DateTime<Utc>) {
+        self.end_time = Some(end_time);
+    }
+
+    fn limit(&self) -> Option<usize> {
+        Some(FUNDING_RATES_PAGE_SIZE)
+    }
+}
+
+impl PaginatedRow for FundingRate {
+    type Key = (Symbol, DateTime<Utc>);
+
+    fn key(&self) -> Self::Key {
+        (self.future.clone(), self.time)
+    }
+
+    fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
+}
+
+impl Paginated for GetHistoricalPrices<'_> {
+    fn set_end_time(&mut self, end_time: DateTime<Utc>) {
+        self.end_time = Some(end_time);
+    }
+
+    fn limit(&self) -> Option<usize> {
+        self.limit.map(|limit| limit as usize)
+    }
+}
+
+impl PaginatedRow for Candle {
+    type Key = DateTime<Utc>;
+
+    fn key(&self) -> Self::Key {
+        self.start_time
+    }
+
+    fn time(&self) -> DateTime<Utc> {
+        self.start_time
+    }
+}
+
+impl Paginated for GetFundingPayments<'_> {
+    fn set_end_time(&mut self, end_time: DateTime<Utc>) {
+        self.end_time = Some(end_time);
+    }
+
+    fn limit(&self) -> Option<usize> {
+        None
+    }
+
+    /// The page size is not documented, so pages are requested until one
+    /// has no new payments.
+    fn more_pages(&self, rows: usize) -> bool {
+        rows > 0
+    }
+}
+
+impl PaginatedRow for FundingPayment {
+    type Key = Id;
+
+    fn key(&self) -> Id {
+        self.id
+    }
+
+    fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
+}
+
+impl Rest {
+    /// Streams all rows of `request`, newest first, sending requests for
+    /// further pages as long as FTX reports more data.
+    ///
+    /// Pages overlap at the timestamp of the oldest row of the previous
+    /// page, so rows are deduplicated by `PaginatedRow::key`.
+    pub fn paginate<'a, R, T>(&'a self, request: R) -> impl Stream<Item = Result<T>> + 'a
+    where
+        R: Paginated<Response = Vec<T>> + 'a,
+        T: PaginatedRow + 'a,
+    {
+        stream::try_unfold(Some((request, HashSet::new())), move |state| async move {
+            let (mut request, mut seen) = match state {
+                Some(state) => state,
+                None => return Result::Ok(None),
+            };
+            let page = self.request_envelope(request.clone()).await?;
+            let page_len = page.result.len();
+            let rows: Vec<T> = page
+                .result
+                .into_iter()
+                .filter(|row| seen.insert(row.key()))
+                .collect();
+
+            let has_more_data = page
+                .has_more_data
+                .unwrap_or_else(|| request.more_pages(page_len));
+            let next = match rows.iter().map(PaginatedRow::time).min() {
+                Some(oldest) if has_more_data => {
+                    request.set_end_time(oldest);
+                    Some((request, seen))
+                }
+                _ => None,
+            };
+            Ok(Some((stream::iter(rows.into_iter().map(Ok)), next)))
+        })
+        .try_flatten()
+    }
+}
@@ -0,0 +1,144 @@
+use super::{
+    Error, GetHistoricalBalances, GetLeveragedTokenCreations, GetLeveragedTokenRedemptions,
+    HistoricalBalances, HistoricalBalancesStatus, Id, LeveragedTokenCreation,
+    LeveragedTokenRedemption, Request, Rest, Result,
+};
+use std::future::Future;
+use std::time::Duration;
+use tokio::time;
+
+/// State of a job that FTX processes asynchronously.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PollStatus {
+    /// Still being processed, poll again later
+    Pending,
+    /// Processed successfully
+    Complete,
+    /// Processing failed for the given reason
+    Failed(String),
+}
+
+/// A request whose response reports the state of an asynchronously
+/// processed job, e.g. a historical balances report. Leveraged token
+/// creations and redemptions are only listed, see
+/// `Rest::poll_leveraged_token_creation`.
+pub trait Pollable: Request + Clone {
+    fn status(response: &Self::Response) -> PollStatus;
+}
+
+impl Pollable for GetHistoricalBalances {
+    fn status(response: &HistoricalBalances) -> PollStatus {
+        match response.status {
+            _ if response.error => PollStatus::Failed(format!(
+                "historical balances request {} failed",
+                response.id
+            )),
+            HistoricalBalancesStatus::Complete => PollStatus::Complete,
+            HistoricalBalancesStatus::Requested | HistoricalBalancesStatus::Processing => {
+                PollStatus::Pending
+            }
+        }
+    }
+}
+
+impl Rest {
+    /// Repeats `req` every `interval` until the job it reports on is
+    /// complete. Returns `Error::JobFailed` if the job failed and
+    /// `Error::Timeout` if it did not finish within `timeout`.
+    pub async fn poll_until_complete<R: Pollable>(
+        &self,
+        req: R,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<R::Response> {
+        poll_until(interval, timeout, R::status, || self.request(req.clone())).await
+    }
+
+    /// Polls the leveraged token creation `id` until it is no longer
+    /// pending. FTX has no endpoint for a single creation, so it is looked
+    /// up in `GetLeveragedTokenCreations`; `Error::JobFailed` is returned if
+    /// it is not listed.
+    pub async fn poll_leveraged_token_creation(
+        &self,
+        id: Id,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<LeveragedTokenCreation> {
+        self.poll_listed(
+            GetLeveragedTokenCreations {},
+            |creation| (creation.id == id).then_some(creation.pending),
+            interval,
+            timeout,
+        )
+        .await
+    }
+
+    /// Like `poll_leveraged_token_creation`, for the redemption `id`.
+    pub async fn poll_leveraged_token_redemption(
+        &self,
+        id: Id,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<LeveragedTokenRedemption> {
+        self.poll_listed(
+            GetLeveragedTokenRedemptions {},
+            |redemption| (redemption.id == id).then_some(redemption.pending),
+            interval,
+            timeout,
+        )
+        .await
+    }
+
+    /// Polls the job listed by `req` for which `pending` returns `Some`,
+    /// until it returns `Some(false)`.
+    async fn poll_listed<R, T>(
+        &self,
+        req: R,
+        pending: impl Fn(&T) -> Option<bool>,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<T>
+    where
+        R: Request<Response = Vec<T>> + Clone,
+    {
+        let status = |job: &Option<T>| match job.as_ref().and_then(&pending) {
+            Some(true) => PollStatus::Pending,
+            Some(false) => PollStatus::Complete,
+            None => PollStatus::Failed(format!("job not listed by {}", R::PATH)),
+        };
+        let fetch = || async {
+            let jobs = self.request(req.clone()).await?;
+            Ok(jobs.into_iter().find(|job| pending(job).is_some()))
+        };
+        let job = poll_until(interval, timeout, status, fetch).await?;
+        Ok(job.expect("only listed jobs are complete"))
+    }
+}
+
+pub(crate) async fn poll_until<T, F, Fut>(
+    interval: Duration,
+    timeout: Duration,
+    status: impl Fn(&T) -> PollStatus,
+    mut fetch: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let poll = async {
+        let mut interval = time::interval(interval);
+        loop {
+            interval.tick().await;
+            let response = fetch().await?;
+            match status(&response) {
+                PollStatus::Pending => {}
+                PollStatus::Complete => return Ok(response),
+                PollStatus::Failed(reason) => return Err(Error::JobFailed(reason)),
+            }
+        }
+    };
+
+    time::timeout(timeout, poll)
+        .await
+        .map_err(|_| Error::Timeout)?
+}
@@ -0,0 +1,150 @@
+use super::{Fill, Id, Liquidity, OrderInfo, OrderStatus, OrderType, Side};
+use std::collections::HashMap;
+
+/// Client-side filter over orders, e.g. the result of `GetOrderHistory` or
+/// `GetOpenOrders`. Criteria left as `None` match every order.
+#[derive(Clone, Debug, Default)]
+pub struct OrderFilter<'a> {
+    pub market: Option<&'a str>,
+    pub side: Option<Side>,
+    pub status: Option<OrderStatus>,
+    pub r#type: Option<OrderType>,
+    pub client_id_prefix: Option<&'a str>,
+    pub reduce_only: Option<bool>,
+}
+
+impl<'a> OrderFilter<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn market(mut self, market: &'a str) -> Self {
+        self.market = Some(market);
+        self
+    }
+
+    #[must_use]
+    pub fn side(mut self, side: Side) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    #[must_use]
+    pub fn status(mut self, status: OrderStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    #[must_use]
+    pub fn order_type(mut self, r#type: OrderType) -> Self {
+        self.r#type = Some(r#type);
+        self
+    }
+
+    #[must_use]
+    pub fn client_id_prefix(mut self, prefix: &'a str) -> Self {
+        self.client_id_prefix = Some(prefix);
+        self
+    }
+
+    #[must_use]
+    pub fn reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = Some(reduce_only);
+        self
+    }
+
+    pub fn matches(&self, order: &OrderInfo) -> bool {
+        self.market.is_none_or(|market| order.market == market)
+            && self.side.is_none_or(|side| order.side == side)
+            && self.status.is_none_or(|status| order.status == status)
+            && self.r#type.is_none_or(|r#type| order.r#type == r#type)
+            && self.client_id_prefix.is_none_or(|prefix| {
+                order
+                    .client_id
+                    .as_deref()
+                    .is_some_and(|client_id| client_id.starts_with(prefix))
+            })
+            // FTX omits reduceOnly for some order types, treat that as false
+            && self.reduce_only.is_none_or(|reduce_only| {
+                order.reduce_only.unwrap_or_default() == reduce_only
+            })
+    }
+
+    pub fn apply<'o>(&self, orders: &'o [OrderInfo]) -> Vec<&'o OrderInfo> {
+        orders.iter().filter(|order| self.matches(order)).collect()
+    }
+}
+
+/// Client-side filter over fills, e.g. the result of `GetFills`.
+/// Criteria left as `None` match every fill.
+#[derive(Clone, Debug, Default)]
+pub struct FillFilter<'a> {
+    pub market: Option<&'a str>,
+    pub side: Option<Side>,
+    pub order_id: Option<Id>,
+    pub liquidity: Option<Liquidity>,
+}
+
+impl<'a> FillFilter<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn market(mut self, market: &'a str) -> Self {
+        self.market = Some(market);
+        self
+    }
+
+    #[must_use]
+    pub fn side(mut self, side: Side) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    #[must_use]
+    pub fn order_id(mut self, order_id: Id) -> Self {
+        self.order_id = Some(order_id);
+        self
+    }
+
+    #[must_use]
+    pub fn liquidity(mut self, liquidity: Liquidity) -> Self {
+        self.liquidity = Some(liquidity);
+        self
+    }
+
+    pub fn matches(&self, fill: &Fill) -> bool {
+        self.market
+            .is_none_or(|market| fill.market.as_deref() == Some(market))
+            && self.side.is_none_or(|side| fill.side == side)
+            && self
+                .order_id
+                .is_none_or(|order_id| fill.order_id == Some(order_id))
+            && self
+                .liquidity
+                .is_none_or(|liquidity| fill.liquidity == liquidity)
+    }
+
+    pub fn apply<'f>(&self, fills: &'f [Fill]) -> Vec<&'f Fill> {
+        fills.iter().filter(|fill| self.matches(fill)).collect()
+    }
+}
+
+/// Indexes orders by their client ID, skipping orders without one.
+/// If a client ID was reused, the most recently created order wins.
+pub fn index_by_client_id(orders: &[OrderInfo]) -> HashMap<&str, &OrderInfo> {
+    let mut index: HashMap<&str, &OrderInfo> = HashMap::new();
+    for order in orders {
+        if let Some(client_id) = order.client_id.as_deref() {
+            match index.get(client_id) {
+                Some(existing) if existing.created_at > order.created_at => {}
+                _ => {
+                    index.insert(client_id, order);
+                }
+            }
+        }
+    }
+    index
+}
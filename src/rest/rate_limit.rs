@@ -0,0 +1,376 @@
+//! Request budgets shared by clients, see `Options::rate_limit`. The Redis
+//! store for clients in several processes is built with the `redis`
+//! feature.
+
+#[cfg(feature = "redis")]
+use super::Error;
+use super::{HttpResponse, Rest, Result};
+use async_trait::async_trait;
+use reqwest::{Method, StatusCode};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::time;
+
+/// Length of the windows budgets are counted in.
+const WINDOW: Duration = Duration::from_secs(1);
+/// Pause after the first 429 response, doubled after each further one.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Counters shared by the clients which coordinate a budget.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync + fmt::Debug {
+    /// Increments the counter `key`, which is dropped `ttl` after it was
+    /// created, and returns its new value.
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u64>;
+}
+
+#[async_trait]
+impl<T: RateLimitStore + ?Sized> RateLimitStore for Arc<T> {
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u64> {
+        (**self).increment(key, ttl).await
+    }
+}
+
+/// Counters in memory, shared by the clients of one process.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    counters: Mutex<HashMap<String, (u64, Instant)>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn increment(&self, key: &str, ttl: Duration) -> Result<u64> {
+        let now = Instant::now();
+        let mut counters = self.counters.lock().unwrap();
+        counters.retain(|_, (_, expiry)| *expiry > now);
+        let (count, _) = counters.entry(key.to_owned()).or_insert((0, now + ttl));
+        *count += 1;
+        Ok(*count)
+    }
+}
+
+/// Remaining budget of a category of requests in the current window.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Budget {
+    /// `"requests"`, `"orders"` or the path prefix of an endpoint budget
+    pub category: String,
+    pub limit: u32,
+    pub remaining: u32,
+}
+
+#[derive(Debug, Default)]
+struct Usage {
+    /// Window and count last seen per category
+    counts: HashMap<String, (u128, u64)>,
+    backoff: Option<Duration>,
+    paused_until: Option<Instant>,
+}
+
+/// Budget of requests per second, and optionally of orders per second and
+/// of requests to specific endpoints, shared by all clients using the same
+/// store and namespace.
+///
+/// FTX limits requests per account, so processes sharing an API key should
+/// share a `RedisStore` and namespace to stay below the limit together.
+/// Requests over budget wait for the next one second window, which is
+/// aligned to the system clock so it is the same for all processes.
+///
+/// Requests rejected with 429 anyway, e.g. because of other clients of the
+/// account, pause all requests using this budget with an exponential
+/// backoff and are retried up to `max_retries` times. `Options::retry` does
+/// not retry them again.
+#[derive(Clone, Debug)]
+pub struct RateLimit {
+    requests_per_second: u32,
+    orders_per_second: Option<u32>,
+    endpoints: Vec<(String, u32)>,
+    max_retries: u32,
+    namespace: String,
+    store: Arc<dyn RateLimitStore>,
+    usage: Arc<Mutex<Usage>>,
+}
+
+impl RateLimit {
+    /// Budget of `requests_per_second` in memory.
+    pub fn new(requests_per_second: u32) -> Self {
+        Self {
+            requests_per_second,
+            orders_per_second: None,
+            endpoints: Vec::new(),
+            max_retries: 3,
+            namespace: "ftx".to_owned(),
+            store: Arc::new(MemoryStore::new()),
+            usage: Default::default(),
+        }
+    }
+
+    /// Additionally limits orders, i.e. requests placing, modifying or
+    /// cancelling orders.
+    #[must_use]
+    pub fn orders_per_second(mut self, orders_per_second: u32) -> Self {
+        self.orders_per_second = Some(orders_per_second);
+        self
+    }
+
+    /// Additionally limits requests to paths starting with `path_prefix`,
+    /// e.g. `"/wallet/withdrawals"`.
+    #[must_use]
+    pub fn endpoint(mut self, path_prefix: impl Into<String>, requests_per_second: u32) -> Self {
+        self.endpoints
+            .push((path_prefix.into(), requests_per_second));
+        self
+    }
+
+    /// Number of times requests rejected with 429 are retried, 3 by
+    /// default.
+    #[must_use]
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Prefix of the counters, e.g. the account, to keep the budgets of
+    /// different API keys apart in a shared store.
+    #[must_use]
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    #[must_use]
+    pub fn store(mut self, store: impl RateLimitStore + 'static) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// Remaining budget of every category in the current window, as of the
+    /// last request sent with this budget. Requests of other clients
+    /// sharing the store since then are not included.
+    pub fn remaining(&self) -> Vec<Budget> {
+        let window = current_window();
+        let usage = self.usage.lock().unwrap();
+        self.categories()
+            .map(|(category, limit)| {
+                let used = match usage.counts.get(category) {
+                    Some((seen, count)) if *seen == window => *count,
+                    _ => 0,
+                };
+                Budget {
+                    category: category.to_owned(),
+                    limit,
+                    remaining: u64::from(limit).saturating_sub(used) as u32,
+                }
+            })
+            .collect()
+    }
+
+    /// Time left until requests are sent again after a 429 response.
+    pub fn backoff(&self) -> Option<Duration> {
+        let paused_until = self.usage.lock().unwrap().paused_until?;
+        Some(paused_until.saturating_duration_since(Instant::now())).filter(|d| !d.is_zero())
+    }
+
+    fn categories(&self) -> impl Iterator<Item = (&str, u32)> {
+        std::iter::once(("requests", self.requests_per_second))
+            .chain(self.orders_per_second.map(|limit| ("orders", limit)))
+            .chain(
+                self.endpoints
+                    .iter()
+                    .map(|(prefix, limit)| (prefix.as_str(), *limit)),
+            )
+    }
+
+    /// Waits until the budget allows a request, counting it.
+    pub(super) async fn acquire(&self, method: &Method, path: &str) -> Result<()> {
+        if let Some(backoff) = self.backoff() {
+            time::sleep(backoff).await;
+        }
+        let order = is_order(method, path);
+        for (category, limit) in self.categories() {
+            let applies = match category {
+                "requests" => true,
+                "orders" => order,
+                prefix => path.starts_with(prefix),
+            };
+            if applies {
+                self.acquire_bucket(category, limit).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn acquire_bucket(&self, bucket: &str, limit: u32) -> Result<()> {
+        loop {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+            let window = now.as_millis() / WINDOW.as_millis();
+            let key = format!("{}:{}:{}", self.namespace, bucket, window);
+            // Kept a little longer than the window for clocks running late
+            let count = self.store.increment(&key, WINDOW * 2).await?;
+            self.usage
+                .lock()
+                .unwrap()
+                .counts
+                .insert(bucket.to_owned(), (window, count));
+            if count <= u64::from(limit) {
+                return Ok(());
+            }
+            let next_window = Duration::from_millis(((window + 1) * WINDOW.as_millis()) as u64);
+            log::debug!("{} budget of {} exhausted", bucket, self.namespace);
+            time::sleep(next_window.saturating_sub(now)).await;
+        }
+    }
+
+    /// Pauses requests after a 429 response, doubling the pause if the
+    /// previous one did not help, for at least `retry_after` if the
+    /// response asked for it.
+    fn throttled(&self, retry_after: Option<Duration>) {
+        let mut usage = self.usage.lock().unwrap();
+        let backoff = usage
+            .backoff
+            .map_or(INITIAL_BACKOFF, |backoff| (backoff * 2).min(MAX_BACKOFF));
+        usage.backoff = Some(backoff);
+        usage.paused_until = Some(Instant::now() + retry_after.unwrap_or_default().max(backoff));
+    }
+
+    fn succeeded(&self) {
+        self.usage.lock().unwrap().backoff = None;
+    }
+}
+
+fn current_window() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+        / WINDOW.as_millis()
+}
+
+/// Whether the request places, modifies or cancels orders.
+fn is_order(method: &Method, path: &str) -> bool {
+    *method != Method::GET
+        && (path.starts_with("/orders") || path.starts_with("/conditional_orders"))
+}
+
+impl Rest {
+    /// The budget configured with `Options::rate_limit`, e.g. to check the
+    /// remaining budget before sending a burst of orders.
+    pub fn rate_limit(&self) -> Option<&RateLimit> {
+        self.rate_limit.as_ref()
+    }
+
+    /// Sends a request to `url` within the budget, retrying it after 429
+    /// responses, which FTX sends for requests it did not process.
+    pub(super) async fn send_within_budget(
+        &self,
+        method: Method,
+        url: String,
+        path: &str,
+        body: Option<String>,
+        auth: bool,
+    ) -> Result<HttpResponse> {
+        let rate_limit = match &self.rate_limit {
+            Some(rate_limit) => rate_limit,
+            None => return self.send_to(method, url, path, body, auth).await,
+        };
+        let mut retries = 0;
+        loop {
+            rate_limit.acquire(&method, path).await?;
+            let response = self
+                .send_to(method.clone(), url.clone(), path, body.clone(), auth)
+                .await;
+            match &response {
+                Ok(throttled) if throttled.status == StatusCode::TOO_MANY_REQUESTS => {
+                    rate_limit.throttled(throttled.retry_after);
+                    if retries == rate_limit.max_retries {
+                        return response;
+                    }
+                    retries += 1;
+                    log::warn!("{} {} rate limited, retrying", method, path);
+                }
+                Ok(_) => {
+                    rate_limit.succeeded();
+                    return response;
+                }
+                Err(_) => return response,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use redis::RedisStore;
+
+#[cfg(feature = "redis")]
+mod redis {
+    use super::*;
+    use ::redis::aio::ConnectionManager;
+    use ::redis::{Client, Script};
+    use tokio::sync::OnceCell;
+
+    /// Increments the counter and sets its expiry when it is created.
+    const INCREMENT: &str = "local count = redis.call('INCR', KEYS[1]) \
+        if count == 1 then redis.call('PEXPIRE', KEYS[1], ARGV[1]) end \
+        return count";
+
+    /// Counters in Redis, shared by clients in any number of processes.
+    ///
+    /// Connects on first use and reconnects after errors.
+    pub struct RedisStore {
+        client: Client,
+        connection: OnceCell<ConnectionManager>,
+        script: Script,
+    }
+
+    impl RedisStore {
+        /// Store on the Redis server at `url`, e.g.
+        /// `"redis://:password@127.0.0.1:6379"`. Fails with
+        /// `Error::Config` if the URL is invalid.
+        pub fn open(url: &str) -> Result<Self> {
+            let client = Client::open(url)
+                .map_err(|e| Error::Config(format!("invalid redis url {}: {}", url, e)))?;
+            Ok(Self {
+                client,
+                connection: OnceCell::new(),
+                script: Script::new(INCREMENT),
+            })
+        }
+    }
+
+    impl fmt::Debug for RedisStore {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("RedisStore")
+                .field("addr", &self.client.get_connection_info().addr)
+                .finish()
+        }
+    }
+
+    #[async_trait]
+    impl RateLimitStore for RedisStore {
+        async fn increment(&self, key: &str, ttl: Duration) -> Result<u64> {
+            let connection = self
+                .connection
+                .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+                .await
+                .map_err(redis_error)?;
+            self.script
+                .key(key)
+                .arg(ttl.as_millis() as u64)
+                .invoke_async(&mut connection.clone())
+                .await
+                .map_err(redis_error)
+        }
+    }
+
+    fn redis_error(error: ::redis::RedisError) -> Error {
+        Error::Io(std::io::Error::other(format!("redis: {}", error)))
+    }
+}
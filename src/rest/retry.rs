@@ -0,0 +1,200 @@
+use super::{failover, CancelOrderByClientId, HttpResponse, Request, Rest, Result};
+use reqwest::{Method, StatusCode};
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Decides which requests are sent again after transient failures, and
+/// when, see `Options::retry`.
+pub trait RetryPolicy: Send + Sync + fmt::Debug {
+    /// Whether a request to `path`, without its query, may be sent again,
+    /// i.e. sending it twice has the same effect as sending it once.
+    fn is_retryable(&self, method: &Method, path: &str) -> bool;
+
+    /// Delay before retry number `attempt`, starting at 1, or `None` to
+    /// give up.
+    fn backoff(&self, attempt: u32) -> Option<Duration>;
+}
+
+impl<T: RetryPolicy + ?Sized> RetryPolicy for Arc<T> {
+    fn is_retryable(&self, method: &Method, path: &str) -> bool {
+        (**self).is_retryable(method, path)
+    }
+
+    fn backoff(&self, attempt: u32) -> Option<Duration> {
+        (**self).backoff(attempt)
+    }
+}
+
+/// Retries GET requests and cancellations by client ID with exponentially
+/// growing delays, each shortened by a random amount of up to half so
+/// clients failing together do not retry together.
+#[derive(Clone, Debug)]
+pub struct ExponentialBackoff {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    /// Endpoints other than GET requests which are retried
+    endpoints: Vec<(Method, &'static str)>,
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            endpoints: vec![(CancelOrderByClientId::METHOD, CancelOrderByClientId::PATH)],
+        }
+    }
+}
+
+impl ExponentialBackoff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Delay before the first retry, doubled for every further one.
+    #[must_use]
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    #[must_use]
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Also retries requests of type `R`, which must be safe to send
+    /// twice.
+    #[must_use]
+    pub fn retry<R: Request>(mut self) -> Self {
+        self.endpoints.push((R::METHOD, R::PATH));
+        self
+    }
+}
+
+impl RetryPolicy for ExponentialBackoff {
+    fn is_retryable(&self, method: &Method, path: &str) -> bool {
+        *method == Method::GET
+            || self
+                .endpoints
+                .iter()
+                .any(|(m, template)| m == method && path_matches(template, path))
+    }
+
+    fn backoff(&self, attempt: u32) -> Option<Duration> {
+        if attempt > self.max_retries {
+            return None;
+        }
+        let backoff = self
+            .initial_backoff
+            .checked_mul(1 << (attempt - 1).min(16))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+        Some(backoff.mul_f64(1.0 - random_fraction() / 2.0))
+    }
+}
+
+/// Whether `path` matches `Request::PATH` `template`, where `{}` stands for
+/// any segment.
+pub(super) fn path_matches(template: &str, path: &str) -> bool {
+    let mut segments = path.split('/');
+    template.split('/').all(|expected| {
+        segments
+            .next()
+            .is_some_and(|segment| expected == "{}" || expected == segment)
+    }) && segments.next().is_none()
+}
+
+/// Random number in [0, 1).
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Whether `status` is sent for requests the exchange could not process at
+/// the moment.
+fn transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+impl Rest {
+    /// Sends a request for `path`, signed if `auth` is set, and returns the
+    /// response, retrying transient failures of requests the retry
+    /// policy allows to send again.
+    pub(super) async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<String>,
+        auth: bool,
+        optimized_access: bool,
+    ) -> Result<HttpResponse> {
+        let endpoint = path.split('?').next().unwrap_or(path);
+        let policy = match &self.retry_policy {
+            Some(policy) if policy.is_retryable(&method, endpoint) => policy,
+            _ => {
+                return self
+                    .failover_send(method, path, body, auth, optimized_access)
+                    .await
+            }
+        };
+        let mut attempt = 0;
+        loop {
+            let response = self
+                .failover_send(method.clone(), path, body.clone(), auth, optimized_access)
+                .await;
+            let retry_after = match &response {
+                // Already retried within the budget of `Options::rate_limit`
+                Ok(throttled)
+                    if throttled.status == StatusCode::TOO_MANY_REQUESTS
+                        && self.rate_limit.is_some() =>
+                {
+                    return response
+                }
+                Ok(response) if transient_status(response.status) => response.retry_after,
+                // Unreachable endpoints, as failover decides them
+                Err(e) if failover::unreachable(e).is_some() => None,
+                _ => return response,
+            };
+            attempt += 1;
+            let backoff = match policy.backoff(attempt) {
+                Some(backoff) => {
+                    retry_after.map_or(backoff, |retry_after| retry_after.max(backoff))
+                }
+                None => return response,
+            };
+            log::debug!(
+                "retrying {} {} in {:?} (attempt {})",
+                method,
+                path,
+                backoff,
+                attempt
+            );
+            tokio::time::sleep(backoff).await;
+        }
+    }
+}
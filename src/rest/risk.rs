@@ -0,0 +1,77 @@
+use super::{Error, PlaceOrder, Result, Symbol};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+
+/// Client-side limits orders are checked against before they are sent.
+#[derive(Debug, Clone, Default)]
+pub struct RiskLimits {
+    /// Markets orders may be placed in, all if `None`
+    pub allowed_markets: Option<HashSet<Symbol>>,
+    /// Maximum order size per market
+    pub max_order_size: HashMap<Symbol, Decimal>,
+    /// Maximum notional value of a single order
+    pub max_order_notional: Option<Decimal>,
+}
+
+impl RiskLimits {
+    #[must_use]
+    pub fn allow_market(mut self, market: &str) -> Self {
+        self.allowed_markets
+            .get_or_insert_with(HashSet::new)
+            .insert(market.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn max_order_size(mut self, market: &str, size: Decimal) -> Self {
+        self.max_order_size.insert(market.to_owned(), size);
+        self
+    }
+
+    #[must_use]
+    pub fn max_order_notional(mut self, notional: Decimal) -> Self {
+        self.max_order_notional = Some(notional);
+        self
+    }
+
+    /// Checks `order` against the limits. The notional value of market
+    /// orders is computed from `reference_price`. Without it, market orders
+    /// are rejected if `max_order_notional` is set.
+    pub fn check(&self, order: &PlaceOrder, reference_price: Option<Decimal>) -> Result<()> {
+        if let Some(allowed) = &self.allowed_markets {
+            if !allowed.contains(order.market) {
+                return Err(Error::RiskLimitExceeded(format!(
+                    "market {} is not allowed",
+                    order.market
+                )));
+            }
+        }
+
+        if let Some(max_size) = self.max_order_size.get(order.market) {
+            if order.size > *max_size {
+                return Err(Error::RiskLimitExceeded(format!(
+                    "size {} exceeds the maximum of {} in {}",
+                    order.size, max_size, order.market
+                )));
+            }
+        }
+
+        if let Some(max_notional) = self.max_order_notional {
+            let price = order.price.or(reference_price).ok_or_else(|| {
+                Error::RiskLimitExceeded(format!(
+                    "no price to check the notional of the order in {}",
+                    order.market
+                ))
+            })?;
+            let notional = order.size * price;
+            if notional > max_notional {
+                return Err(Error::RiskLimitExceeded(format!(
+                    "notional {} exceeds the maximum of {}",
+                    notional, max_notional
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+use super::gtd::{read_state, write_state};
+use super::{
+    GetFutureStats, OrderInfo, OrderType, PlaceOrder, Request, Rest, Result, Side, Symbol,
+    TimeInForce,
+};
+#[cfg(feature = "ws")]
+use crate::ws::{Data, Ws};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time;
+
+/// Condition on which a scheduled order is placed.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Trigger {
+    /// At a wall-clock time
+    At(DateTime<Utc>),
+    /// Once the last price of `market` is at or above `price`
+    PriceAbove { market: Symbol, price: Decimal },
+    /// Once the last price of `market` is at or below `price`
+    PriceBelow { market: Symbol, price: Decimal },
+    /// Once the predicted funding rate of `future` is at or above `rate`
+    FundingRateAbove { future: Symbol, rate: Decimal },
+    /// Once the predicted funding rate of `future` is at or below `rate`
+    FundingRateBelow { future: Symbol, rate: Decimal },
+}
+
+/// Owned copy of a `PlaceOrder` request, stored as its body.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreparedOrder {
+    pub market: Symbol,
+    pub side: Side,
+    pub price: Option<Decimal>,
+    pub r#type: OrderType,
+    pub size: Decimal,
+    pub reduce_only: bool,
+    /// Stored as the `ioc` and `postOnly` flags
+    #[serde(flatten)]
+    pub time_in_force: TimeInForce,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    pub reject_on_price_band: bool,
+}
+
+impl PreparedOrder {
+    pub fn request(&self) -> PlaceOrder<'_> {
+        PlaceOrder {
+            market: &self.market,
+            side: self.side,
+            price: self.price,
+            r#type: self.r#type,
+            size: self.size,
+            reduce_only: self.reduce_only,
+            time_in_force: self.time_in_force,
+            client_id: self.client_id.as_deref(),
+            reject_on_price_band: self.reject_on_price_band,
+        }
+    }
+}
+
+impl From<&PlaceOrder<'_>> for PreparedOrder {
+    fn from(order: &PlaceOrder<'_>) -> Self {
+        Self {
+            market: order.market.to_owned(),
+            side: order.side,
+            price: order.price,
+            r#type: order.r#type,
+            size: order.size,
+            reduce_only: order.reduce_only,
+            time_in_force: order.time_in_force,
+            client_id: order.client_id.map(str::to_owned),
+            reject_on_price_band: order.reject_on_price_band,
+        }
+    }
+}
+
+/// An order waiting for its trigger.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScheduledOrder {
+    pub trigger: Trigger,
+    pub order: PreparedOrder,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Schedule {
+    next_id: u64,
+    orders: BTreeMap<u64, ScheduledOrder>,
+}
+
+/// Places prepared orders at a given time or once a market condition is
+/// met, e.g. to capture funding or to trade around the open or close.
+///
+/// Prices are observed on the websocket with `observe`, or passed in with
+/// `observe_price`. Predicted funding rates are polled over REST by `run`,
+/// or passed in with `observe_funding_rate`. Every order is placed at most
+/// once: it is removed from the schedule before it is sent.
+///
+/// With `persist_to`, scheduled orders are kept in a JSON file and survive
+/// restarts.
+#[derive(Debug, Clone)]
+pub struct OrderScheduler {
+    rest: Rest,
+    schedule: Arc<Mutex<Schedule>>,
+    path: Option<PathBuf>,
+}
+
+impl OrderScheduler {
+    pub fn new(rest: Rest) -> Self {
+        Self {
+            rest,
+            schedule: Default::default(),
+            path: None,
+        }
+    }
+
+    /// Persists the schedule to `path`, continuing with the orders stored
+    /// there if the file exists.
+    pub fn persist_to(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        if let Some(schedule) = read_state(&path)? {
+            *self.schedule.lock().unwrap() = schedule;
+        }
+        self.path = Some(path);
+        Ok(self)
+    }
+
+    /// Places `order` once `trigger` fires, returning the ID of the
+    /// scheduled order. Fails if the order would be rejected, see
+    /// `PlaceOrder::validate`.
+    pub fn schedule(&self, order: &PlaceOrder<'_>, trigger: Trigger) -> Result<u64> {
+        order.validate()?;
+        let order = PreparedOrder::from(order);
+        let mut schedule = self.schedule.lock().unwrap();
+        let id = schedule.next_id;
+        schedule.next_id += 1;
+        schedule
+            .orders
+            .insert(id, ScheduledOrder { trigger, order });
+        self.save(&schedule)?;
+        Ok(id)
+    }
+
+    /// Removes the scheduled order `id`, returning whether it was still
+    /// waiting for its trigger.
+    pub fn cancel(&self, id: u64) -> Result<bool> {
+        let mut schedule = self.schedule.lock().unwrap();
+        let cancelled = schedule.orders.remove(&id).is_some();
+        if cancelled {
+            self.save(&schedule)?;
+        }
+        Ok(cancelled)
+    }
+
+    /// Orders waiting for their triggers, by ID.
+    pub fn scheduled(&self) -> BTreeMap<u64, ScheduledOrder> {
+        self.schedule.lock().unwrap().orders.clone()
+    }
+
+    /// Places the orders whose time has come.
+    pub async fn submit_due(&self) -> Result<Vec<(u64, Result<OrderInfo>)>> {
+        let now = Utc::now();
+        self.submit(|trigger| matches!(trigger, Trigger::At(time) if *time <= now))
+            .await
+    }
+
+    /// Places the orders triggered by `price` of `market`.
+    pub async fn observe_price(
+        &self,
+        market: &str,
+        price: Decimal,
+    ) -> Result<Vec<(u64, Result<OrderInfo>)>> {
+        self.submit(|trigger| match trigger {
+            Trigger::PriceAbove {
+                market: m,
+                price: p,
+            } => m == market && price >= *p,
+            Trigger::PriceBelow {
+                market: m,
+                price: p,
+            } => m == market && price <= *p,
+            _ => false,
+        })
+        .await
+    }
+
+    /// Places the orders triggered by the predicted funding `rate` of
+    /// `future`.
+    pub async fn observe_funding_rate(
+        &self,
+        future: &str,
+        rate: Decimal,
+    ) -> Result<Vec<(u64, Result<OrderInfo>)>> {
+        self.submit(|trigger| match trigger {
+            Trigger::FundingRateAbove { future: f, rate: r } => f == future && rate >= *r,
+            Trigger::FundingRateBelow { future: f, rate: r } => f == future && rate <= *r,
+            _ => false,
+        })
+        .await
+    }
+
+    /// Places the orders triggered by the last price of a ticker or trade
+    /// received on the websocket.
+    #[cfg(feature = "ws")]
+    pub async fn observe(
+        &self,
+        market: &str,
+        data: &Data,
+    ) -> Result<Vec<(u64, Result<OrderInfo>)>> {
+        match data {
+            Data::Ticker(ticker) => self.observe_price(market, ticker.last).await,
+            Data::Trade(trade) => self.observe_price(market, trade.price).await,
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Places orders as their triggers fire, observing prices on `ws`, which
+    /// must be subscribed to the ticker or trades of the markets, and
+    /// checking times and funding rates every `interval`. Returns once `ws`
+    /// closes.
+    #[cfg(feature = "ws")]
+    pub async fn run(&self, ws: &mut Ws, interval: Duration) -> Result<()> {
+        use futures::StreamExt;
+
+        let mut interval = time::interval(interval);
+        loop {
+            let submitted = tokio::select! {
+                _ = interval.tick() => self.check_times_and_funding().await?,
+                data = ws.next() => match data {
+                    Some(Ok((Some(market), data))) => self.observe(&market, &data).await?,
+                    Some(Ok(_)) => Vec::new(),
+                    Some(Err(err)) => return Err(Box::new(err).into()),
+                    None => return Ok(()),
+                },
+            };
+            log_submitted(&submitted);
+        }
+    }
+
+    /// Places orders as their time or funding triggers fire, checking every
+    /// `interval`, forever.
+    pub async fn run_without_ws(&self, interval: Duration) -> Result<()> {
+        let mut interval = time::interval(interval);
+        loop {
+            interval.tick().await;
+            log_submitted(&self.check_times_and_funding().await?);
+        }
+    }
+
+    async fn check_times_and_funding(&self) -> Result<Vec<(u64, Result<OrderInfo>)>> {
+        let mut submitted = self.submit_due().await?;
+        let futures: BTreeSet<Symbol> = self
+            .schedule
+            .lock()
+            .unwrap()
+            .orders
+            .values()
+            .filter_map(|order| match &order.trigger {
+                Trigger::FundingRateAbove { future, .. }
+                | Trigger::FundingRateBelow { future, .. } => Some(future.clone()),
+                _ => None,
+            })
+            .collect();
+        for future in futures {
+            let stats = match self.rest.request(GetFutureStats::new(&future)).await {
+                Ok(stats) => stats,
+                Err(error) => {
+                    log::warn!("failed to get funding rate of {}: {}", future, error);
+                    continue;
+                }
+            };
+            if let Some(rate) = stats.next_funding_rate {
+                submitted.extend(self.observe_funding_rate(&future, rate).await?);
+            }
+        }
+        Ok(submitted)
+    }
+
+    /// Removes the orders whose trigger fired from the schedule and places
+    /// them.
+    async fn submit(
+        &self,
+        fired: impl Fn(&Trigger) -> bool,
+    ) -> Result<Vec<(u64, Result<OrderInfo>)>> {
+        let due: Vec<(u64, ScheduledOrder)> = {
+            let mut schedule = self.schedule.lock().unwrap();
+            let ids: Vec<u64> = schedule
+                .orders
+                .iter()
+                .filter(|(_, order)| fired(&order.trigger))
+                .map(|(id, _)| *id)
+                .collect();
+            if ids.is_empty() {
+                return Ok(Vec::new());
+            }
+            let due = ids
+                .into_iter()
+                .filter_map(|id| schedule.orders.remove(&id).map(|order| (id, order)))
+                .collect();
+            self.save(&schedule)?;
+            due
+        };
+
+        let mut submitted = Vec::new();
+        for (id, scheduled) in due {
+            let result = self.rest.request(scheduled.order.request()).await;
+            submitted.push((id, result));
+        }
+        Ok(submitted)
+    }
+
+    fn save(&self, schedule: &Schedule) -> Result<()> {
+        match &self.path {
+            Some(path) => write_state(path, schedule),
+            None => Ok(()),
+        }
+    }
+}
+
+fn log_submitted(submitted: &[(u64, Result<OrderInfo>)]) {
+    for (id, result) in submitted {
+        match result {
+            Ok(order) => log::info!("scheduled order {} placed as {}", id, order.id),
+            Err(error) => log::warn!("failed to place scheduled order {}: {}", id, error),
+        }
+    }
+}
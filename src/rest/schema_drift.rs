@@ -0,0 +1,282 @@
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+use serde::forward_to_deserialize_any;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+/// Fields of a struct at one position of a response.
+#[derive(Debug, Default)]
+struct Fields {
+    r#struct: &'static str,
+    expected: BTreeSet<&'static str>,
+    seen: BTreeSet<String>,
+}
+
+/// Fields of responses which the models ignore or never receive, for one
+/// struct of one endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Drift {
+    /// Path of the request, e.g. `/markets/{}`
+    pub endpoint: String,
+    /// Position of the struct in the response, e.g. `positions[]` in the
+    /// response of `/account` or `[]` in that of `/markets`, empty for the
+    /// response itself
+    pub path: String,
+    pub r#struct: &'static str,
+    /// Fields sent by the exchange which the model does not have
+    pub new_fields: Vec<String>,
+    /// Fields of the model which none of the recorded responses had
+    pub removed_fields: Vec<&'static str>,
+}
+
+/// Records the fields of responses per endpoint and compares them to the
+/// fields of the models, to notice when the exchange changes its responses.
+/// Enabled with `Options::detect_schema_drift`.
+#[derive(Debug, Default)]
+pub struct SchemaDrift {
+    endpoints: Mutex<BTreeMap<String, BTreeMap<String, Fields>>>,
+}
+
+impl SchemaDrift {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deserializes `value` as the response of `endpoint`, recording its
+    /// fields and the fields `T` expects.
+    pub fn record<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        value: &Value,
+    ) -> serde_json::Result<T> {
+        let mut recorded = BTreeMap::new();
+        let result = T::deserialize(Recorder {
+            value,
+            path: String::new(),
+            recorded: &mut recorded,
+        });
+
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let structs = endpoints.entry(endpoint.to_owned()).or_default();
+        for (path, fields) in recorded {
+            let known = structs.entry(path.clone()).or_default();
+            for field in fields.seen.difference(&known.seen) {
+                if !fields.expected.contains(field.as_str()) {
+                    log::warn!(
+                        "new field {} of {} in response of {}",
+                        field,
+                        fields.r#struct,
+                        endpoint
+                    );
+                }
+            }
+            known.r#struct = fields.r#struct;
+            known.expected.extend(fields.expected);
+            known.seen.extend(fields.seen);
+        }
+        result
+    }
+
+    /// Differences between the recorded responses and the models, for the
+    /// structs which have any.
+    pub fn report(&self) -> Vec<Drift> {
+        let endpoints = self.endpoints.lock().unwrap();
+        let mut report = Vec::new();
+        for (endpoint, structs) in endpoints.iter() {
+            for (path, fields) in structs {
+                let new_fields: Vec<String> = fields
+                    .seen
+                    .iter()
+                    .filter(|field| !fields.expected.contains(field.as_str()))
+                    .cloned()
+                    .collect();
+                let removed_fields: Vec<&'static str> = fields
+                    .expected
+                    .iter()
+                    .filter(|field| !fields.seen.contains(**field))
+                    .copied()
+                    .collect();
+                if !new_fields.is_empty() || !removed_fields.is_empty() {
+                    report.push(Drift {
+                        endpoint: endpoint.clone(),
+                        path: path.clone(),
+                        r#struct: fields.r#struct,
+                        new_fields,
+                        removed_fields,
+                    });
+                }
+            }
+        }
+        report
+    }
+
+    /// Forgets all recorded responses.
+    pub fn clear(&self) {
+        self.endpoints.lock().unwrap().clear();
+    }
+}
+
+/// Deserializer of a `serde_json::Value` which records the fields of the
+/// structs it deserializes.
+struct Recorder<'a, 'de> {
+    value: &'de Value,
+    path: String,
+    recorded: &'a mut BTreeMap<String, Fields>,
+}
+
+impl<'de> Recorder<'_, 'de> {
+    fn visit_map<V: Visitor<'de>>(
+        self,
+        object: &'de Map<String, Value>,
+        visitor: V,
+    ) -> Result<V::Value, serde_json::Error> {
+        visitor.visit_map(Entries {
+            entries: object.iter(),
+            value: None,
+            path: self.path,
+            recorded: self.recorded,
+        })
+    }
+}
+
+impl<'de> Deserializer<'de> for Recorder<'_, 'de> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Array(items) => visitor.visit_seq(Items {
+                items: items.iter(),
+                path: format!("{}[]", self.path),
+                recorded: self.recorded,
+            }),
+            Value::Object(object) => self.visit_map(object, visitor),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let object = match self.value {
+            Value::Object(object) => object,
+            value => return value.deserialize_any(visitor),
+        };
+        let recorded = self.recorded.entry(self.path.clone()).or_default();
+        recorded.r#struct = name;
+        recorded.expected.extend(fields);
+        recorded.seen.extend(object.keys().cloned());
+        self.visit_map(object, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::String(variant) => visitor.visit_enum(variant.as_str().into_deserializer()),
+            value => value.deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier
+        ignored_any
+    }
+}
+
+struct Items<'a, 'de> {
+    items: std::slice::Iter<'de, Value>,
+    path: String,
+    recorded: &'a mut BTreeMap<String, Fields>,
+}
+
+impl<'de> SeqAccess<'de> for Items<'_, 'de> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        match self.items.next() {
+            Some(value) => seed
+                .deserialize(Recorder {
+                    value,
+                    path: self.path.clone(),
+                    recorded: self.recorded,
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct Entries<'a, 'de> {
+    entries: serde_json::map::Iter<'de>,
+    value: Option<(&'de String, &'de Value)>,
+    path: String,
+    recorded: &'a mut BTreeMap<String, Fields>,
+}
+
+impl<'de> MapAccess<'de> for Entries<'_, 'de> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some((key, value));
+                seed.deserialize(key.as_str().into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let (key, value) = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before key"))?;
+        let path = match self.path.as_str() {
+            "" => key.clone(),
+            path => format!("{}.{}", path, key),
+        };
+        seed.deserialize(Recorder {
+            value,
+            path,
+            recorded: self.recorded,
+        })
+    }
+}
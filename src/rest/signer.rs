@@ -0,0 +1,59 @@
+use super::{Rest, Result};
+use async_trait::async_trait;
+use hmac_sha256::HMAC;
+use std::fmt;
+use std::sync::Arc;
+
+/// Signs authenticated requests, see `Rest::with_signer`. Implementations
+/// can keep the API secret outside of the process, e.g. in an HSM, a
+/// keyring or a remote signing service.
+#[async_trait]
+pub trait Signer: Send + Sync + fmt::Debug {
+    /// Hex encoded HMAC-SHA256 of `payload`, which is the timestamp, method,
+    /// path and body of the request, keyed with the API secret.
+    async fn sign(&self, payload: &[u8]) -> Result<String>;
+}
+
+#[async_trait]
+impl<T: Signer + ?Sized> Signer for Arc<T> {
+    async fn sign(&self, payload: &[u8]) -> Result<String> {
+        (**self).sign(payload).await
+    }
+}
+
+/// Signs with the API secret held in memory, used for `Options::secret`.
+#[derive(Clone)]
+pub struct SecretSigner {
+    secret: String,
+}
+
+impl SecretSigner {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+        }
+    }
+}
+
+impl fmt::Debug for SecretSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretSigner").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl Signer for SecretSigner {
+    async fn sign(&self, payload: &[u8]) -> Result<String> {
+        Ok(hex::encode(HMAC::mac(payload, self.secret.as_bytes())))
+    }
+}
+
+impl Rest {
+    /// Signs authenticated requests with `signer` instead of the secret of
+    /// the options. The API key is still sent from the options.
+    #[must_use]
+    pub fn with_signer(mut self, signer: impl Signer + 'static) -> Self {
+        self.signer = Some(Arc::new(signer));
+        self
+    }
+}
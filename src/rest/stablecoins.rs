@@ -0,0 +1,111 @@
+use super::{
+    CoinInfo, GetAccount, GetCoins, GetMarket, GetWalletBalances, OrderType, PlaceOrder,
+    QuoteDecision, Rest, Result, Side,
+};
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Returns whether FTX treats `coin` as USD, e.g. USDC, which is credited
+/// as USD collateral without any conversion.
+pub fn is_usd_equivalent(coin: &CoinInfo) -> bool {
+    coin.id == "USD" || coin.usd_fungible
+}
+
+/// Free collateral gained per unit of `coin` converted into USD, assuming
+/// it trades at 1 USD. Coins which count as collateral already contribute
+/// their balance times their collateral weight, so converting them only
+/// gains the rest.
+pub fn collateral_gain(coin: &CoinInfo) -> Decimal {
+    if coin.collateral {
+        (Decimal::ONE - coin.collateral_weight).max(Decimal::ZERO)
+    } else {
+        Decimal::ONE
+    }
+}
+
+/// How stablecoins are converted into USD.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConversionMethod {
+    /// Market sell on the `<COIN>/USD` spot market
+    Spot,
+    /// Accept an OTC quote, see `Rest::convert`
+    Otc,
+}
+
+impl Rest {
+    /// Converts the free balances of the stablecoins `coins` into USD if the
+    /// free collateral of the account is below `threshold`, until the
+    /// shortfall is covered, weighting each coin with `collateral_gain`.
+    /// Coins FTX already treats as USD are skipped. Returns the converted
+    /// coins and sizes.
+    pub async fn top_up_usd_collateral(
+        &self,
+        coins: &[&str],
+        threshold: Decimal,
+        method: ConversionMethod,
+    ) -> Result<Vec<(String, Decimal)>> {
+        let account = self.request(GetAccount {}).await?;
+        let mut shortfall = threshold - account.free_collateral;
+        if shortfall <= Decimal::ZERO {
+            return Ok(Vec::new());
+        }
+
+        let infos = self.request(GetCoins {}).await?;
+        let balances = self.request(GetWalletBalances {}).await?;
+
+        let mut converted = Vec::new();
+        for &coin in coins {
+            if shortfall <= Decimal::ZERO {
+                break;
+            }
+            let gain = match infos.iter().find(|info| info.id == coin) {
+                Some(info) if !is_usd_equivalent(info) => collateral_gain(info),
+                _ => continue,
+            };
+            let free = match balances.iter().find(|balance| balance.coin == coin) {
+                Some(balance) if !gain.is_zero() => balance.free,
+                _ => continue,
+            };
+            let needed = shortfall / gain;
+
+            let size = match method {
+                ConversionMethod::Spot => {
+                    let market = format!("{}/USD", coin);
+                    let increment = self.request(GetMarket::new(&market)).await?.size_increment;
+                    let size = (free.min(needed) / increment)
+                        .round_dp_with_strategy(0, RoundingStrategy::ToZero)
+                        * increment;
+                    if size.is_zero() {
+                        continue;
+                    }
+                    self.request(PlaceOrder {
+                        market: &market,
+                        side: Side::Sell,
+                        r#type: OrderType::Market,
+                        size,
+                        ..Default::default()
+                    })
+                    .await?;
+                    size
+                }
+                ConversionMethod::Otc => {
+                    let size = free.min(needed);
+                    if size.is_zero()
+                        || self
+                            .convert(coin, "USD", size, |_| QuoteDecision::Accept)
+                            .await?
+                            .is_none()
+                    {
+                        continue;
+                    }
+                    size
+                }
+            };
+
+            // Stablecoins trade close enough to 1 USD to use the size
+            shortfall -= size * gain;
+            converted.push((coin.to_owned(), size));
+        }
+
+        Ok(converted)
+    }
+}
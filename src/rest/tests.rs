@@ -1,5 +1,6 @@
 use super::*;
 use dotenvy::dotenv;
+use reqwest::StatusCode;
 use rust_decimal_macros::dec;
 use std::env::var;
 
@@ -23,12 +24,13 @@ async fn init_unauthenticated_api() -> Rest {
 }
 
 fn read_only<T: std::fmt::Debug>(result: Result<T>) {
-    result.expect_err("Expected error");
-    //TODO: match specific error
-    // match result {
-    //     Err(Error::Api(error)) if error == *"Not allowed with read-only permissions" => {}
-    //     _ => panic!("Expected read-only subaccount."),
-    // }
+    match result {
+        Err(Error::Exchange {
+            error: ApiError::ReadOnlyPermissions,
+            ..
+        }) => {}
+        result => panic!("Expected read-only subaccount, got {:?}", result),
+    }
 }
 
 #[tokio::test]
@@ -180,6 +182,192 @@ async fn get_funding_payments() {
         .unwrap();
 }
 
+#[tokio::test]
+#[ignore]
+async fn get_airdrops() {
+    init_api()
+        .await
+        .request(GetAirdrops::default())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore]
+async fn get_fiat_deposit_instructions() {
+    init_api()
+        .await
+        .request(GetFiatDepositInstructions::new("USD"))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore]
+async fn get_fiat_withdrawals() {
+    init_api()
+        .await
+        .request(GetFiatWithdrawals::default())
+        .await
+        .unwrap();
+}
+
+#[test]
+fn fiat_deposit_instructions_deserialization() {
+    let json = r#"{
+        "coin": "USD",
+        "bankName": "Example Bank",
+        "bankAddress": "1 Example Street",
+        "accountName": "FTX",
+        "accountNumber": "0000000000",
+        "routingNumber": "000000000",
+        "swiftCode": null,
+        "iban": null,
+        "reference": "FTX-1234567"
+    }"#;
+    let instructions: FiatDepositInstructions = serde_json::from_str(json).unwrap();
+    assert_eq!(instructions.reference.as_deref(), Some("FTX-1234567"));
+    assert!(instructions.iban.is_none());
+}
+
+#[test]
+fn airdrop_deserialization() {
+    let json = r#"[{
+        "coin": "SRM",
+        "id": 1031,
+        "size": 15.0,
+        "time": "2020-05-22T15:12:29.611079+00:00",
+        "status": "confirmed"
+    }]"#;
+    let airdrops: Vec<Airdrop> = serde_json::from_str(json).unwrap();
+    assert_eq!(airdrops[0].status, DepositStatus::Confirmed);
+}
+
+#[tokio::test]
+#[ignore]
+async fn convert_rejected() {
+    let rest = init_api().await;
+    let converted = rest
+        .convert("USD", "BTC", dec!(1), |quote| {
+            assert!(!quote.expired);
+            QuoteDecision::Reject
+        })
+        .await
+        .unwrap();
+    assert!(converted.is_none());
+}
+
+#[tokio::test]
+async fn convert_accepted() {
+    let expiry = (Utc::now() + chrono::Duration::seconds(30)).timestamp();
+    let quote = serde_json::json!({
+        "baseCoin": "BTC",
+        "cost": 1.0,
+        "expired": false,
+        "expiry": expiry,
+        "filled": false,
+        "fromCoin": "USD",
+        "id": 3,
+        "price": 0.00002,
+        "proceeds": 0.00002,
+        "quoteCoin": "USD",
+        "side": "buy",
+        "toCoin": "BTC"
+    });
+    let transport = MockTransport::new()
+        .on::<RequestQuote>(serde_json::json!({ "quoteId": 3 }))
+        .on::<GetQuote>(quote)
+        .on::<AcceptQuote>(serde_json::Value::Null);
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport.clone());
+
+    let converted = rest
+        .convert("USD", "BTC", dec!(1), |quote: &QuoteInfo| {
+            assert!(quote.expires_in() > chrono::Duration::zero());
+            QuoteDecision::Accept
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(converted.proceeds, dec!(0.00002));
+
+    let paths: Vec<_> = transport
+        .requests()
+        .into_iter()
+        .map(|request| (request.method, request.path))
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            (Method::POST, "/otc/quotes".to_owned()),
+            (Method::GET, "/otc/quotes/3".to_owned()),
+            (Method::POST, "/otc/quotes/3/accept".to_owned()),
+            (Method::GET, "/otc/quotes/3".to_owned()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn convert_expired() {
+    let quote = serde_json::json!({
+        "baseCoin": "BTC",
+        "cost": 1.0,
+        "expired": true,
+        "expiry": Utc::now().timestamp(),
+        "filled": false,
+        "fromCoin": "USD",
+        "id": 3,
+        "price": 0.00002,
+        "proceeds": 0.00002,
+        "quoteCoin": "USD",
+        "side": "buy",
+        "toCoin": "BTC"
+    });
+    let transport = MockTransport::new()
+        .on::<RequestQuote>(serde_json::json!({ "quoteId": 3 }))
+        .on::<GetQuote>(quote);
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport.clone());
+
+    let converted = rest
+        .convert("USD", "BTC", dec!(1), |_| QuoteDecision::Accept)
+        .await;
+    assert!(matches!(
+        converted,
+        Err(Error::QuoteExpiredAfterRequotes(MAX_REQUOTES))
+    ));
+    assert_eq!(transport.requests().len(), 2 * (MAX_REQUOTES + 1));
+}
+
+#[test]
+fn quote_deserialization() {
+    let json = r#"{
+        "baseCoin": "BTC",
+        "cost": 22.2,
+        "expired": false,
+        "expiry": 1630000000.5,
+        "filled": false,
+        "fromCoin": "USD",
+        "id": 3,
+        "price": 0.00002,
+        "proceeds": 0.0004,
+        "quoteCoin": "USD",
+        "side": "buy",
+        "toCoin": "BTC"
+    }"#;
+    let quote: Quote = serde_json::from_str(json).unwrap();
+    assert_eq!(quote.side, Side::Buy);
+    assert_eq!(quote.expiry.timestamp_millis(), 1630000000500);
+}
+
 #[tokio::test]
 async fn get_futures() {
     init_unauthenticated_api()
@@ -276,6 +464,42 @@ async fn account_deserialization() {
     let _account: Account = serde_json::from_str(json).unwrap();
 }
 
+#[test]
+fn positions_with_avg_price() {
+    assert_eq!(serde_qs::to_string(&GetPositions::new()).unwrap(), "");
+    assert_eq!(
+        serde_qs::to_string(&GetPositions::with_avg_price()).unwrap(),
+        "showAvgPrice=true"
+    );
+
+    let json = r#"{
+        "cost": -31.7906,
+        "cumulativeBuySize": 1.2,
+        "cumulativeSellSize": 0.0,
+        "entryPrice": 138.22,
+        "estimatedLiquidationPrice": 152.1,
+        "future": "ETH-PERP",
+        "initialMarginRequirement": 0.1,
+        "longOrderSize": 1744.55,
+        "maintenanceMarginRequirement": 0.04,
+        "netSize": -0.23,
+        "openSize": 1744.32,
+        "realizedPnl": 3.39441714,
+        "recentAverageOpenPrice": 135.31,
+        "recentBreakEvenPrice": 135.31,
+        "recentPnl": 3.1134,
+        "shortOrderSize": 1732.09,
+        "side": "sell",
+        "size": 0.23,
+        "unrealizedPnl": 0,
+        "collateralUsed": 3.17906
+    }"#;
+    let position: Position = serde_json::from_str(json).unwrap();
+    assert_eq!(position.estimated_liquidation_price, Some(dec!(152.1)));
+    assert_eq!(position.recent_average_open_price, Some(dec!(135.31)));
+    assert_eq!(position.cumulative_buy_size, Some(dec!(1.2)));
+}
+
 #[tokio::test]
 async fn get_coins() {
     init_unauthenticated_api()
@@ -347,7 +571,7 @@ pub async fn manipulate_orders() {
             price: Some(initial_bid_price),
             r#type: OrderType::Limit,
             size: initial_bid_size,
-            post_only: true,
+            time_in_force: TimeInForce::PostOnly,
             ..Default::default()
         })
         .await
@@ -401,7 +625,7 @@ pub async fn manipulate_orders() {
             price: Some(rejected_bid_price),
             r#type: OrderType::Limit,
             size: initial_bid_size,
-            post_only: true,
+            time_in_force: TimeInForce::PostOnly,
             ..Default::default()
         })
         .await
@@ -426,3 +650,3437 @@ async fn get_open_orders() {
         .await
         .unwrap();
 }
+
+#[tokio::test]
+#[ignore]
+async fn place_orders() {
+    let api = init_api().await;
+    let market = "ETH-PERP";
+    let price = api
+        .request(GetMarket::new(market))
+        .await
+        .unwrap()
+        .price
+        .unwrap();
+
+    // Two post-only bids far below the market, and one order that is
+    // rejected because it is missing a market
+    let report = api
+        .place_orders(vec![
+            PlaceOrder {
+                market,
+                side: Side::Buy,
+                price: Some((dec!(0.9) * price).round_dp(1)),
+                r#type: OrderType::Limit,
+                size: dec!(0.001),
+                time_in_force: TimeInForce::PostOnly,
+                ..Default::default()
+            },
+            PlaceOrder {
+                market,
+                side: Side::Buy,
+                price: Some((dec!(0.8) * price).round_dp(1)),
+                r#type: OrderType::Limit,
+                size: dec!(0.001),
+                time_in_force: TimeInForce::PostOnly,
+                ..Default::default()
+            },
+            PlaceOrder {
+                market: "",
+                ..Default::default()
+            },
+        ])
+        .await;
+
+    assert_eq!(report.results.len(), 3);
+    assert_eq!(report.succeeded().count(), 2);
+    assert_eq!(report.failed().count(), 1);
+    assert!(report.results[2].1.is_err());
+
+    api.request(CancelAllOrder::with_market(market))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn batch_concurrency() {
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Cancels orders slowly, failing for order 2, and records the most
+    /// requests in flight at once.
+    #[derive(Debug, Default)]
+    struct Slow {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for Slow {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(if request.path == "/orders/2" {
+                HttpResponse {
+                    status: StatusCode::BAD_REQUEST,
+                    ..HttpResponse::ok(r#"{"success":false,"error":"Order already closed"}"#)
+                }
+            } else {
+                HttpResponse::ok(r#"{"success":true,"result":"Order queued for cancellation"}"#)
+            })
+        }
+    }
+
+    let transport = Arc::new(Slow::default());
+    let rest = Rest::new(Options::default()).with_transport(transport.clone());
+    let report = rest
+        .cancel_orders_with_concurrency(&[1, 2, 3, 4, 5], 2)
+        .await;
+    assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 2);
+    let ids: Vec<Id> = report.results.iter().map(|(id, _)| *id).collect();
+    assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+    assert_eq!(report.succeeded().count(), 4);
+    let failed: Vec<_> = report.failed().collect();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(*failed[0].0, 2);
+    assert!(matches!(
+        failed[0].1,
+        Error::Exchange {
+            error: ApiError::OrderAlreadyClosed,
+            ..
+        }
+    ));
+
+    rest.cancel_orders(&[1, 2, 3, 4, 5]).await;
+    assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 5);
+}
+
+#[test]
+fn modify_order_by_client_id_serialization() {
+    // The client ID in the path must not leak into the body, while the new
+    // client ID is sent as `clientId`
+    let req = ModifyOrderByClientId {
+        client_id: "old",
+        price: Some(dec!(1.5)),
+        new_client_id: Some("new"),
+        ..Default::default()
+    };
+    assert_eq!(req.path(), "/orders/by_client_id/old/modify");
+    assert_eq!(
+        serde_json::to_string(&req).unwrap(),
+        r#"{"price":"1.5","clientId":"new"}"#
+    );
+}
+
+fn order_info(id: Id, client_id: Option<&str>, status: &str, reduce_only: bool) -> OrderInfo {
+    serde_json::from_value(serde_json::json!({
+        "id": id,
+        "market": "ETH-PERP",
+        "future": "ETH-PERP",
+        "type": "limit",
+        "side": "buy",
+        "price": 1000.0,
+        "size": 0.1,
+        "reduceOnly": reduce_only,
+        "ioc": false,
+        "postOnly": true,
+        "status": status,
+        "filledSize": 0.0,
+        "remainingSize": 0.1,
+        "avgFillPrice": null,
+        "liquidation": null,
+        "createdAt": format!("2022-01-01T00:00:{:02}.000000+00:00", id),
+        "clientId": client_id,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn order_filter() {
+    let orders = vec![
+        order_info(1, Some("mm-1"), "closed", false),
+        order_info(2, Some("mm-2"), "open", true),
+        order_info(3, Some("hedge-1"), "open", false),
+        order_info(4, None, "open", false),
+    ];
+
+    let ids = |filter: OrderFilter| -> Vec<Id> {
+        filter.apply(&orders).iter().map(|order| order.id).collect()
+    };
+
+    assert_eq!(ids(OrderFilter::new()), vec![1, 2, 3, 4]);
+    assert_eq!(ids(OrderFilter::new().client_id_prefix("mm-")), vec![1, 2]);
+    assert_eq!(
+        ids(OrderFilter::new().status(OrderStatus::Open)),
+        vec![2, 3, 4]
+    );
+    assert_eq!(ids(OrderFilter::new().reduce_only(true)), vec![2]);
+    assert_eq!(
+        ids(OrderFilter::new()
+            .client_id_prefix("mm-")
+            .status(OrderStatus::Closed)),
+        vec![1]
+    );
+    assert!(ids(OrderFilter::new().order_type(OrderType::Market)).is_empty());
+}
+
+#[test]
+fn order_index_by_client_id() {
+    let orders = vec![
+        order_info(1, Some("a"), "closed", false),
+        order_info(3, Some("b"), "open", false),
+        // Client ID "a" reused by a more recent order
+        order_info(2, Some("a"), "open", false),
+        order_info(4, None, "open", false),
+    ];
+
+    let index = index_by_client_id(&orders);
+    assert_eq!(index.len(), 2);
+    assert_eq!(index["a"].id, 2);
+    assert_eq!(index["b"].id, 3);
+}
+
+#[tokio::test]
+#[ignore]
+async fn await_order_final() {
+    let api = init_api().await;
+    let market = "ETH-PERP";
+    let price = api
+        .request(GetMarket::new(market))
+        .await
+        .unwrap()
+        .price
+        .unwrap();
+
+    let order = api
+        .request(PlaceOrder {
+            market,
+            side: Side::Buy,
+            price: Some((dec!(0.9) * price).round_dp(1)),
+            r#type: OrderType::Limit,
+            size: dec!(0.001),
+            time_in_force: TimeInForce::PostOnly,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    // The order rests on the book, so waiting for it times out
+    assert!(matches!(
+        api.await_order_final(order.id, std::time::Duration::from_secs(2))
+            .await,
+        Err(Error::Timeout)
+    ));
+
+    api.request(CancelOrder::new(order.id)).await.unwrap();
+    let closed = api
+        .await_order_final(order.id, std::time::Duration::from_secs(10))
+        .await
+        .unwrap();
+    assert_eq!(OrderStatus::Closed, closed.status);
+}
+
+#[cfg(feature = "mock-server")]
+#[tokio::test]
+async fn await_order_final_with_updates() {
+    use crate::mock::MockServer;
+    use std::time::{Duration, Instant};
+
+    let handle = MockServer::new("key", "secret")
+        .with_default_markets()
+        .start()
+        .await
+        .unwrap();
+    let rest = Rest::new(handle.options());
+    let id = rest
+        .request(PlaceOrder::limit(
+            "BTC-PERP",
+            Side::Buy,
+            dec!(30000),
+            dec!(0.1),
+        ))
+        .await
+        .unwrap()
+        .id;
+
+    assert!(matches!(
+        rest.await_order_final(id, Duration::from_millis(500)).await,
+        Err(Error::Timeout)
+    ));
+
+    let cancel = {
+        let rest = rest.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            rest.request(CancelOrder::new(id)).await.unwrap();
+        })
+    };
+    let start = Instant::now();
+    let closed = rest
+        .await_order_final(id, Duration::from_secs(5))
+        .await
+        .unwrap();
+    // Resolved by the websocket update rather than the next poll a second
+    // later
+    assert!(start.elapsed() < Duration::from_millis(900));
+    assert_eq!(closed.status, OrderStatus::Closed);
+    cancel.await.unwrap();
+}
+
+#[test]
+fn trigger_order_constructors() {
+    let stop = PlaceTriggerOrder::stop_loss("BTC-PERP", Side::Buy, dec!(1), dec!(30000));
+    assert_eq!(stop.side, Side::Sell);
+    assert_eq!(stop.r#type, OrderType::Stop);
+    assert_eq!(stop.reduce_only, Some(true));
+
+    let take_profit = PlaceTriggerOrder::take_profit("BTC-PERP", Side::Sell, dec!(1), dec!(30000));
+    assert_eq!(take_profit.side, Side::Buy);
+    assert_eq!(take_profit.r#type, OrderType::TakeProfit);
+    assert_eq!(
+        serde_json::to_string(&take_profit).unwrap(),
+        r#"{"market":"BTC-PERP","side":"buy","size":"1","type":"takeProfit","triggerPrice":"30000","reduceOnly":true}"#
+    );
+}
+
+#[test]
+fn trailing_stop() {
+    assert!(matches!(
+        PlaceTrailingStop::new("BTC-PERP", Side::Sell, dec!(1), dec!(50)),
+        Err(Error::TrailValueSignMismatch)
+    ));
+    assert!(matches!(
+        PlaceTrailingStop::new("BTC-PERP", Side::Buy, dec!(1), dec!(0)),
+        Err(Error::TrailValueSignMismatch)
+    ));
+
+    let req = PlaceTrailingStop::new("BTC-PERP", Side::Sell, dec!(1), dec!(-50))
+        .unwrap()
+        .reduce_only();
+    assert_eq!(
+        serde_json::to_string(&req).unwrap(),
+        r#"{"market":"BTC-PERP","side":"sell","size":"1","type":"trailingStop","trailValue":"-50","reduceOnly":true}"#
+    );
+
+    let json = r#"{
+        "createdAt": "2019-03-05T09:56:55.728933+00:00",
+        "future": "XRP-PERP",
+        "id": 50001,
+        "market": "XRP-PERP",
+        "triggerPrice": 0.49,
+        "orderId": null,
+        "side": "sell",
+        "size": 0.003,
+        "status": "open",
+        "type": "trailing_stop",
+        "orderPrice": null,
+        "error": null,
+        "triggeredAt": null,
+        "reduceOnly": true,
+        "orderType": "market",
+        "retryUntilFilled": true,
+        "trailValue": -0.05,
+        "trailStart": 0.54
+    }"#;
+    let order: OrderInfo = serde_json::from_str(json).unwrap();
+    assert_eq!(order.r#type, OrderType::TrailingStop);
+    assert_eq!(order.trail_value, Some(dec!(-0.05)));
+    assert_eq!(order.trail_start, Some(dec!(0.54)));
+}
+
+#[test]
+fn take_profit_limit() {
+    let req = PlaceTriggerOrder::take_profit("XRP-PERP", Side::Buy, dec!(10), dec!(0.6))
+        .order_price(dec!(0.59));
+    assert_eq!(
+        serde_json::to_string(&req).unwrap(),
+        r#"{"market":"XRP-PERP","side":"sell","size":"10","type":"takeProfit","triggerPrice":"0.6","reduceOnly":true,"orderPrice":"0.59"}"#
+    );
+
+    let json = r#"{
+        "createdAt": "2019-03-05T09:56:55.728933+00:00",
+        "future": "XRP-PERP",
+        "id": 50001,
+        "market": "XRP-PERP",
+        "triggerPrice": 0.6,
+        "orderId": 9596912,
+        "side": "sell",
+        "size": 10,
+        "status": "closed",
+        "type": "take_profit",
+        "orderPrice": 0.59,
+        "error": null,
+        "triggeredAt": "2019-03-05T10:01:02.123456+00:00",
+        "reduceOnly": true,
+        "orderType": "limit",
+        "retryUntilFilled": false
+    }"#;
+    let order: OrderInfo = serde_json::from_str(json).unwrap();
+    assert_eq!(order.r#type, OrderType::TakeProfit);
+    assert_eq!(order.order_type, Some(OrderType::Limit));
+    assert_eq!(order.order_price, Some(dec!(0.59)));
+    assert_eq!(order.order_id, Some(9596912));
+}
+
+#[test]
+fn retry_until_filled() {
+    let stop = PlaceTriggerOrder::stop_loss("BTC-PERP", Side::Buy, dec!(1), dec!(30000))
+        .retry_until_filled(true);
+    assert!(stop.validate().is_ok());
+    assert!(matches!(
+        stop.order_price(dec!(29900)).validate(),
+        Err(Error::RetryUntilFilledRequiresMarketTrigger)
+    ));
+
+    // Trigger order history reports triggered and cancelled orders
+    for (status, expected) in [
+        ("open", OrderStatus::Open),
+        ("triggered", OrderStatus::Triggered),
+        ("cancelled", OrderStatus::Cancelled),
+    ] {
+        let parsed: OrderStatus = serde_json::from_str(&format!("\"{}\"", status)).unwrap();
+        assert_eq!(parsed, expected);
+    }
+}
+
+#[test]
+fn trigger_order_requests() {
+    let modify = ModifyTriggerOrder {
+        id: 50001,
+        size: dec!(0.003),
+        trigger_price: Some(dec!(0.5)),
+        ..Default::default()
+    };
+    assert_eq!(modify.path(), "/conditional_orders/50001/modify");
+    assert_eq!(
+        serde_json::to_string(&modify).unwrap(),
+        r#"{"size":"0.003","triggerPrice":"0.5"}"#
+    );
+    assert_eq!(
+        GetTriggerOrderTriggers::new(50001).path(),
+        "/conditional_orders/50001/triggers"
+    );
+    let history = GetTriggerOrderHistory {
+        market: Some("XRP-PERP"),
+        r#type: Some(TriggerOrderType::TrailingStop),
+        order_type: Some(OrderType::Market),
+        ..Default::default()
+    };
+    assert_eq!(
+        serde_qs::to_string(&history).unwrap(),
+        "market=XRP-PERP&type=trailing_stop&orderType=market"
+    );
+    let open = GetOpenTriggerOrders {
+        r#type: Some(TriggerOrderType::TakeProfit),
+        ..GetOpenTriggerOrders::with_market("XRP-PERP")
+    };
+    assert_eq!(
+        serde_qs::to_string(&open).unwrap(),
+        "market=XRP-PERP&type=take_profit"
+    );
+
+    let json = r#"{
+        "createdAt": "2019-03-05T09:56:55.728933+00:00",
+        "error": null,
+        "future": "XRP-PERP",
+        "id": 50001,
+        "market": "XRP-PERP",
+        "orderId": null,
+        "orderPrice": null,
+        "reduceOnly": false,
+        "side": "buy",
+        "size": 0.003,
+        "status": "open",
+        "trailStart": null,
+        "trailValue": null,
+        "triggerPrice": 0.49,
+        "triggeredAt": null,
+        "type": "stop",
+        "orderType": "market",
+        "filledSize": 0,
+        "avgFillPrice": null,
+        "retryUntilFilled": false
+    }"#;
+    let order: TriggerOrderInfo = serde_json::from_str(json).unwrap();
+    assert_eq!(order.r#type, TriggerOrderType::Stop);
+    assert_eq!(order.order_type, OrderType::Market);
+    assert_eq!(order.trigger_price, Some(dec!(0.49)));
+    assert_eq!(order.status, OrderStatus::Open);
+
+    let json = r#"[{
+        "time": "2020-01-19T09:23:36.570904+00:00",
+        "orderSize": 0.003,
+        "filledSize": 0.003,
+        "orderId": 38066506,
+        "error": null
+    }]"#;
+    let triggers: Vec<TriggerEvent> = serde_json::from_str(json).unwrap();
+    assert_eq!(triggers[0].order_id, Some(38066506));
+    assert_eq!(triggers[0].filled_size, Some(dec!(0.003)));
+}
+
+#[tokio::test]
+async fn funding_rate_history() {
+    use chrono::{Duration, TimeZone};
+    use std::collections::HashMap;
+
+    // Hourly funding rates over 50 days, i.e. more than two full pages
+    let start = Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap();
+    let times: Vec<i64> = (0..1200)
+        .map(|hour| (start + Duration::hours(hour)).timestamp())
+        .collect();
+    let end = Utc.timestamp_opt(*times.last().unwrap(), 0).unwrap();
+
+    let transport = MockTransport::new().on_request::<GetFundingRates>(move |request| {
+        let target = request.path.as_str();
+        let query: HashMap<&str, &str> = target
+            .split_once('?')
+            .unwrap()
+            .1
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .collect();
+        assert!(target.starts_with("/funding_rates?"));
+        assert_eq!(query["future"], "BTC-PERP");
+        let range = query["start_time"].parse().unwrap()..=query["end_time"].parse().unwrap();
+
+        // Like FTX, return the newest rows of the range first
+        let rates: Vec<_> = times
+            .iter()
+            .rev()
+            .filter(|time| range.contains(*time))
+            .take(500)
+            .map(|time| {
+                serde_json::json!({
+                    "future": "BTC-PERP",
+                    "rate": 0.0001,
+                    "time": Utc.timestamp_opt(*time, 0).unwrap(),
+                })
+            })
+            .collect();
+        serde_json::json!({ "success": true, "result": rates })
+    });
+    let rest = Rest::new(Options::default()).with_transport(transport.clone());
+
+    let history = rest
+        .get_funding_rate_history("BTC-PERP", start, end)
+        .await
+        .unwrap();
+    assert_eq!(transport.requests().len(), 3);
+    assert_eq!(history.len(), 1200);
+    assert_eq!(history[0].time, start);
+    assert!(history
+        .windows(2)
+        .all(|pair| pair[1].time - pair[0].time == Duration::hours(1)));
+}
+
+#[tokio::test]
+async fn candle_history() {
+    use chrono::{Duration, TimeZone};
+    use std::collections::HashMap;
+
+    // Serves minute candles of three days, at most `limit` of the newest in
+    // range
+    let day = Utc
+        .with_ymd_and_hms(2022, 1, 1, 0, 0, 0)
+        .unwrap()
+        .timestamp();
+    let transport = MockTransport::new().on_request::<GetHistoricalPrices>(move |request| {
+        let target = request.path.as_str();
+        let query: HashMap<&str, i64> = target
+            .split_once('?')
+            .unwrap()
+            .1
+            .split('&')
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap();
+                (key, value.parse().unwrap())
+            })
+            .collect();
+        assert!(target.starts_with("/markets/BTC-PERP/candles?"));
+        assert_eq!(query["resolution"], 60);
+
+        let mut times: Vec<i64> = (0..3 * 24 * 60)
+            .map(|minute| day + minute * 60)
+            .filter(|time| (query["start_time"]..=query["end_time"]).contains(time))
+            .collect();
+        let skip = times.len().saturating_sub(query["limit"] as usize);
+        times.drain(..skip);
+        let candles: Vec<_> = times
+            .iter()
+            .map(|time| {
+                serde_json::json!({
+                    "open": 1, "high": 2, "low": 0.5, "close": 1.5, "volume": 10,
+                    "startTime": Utc.timestamp_opt(*time, 0).unwrap(),
+                })
+            })
+            .collect();
+        serde_json::json!({ "success": true, "result": candles })
+    });
+    let rest = Rest::new(Options::default()).with_transport(transport.clone());
+
+    let start = Utc.timestamp_opt(day, 0).unwrap();
+    let end = start + Duration::minutes(24 * 60 - 1);
+    let candles = rest
+        .get_candle_history("BTC-PERP", Resolution::Minute, start, end)
+        .await
+        .unwrap();
+    assert_eq!(transport.requests().len(), 1);
+    assert_eq!(candles.len(), 24 * 60);
+    assert_eq!(candles[0].start_time, start);
+    assert!(candles
+        .windows(2)
+        .all(|pair| pair[1].start_time - pair[0].start_time == Duration::minutes(1)));
+
+    // Three days do not fit in one page
+    let end = start + Duration::minutes(3 * 24 * 60 - 1);
+    let candles = rest
+        .get_candle_history("BTC-PERP", Resolution::Minute, start, end)
+        .await
+        .unwrap();
+    assert_eq!(transport.requests().len(), 4);
+    assert_eq!(candles.len(), 3 * 24 * 60);
+    assert_eq!(candles[0].start_time, start);
+    assert_eq!(candles.last().unwrap().start_time, end);
+}
+
+#[tokio::test]
+async fn open_interest_sampler() {
+    let transport = MockTransport::new().on::<GetFutureStats>(serde_json::json!({
+        "volume": 1000.23,
+        "nextFundingRate": 0.00025,
+        "nextFundingTime": "2019-03-29T03:00:00+00:00",
+        "openInterest": 21124.583,
+    }));
+    let mut sampler = OpenInterestSampler::new(
+        Rest::new(Options::default()).with_transport(transport.clone()),
+        vec!["BTC-PERP".to_owned(), "ETH-PERP".to_owned()],
+    );
+    sampler.sample().await.unwrap();
+    assert_eq!(transport.requests().len(), 2);
+    assert_eq!(sampler.series("BTC-PERP").len(), 1);
+    assert_eq!(sampler.series("BTC-PERP")[0].open_interest, dec!(21124.583));
+    assert_eq!(sampler.series("ETH-PERP").len(), 1);
+    assert!(sampler.series("SOL-PERP").is_empty());
+}
+
+#[test]
+fn volume_ranking() {
+    let market = |name: &str, volume_usd24h| Market {
+        market_type: MarketType::Future,
+        name: name.to_owned(),
+        underlying: None,
+        base_currency: None,
+        quote_currency: None,
+        enabled: true,
+        ask: None,
+        bid: None,
+        last: None,
+        post_only: false,
+        price_increment: dec!(1),
+        size_increment: dec!(0.001),
+        restricted: false,
+        min_provide_size: dec!(0.001),
+        price: None,
+        high_leverage_fee_exempt: false,
+        change1h: dec!(0),
+        change24h: dec!(0),
+        change_bod: dec!(0),
+        quote_volume24h: volume_usd24h,
+        volume_usd24h,
+        raw: Default::default(),
+    };
+    let markets = [
+        market("ETH-PERP", dec!(2000)),
+        market("BTC-PERP", dec!(3000)),
+        market("BTC-0930", dec!(100)),
+        market("SOL-PERP", dec!(2000)),
+    ];
+
+    let ranking = rank_by_volume_24h(
+        markets
+            .iter()
+            .filter(|market| market.name.ends_with("-PERP")),
+    );
+    let names: Vec<&str> = ranking.iter().map(|v| v.market.as_str()).collect();
+    // Ties are ordered by name
+    assert_eq!(names, vec!["BTC-PERP", "ETH-PERP", "SOL-PERP"]);
+    assert_eq!(ranking[0].volume_usd, dec!(3000));
+}
+
+#[test]
+fn market_rules() {
+    let market: Market = serde_json::from_value(serde_json::json!({
+        "name": "BTC-0628",
+        "baseCurrency": null,
+        "quoteCurrency": null,
+        "quoteVolume24h": 28914.76,
+        "change1h": 0.012,
+        "change24h": 0.0299,
+        "changeBod": 0.0156,
+        "highLeverageFeeExempt": false,
+        "minProvideSize": 0.001,
+        "type": "future",
+        "underlying": "BTC",
+        "enabled": true,
+        "ask": 3949.25,
+        "bid": 3949,
+        "last": 10579.52,
+        "postOnly": false,
+        "price": 10579.52,
+        "priceIncrement": 0.25,
+        "sizeIncrement": 0.0001,
+        "restricted": false,
+        "volumeUsd24h": 28914.76,
+    }))
+    .unwrap();
+    assert_eq!(market.round_price(dec!(3949.13)), dec!(3949.25));
+    assert_eq!(market.round_price(dec!(3949.1)), dec!(3949));
+    assert_eq!(market.round_size(dec!(0.12345)), dec!(0.1234));
+
+    let rules = MarketRules::from(&market);
+    assert_eq!(rules.round_price_for(Side::Buy, dec!(3949.2)), dec!(3949));
+    assert_eq!(
+        rules.round_price_for(Side::Sell, dec!(3949.01)),
+        dec!(3949.25)
+    );
+    let order = PlaceOrder::limit("BTC-0628", Side::Sell, dec!(3949.1), dec!(0.12345))
+        .normalized(&rules)
+        .unwrap();
+    assert_eq!(
+        (order.price, order.size),
+        (Some(dec!(3949.25)), dec!(0.1234))
+    );
+    assert!(matches!(
+        PlaceOrder::market("BTC-0628", Side::Buy, dec!(0.00005)).normalized(&rules),
+        Err(Error::SizeBelowMinimum(_, _))
+    ));
+    // Post-only orders have to provide at least `min_provide_size`
+    let post_only = PlaceOrder::limit("BTC-0628", Side::Buy, dec!(3949), dec!(0.0005))
+        .post_only()
+        .unwrap();
+    assert!(matches!(
+        post_only.clone().normalized(&rules),
+        Err(Error::SizeBelowMinimum(_, _))
+    ));
+    assert!(PlaceOrder {
+        size: dec!(0.001),
+        ..post_only
+    }
+    .normalized(&rules)
+    .is_ok());
+
+    let stats: FutureStats = serde_json::from_value(serde_json::json!({
+        "volume": 1000.23,
+        "nextFundingRate": 0.00025,
+        "nextFundingTime": "2019-03-29T03:00:00+00:00",
+        "expirationPrice": 3992.1,
+        "predictedExpirationPrice": 3993.6,
+        "strikePrice": 8182.35,
+        "openInterest": 21124.583,
+    }))
+    .unwrap();
+    assert_eq!(stats.next_funding_rate, Some(dec!(0.00025)));
+    assert_eq!(stats.open_interest, dec!(21124.583));
+}
+
+#[test]
+fn average_fill_price_of_fills() {
+    let fill = |price, size| -> Fill {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "market": "ETH-PERP",
+            "future": "ETH-PERP",
+            "baseCurrency": null,
+            "quoteCurrency": null,
+            "type": "order",
+            "side": "buy",
+            "price": price,
+            "size": size,
+            "orderId": 2,
+            "tradeId": 3,
+            "time": "2022-01-01T00:00:00.000000+00:00",
+            "fee": 0.0,
+            "feeRate": 0.0,
+            "feeCurrency": "USD",
+            "liquidity": "taker",
+        }))
+        .unwrap()
+    };
+
+    assert_eq!(average_fill_price(&[]), None);
+    // 1 at $100, 3 at $104 = $103
+    assert_eq!(
+        average_fill_price(&[fill(100, 1), fill(104, 3)]),
+        Some(dec!(103))
+    );
+}
+
+#[tokio::test]
+async fn poll_until_complete() {
+    use std::time::Duration;
+
+    let report = |status: &str| -> HistoricalBalances {
+        serde_json::from_value(serde_json::json!({
+            "id": 12345,
+            "accounts": ["main", "Subaccount"],
+            "time": 1613548800.0,
+            "endTime": 1613548800.0,
+            "status": status,
+            "error": false,
+            "results": [{
+                "account": "main",
+                "ticker": "BTC",
+                "size": 0.5,
+                "price": 50000.0,
+            }],
+        }))
+        .unwrap()
+    };
+
+    let mut statuses = vec!["complete", "processing", "requested"];
+    let complete = poll::poll_until(
+        Duration::from_millis(1),
+        Duration::from_secs(1),
+        GetHistoricalBalances::status,
+        || {
+            let report = report(statuses.pop().unwrap());
+            async move { Ok(report) }
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(complete.status, HistoricalBalancesStatus::Complete);
+    assert!(statuses.is_empty());
+
+    let pending = poll::poll_until(
+        Duration::from_millis(1),
+        Duration::from_millis(20),
+        GetHistoricalBalances::status,
+        || {
+            let report = report("processing");
+            async move { Ok(report) }
+        },
+    )
+    .await;
+    assert!(matches!(pending, Err(Error::Timeout)));
+}
+
+#[tokio::test]
+async fn poll_leveraged_token_creation() {
+    use std::time::Duration;
+
+    let transport = MockTransport::new().on::<GetLeveragedTokenCreations>(serde_json::json!([{
+        "id": 123,
+        "token": "BULL",
+        "requestedSize": 10,
+        "pending": false,
+        "createdSize": 10,
+        "price": 1000,
+        "cost": 10000,
+        "fee": 10,
+        "requestedAt": "2019-03-20T15:14:23.442314+00:00",
+        "fulfilledAt": "2019-03-20T15:14:23.444216+00:00"
+    }]));
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport);
+
+    let interval = Duration::from_millis(1);
+    let timeout = Duration::from_secs(1);
+    let creation = rest
+        .poll_leveraged_token_creation(123, interval, timeout)
+        .await
+        .unwrap();
+    assert_eq!(creation.created_size, Some(dec!(10)));
+    assert!(matches!(
+        rest.poll_leveraged_token_creation(124, interval, timeout)
+            .await,
+        Err(Error::JobFailed(_))
+    ));
+}
+
+#[test]
+fn address_book_guard() {
+    let json = r#"[{
+        "address": "rExampleAddress",
+        "coin": "XRP",
+        "fiat": false,
+        "id": 31189,
+        "isPrimetrust": false,
+        "isSwipeCard": false,
+        "lastUsedAt": "2020-05-22T15:12:29.611079+00:00",
+        "name": "exchange",
+        "tag": "123456",
+        "wallet": "xrp",
+        "whitelisted": true,
+        "whitelistedAfter": "2020-05-23T15:12:29.611079+00:00"
+    }]"#;
+    let saved: Vec<SavedAddress> = serde_json::from_str(json).unwrap();
+
+    assert!(withdraw::is_saved_address(
+        &saved,
+        "rExampleAddress",
+        Some("123456")
+    ));
+    assert!(!withdraw::is_saved_address(&saved, "rExampleAddress", None));
+    assert!(!withdraw::is_saved_address(
+        &saved,
+        "rOtherAddress",
+        Some("123456")
+    ));
+}
+
+#[test]
+fn withdrawal_address_validation() {
+    for (method, address, valid) in [
+        ("erc20", "0xdAC17F958D2ee523a2206206994597C13D831ec7", true),
+        ("erc20", "0xdAC17F958D2ee523a2206206994597C13D831ec", false),
+        ("trx", "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t", true),
+        ("trx", "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6T", false),
+        ("sol", "BQcdHdAQW1hczDbBi9hiegXAR7A98Q9jx3X3iBBBDiq4", true),
+        ("sol", "0xdAC17F958D2ee523a2206206994597C13D831ec7", false),
+        ("btc", "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", true),
+        ("btc", "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb", false),
+        ("btc", "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq", true),
+        ("btc", "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdp", false),
+    ] {
+        assert_eq!(is_valid_address(method, address), Some(valid), "{address}");
+    }
+    assert_eq!(is_valid_address("unknown", "anything"), None);
+
+    let json = r#"{
+        "id": "XRP",
+        "name": "XRP",
+        "collateral": true,
+        "usdFungible": false,
+        "isEtf": false,
+        "isToken": false,
+        "hidden": false,
+        "canDeposit": true,
+        "canWithdraw": true,
+        "canConvert": true,
+        "hasTag": true,
+        "collateralWeight": 0.9,
+        "fiat": false,
+        "methods": ["xrp"],
+        "erc20Contract": null,
+        "bep2Asset": null,
+        "trc20Contract": null,
+        "splMint": null,
+        "creditTo": null,
+        "spotMargin": true,
+        "tokenizedEquity": null,
+        "indexPrice": 0.5
+    }"#;
+    let xrp: CoinInfo = serde_json::from_str(json).unwrap();
+    assert!(matches!(
+        validate_withdrawal(&xrp, "rExampleAddress", None, None),
+        Err(Error::TagRequired(_))
+    ));
+    validate_withdrawal(&xrp, "rExampleAddress", Some("123456"), None).unwrap();
+    assert!(matches!(
+        validate_withdrawal(&xrp, "rExampleAddress", Some("123456"), Some("erc20")),
+        Err(Error::InvalidAddress(..))
+    ));
+}
+
+#[test]
+fn auto_lend_target() {
+    let config =
+        AutoLendConfig::new(vec!["USD".to_owned()], dec!(0.5), dec!(0.000001)).reserve(dec!(100));
+    let info = LendingInfo {
+        coin: "USD".to_owned(),
+        lendable: dec!(1100),
+        locked: dec!(0),
+        min_rate: Some(dec!(0.000001)),
+        offered: dec!(500),
+    };
+    let balance = WalletBalance {
+        coin: "USD".to_owned(),
+        free: dec!(900),
+        total: dec!(1100),
+        usd_value: Some(dec!(1100)),
+        spot_borrow: dec!(0),
+        available_without_borrow: dec!(900),
+    };
+
+    assert_eq!(config.target_size(&info, None), dec!(500));
+    assert!(!config.needs_update(&info, dec!(500)));
+    // Funds locked by open orders are not lent
+    assert_eq!(config.target_size(&info, Some(&balance)), dec!(400));
+    assert!(config.needs_update(&info, dec!(400)));
+    assert!(!config.needs_update(&info, dec!(499)));
+}
+
+#[tokio::test]
+async fn spot_margin_requests() {
+    let transport = MockTransport::new()
+        .on::<GetBorrowRates>(serde_json::json!([
+            { "coin": "BTC", "estimate": 0.0000152, "previous": 0.0000149 }
+        ]))
+        .on::<GetMyBorrowHistory>(serde_json::json!([{
+            "coin": "USD",
+            "cost": 0.0000153,
+            "rate": 0.0000153,
+            "size": 1.0,
+            "time": "2020-04-28T04:00:00+00:00"
+        }]))
+        .on::<CancelLendingOffer>(serde_json::Value::Null);
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport.clone());
+
+    let rates = rest.request(GetBorrowRates {}).await.unwrap();
+    assert_eq!(rates[0].previous, Some(dec!(0.0000149)));
+    let history = rest.request(GetMyBorrowHistory::default()).await.unwrap();
+    assert_eq!(history[0].cost, dec!(0.0000153));
+    rest.request(CancelLendingOffer::new("USD")).await.unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(requests[2].path, "/spot_margin/offers");
+    let body: serde_json::Value = serde_json::from_str(requests[2].body.as_ref().unwrap()).unwrap();
+    assert_eq!(body["coin"], "USD");
+    assert_eq!(body["size"], "0");
+}
+
+#[tokio::test]
+async fn get_leveraged_tokens() {
+    let transport = MockTransport::new().on::<GetLeveragedTokens>(serde_json::json!([{
+        "name": "HEDGE",
+        "description": "1x Short Bitcoin Token",
+        "underlying": "BTC-PERP",
+        "leverage": 1.0,
+        "outstanding": 22.0,
+        "pricePerShare": 2022.0,
+        "positionPerShare": 0.1,
+        "underlyingMark": 10000.0,
+        "contractAddress": "0x82211979d6d4e5e8ab5d8b9f8f87e8e0c5b6e4f0",
+        "change1h": 0.0,
+        "change24h": 0.0
+    }]));
+    let tokens = Rest::new(Options::default())
+        .with_transport(transport)
+        .request(GetLeveragedTokens {})
+        .await
+        .unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].name, "HEDGE");
+    assert_eq!(tokens[0].position_per_share, dec!(0.1));
+}
+
+#[test]
+fn leveraged_token_size_validation() {
+    let json = r#"{"token": "BULL", "balance": 1000.0}"#;
+    let balance: LeveragedTokenBalance = serde_json::from_str(json).unwrap();
+
+    RequestLeveragedTokenCreation::validated("BULL", dec!(1.5)).unwrap();
+    assert!(matches!(
+        RequestLeveragedTokenCreation::validated("BULL", dec!(0)),
+        Err(Error::SizeBelowMinimum(..))
+    ));
+    let redemption = RequestLeveragedTokenRedemption::validated(&balance, dec!(1000)).unwrap();
+    assert_eq!(redemption.token, "BULL");
+    assert!(matches!(
+        RequestLeveragedTokenRedemption::validated(&balance, dec!(1001)),
+        Err(Error::SizeExceedsLimit(..))
+    ));
+    assert!(matches!(
+        RequestLeveragedTokenRedemption::validated(&balance, dec!(-1)),
+        Err(Error::SizeBelowMinimum(..))
+    ));
+}
+
+#[test]
+fn options_chain() {
+    let json = r#"[{
+        "id": 1,
+        "option": {
+            "underlying": "BTC",
+            "type": "call",
+            "strike": 60000.0,
+            "expiry": "2021-12-31T03:00:00+00:00"
+        },
+        "price": 1500.0,
+        "size": 0.1,
+        "time": "2021-11-01T00:00:00+00:00"
+    }, {
+        "id": 2,
+        "option": {
+            "underlying": "BTC",
+            "type": "put",
+            "strike": 60000.0,
+            "expiry": "2021-12-31T03:00:00+00:00"
+        },
+        "price": 2500.0,
+        "size": 0.1,
+        "time": "2021-11-01T00:00:00+00:00"
+    }, {
+        "id": 3,
+        "option": {
+            "underlying": "BTC",
+            "type": "call",
+            "strike": 50000.0,
+            "expiry": "2021-11-26T03:00:00+00:00"
+        },
+        "price": 5000.0,
+        "size": 0.1,
+        "time": "2021-11-01T00:00:00+00:00"
+    }]"#;
+    let trades: Vec<OptionTrade> = serde_json::from_str(json).unwrap();
+
+    let mut chain = OptionsChain::default();
+    chain.apply_trades(&trades);
+
+    assert_eq!(chain.underlyings().collect::<Vec<_>>(), ["BTC"]);
+    let expiries: Vec<_> = chain.expiries("BTC").map(|(expiry, _)| *expiry).collect();
+    assert_eq!(expiries, [trades[2].option.expiry, trades[0].option.expiry]);
+
+    let strike = &chain.strikes("BTC", trades[0].option.expiry).unwrap()[&dec!(60000)];
+    assert_eq!(strike.call.as_ref().unwrap().last_price, Some(dec!(1500)));
+    assert_eq!(strike.put.as_ref().unwrap().last_price, Some(dec!(2500)));
+    assert_eq!(strike.call.as_ref().unwrap().greeks, Greeks::default());
+    assert!(chain.get(&trades[2].option).unwrap().greeks.delta.is_none());
+}
+
+#[tokio::test]
+async fn option_quote_requests() {
+    let option = OptionContract {
+        underlying: "BTC".to_owned(),
+        r#type: OptionType::Call,
+        strike: dec!(60000),
+        expiry: "2021-12-31T03:00:00Z".parse().unwrap(),
+    };
+    let transport = MockTransport::new()
+        .on::<CreateOptionQuoteRequest>(serde_json::json!({
+            "id": 3,
+            "option": option,
+            "side": "buy",
+            "size": 0.1,
+            "time": "2021-11-01T00:00:00+00:00",
+            "requestExpiry": "2021-11-01T00:05:00+00:00",
+            "status": "open",
+            "limitPrice": null
+        }))
+        .on::<GetOptionQuotes>(serde_json::json!([{
+            "collateral": 0.1,
+            "id": 7,
+            "price": 1500.0,
+            "quoteExpiry": null,
+            "requestId": 3,
+            "status": "open",
+            "time": "2021-11-01T00:01:00+00:00"
+        }]))
+        .on::<AcceptOptionQuote>(serde_json::json!({
+            "id": 7,
+            "option": option,
+            "price": 1500.0,
+            "quoteExpiry": null,
+            "quoterSide": "sell",
+            "requestId": 3,
+            "requestSide": "buy",
+            "size": 0.1,
+            "status": "filled",
+            "time": "2021-11-01T00:01:00+00:00"
+        }));
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport.clone());
+
+    let request = rest
+        .request(
+            CreateOptionQuoteRequest::new(&option, Side::Buy, dec!(0.1)).limit_price(dec!(2000)),
+        )
+        .await
+        .unwrap();
+    let quotes = rest
+        .request(GetOptionQuotes::new(request.id))
+        .await
+        .unwrap();
+    let accepted = rest
+        .request(AcceptOptionQuote::new(quotes[0].id))
+        .await
+        .unwrap();
+    assert_eq!(accepted.status, OptionQuoteStatus::Filled);
+    assert_eq!(accepted.option, Some(option));
+
+    let requests = transport.requests();
+    let body: serde_json::Value = serde_json::from_str(requests[0].body.as_ref().unwrap()).unwrap();
+    assert_eq!(body["expiry"], 1640919600);
+    assert_eq!(body["limitPrice"], "2000");
+    assert_eq!(requests[1].path, "/options/requests/3/quotes");
+    assert_eq!(requests[2].path, "/options/quotes/7/accept");
+}
+
+#[tokio::test]
+async fn staking_requests() {
+    let transport = MockTransport::new()
+        .on::<Stake>(serde_json::json!({
+            "coin": "SRM",
+            "createdAt": "2020-08-12T20:49:05.829024+00:00",
+            "id": 1,
+            "size": 12.0
+        }))
+        .on::<GetStakeBalances>(serde_json::json!([{
+            "coin": "SRM",
+            "lifetimeRewards": 0.5,
+            "scheduledToUnstake": 2.0,
+            "staked": 12.0
+        }]))
+        .on::<CreateUnstakeRequest>(serde_json::json!({
+            "coin": "SRM",
+            "createdAt": "2020-09-12T20:49:05.829024+00:00",
+            "id": 3,
+            "size": 2.0,
+            "status": "pending",
+            "unlockAt": "2020-09-19T20:49:05.829024+00:00"
+        }))
+        .on::<CancelUnstakeRequest>(serde_json::json!("Cancelled"))
+        .on::<GetStakingRewards>(serde_json::json!([{
+            "coin": "SRM",
+            "id": 5,
+            "size": 0.01,
+            "status": "complete",
+            "time": "2020-09-13T00:00:00+00:00"
+        }]));
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport.clone());
+
+    let stake = rest.request(Stake::new("SRM", dec!(12))).await.unwrap();
+    assert_eq!(stake.size, dec!(12));
+    let balances = rest.request(GetStakeBalances {}).await.unwrap();
+    assert_eq!(balances[0].scheduled_to_unstake, dec!(2));
+    let request = rest
+        .request(CreateUnstakeRequest::new("SRM", dec!(2)))
+        .await
+        .unwrap();
+    assert_eq!(request.status, UnstakeRequestStatus::Pending);
+    rest.request(CancelUnstakeRequest::new(request.id))
+        .await
+        .unwrap();
+    let rewards = rest.request(GetStakingRewards {}).await.unwrap();
+    assert_eq!(rewards[0].size, dec!(0.01));
+
+    let requests = transport.requests();
+    assert_eq!(requests[0].path, "/srm_stakes/stakes");
+    assert_eq!(
+        requests[0].body.as_deref(),
+        Some(r#"{"coin":"SRM","size":"12"}"#)
+    );
+    assert_eq!(requests[3].method, Method::DELETE);
+    assert_eq!(requests[3].path, "/staking/unstake_requests/3");
+}
+
+#[test]
+fn expiry_calendar() {
+    use chrono::TimeZone;
+
+    let future = |name: &str, expiry: Option<&str>, r#type: &str| -> Future {
+        serde_json::from_value(serde_json::json!({
+            "ask": null,
+            "bid": null,
+            "change1h": null,
+            "change24h": null,
+            "changeBod": null,
+            "volumeUsd24h": null,
+            "volume": null,
+            "description": name,
+            "enabled": true,
+            "expired": false,
+            "expiry": expiry,
+            "index": null,
+            "imfFactor": 0.002,
+            "last": null,
+            "lowerBound": 1.0,
+            "mark": null,
+            "name": name,
+            "perpetual": r#type == "perpetual",
+            "positionLimitWeight": 1.0,
+            "postOnly": false,
+            "priceIncrement": 1.0,
+            "sizeIncrement": 0.0001,
+            "underlying": "BTC",
+            "upperBound": 100000.0,
+            "type": r#type,
+        }))
+        .unwrap()
+    };
+    let futures = [
+        future("BTC-0325", Some("2022-03-25T03:00:00+00:00"), "future"),
+        future("BTC-PERP", None, "perpetual"),
+        future("BTC-1231", Some("2021-12-31T03:00:00+00:00"), "future"),
+    ];
+
+    let calendar = ExpiryCalendar::new(&futures).roll_period(chrono::Duration::days(7));
+    let names = |futures: &[DatedFuture]| -> Vec<String> {
+        futures.iter().map(|future| future.name.clone()).collect()
+    };
+    assert_eq!(names(calendar.futures("BTC")), ["BTC-1231", "BTC-0325"]);
+    assert_eq!(calendar.expiries().len(), 2);
+
+    let now = Utc.with_ymd_and_hms(2021, 12, 1, 0, 0, 0).unwrap();
+    assert_eq!(calendar.front("BTC", now).unwrap().name, "BTC-1231");
+    assert_eq!(calendar.back("BTC", now).unwrap().name, "BTC-0325");
+    assert!(calendar.front("BTC", now).unwrap().days_to_expiry(now) > 30.0);
+
+    // Within the roll period of the December future
+    let now = Utc.with_ymd_and_hms(2021, 12, 28, 0, 0, 0).unwrap();
+    assert_eq!(calendar.front("BTC", now).unwrap().name, "BTC-0325");
+    assert!(calendar.back("BTC", now).is_none());
+}
+
+#[test]
+fn move_contract_parsing() {
+    use chrono::TimeZone;
+
+    let reference = Utc.with_ymd_and_hms(2021, 12, 30, 12, 0, 0).unwrap();
+
+    let daily = MoveContract::parse("BTC-MOVE-1231", reference).unwrap();
+    assert_eq!(daily.underlying, "BTC");
+    assert_eq!(daily.period, MovePeriod::Daily);
+    assert_eq!(
+        daily.strike_time,
+        Utc.with_ymd_and_hms(2021, 12, 31, 0, 0, 0).unwrap()
+    );
+    assert_eq!(
+        daily.expiry,
+        Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap()
+    );
+
+    // The year is resolved relative to the reference
+    let weekly = MoveContract::parse("ETH-MOVE-WK-0107", reference).unwrap();
+    assert_eq!(weekly.period, MovePeriod::Weekly);
+    assert_eq!(
+        weekly.strike_time,
+        Utc.with_ymd_and_hms(2022, 1, 7, 0, 0, 0).unwrap()
+    );
+    assert_eq!(
+        weekly.expiry,
+        Utc.with_ymd_and_hms(2022, 1, 14, 0, 0, 0).unwrap()
+    );
+
+    let quarterly = MoveContract::parse("BTC-MOVE-2021Q4", reference).unwrap();
+    assert_eq!(quarterly.period, MovePeriod::Quarterly);
+    assert!(quarterly.is_active(reference));
+    assert_eq!(
+        quarterly.expiry,
+        Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap()
+    );
+
+    assert!(MoveContract::parse("BTC-PERP", reference).is_none());
+    assert!(MoveContract::parse("BTC-MOVE-2021Q5", reference).is_none());
+}
+
+#[test]
+fn coin_classification() {
+    assert_eq!(
+        classify_coin("BVOL"),
+        CoinClass::Volatility {
+            underlying: "BTC".to_owned(),
+            inverse: false
+        }
+    );
+    assert_eq!(
+        classify_coin("IBVOL"),
+        CoinClass::Volatility {
+            underlying: "BTC".to_owned(),
+            inverse: true
+        }
+    );
+    assert_eq!(classify_coin("ETHBULL").underlying(), Some("ETH"));
+    assert_eq!(
+        classify_coin("HEDGE"),
+        CoinClass::Leveraged {
+            underlying: "BTC".to_owned(),
+            kind: LeveragedTokenKind::Hedge
+        }
+    );
+    assert_eq!(classify_coin("SOL"), CoinClass::Coin);
+}
+
+#[tokio::test]
+#[ignore]
+async fn top_up_usd_collateral() {
+    let rest = init_api().await;
+    let usdc_fungible = rest
+        .request(GetCoins {})
+        .await
+        .unwrap()
+        .iter()
+        .any(|coin| coin.id == "USDC" && is_usd_equivalent(coin));
+    assert!(usdc_fungible);
+
+    // A threshold of zero never requires a conversion
+    let converted = rest
+        .top_up_usd_collateral(&["USDT"], dec!(0), ConversionMethod::Spot)
+        .await
+        .unwrap();
+    assert!(converted.is_empty());
+}
+
+#[test]
+fn stablecoin_collateral_gain() {
+    let mut usdt = serde_json::json!({
+        "id": "USDT",
+        "name": "USD Tether",
+        "collateral": true,
+        "usdFungible": false,
+        "isEtf": false,
+        "isToken": false,
+        "hidden": false,
+        "canDeposit": true,
+        "canWithdraw": true,
+        "canConvert": true,
+        "hasTag": false,
+        "collateralWeight": 0.975,
+        "fiat": false,
+        "methods": ["erc20", "trx", "sol"],
+        "erc20Contract": "0xdAC17F958D2ee523a2206206994597C13D831ec7",
+        "bep2Asset": null,
+        "trc20Contract": "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t",
+        "splMint": "BQcdHdAQW1hczDbBi9hiegXAR7A98Q9jx3X3iBBBDiq4",
+        "creditTo": null,
+        "spotMargin": true,
+        "tokenizedEquity": null,
+        "indexPrice": 1.0002
+    });
+    let coin: CoinInfo = serde_json::from_value(usdt.clone()).unwrap();
+    assert_eq!(collateral_gain(&coin), dec!(0.025));
+
+    usdt["collateral"] = false.into();
+    let coin: CoinInfo = serde_json::from_value(usdt).unwrap();
+    assert_eq!(collateral_gain(&coin), dec!(1));
+}
+
+#[test]
+fn risk_limits() {
+    let limits = RiskLimits::default()
+        .allow_market("BTC-PERP")
+        .max_order_size("BTC-PERP", dec!(1))
+        .max_order_notional(dec!(50000));
+    let order = PlaceOrder {
+        market: "BTC-PERP",
+        side: Side::Buy,
+        price: Some(dec!(40000)),
+        size: dec!(1),
+        ..Default::default()
+    };
+    limits.check(&order, None).unwrap();
+
+    let wrong_market = PlaceOrder {
+        market: "ETH-PERP",
+        ..order.clone()
+    };
+    assert!(matches!(
+        limits.check(&wrong_market, None),
+        Err(Error::RiskLimitExceeded(_))
+    ));
+
+    let too_large = PlaceOrder {
+        size: dec!(1.5),
+        ..order.clone()
+    };
+    assert!(limits.check(&too_large, None).is_err());
+
+    // Market orders are checked against the reference price, and rejected
+    // without one
+    let market_order = PlaceOrder {
+        price: None,
+        r#type: OrderType::Market,
+        ..order
+    };
+    assert!(matches!(
+        limits.check(&market_order, None),
+        Err(Error::RiskLimitExceeded(_))
+    ));
+    RiskLimits::default().check(&market_order, None).unwrap();
+    limits.check(&market_order, Some(dec!(45000))).unwrap();
+    assert!(limits.check(&market_order, Some(dec!(60000))).is_err());
+}
+
+#[tokio::test]
+async fn alert_sinks() {
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl AlertSink for Recorder {
+        async fn send(&self, alert: &Alert) -> Result<()> {
+            self.0.lock().unwrap().push(alert.to_string());
+            Ok(())
+        }
+    }
+
+    let first = Arc::new(Recorder::default());
+    let second = Arc::new(Recorder::default());
+    let sinks = vec![first.clone(), second.clone()];
+    let alert = Alert::new(Severity::Critical, "risk", "market ETH-PERP is not allowed");
+    sinks.send(&alert).await.unwrap();
+
+    for recorder in [first, second].iter() {
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec!["[Critical] risk: market ETH-PERP is not allowed"]
+        );
+    }
+}
+
+#[tokio::test]
+async fn external_signer() {
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl Signer for Recorder {
+        async fn sign(&self, payload: &[u8]) -> Result<String> {
+            let payload = String::from_utf8(payload.to_vec()).unwrap();
+            self.0.lock().unwrap().push(payload);
+            Ok("00".to_owned())
+        }
+    }
+
+    // Example from https://docs.ftx.com/#authentication
+    let signer = SecretSigner::new("T4lPid48QtjNxjLUFOcUZghD7CUJ7sTVsfuvQZF2");
+    assert_eq!(
+        signer.sign(b"1588591511721GET/api/markets").await.unwrap(),
+        "dbc62ec300b2624c580611858d94f2332ac636bb86eccfa1167a7777c496ee6f"
+    );
+    assert!(!format!("{:?}", signer).contains("T4lP"));
+
+    let transport = MockTransport::new()
+        .on::<GetOpenOrders>(serde_json::json!([]))
+        .on::<GetMarkets>(serde_json::json!([]));
+    let recorder = Arc::new(Recorder::default());
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        ..Default::default()
+    })
+    .with_signer(recorder.clone())
+    .with_transport(transport);
+    rest.request(GetOpenOrders::all_market()).await.unwrap();
+    // Public requests are not signed
+    rest.request(GetMarkets {}).await.unwrap();
+
+    let payloads = recorder.0.lock().unwrap();
+    assert_eq!(payloads.len(), 1);
+    assert!(payloads[0].ends_with("GET/api/orders"));
+}
+
+#[tokio::test]
+async fn mock_transport() {
+    let transport = MockTransport::new()
+        .on::<GetFutureStats>(serde_json::json!({
+            "volume": 1000.23,
+            "nextFundingRate": 0.00025,
+            "nextFundingTime": "2019-03-29T03:00:00+00:00",
+            "openInterest": 21124.583,
+        }))
+        .fail::<PlaceOrder>(StatusCode::BAD_REQUEST, "Not enough balances");
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport.clone());
+
+    let stats = rest.request(GetFutureStats::new("BTC-PERP")).await.unwrap();
+    assert_eq!(stats.open_interest, dec!(21124.583));
+    assert!(matches!(
+        rest.request(PlaceOrder::market("BTC-PERP", Side::Buy, dec!(1)))
+            .await,
+        Err(Error::Exchange {
+            status: StatusCode::BAD_REQUEST,
+            error: ApiError::InsufficientBalance,
+            ..
+        })
+    ));
+    // Requests without a route are not found
+    assert!(matches!(
+        rest.request(GetMarkets {}).await,
+        Err(Error::Exchange {
+            status: StatusCode::NOT_FOUND,
+            ..
+        })
+    ));
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].path, "/futures/BTC-PERP/stats");
+    assert_eq!(requests[1].method, Method::POST);
+    assert!(requests[1]
+        .body
+        .as_ref()
+        .unwrap()
+        .contains(r#""market":"BTC-PERP""#));
+    assert!(requests[1].headers.contains_key("FTX-SIGN"));
+    // The key is sent by the transport, not by a default header of the client
+    assert_eq!(requests[1].headers["FTX-KEY"], "key");
+}
+
+#[tokio::test]
+async fn proxy_and_endpoint_options() {
+    assert!(Options::default().proxy("socks5h://127.0.0.1:1080").is_ok());
+    assert!(Options::default().proxy("ftp://127.0.0.1:21").is_err());
+    assert!(Options::default().proxy("not a url").is_err());
+
+    // Invalid options set on the fields directly fail without panicking
+    let proxy = reqwest::Url::parse("ftp://127.0.0.1:21").unwrap();
+    assert!(matches!(
+        Rest::try_new(Options {
+            proxy: Some(proxy),
+            ..Default::default()
+        }),
+        Err(Error::Config(_))
+    ));
+    assert!(matches!(
+        Rest::try_new(Options {
+            key: Some("key\n".to_owned()),
+            ..Default::default()
+        }),
+        Err(Error::Config(_))
+    ));
+}
+
+#[cfg(feature = "mock-server")]
+#[tokio::test]
+async fn http_proxy() {
+    use crate::mock::MockServer;
+    use crate::options::Endpoint;
+
+    // Requests through a proxy name the absolute URL of the endpoint, which
+    // the mock server answers like a request for its path
+    let handle = MockServer::new("key", "secret")
+        .with_default_markets()
+        .start()
+        .await
+        .unwrap();
+    let options = Options::default()
+        .endpoint(Endpoint::custom(
+            "http://ftx.invalid/api",
+            "ws://ftx.invalid/ws",
+        ))
+        .proxy(&format!("http://{}", handle.rest_addr))
+        .unwrap()
+        .timeout(std::time::Duration::from_secs(5));
+
+    let markets = Rest::new(options).request(GetMarkets {}).await.unwrap();
+    assert!(markets.iter().any(|market| market.name == "BTC-PERP"));
+}
+
+#[tokio::test]
+async fn middleware_hooks() {
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct Recorder {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl Middleware for Recorder {
+        fn before(&self, request: &RequestInfo<'_>) {
+            let call = format!("before {} {}", request.method, request.path);
+            self.calls.lock().unwrap().push(call);
+        }
+
+        fn after(&self, request: &RequestInfo<'_>, response: &ResponseInfo) {
+            let call = format!(
+                "after {} {:?} {:?}",
+                request.endpoint, response.status, response.success
+            );
+            self.calls.lock().unwrap().push(call);
+        }
+    }
+
+    let recorder = std::sync::Arc::new(Recorder::default());
+    let transport = MockTransport::new()
+        .on::<GetMarket>(serde_json::json!({}))
+        .fail::<CancelOrder>(StatusCode::BAD_REQUEST, "Order already closed");
+    let rest = Rest::new(
+        Options {
+            key: Some("key".to_owned()),
+            secret: Some("secret".to_owned()),
+            ..Default::default()
+        }
+        .middleware(recorder.clone()),
+    )
+    .with_transport(transport);
+
+    // The body does not parse as a market, but the request succeeded
+    assert!(rest.request(GetMarket::new("BTC-PERP")).await.is_err());
+    assert!(rest.request(CancelOrder::new(1)).await.is_err());
+
+    assert_eq!(
+        *recorder.calls.lock().unwrap(),
+        [
+            "before GET /markets/BTC-PERP",
+            "after /markets/{} Some(200) Some(true)",
+            "before DELETE /orders/1",
+            "after /orders/{} Some(400) Some(false)",
+        ]
+    );
+}
+
+#[test]
+fn middleware_success_flag() {
+    use super::middleware::success_flag;
+
+    assert_eq!(success_flag(br#"{"success":true,"result":[]}"#), Some(true));
+    assert_eq!(
+        success_flag(b"{ \"success\" : false, \"error\": \"Not logged in\" }"),
+        Some(false)
+    );
+    assert_eq!(success_flag(br#"{"result":[],"success":true}"#), None);
+    assert_eq!(success_flag(br#"{"success":"yes"}"#), None);
+    assert_eq!(success_flag(b"<html>Bad Gateway</html>"), None);
+}
+
+#[cfg(feature = "optimized-access")]
+#[tokio::test]
+async fn borrowed_open_orders() {
+    use std::borrow::Cow;
+
+    let transport = MockTransport::new()
+        .on::<GetOpenOrders>(serde_json::json!([{
+            "createdAt": "2019-03-05T09:56:55.728933+00:00",
+            "filledSize": 10,
+            "future": "XRP-PERP",
+            "id": 9596912,
+            "market": "XRP-PERP",
+            "price": 0.306525,
+            "avgFillPrice": 0.306526,
+            "remainingSize": 31421,
+            "side": "sell",
+            "size": 31431,
+            "status": "open",
+            "type": "limit",
+            "reduceOnly": false,
+            "ioc": false,
+            "postOnly": false,
+            "clientId": "quote\"1\""
+        }]))
+        .fail::<GetOrder>(StatusCode::NOT_FOUND, "Order not found");
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport);
+
+    let buffer = rest
+        .request_borrowed(GetOpenOrders::all_market())
+        .await
+        .unwrap();
+    let orders = buffer.parse().unwrap();
+    assert_eq!(orders[0].market, "XRP-PERP");
+    // Escaped strings are copied
+    assert!(matches!(&orders[0].client_id, Some(Cow::Owned(id)) if id == "quote\"1\""));
+    assert_eq!(
+        orders[0].clone().into_owned().remaining_size,
+        Some(dec!(31421))
+    );
+
+    assert!(matches!(
+        rest.request_borrowed(GetOrder::new(1)).await,
+        Err(Error::Exchange {
+            error: ApiError::OrderNotFound,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn clock_skew() {
+    use std::time::Duration;
+
+    // The exchange clock is 5 seconds ahead
+    let server_time = || serde_json::json!(Utc::now() + chrono::Duration::seconds(5));
+    let transport = MockTransport::new()
+        .on::<GetTime>(server_time())
+        .on::<GetAccount>(serde_json::Value::Null);
+    let near_5s = |offset: chrono::Duration| (offset.num_milliseconds() - 5000).abs() < 1000;
+
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport.clone());
+    assert_eq!(rest.clock_offset(), chrono::Duration::zero());
+    assert!(near_5s(rest.sync_time().await.unwrap()));
+    assert!(near_5s(rest.for_subaccount("Bot").clock_offset()));
+
+    let _ = rest.request(GetAccount {}).await;
+    let request = transport.requests().pop().unwrap();
+    let timestamp: i64 = request.headers["FTX-TS"].to_str().unwrap().parse().unwrap();
+    assert!(near_5s(chrono::Duration::milliseconds(
+        timestamp - Utc::now().timestamp_millis()
+    )));
+
+    // Resyncs in the background, starting with the first request
+    let rest = Rest::new(Options::default().sync_time(Duration::from_secs(60)))
+        .with_transport(transport.clone());
+    let _ = rest.request(GetMarkets {}).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(near_5s(rest.clock_offset()));
+}
+
+#[tokio::test]
+async fn dead_mans_switch() {
+    use std::time::Duration;
+
+    let transport = MockTransport::new().on::<CancelAllAfter>(serde_json::Value::Null);
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport.clone());
+    let timeouts = || -> Vec<serde_json::Value> {
+        transport
+            .requests()
+            .iter()
+            .map(|request| serde_json::from_str(request.body.as_ref().unwrap()).unwrap())
+            .map(|body: serde_json::Value| body["timeout"].clone())
+            .collect()
+    };
+
+    assert!(rest
+        .spawn_dead_mans_switch(Duration::from_secs(1), Duration::from_secs(1), None)
+        .await
+        .is_err());
+
+    let switch = rest
+        .spawn_dead_mans_switch(
+            Duration::from_millis(1500),
+            Duration::from_millis(100),
+            None,
+        )
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(350)).await;
+    let refreshed = timeouts();
+    assert!(refreshed.len() >= 3);
+    assert!(refreshed.iter().all(|timeout| timeout == 2));
+
+    switch.disarm().await.unwrap();
+    let count = timeouts().len();
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(timeouts().len(), count);
+    assert_eq!(timeouts().last().unwrap(), 0);
+
+    // Refreshes that keep failing raise one critical alert
+    #[derive(Debug, Default)]
+    struct Recorder(std::sync::Mutex<Vec<Alert>>);
+
+    #[async_trait::async_trait]
+    impl AlertSink for Recorder {
+        async fn send(&self, alert: &Alert) -> Result<()> {
+            self.0.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    let transport = MockTransport::new()
+        .fail::<CancelAllAfter>(StatusCode::INTERNAL_SERVER_ERROR, "Try again later")
+        .on::<CancelAllAfter>(serde_json::Value::Null)
+        .times(1);
+    let recorder = Arc::new(Recorder::default());
+    let switch = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport.clone())
+    .spawn_dead_mans_switch(
+        Duration::from_millis(1500),
+        Duration::from_millis(100),
+        Some(recorder.clone()),
+    )
+    .await
+    .unwrap();
+    tokio::time::sleep(Duration::from_millis(350)).await;
+    drop(switch);
+    assert!(transport.requests().len() >= 4);
+    let alerts = recorder.0.lock().unwrap();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].severity, Severity::Critical);
+}
+
+#[tokio::test(start_paused = true)]
+async fn idempotent_order_placement() {
+    let ids = ClientIdGenerator::new("bot");
+    let (first, second) = (ids.next(), ids.next());
+    assert!(first.starts_with("bot-"));
+    assert_ne!(first, second);
+    assert_ne!(first, ClientIdGenerator::new("bot").next());
+
+    let order = PlaceOrder {
+        market: "ETH-PERP",
+        side: Side::Buy,
+        price: Some(dec!(1000)),
+        size: dec!(0.1),
+        ..Default::default()
+    };
+    let client = |transport: &MockTransport| {
+        Rest::new(Options {
+            key: Some("key".to_owned()),
+            secret: Some("secret".to_owned()),
+            ..Default::default()
+        })
+        .with_transport(transport.clone())
+    };
+    let placed = |transport: &MockTransport| -> Vec<serde_json::Value> {
+        transport
+            .requests()
+            .iter()
+            .filter(|request| request.path == "/orders")
+            .map(|request| serde_json::from_str(request.body.as_ref().unwrap()).unwrap())
+            .collect()
+    };
+    let existing = serde_json::to_value(order_info(7, Some("bot-x"), "open", false)).unwrap();
+
+    // Gateway timeout, but the order arrived
+    let transport = MockTransport::new()
+        .fail::<PlaceOrder>(StatusCode::GATEWAY_TIMEOUT, "Gateway timeout")
+        .on::<GetOrderByClientId>(existing.clone());
+    let order_info = client(&transport)
+        .place_order_idempotent(order.clone(), &ids)
+        .await
+        .unwrap();
+    assert_eq!(order_info.id, 7);
+    let bodies = placed(&transport);
+    assert_eq!(bodies.len(), 1);
+    let client_id = bodies[0]["clientId"].as_str().unwrap().to_owned();
+    assert!(client_id.starts_with("bot-"));
+    assert_eq!(
+        transport.requests().last().unwrap().path,
+        format!("/orders/by_client_id/{}", client_id)
+    );
+
+    // The client ID of the order is kept, and a duplicate is looked up
+    let transport = MockTransport::new()
+        .fail::<PlaceOrder>(StatusCode::BAD_REQUEST, "Duplicate client order ID")
+        .on::<GetOrderByClientId>(existing);
+    let order_info = client(&transport)
+        .place_order_idempotent(
+            PlaceOrder {
+                client_id: Some("bot-x"),
+                ..order.clone()
+            },
+            &ids,
+        )
+        .await
+        .unwrap();
+    assert_eq!(order_info.client_id.as_deref(), Some("bot-x"));
+    assert_eq!(placed(&transport)[0]["clientId"], "bot-x");
+
+    // Resubmitted with the same client ID while the order is not found
+    let transport = MockTransport::new()
+        .fail::<PlaceOrder>(StatusCode::GATEWAY_TIMEOUT, "Gateway timeout")
+        .fail::<GetOrderByClientId>(StatusCode::NOT_FOUND, "Order not found");
+    assert!(matches!(
+        client(&transport)
+            .place_order_idempotent(order.clone(), &ids)
+            .await,
+        Err(Error::Exchange {
+            status: StatusCode::GATEWAY_TIMEOUT,
+            ..
+        })
+    ));
+    let bodies = placed(&transport);
+    assert_eq!(bodies.len(), 3);
+    assert!(bodies
+        .iter()
+        .all(|body| body["clientId"] == bodies[0]["clientId"]));
+    // Each resubmit follows two lookups
+    let lookups = |transport: &MockTransport| {
+        transport
+            .requests()
+            .iter()
+            .filter(|request| request.path.starts_with("/orders/by_client_id/"))
+            .count()
+    };
+    assert_eq!(lookups(&transport), 4);
+
+    // Not resubmitted if the lookup fails otherwise
+    let transport = MockTransport::new()
+        .fail::<PlaceOrder>(StatusCode::GATEWAY_TIMEOUT, "Gateway timeout")
+        .fail::<GetOrderByClientId>(StatusCode::BAD_GATEWAY, "Bad gateway");
+    assert!(client(&transport)
+        .place_order_idempotent(order, &ids)
+        .await
+        .is_err());
+    assert_eq!(placed(&transport).len(), 1);
+}
+
+#[tokio::test]
+async fn lenient_responses() {
+    let mut order = serde_json::to_value(order_info(1, None, "open", false)).unwrap();
+    order["orderType"] = "twap".into();
+    order["newField"] = 42.into();
+    let transport = MockTransport::new().on::<GetOrder>(order.clone());
+    let client = |options: Options| Rest::new(options).with_transport(transport.clone());
+
+    // An optional field with an unknown variant fails strict parsing
+    assert!(matches!(
+        client(Options::default()).request(GetOrder::new(1)).await,
+        Err(Error::Json(_))
+    ));
+
+    let lenient = Options::default().strictness(Strictness::Lenient);
+    let info = client(lenient.clone())
+        .request(GetOrder::new(1))
+        .await
+        .unwrap();
+    assert_eq!(info.order_type, None);
+    assert!(info.raw().is_none());
+
+    let info = client(lenient.clone().capture_raw())
+        .request(GetOrder::new(1))
+        .await
+        .unwrap();
+    assert_eq!(info.raw.field("newField"), Some(&42.into()));
+    assert_eq!(info.raw.field("orderType"), Some(&"twap".into()));
+
+    // Required fields still fail
+    order["type"] = "twap".into();
+    let transport = MockTransport::new().on::<GetOrder>(order);
+    assert!(Rest::new(lenient)
+        .with_transport(transport)
+        .request(GetOrder::new(1))
+        .await
+        .is_err());
+
+    // Captured per item of lists
+    let orders: Vec<_> = (1..=2)
+        .map(|id| {
+            let mut order = serde_json::to_value(order_info(id, None, "open", false)).unwrap();
+            order["newField"] = id.into();
+            order
+        })
+        .collect();
+    let transport = MockTransport::new().on::<GetOpenOrders>(orders.into());
+    let orders = Rest::new(Options::default().capture_raw())
+        .with_transport(transport)
+        .request(GetOpenOrders::all_market())
+        .await
+        .unwrap();
+    for order in orders {
+        assert_eq!(order.raw.field("newField"), Some(&order.id.into()));
+    }
+}
+
+#[tokio::test]
+async fn export_csv() {
+    use crate::export::{export_fills, export_funding_payments, CsvWriter};
+    use chrono::TimeZone;
+
+    let fill = |id: u64, second: u32, r#type: &str| {
+        serde_json::json!({
+            "id": id,
+            "market": "BTC-PERP",
+            "future": "BTC-PERP",
+            "baseCurrency": null,
+            "quoteCurrency": null,
+            "type": r#type,
+            "side": "buy",
+            "price": "20000.5",
+            "size": "0.0001",
+            "orderId": 7,
+            "tradeId": null,
+            "time": format!("2022-01-01T00:00:{:02}+00:00", second),
+            "fee": "0.00000012345678901234",
+            "feeRate": "0.0002",
+            "feeCurrency": "USD",
+            "liquidity": "taker",
+        })
+    };
+    let payment = serde_json::json!({
+        "id": 1,
+        "future": "ETH-PERP",
+        "payment": "-0.1",
+        "time": "2022-01-01T01:00:00+00:00",
+        "rate": null,
+    });
+    let transport = MockTransport::new()
+        .on::<GetFills>(serde_json::json!([
+            fill(2, 30, "order"),
+            fill(1, 10, "a,\"b\"")
+        ]))
+        .on::<GetFundingPayments>(serde_json::json!([payment]));
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport.clone());
+    let (start, end) = (
+        Utc.timestamp_opt(1640995200, 0).unwrap(),
+        Utc.timestamp_opt(1641081600, 0).unwrap(),
+    );
+
+    let mut csv = Vec::new();
+    assert_eq!(
+        export_fills(
+            &rest,
+            Some("BTC-PERP"),
+            start,
+            end,
+            CsvWriter::new(&mut csv)
+        )
+        .await
+        .unwrap(),
+        2
+    );
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "id,time,market,future,baseCurrency,quoteCurrency,type,side,price,size,orderId,\
+         tradeId,fee,feeRate,feeCurrency,liquidity"
+    );
+    // Streamed newest first
+    assert!(lines[1].starts_with("2,2022-01-01T00:00:30Z,"));
+    assert_eq!(
+        lines[2],
+        "1,2022-01-01T00:00:10Z,BTC-PERP,BTC-PERP,,,\"a,\"\"b\"\"\",buy,20000.5,0.0001,7,,\
+         0.00000012345678901234,0.0002,USD,taker"
+    );
+
+    let mut csv = Vec::new();
+    assert_eq!(
+        export_funding_payments(&rest, None, start, end, CsvWriter::new(&mut csv))
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "id,time,future,payment,rate\r\n1,2022-01-01T01:00:00Z,ETH-PERP,-0.1,\r\n"
+    );
+    // Paginated until a page without new payments
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 3);
+    assert!(requests[2].path.contains("end_time=1640998800"));
+
+    // Only the header without rows
+    let rest =
+        rest.with_transport(MockTransport::new().on::<GetFundingPayments>(serde_json::json!([])));
+    let mut csv = Vec::new();
+    export_funding_payments(&rest, None, start, end, CsvWriter::new(&mut csv))
+        .await
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "id,time,future,payment,rate\r\n"
+    );
+}
+
+#[cfg(feature = "parquet")]
+#[tokio::test]
+async fn export_parquet() {
+    use crate::export::{export_funding_payments, ParquetWriter};
+    use arrow_array::{cast::AsArray, Array};
+    use chrono::TimeZone;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let transport = MockTransport::new().on::<GetFundingPayments>(serde_json::json!([
+        {
+            "id": 2,
+            "future": "ETH-PERP",
+            "payment": "0.00000012345678901234",
+            "time": "2022-01-01T02:00:00+00:00",
+            "rate": "0.0001",
+        },
+        {
+            "id": 1,
+            "future": "ETH-PERP",
+            "payment": "-0.1",
+            "time": "2022-01-01T01:00:00+00:00",
+            "rate": null,
+        }
+    ]));
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport);
+    let start = Utc.timestamp_opt(1640995200, 0).unwrap();
+
+    let mut parquet = Vec::new();
+    let writer = ParquetWriter::new(&mut parquet).unwrap();
+    let count = export_funding_payments(
+        &rest,
+        None,
+        start,
+        start + chrono::Duration::days(1),
+        writer,
+    )
+    .await
+    .unwrap();
+    assert_eq!(count, 2);
+
+    let batches: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(parquet))
+        .unwrap()
+        .build()
+        .unwrap()
+        .collect::<std::result::Result<_, _>>()
+        .unwrap();
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    let column = |name| batch.column_by_name(name).unwrap().as_string::<i32>();
+    assert_eq!(column("id").value(0), "2");
+    assert_eq!(column("payment").value(0), "0.00000012345678901234");
+    assert_eq!(column("time").value(1), "2022-01-01T01:00:00Z");
+    assert!(column("rate").is_null(1));
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_client() {
+    let transport = MockTransport::new()
+        .on::<GetMarkets>(serde_json::json!([]))
+        .fail::<GetOrder>(StatusCode::NOT_FOUND, "Order not found");
+    let rest = crate::blocking::Rest::new(Options::default()).with_transport(transport.clone());
+    assert!(rest.request(GetMarkets {}).unwrap().is_empty());
+    assert!(matches!(
+        rest.request(GetOrder::new(1)),
+        Err(Error::Exchange {
+            error: ApiError::OrderNotFound,
+            ..
+        })
+    ));
+
+    let subaccount = rest.for_subaccount("sub");
+    assert_eq!(subaccount.subaccount(), Some("sub"));
+    let report = subaccount.block_on(subaccount.as_async().cancel_orders(&[1]));
+    assert!(!report.is_all_ok());
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[cfg(feature = "config")]
+#[test]
+fn config_loading() {
+    use crate::config::{Config, ConfigError};
+    use crate::credentials::CredentialsError;
+    use crate::options::Endpoint;
+
+    let config: Config = r#"
+        markets = ["BTC-PERP"]
+
+        [client]
+        endpoint = "us"
+        subaccount = "bot"
+
+        [credentials]
+        source = "env"
+        key_var = "FTX_CONFIG_TEST_KEY"
+        secret_var = "FTX_CONFIG_TEST_SECRET"
+
+        [risk]
+        allowed_markets = ["BTC-PERP", "ETH-PERP"]
+        max_order_notional = 10000
+        max_order_size = { BTC-PERP = 0.5 }
+
+        [strategy]
+        spread = 0.001
+    "#
+    .parse()
+    .unwrap();
+    assert!(matches!(config.client.endpoint, Endpoint::Us));
+    assert_eq!(config.risk_limits().max_order_size["BTC-PERP"], dec!(0.5));
+
+    #[derive(serde::Deserialize)]
+    struct Settings {
+        spread: Decimal,
+    }
+    assert_eq!(config.strategy::<Settings>().unwrap().spread, dec!(0.001));
+
+    assert!(matches!(
+        config.options(),
+        Err(ConfigError::Credentials(CredentialsError::MissingEnv(name)))
+            if name == "FTX_CONFIG_TEST_KEY"
+    ));
+
+    // Unknown keys and inconsistent settings are rejected
+    assert!(matches!(
+        "[client]\nendpiont = \"us\"".parse::<Config>(),
+        Err(ConfigError::Parse(_))
+    ));
+    assert!(matches!(
+        "markets = [\"SOL-PERP\"]\n[risk]\nallowed_markets = [\"BTC-PERP\"]".parse::<Config>(),
+        Err(ConfigError::Invalid("markets", _))
+    ));
+    assert!(matches!(
+        "[risk]\nmax_order_notional = -1".parse::<Config>(),
+        Err(ConfigError::Invalid("risk.max_order_notional", _))
+    ));
+}
+
+#[cfg(feature = "encrypted-credentials")]
+#[test]
+fn encrypted_credentials() {
+    use crate::credentials::*;
+
+    let credentials = Credentials {
+        key: "key".to_owned(),
+        secret: "secret".to_owned(),
+        subaccount: Some("bot".to_owned()),
+    };
+    let dir = std::env::temp_dir();
+    let passphrase_path = dir.join(format!("ftx-credentials-{}-a.json", std::process::id()));
+    let key_path = dir.join(format!("ftx-credentials-{}-b.json", std::process::id()));
+    std::fs::write(
+        &passphrase_path,
+        encrypt_credentials(&credentials, "correct horse"),
+    )
+    .unwrap();
+    std::fs::write(
+        &key_path,
+        encrypt_credentials_with_key(&credentials, &[7; 32]),
+    )
+    .unwrap();
+
+    let provider = EncryptedFile::with_passphrase(&passphrase_path, "correct horse");
+    assert_eq!(provider.credentials().unwrap(), credentials);
+    let provider = EncryptedFile::with_key(&key_path, [7; 32]);
+    assert_eq!(provider.credentials().unwrap(), credentials);
+
+    assert!(matches!(
+        EncryptedFile::with_passphrase(&passphrase_path, "wrong").credentials(),
+        Err(CredentialsError::Decryption)
+    ));
+    assert!(matches!(
+        EncryptedFile::with_key(&key_path, [8; 32]).credentials(),
+        Err(CredentialsError::Decryption)
+    ));
+    assert!(matches!(
+        EncryptedFile::with_key(&passphrase_path, [7; 32]).credentials(),
+        Err(CredentialsError::Format(_))
+    ));
+    assert!(!format!("{:?}", credentials).contains("secret\""));
+
+    std::fs::remove_file(passphrase_path).unwrap();
+    std::fs::remove_file(key_path).unwrap();
+}
+
+#[cfg(feature = "mock-server")]
+#[tokio::test]
+async fn mock_server() {
+    use crate::mock::MockServer;
+    use crate::ws::{Channel, Data, Orderbook, Ws};
+    use futures::StreamExt;
+
+    let handle = MockServer::new("key", "secret")
+        .with_default_markets()
+        .start()
+        .await
+        .unwrap();
+    let api = Rest::new(handle.options());
+    let mut ws = Ws::connect(handle.options()).await.unwrap();
+    ws.subscribe(&[Channel::Orderbook("BTC-PERP".to_owned()), Channel::Fills])
+        .await
+        .unwrap();
+    let mut book = Orderbook::new("BTC-PERP".to_owned());
+    match ws.next().await.unwrap().unwrap() {
+        (_, Data::OrderbookData(data)) => book.update(&data).unwrap(),
+        data => panic!("Expected orderbook partial, got {:?}", data),
+    }
+
+    let markets = api.request(GetMarkets {}).await.unwrap();
+    assert_eq!(markets.len(), 3);
+    let market = api.request(GetMarket::new("BTC/USD")).await.unwrap();
+    assert_eq!(market.base_currency.as_deref(), Some("BTC"));
+    assert_eq!(market.ask, Some(dec!(40001)));
+
+    // Takes the 0.5 at 40001 and half of the 1.0 at 40002
+    let order = api
+        .request(PlaceOrder {
+            market: "BTC-PERP",
+            side: Side::Buy,
+            r#type: OrderType::Market,
+            size: dec!(1),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(order.status, OrderStatus::Closed);
+    assert_eq!(order.filled_size, Some(dec!(1)));
+    assert_eq!(order.avg_fill_price, Some(dec!(40001.5)));
+
+    let positions = api.request(GetPositions::new()).await.unwrap();
+    assert_eq!(positions[0].future, "BTC-PERP");
+    assert_eq!(positions[0].net_size, dec!(1));
+
+    let resting = api
+        .request(PlaceOrder {
+            market: "BTC-PERP",
+            side: Side::Sell,
+            price: Some(dec!(40010)),
+            r#type: OrderType::Limit,
+            size: dec!(0.1),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(resting.status, OrderStatus::Open);
+    assert_eq!(
+        api.request(GetOpenOrders::all_market())
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    api.request(CancelOrder::new(resting.id)).await.unwrap();
+    assert!(api
+        .request(GetOpenOrders::all_market())
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Fills and book updates arrive on the websocket, with valid checksums
+    let mut fills = 0;
+    let mut updates = 0;
+    while fills < 2 || updates < 3 {
+        match ws.next().await.unwrap().unwrap() {
+            (_, Data::Fill(fill)) => {
+                assert_eq!(fill.order_id, Some(order.id));
+                fills += 1;
+            }
+            (_, Data::OrderbookData(data)) => {
+                book.update(&data).unwrap();
+                updates += 1;
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(book.ask_price(), Some(&dec!(40002)));
+    assert_eq!(book.asks[&dec!(40002)], dec!(0.5));
+
+    // Requests signed with other credentials are rejected
+    let api = Rest::new(
+        handle
+            .options()
+            .authenticate("key".to_owned(), "wrong".to_owned()),
+    );
+    assert!(matches!(
+        api.request(GetPositions::new()).await,
+        Err(Error::Exchange {
+            status: StatusCode::UNAUTHORIZED,
+            error: ApiError::NotLoggedIn,
+            ..
+        })
+    ));
+}
+
+#[cfg(feature = "test-util")]
+fn fuzz_deserialization<T: serde::de::DeserializeOwned>(template: serde_json::Value) {
+    use crate::test_util::{adversarial, valid};
+    use proptest::test_runner::{Config, TestCaseError, TestRunner};
+
+    let mut runner = TestRunner::new(Config {
+        cases: 128,
+        ..Config::default()
+    });
+    runner
+        .run(&valid(&template), |json| {
+            serde_json::from_value::<T>(json.clone())
+                .map_err(|e| TestCaseError::fail(format!("{} in {}", e, json)))?;
+            Ok(())
+        })
+        .unwrap();
+    // Malformed responses must be rejected without panicking
+    runner
+        .run(&adversarial(&template), |json| {
+            let _ = serde_json::from_value::<T>(json);
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn response_deserialization_fuzzing() {
+    use crate::test_util::templates;
+    #[cfg(feature = "ws")]
+    use crate::ws::{OrderbookData, Ticker};
+
+    fuzz_deserialization::<Market>(templates::market());
+    fuzz_deserialization::<Future>(templates::future());
+    fuzz_deserialization::<Orderbook>(templates::orderbook());
+    fuzz_deserialization::<Trade>(templates::trade());
+    fuzz_deserialization::<Candle>(templates::candle());
+    fuzz_deserialization::<OrderInfo>(templates::order());
+    fuzz_deserialization::<Position>(templates::position());
+    fuzz_deserialization::<Account>(templates::account());
+    fuzz_deserialization::<WalletBalance>(templates::balance());
+    fuzz_deserialization::<Fill>(templates::fill());
+    fuzz_deserialization::<FundingPayment>(templates::funding_payment());
+    fuzz_deserialization::<FundingRate>(templates::funding_rate());
+    fuzz_deserialization::<FutureStats>(templates::future_stats());
+    fuzz_deserialization::<HistoricalCandle>(templates::historical_candle());
+    fuzz_deserialization::<QuoteId>(templates::quote_id());
+    fuzz_deserialization::<Quote>(templates::quote());
+    fuzz_deserialization::<FiatDepositInstructions>(templates::fiat_deposit_instructions());
+    fuzz_deserialization::<HistoricalBalances>(templates::historical_balances());
+    fuzz_deserialization::<LeveragedToken>(templates::leveraged_token());
+    fuzz_deserialization::<LeveragedTokenBalance>(templates::leveraged_token_balance());
+    fuzz_deserialization::<LeveragedTokenCreation>(templates::leveraged_token_creation());
+    fuzz_deserialization::<LeveragedTokenRedemption>(templates::leveraged_token_redemption());
+    fuzz_deserialization::<OptionQuoteRequest>(templates::option_quote_request());
+    fuzz_deserialization::<MyOptionQuoteRequest>(templates::my_option_quote_request());
+    fuzz_deserialization::<OptionQuote>(templates::option_quote());
+    fuzz_deserialization::<OptionsAccountInfo>(templates::options_account_info());
+    fuzz_deserialization::<OptionPosition>(templates::option_position());
+    fuzz_deserialization::<OptionFill>(templates::option_fill());
+    fuzz_deserialization::<OptionTrade>(templates::option_trade());
+    fuzz_deserialization::<TriggerOrderInfo>(templates::trigger_order());
+    fuzz_deserialization::<TriggerEvent>(templates::trigger_event());
+    fuzz_deserialization::<LendingRate>(templates::spot_margin_rate());
+    fuzz_deserialization::<BorrowRate>(templates::spot_margin_rate());
+    fuzz_deserialization::<LendingHistory>(templates::lending_history());
+    fuzz_deserialization::<MyLendingHistory>(templates::my_lending_history());
+    fuzz_deserialization::<MyBorrowHistory>(templates::my_borrow_history());
+    fuzz_deserialization::<LendingInfo>(templates::lending_info());
+    fuzz_deserialization::<StakeInfo>(templates::stake());
+    fuzz_deserialization::<StakeBalance>(templates::stake_balance());
+    fuzz_deserialization::<UnstakeRequest>(templates::unstake_request());
+    fuzz_deserialization::<StakingReward>(templates::staking_reward());
+    fuzz_deserialization::<Subaccount>(templates::subaccount());
+    fuzz_deserialization::<Create>(templates::created_subaccount());
+    fuzz_deserialization::<Balance>(templates::subaccount_balance());
+    fuzz_deserialization::<Transfer>(templates::transfer());
+    fuzz_deserialization::<WalletDepositAddress>(templates::deposit_address());
+    fuzz_deserialization::<WalletDeposit>(templates::deposit());
+    fuzz_deserialization::<WalletWithdrawal>(templates::withdrawal());
+    fuzz_deserialization::<Airdrop>(templates::airdrop());
+    fuzz_deserialization::<CoinInfo>(templates::coin_info());
+    fuzz_deserialization::<SavedAddress>(templates::saved_address());
+    #[cfg(feature = "ws")]
+    {
+        fuzz_deserialization::<Ticker>(templates::ticker());
+        fuzz_deserialization::<OrderbookData>(templates::orderbook_data());
+    }
+}
+
+#[test]
+fn schema_drift_detection() {
+    let drift = SchemaDrift::new();
+    let position = serde_json::json!({
+        "collateralUsed": 1, "cost": 1, "entryPrice": 1, "future": "BTC-PERP",
+        "initialMarginRequirement": 0.1, "longOrderSize": 0, "maintenanceMarginRequirement": 0.03,
+        "netSize": 1, "openSize": 1, "realizedPnl": 0, "shortOrderSize": 0, "side": "buy",
+        "size": 1, "unrealizedPnl": 0,
+        // Not in the model
+        "cumulativeFundingPayment": 12
+    });
+    let positions: Vec<Position> = drift
+        .record(GetPositions::PATH, &serde_json::json!([position]))
+        .unwrap();
+    assert_eq!(positions[0].net_size, dec!(1));
+
+    assert_eq!(
+        drift.report(),
+        vec![Drift {
+            endpoint: "/positions".to_owned(),
+            path: "[]".to_owned(),
+            r#struct: "Position",
+            new_fields: vec!["cumulativeFundingPayment".to_owned()],
+            removed_fields: vec![
+                "cumulativeBuySize",
+                "cumulativeSellSize",
+                "estimatedLiquidationPrice",
+                "recentAverageOpenPrice",
+                "recentBreakEvenPrice",
+                "recentPnl",
+            ],
+        }]
+    );
+
+    // Models still reject responses they cannot represent
+    assert!(drift
+        .record::<Vec<Position>>(GetPositions::PATH, &serde_json::json!([{ "cost": 1 }]))
+        .is_err());
+}
+
+/// Sends requests to the `MockTransport` of their endpoint, and fails to
+/// connect to endpoints without one.
+#[derive(Clone, Debug, Default)]
+struct EndpointTransports(std::sync::Arc<std::sync::Mutex<Vec<(String, MockTransport)>>>);
+
+impl EndpointTransports {
+    fn serve(&self, endpoint: &crate::options::Endpoint, transport: MockTransport) {
+        let mut transports = self.0.lock().unwrap();
+        transports.push((endpoint.rest().to_owned(), transport));
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for EndpointTransports {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let transport = self
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(rest, _)| request.url.starts_with(rest.as_str()))
+            .map(|(_, transport)| transport.clone());
+        match transport {
+            Some(transport) => transport.send(request).await,
+            None => Err(std::io::Error::from(std::io::ErrorKind::ConnectionRefused).into()),
+        }
+    }
+}
+
+#[tokio::test]
+async fn paginate_fills() {
+    use chrono::TimeZone;
+    use futures::TryStreamExt;
+
+    // Serves 5 fills, one per minute, with IDs 1 to 5 and at most 2 per
+    // page ending at `end_time`, inclusive
+    let transport = MockTransport::new().on_request::<GetFills>(|request| {
+        let query = request.path.strip_prefix("/fills?").unwrap();
+        let end_time = query.split('&').find_map(|pair| {
+            pair.strip_prefix("end_time=")
+                .map(|time| time.parse::<i64>().unwrap())
+        });
+        let in_range: Vec<i64> = (1..=5)
+            .rev()
+            .filter(|id| end_time.is_none_or(|end_time| id * 60 <= end_time))
+            .collect();
+        let fills: Vec<_> = in_range
+            .iter()
+            .take(2)
+            .map(|id| {
+                serde_json::json!({
+                    "id": id,
+                    "market": "ETH-PERP",
+                    "future": "ETH-PERP",
+                    "baseCurrency": null,
+                    "quoteCurrency": null,
+                    "type": "order",
+                    "side": "buy",
+                    "price": 100,
+                    "size": 1,
+                    "orderId": id,
+                    "tradeId": id,
+                    "time": Utc.timestamp_opt(id * 60, 0).unwrap(),
+                    "fee": 0.0,
+                    "feeRate": 0.0,
+                    "feeCurrency": "USD",
+                    "liquidity": "taker",
+                })
+            })
+            .collect();
+        serde_json::json!({ "success": true, "result": fills, "hasMoreData": in_range.len() > 2 })
+    });
+    let rest = Rest::new(Options::default()).with_transport(transport.clone());
+
+    let mut request = GetFills::all_markets();
+    request.limit = Some(2);
+    let fills: Vec<Fill> = rest.paginate(request).try_collect().await.unwrap();
+    let ids: Vec<Id> = fills.iter().map(|fill| fill.id).collect();
+    // Pages overlap by the oldest fill of the previous one
+    assert_eq!(ids, vec![5, 4, 3, 2, 1]);
+    assert_eq!(transport.requests().len(), 4);
+}
+
+#[tokio::test]
+async fn cache_public_requests() {
+    use std::time::Duration;
+
+    let transport = MockTransport::new()
+        .on::<GetMarkets>(serde_json::json!([]))
+        .on::<GetFutures>(serde_json::json!([]));
+    let requests = || transport.requests().len();
+    let rest = Rest::new(
+        Options::default().cache(
+            CachePolicy::new()
+                .ttl(GetMarkets::PATH, Duration::from_millis(100))
+                .stale_while_revalidate(Duration::from_millis(200)),
+        ),
+    )
+    .with_transport(transport.clone());
+
+    for _ in 0..3 {
+        assert!(rest.request(GetMarkets {}).await.unwrap().is_empty());
+    }
+    assert_eq!(requests(), 1);
+
+    // Stale responses are returned while they are refreshed once
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    rest.request(GetMarkets {}).await.unwrap();
+    rest.request(GetMarkets {}).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(requests(), 2);
+
+    // Endpoints without a TTL are not cached
+    rest.request(GetFutures {}).await.unwrap();
+    rest.request(GetFutures {}).await.unwrap();
+    assert_eq!(requests(), 4);
+
+    rest.clear_cache();
+    rest.request(GetMarkets {}).await.unwrap();
+    assert_eq!(requests(), 5);
+}
+
+#[tokio::test]
+async fn coalesce_concurrent_public_requests() {
+    use std::time::Duration;
+
+    let transport = MockTransport::new()
+        .on::<GetMarkets>(serde_json::json!([]))
+        .on::<GetFutures>(serde_json::json!([]))
+        .delay(Duration::from_millis(50));
+    let rest = Rest::new(Options::default()).with_transport(transport.clone());
+
+    let responses = futures::future::join_all((0..5).map(|_| rest.request(GetMarkets {}))).await;
+    assert!(responses.iter().all(|response| response.is_ok()));
+    assert_eq!(transport.requests().len(), 1);
+
+    // Different requests and later requests are sent on their own
+    let (markets, futures) =
+        futures::future::join(rest.request(GetMarkets {}), rest.request(GetFutures {})).await;
+    markets.unwrap();
+    futures.unwrap();
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn failover_to_backup_endpoint() {
+    use crate::options::Endpoint;
+    use std::time::Duration;
+
+    let main = Endpoint::custom("http://main.invalid/api", "ws://main.invalid/ws");
+    let backup = Endpoint::custom("http://backup.invalid/api", "ws://backup.invalid/ws");
+    let markets = || {
+        MockTransport::new()
+            .on::<GetMarkets>(serde_json::json!([]))
+            .on::<GetFutures>(serde_json::json!([]))
+    };
+    // The main endpoint cannot be reached until it recovers below
+    let endpoints = EndpointTransports::default();
+    let backup_transport = markets();
+    endpoints.serve(&backup, backup_transport.clone());
+    let rest = Rest::new(
+        Options::default().endpoint(main.clone()).failover(
+            Failover::new(vec![backup.clone()])
+                .failure_threshold(1)
+                .probe_interval(Duration::from_millis(50))
+                .probe_path(GetMarkets::PATH),
+        ),
+    )
+    .with_transport(endpoints.clone());
+
+    rest.request(GetMarkets {}).await.unwrap();
+    assert_eq!(rest.active_endpoint().rest(), backup.rest());
+    // Later requests go straight to the backup
+    rest.request(GetFutures {}).await.unwrap();
+    assert_eq!(backup_transport.requests().len(), 2);
+
+    // Fails back once a probe reaches the main endpoint again
+    let main_transport = markets();
+    endpoints.serve(&main, main_transport.clone());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!main_transport.requests().is_empty());
+    assert_ne!(rest.active_endpoint().rest(), backup.rest());
+    rest.request(GetMarkets {}).await.unwrap();
+    assert_eq!(backup_transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn route_orders_by_latency() {
+    use crate::options::Endpoint;
+    use std::time::Duration;
+
+    let slow = Endpoint::custom("http://slow.invalid/api", "ws://slow.invalid/ws");
+    let fast = Endpoint::custom("http://fast.invalid/api", "ws://fast.invalid/ws");
+    let markets = || MockTransport::new().on::<GetMarkets>(serde_json::json!([]));
+    let (slow_transport, fast_transport) = (markets().delay(Duration::from_millis(100)), markets());
+    let endpoints = EndpointTransports::default();
+    endpoints.serve(&slow, slow_transport.clone());
+    endpoints.serve(&fast, fast_transport.clone());
+    let rest = Rest::new(
+        Options::default().endpoint(slow).failover(
+            Failover::new(vec![fast])
+                .probe_path(GetMarkets::PATH)
+                .route_by_latency(Duration::from_secs(3600)),
+        ),
+    )
+    .with_transport(endpoints);
+
+    // The first request starts measuring, GET requests keep the main endpoint
+    rest.request(GetMarkets {}).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let status = rest.endpoint_status();
+    assert!(status.iter().all(|endpoint| endpoint.healthy));
+    assert!(status[0].latency.unwrap() > status[1].latency.unwrap());
+    assert_eq!(slow_transport.requests().len(), 2);
+    assert_eq!(fast_transport.requests().len(), 1);
+
+    let _ = rest.request(CancelOrder::new(1)).await;
+    assert_eq!(slow_transport.requests().len(), 2);
+    assert_eq!(fast_transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn probes_use_transport() {
+    use crate::options::Endpoint;
+    use std::time::Duration;
+
+    let transport = MockTransport::new().on::<GetMarkets>(serde_json::json!([]));
+    let backup = Endpoint::custom("http://backup.invalid/api", "ws://backup.invalid/ws");
+    let rest = Rest::new(
+        Options::default()
+            .endpoint(Endpoint::custom(
+                "http://main.invalid/api",
+                "ws://main.invalid/ws",
+            ))
+            .failover(
+                Failover::new(vec![backup])
+                    .probe_path(GetMarkets::PATH)
+                    .route_by_latency(Duration::from_secs(3600)),
+            ),
+    )
+    .with_transport(transport.clone());
+
+    rest.request(GetMarkets {}).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let probes: Vec<String> = transport.requests()[1..]
+        .iter()
+        .map(|request| request.url.clone())
+        .collect();
+    assert_eq!(
+        probes,
+        [
+            "http://main.invalid/api/markets",
+            "http://backup.invalid/api/markets"
+        ]
+    );
+    assert!(rest
+        .endpoint_status()
+        .iter()
+        .all(|endpoint| endpoint.latency.is_some()));
+}
+
+#[test]
+fn time_in_force() {
+    let order = PlaceOrder::limit("BTC-PERP", Side::Buy, dec!(20000), dec!(0.1))
+        .time_in_force(TimeInForce::PostOnly)
+        .unwrap();
+    let json = serde_json::to_value(&order).unwrap();
+    assert_eq!(json["ioc"], false);
+    assert_eq!(json["postOnly"], true);
+    // Price must be serialized even for market orders
+    let json = serde_json::to_value(PlaceOrder::market("BTC-PERP", Side::Sell, dec!(1))).unwrap();
+    assert!(json["price"].is_null());
+    assert_eq!(
+        (json["ioc"].clone(), json["postOnly"].clone()),
+        (false.into(), false.into())
+    );
+
+    assert!(matches!(
+        PlaceOrder::market("BTC-PERP", Side::Buy, dec!(1)).time_in_force(TimeInForce::PostOnly),
+        Err(Error::PostOnlyMarketOrder)
+    ));
+    assert!(matches!(
+        TimeInForce::from_flags(true, true),
+        Err(Error::ConflictingTimeInForce)
+    ));
+    assert_eq!(
+        TimeInForce::from_flags(true, false).unwrap(),
+        TimeInForce::Ioc
+    );
+    assert!(serde_json::from_value::<TimeInForce>(
+        serde_json::json!({ "ioc": true, "postOnly": true })
+    )
+    .is_err());
+
+    let order = PlaceOrder::limit("BTC-PERP", Side::Buy, dec!(20000), dec!(0.1))
+        .ioc()
+        .reduce_only();
+    assert_eq!(order.time_in_force, TimeInForce::Ioc);
+    assert!(order.validate().is_ok());
+    assert!(matches!(
+        PlaceOrder::market("BTC-PERP", Side::Buy, dec!(1)).post_only(),
+        Err(Error::PostOnlyMarketOrder)
+    ));
+
+    // Orders built field by field are checked before they are sent
+    let order = PlaceOrder {
+        market: "BTC-PERP",
+        r#type: OrderType::Limit,
+        size: dec!(1),
+        ..Default::default()
+    };
+    assert!(matches!(
+        order.validate(),
+        Err(Error::PlacingLimitOrderRequiresPrice)
+    ));
+    let order = PlaceOrder {
+        time_in_force: TimeInForce::PostOnly,
+        ..PlaceOrder::market("BTC-PERP", Side::Buy, dec!(1))
+    };
+    assert!(matches!(order.validate(), Err(Error::PostOnlyMarketOrder)));
+}
+
+#[tokio::test]
+async fn gtd_scheduler() {
+    let transport =
+        MockTransport::new().on::<CancelOrder>(serde_json::json!("Order queued for cancellation"));
+    let rest = || Rest::new(Options::default()).with_transport(transport.clone());
+    let path = std::env::temp_dir().join(format!("ftx-gtd-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let scheduler = GtdScheduler::new(rest()).persist_to(&path).unwrap();
+    let now = chrono::Utc::now();
+    scheduler
+        .schedule(1, now - chrono::Duration::seconds(1))
+        .unwrap();
+    scheduler
+        .schedule(2, now + chrono::Duration::hours(1))
+        .unwrap();
+
+    assert_eq!(scheduler.cancel_expired().await.unwrap(), vec![1]);
+    assert_eq!(transport.requests().len(), 1);
+
+    // A restarted scheduler continues with the remaining expiry
+    let restarted = GtdScheduler::new(rest()).persist_to(&path).unwrap();
+    assert_eq!(
+        restarted.expiries().keys().copied().collect::<Vec<_>>(),
+        vec![2]
+    );
+    assert!(restarted.cancel_expired().await.unwrap().is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn order_scheduler() {
+    let transport = MockTransport::new().on::<PlaceOrder>(serde_json::json!({
+        "id": 9,
+        "market": "BTC-PERP",
+        "type": "limit",
+        "side": "buy",
+        "price": 20000,
+        "size": 0.1,
+        "status": "new",
+        "createdAt": "2022-01-01T00:00:00+00:00",
+    }));
+    let rest = || Rest::new(Options::default()).with_transport(transport.clone());
+    let path = std::env::temp_dir().join(format!("ftx-schedule-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let scheduler = OrderScheduler::new(rest()).persist_to(&path).unwrap();
+    let order = PlaceOrder::limit("BTC-PERP", Side::Buy, dec!(20000), dec!(0.1))
+        .post_only()
+        .unwrap();
+    let dip = scheduler
+        .schedule(
+            &order,
+            Trigger::PriceBelow {
+                market: "BTC-PERP".to_owned(),
+                price: dec!(20100),
+            },
+        )
+        .unwrap();
+    let later = scheduler
+        .schedule(
+            &order,
+            Trigger::At(chrono::Utc::now() + chrono::Duration::hours(1)),
+        )
+        .unwrap();
+    let cancelled = scheduler
+        .schedule(
+            &order,
+            Trigger::FundingRateAbove {
+                future: "BTC-PERP".to_owned(),
+                rate: dec!(0.0001),
+            },
+        )
+        .unwrap();
+    assert!(scheduler.cancel(cancelled).unwrap());
+    // Orders FTX would reject are refused when scheduled
+    let invalid = PlaceOrder {
+        price: None,
+        ..order.clone()
+    };
+    assert!(matches!(
+        scheduler.schedule(&invalid, Trigger::At(chrono::Utc::now())),
+        Err(Error::PlacingLimitOrderRequiresPrice)
+    ));
+
+    assert!(scheduler.submit_due().await.unwrap().is_empty());
+    assert!(scheduler
+        .observe_price("BTC-PERP", dec!(20200))
+        .await
+        .unwrap()
+        .is_empty());
+    let submitted = scheduler
+        .observe_price("BTC-PERP", dec!(20050))
+        .await
+        .unwrap();
+    assert_eq!(submitted.len(), 1);
+    assert_eq!(submitted[0].0, dip);
+    assert_eq!(submitted[0].1.as_ref().unwrap().id, 9);
+    // Placed only once
+    assert!(scheduler
+        .observe_price("BTC-PERP", dec!(20000))
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(transport.requests().len(), 1);
+
+    let restarted = OrderScheduler::new(rest()).persist_to(&path).unwrap();
+    let scheduled = restarted.scheduled();
+    assert_eq!(scheduled.keys().copied().collect::<Vec<_>>(), vec![later]);
+    assert_eq!(scheduled[&later].order, PreparedOrder::from(&order));
+    assert_eq!(scheduled[&later].order.time_in_force, TimeInForce::PostOnly);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn audit_log() {
+    let transport =
+        MockTransport::new().on::<CancelOrder>(serde_json::json!("Order queued for cancellation"));
+    let path = std::env::temp_dir().join(format!("ftx-audit-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let rest = Rest::new(Options::default().audit_log(AuditLog::open(&path).unwrap()))
+        .with_transport(transport);
+
+    rest.request(CancelOrder::new(1)).await.unwrap();
+    // Not logged
+    let _ = rest.request(GetMarkets {}).await;
+    let _ = rest
+        .request(
+            RequestWithdrawal::new(
+                "USDT",
+                dec!(10),
+                "0x83a127952d266A6eA306c40Ac62A4a70668FE3BE",
+            )
+            .password("hunter2"),
+        )
+        .await;
+    drop(rest);
+
+    assert_eq!(AuditLog::verify(&path).unwrap(), 4);
+    let log = std::fs::read_to_string(&path).unwrap();
+    let entries: Vec<AuditEntry> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(entries[0].kind, AuditKind::Request);
+    assert_eq!(entries[0].path, "/orders/1");
+    assert_eq!(entries[1].kind, AuditKind::Response);
+    assert_eq!(entries[1].correlation_id, entries[0].correlation_id);
+    assert_ne!(entries[2].correlation_id, entries[0].correlation_id);
+    assert_eq!(entries[2].body["password"], "[redacted]");
+    assert!(!log.contains("hunter2"));
+
+    std::fs::write(&path, log.replacen("/orders/1", "/orders/2", 1)).unwrap();
+    assert!(matches!(
+        AuditLog::verify(&path),
+        Err(Error::AuditLogTampered(0))
+    ));
+    assert!(AuditLog::open(&path).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn shared_rate_limit() {
+    use std::sync::Arc;
+
+    // Two clients sharing a budget of 3 requests per second
+    let store = Arc::new(MemoryStore::new());
+    let a = RateLimit::new(3).store(store.clone());
+    let b = RateLimit::new(3).store(store);
+
+    let mut windows = std::collections::HashMap::new();
+    for limit in [&a, &b, &a, &b, &a, &b, &a, &b] {
+        limit.acquire(&Method::GET, "/markets").await.unwrap();
+        *windows
+            .entry(chrono::Utc::now().timestamp_millis() / 1000)
+            .or_insert(0) += 1;
+    }
+    assert!(windows.len() >= 3);
+    assert!(windows.values().all(|requests| *requests <= 3));
+}
+
+#[tokio::test]
+async fn rate_limit_budgets_and_backoff() {
+    use std::time::Instant;
+
+    // Rejects the first two requests with 429
+    let transport = MockTransport::new()
+        .on::<GetWalletBalances>(serde_json::json!([]))
+        .fail::<GetWalletBalances>(StatusCode::TOO_MANY_REQUESTS, "Try again later")
+        .times(2);
+
+    let limit = RateLimit::new(30)
+        .orders_per_second(10)
+        .endpoint("/wallet", 5);
+    let rest =
+        Rest::new(Options::default().rate_limit(limit.clone())).with_transport(transport.clone());
+
+    // Retried after backing off for 0.5s and 1s
+    let start = Instant::now();
+    rest.request(GetWalletBalances {}).await.unwrap();
+    assert!(start.elapsed() >= std::time::Duration::from_millis(1500));
+    assert_eq!(transport.requests().len(), 3);
+    assert_eq!(limit.backoff(), None);
+
+    rest.request(GetWalletBalances {}).await.unwrap();
+    let remaining = rest.rate_limit().unwrap().remaining();
+    let wallet = remaining.iter().find(|b| b.category == "/wallet").unwrap();
+    assert!(wallet.limit == 5 && wallet.remaining < 5);
+    let orders = remaining.iter().find(|b| b.category == "orders").unwrap();
+    assert_eq!(orders.remaining, 10);
+
+    // Gives up once the retries are exhausted
+    let transport = MockTransport::new()
+        .on::<GetWalletBalances>(serde_json::json!([]))
+        .fail::<GetWalletBalances>(StatusCode::TOO_MANY_REQUESTS, "Try again later")
+        .times(1);
+    let rest = Rest::new(Options::default().rate_limit(RateLimit::new(30).max_retries(0)))
+        .with_transport(transport);
+    assert!(matches!(
+        rest.request(GetWalletBalances {}).await,
+        Err(Error::Exchange {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            ..
+        })
+    ));
+    assert!(rest.rate_limit().unwrap().backoff().is_some());
+
+    // Not retried again by the retry policy
+    let transport =
+        MockTransport::new().fail::<GetWalletBalances>(StatusCode::TOO_MANY_REQUESTS, "Slow down");
+    let rest = Rest::new(
+        Options {
+            key: Some("key".to_owned()),
+            secret: Some("secret".to_owned()),
+            ..Default::default()
+        }
+        .rate_limit(RateLimit::new(30).max_retries(0))
+        .retry(ExponentialBackoff::new().initial_backoff(std::time::Duration::from_millis(10))),
+    )
+    .with_transport(transport.clone());
+    assert!(rest.request(GetWalletBalances {}).await.is_err());
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[test]
+fn api_error_messages() {
+    assert_eq!(
+        ApiError::from_message("Not logged in: Invalid signature"),
+        ApiError::InvalidSignature
+    );
+    assert_eq!(
+        ApiError::from_message("Do not send more than 2 orders per 200ms"),
+        ApiError::RateLimited
+    );
+    assert_eq!(
+        ApiError::from_message("Account does not have enough margin for order."),
+        ApiError::InsufficientMargin
+    );
+    assert_eq!(
+        ApiError::from_message("Size too small for provide"),
+        ApiError::SizeTooSmall
+    );
+    assert_eq!(
+        ApiError::from_message("Trigger price too high"),
+        ApiError::PriceTooHigh
+    );
+    assert_eq!(
+        ApiError::from_message("No such market: BTC-PERPS"),
+        ApiError::NoSuchMarket
+    );
+    assert_eq!(
+        ApiError::from_message("Duplicate client order ID"),
+        ApiError::DuplicateClientId
+    );
+    assert_eq!(
+        ApiError::from_message("Something new"),
+        ApiError::Unknown("Something new".to_owned())
+    );
+}
+
+#[tokio::test]
+async fn exchange_error_keeps_message() {
+    let transport = MockTransport::new().fail::<PlaceOrder>(
+        StatusCode::TOO_MANY_REQUESTS,
+        "Do not send more than 2 orders per 200ms",
+    );
+    let rest = Rest::new(Options {
+        key: Some("key".to_owned()),
+        secret: Some("secret".to_owned()),
+        ..Default::default()
+    })
+    .with_transport(transport);
+
+    let error = rest
+        .request(PlaceOrder::market("BTC-PERP", Side::Buy, dec!(1)))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        &error,
+        Error::Exchange {
+            error: ApiError::RateLimited,
+            ..
+        }
+    ));
+    assert_eq!(
+        error.to_string(),
+        "Api error: Do not send more than 2 orders per 200ms"
+    );
+}
+
+#[tokio::test]
+async fn retry_transient_failures() {
+    use std::time::{Duration, Instant};
+
+    let policy = ExponentialBackoff::new()
+        .initial_backoff(Duration::from_millis(10))
+        .max_retries(2);
+    assert!(policy.is_retryable(&Method::GET, "/markets"));
+    assert!(policy.is_retryable(&Method::DELETE, "/orders/by_client_id/abc"));
+    assert!(!policy.is_retryable(&Method::DELETE, "/orders/123"));
+    assert!(!policy.is_retryable(&Method::POST, "/orders"));
+    assert!(policy
+        .clone()
+        .retry::<CancelOrder>()
+        .is_retryable(&Method::DELETE, "/orders/123"));
+    assert_eq!(policy.backoff(3), None);
+
+    // Retried after server errors, honoring Retry-After
+    let transport = MockTransport::new()
+        .on::<GetMarkets>(serde_json::json!([]))
+        .fail::<GetMarkets>(StatusCode::TOO_MANY_REQUESTS, "Try again later")
+        .retry_after(Duration::from_secs(1))
+        .times(1)
+        .fail::<GetMarkets>(StatusCode::SERVICE_UNAVAILABLE, "Try again later")
+        .times(1);
+    let rest =
+        Rest::new(Options::default().retry(policy.clone())).with_transport(transport.clone());
+    let start = Instant::now();
+    rest.request(GetMarkets {}).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert_eq!(transport.requests().len(), 3);
+
+    // Orders are not retried
+    let transport = MockTransport::new()
+        .fail::<PlaceOrder>(StatusCode::SERVICE_UNAVAILABLE, "Try again later")
+        .times(1);
+    let rest =
+        Rest::new(Options::default().retry(policy.clone())).with_transport(transport.clone());
+    assert!(rest
+        .request(PlaceOrder::market("BTC-PERP", Side::Buy, dec!(1)))
+        .await
+        .is_err());
+    assert_eq!(transport.requests().len(), 1);
+
+    // Retried after transports other than reqwest time out
+    #[derive(Debug, Default)]
+    struct TimesOutOnce(std::sync::atomic::AtomicBool);
+
+    #[async_trait::async_trait]
+    impl Transport for TimesOutOnce {
+        async fn send(&self, _: HttpRequest) -> Result<HttpResponse> {
+            if self.0.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                Ok(HttpResponse::ok(r#"{"success":true,"result":[]}"#))
+            } else {
+                Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into())
+            }
+        }
+    }
+
+    let rest =
+        Rest::new(Options::default().retry(policy.clone())).with_transport(TimesOutOnce::default());
+    assert!(rest.request(GetMarkets {}).await.unwrap().is_empty());
+
+    // Gives up after `max_retries`
+    let transport = MockTransport::new()
+        .fail::<GetMarkets>(StatusCode::INTERNAL_SERVER_ERROR, "Try again later");
+    let rest = Rest::new(Options::default().retry(policy)).with_transport(transport.clone());
+    assert!(matches!(
+        rest.request(GetMarkets {}).await,
+        Err(Error::Exchange {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            ..
+        })
+    ));
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[cfg(feature = "redis")]
+#[tokio::test]
+#[ignore]
+async fn redis_rate_limit_store() {
+    // Needs a Redis server at REDIS_URL
+    dotenv().ok();
+    let store = RedisStore::open(&var("REDIS_URL").unwrap()).unwrap();
+    let key = format!("ftx-test:requests:{}", std::process::id());
+    let ttl = std::time::Duration::from_secs(2);
+    assert_eq!(store.increment(&key, ttl).await.unwrap(), 1);
+    assert_eq!(store.increment(&key, ttl).await.unwrap(), 2);
+    assert!(matches!(
+        RedisStore::open("not a url"),
+        Err(Error::Config(_))
+    ));
+}
+
+#[cfg(feature = "mock-server")]
+#[tokio::test]
+async fn live_order_tracker() {
+    use crate::engine::{LiveOrderTracker, OrderChange};
+    use crate::mock::MockServer;
+    use std::time::Duration;
+
+    let handle = MockServer::new("key", "secret")
+        .with_default_markets()
+        .start()
+        .await
+        .unwrap();
+    let rest = Rest::new(handle.options());
+    let resting = rest
+        .request(PlaceOrder::limit("BTC-PERP", Side::Buy, dec!(1000), dec!(0.1)).client_id("bid-1"))
+        .await
+        .unwrap();
+
+    // Orders placed before connecting are seeded from the snapshot
+    let mut tracker = LiveOrderTracker::connect(handle.options()).await.unwrap();
+    assert_eq!(tracker.open_orders_for("BTC-PERP").count(), 1);
+    assert_eq!(tracker.status("bid-1"), Some(resting.status));
+
+    rest.request(CancelOrder::new(resting.id)).await.unwrap();
+    loop {
+        let change = tokio::time::timeout(Duration::from_secs(5), tracker.next_change())
+            .await
+            .unwrap()
+            .unwrap();
+        if let OrderChange::Closed(order) = change {
+            assert_eq!(order.id, resting.id);
+            break;
+        }
+    }
+    assert_eq!(tracker.status("bid-1"), Some(OrderStatus::Closed));
+    assert_eq!(tracker.open_orders_for("BTC-PERP").count(), 0);
+}
+
+#[cfg(feature = "mock-server")]
+#[tokio::test]
+async fn get_fills_of_account() {
+    use crate::mock::MockServer;
+
+    let handle = MockServer::new("key", "secret")
+        .with_default_markets()
+        .start()
+        .await
+        .unwrap();
+    let rest = Rest::new(handle.options());
+    let order = rest
+        .request(PlaceOrder::market("BTC-PERP", Side::Buy, dec!(0.1)))
+        .await
+        .unwrap();
+
+    let fills = rest.request(GetFills::all_markets()).await.unwrap();
+    assert_eq!(fills.len(), 1);
+    let fill = &fills[0];
+    assert_eq!(fill.order_id, Some(order.id));
+    assert_eq!(fill.market.as_deref(), Some("BTC-PERP"));
+    assert_eq!(fill.size, dec!(0.1));
+    assert_eq!(fill.liquidity, Liquidity::Taker);
+    assert!(fill.fee > dec!(0));
+
+    assert!(rest
+        .request(GetFills::new("ETH-PERP"))
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn subaccount_scoped_client() {
+    let transport = MockTransport::new().on_path(Method::GET, "/positions", serde_json::json!([]));
+    let main = Rest::new(Options::default().authenticate("key".to_owned(), "secret".to_owned()))
+        .with_transport(transport.clone());
+    let bot = main.for_subaccount("bot");
+    assert_eq!(main.subaccount(), None);
+    assert_eq!(bot.subaccount(), Some("bot"));
+
+    bot.request(RawGet::new("/positions", Default::default()))
+        .await
+        .unwrap();
+    main.request(RawGet::new("/positions", Default::default()))
+        .await
+        .unwrap();
+    let requests = transport.requests();
+    assert_eq!(requests[0].headers["FTX-SUBACCOUNT"], "bot");
+    // Signed with the credentials of the main account
+    assert!(requests[0].headers.contains_key("FTX-SIGN"));
+    assert!(!requests[1].headers.contains_key("FTX-SUBACCOUNT"));
+}
+
+#[test]
+fn raw_request_endpoint() {
+    let raw = RawGet::new("/markets/BTC-PERP/orderbook?depth=20", Default::default());
+    assert_eq!(raw.endpoint(), "/markets/BTC-PERP/orderbook");
+    assert_eq!(GetMarkets {}.endpoint(), GetMarkets::PATH);
+}
+
+#[test]
+fn request_withdrawal_body() {
+    let withdrawal = RequestWithdrawal::new("XRP", dec!(25), "rEb8TK3gBgk5auZkwc6sHnwrGVJH8DuaLh")
+        .tag("123456")
+        .code("654321");
+    assert_eq!(
+        serde_json::to_value(&withdrawal).unwrap(),
+        serde_json::json!({
+            "coin": "XRP",
+            "size": "25",
+            "address": "rEb8TK3gBgk5auZkwc6sHnwrGVJH8DuaLh",
+            "tag": "123456",
+            "code": "654321",
+        })
+    );
+}
+
+#[test]
+fn request_fiat_withdrawal_body() {
+    let withdrawal = RequestFiatWithdrawal {
+        coin: "USD".to_owned(),
+        size: dec!(100),
+        saved_address_id: 42,
+        ..Default::default()
+    };
+    assert_eq!(
+        serde_json::to_value(&withdrawal).unwrap(),
+        serde_json::json!({
+            "coin": "USD",
+            "size": "100",
+            "savedAddressId": 42,
+        })
+    );
+}
@@ -0,0 +1,258 @@
+use super::retry::path_matches;
+use super::{Request, Rest, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use reqwest::{header::HeaderMap, Client, Method, StatusCode};
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Request of the `Rest` client, signed and ready to be sent.
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    /// Path below `/api` with the query, e.g. `/markets/BTC-PERP`
+    pub path: String,
+    pub headers: HeaderMap,
+    pub body: Option<String>,
+}
+
+/// Response to a single HTTP request.
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    /// Seconds of the `Retry-After` header
+    pub retry_after: Option<Duration>,
+    pub body: Bytes,
+}
+
+impl HttpResponse {
+    /// Successful response with `body`, e.g. from the cache.
+    pub fn ok(body: impl Into<Bytes>) -> Self {
+        Self {
+            status: StatusCode::OK,
+            retry_after: None,
+            body: body.into(),
+        }
+    }
+}
+
+/// Sends the HTTP requests of the `Rest` client, see `Rest::with_transport`.
+/// Sent with reqwest by default.
+///
+/// Implementations return `Error::Io` with `ErrorKind::ConnectionRefused`
+/// if the endpoint could not be reached, or `ErrorKind::TimedOut` if the
+/// request may have arrived, so that `Failover` tries the next endpoint.
+#[async_trait]
+pub trait Transport: Send + Sync + fmt::Debug {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse>;
+}
+
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        (**self).send(request).await
+    }
+}
+
+#[async_trait]
+impl Transport for Client {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let builder = self
+            .request(request.method, request.url)
+            .headers(request.headers);
+        let builder = match request.body {
+            Some(body) => builder.body(body),
+            None => builder,
+        };
+
+        let response = builder.send().await?;
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok()?.trim().parse().ok())
+            .map(Duration::from_secs);
+        Ok(HttpResponse {
+            status,
+            retry_after,
+            body: response.bytes().await?,
+        })
+    }
+}
+
+type Respond = Arc<dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync>;
+
+struct Route {
+    method: Method,
+    /// `Request::PATH`, where `{}` stands for any segment
+    path: String,
+    respond: Respond,
+    /// Requests the route still answers, `None` if unlimited
+    remaining: Option<usize>,
+}
+
+impl fmt::Debug for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Route")
+            .field("method", &self.method)
+            .field("path", &self.path)
+            .field("remaining", &self.remaining)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Transport answering requests with canned responses, to test code using
+/// the `Rest` client without the exchange.
+///
+/// Requests are matched by method and path without the query, the most
+/// recently added route first. Others are answered with 404.
+#[derive(Clone, Debug, Default)]
+pub struct MockTransport {
+    routes: Arc<Mutex<Vec<Route>>>,
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+    delay: Duration,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers requests of type `R` with `result`.
+    #[must_use]
+    pub fn on<R: Request>(self, result: Value) -> Self {
+        self.on_path(R::METHOD, R::PATH, result)
+    }
+
+    /// Answers requests for `path`, e.g. `/markets/{}`, with `result`.
+    #[must_use]
+    pub fn on_path(self, method: Method, path: &str, result: Value) -> Self {
+        // FTX sends the success flag first
+        let body = format!(r#"{{"success":true,"result":{}}}"#, result);
+        let response = HttpResponse::ok(body);
+        self.respond(method, path, move |_| response.clone())
+    }
+
+    /// Answers requests of type `R` with the body `respond` returns for
+    /// each, e.g. pages depending on the query.
+    #[must_use]
+    pub fn on_request<R: Request>(
+        self,
+        respond: impl Fn(&HttpRequest) -> Value + Send + Sync + 'static,
+    ) -> Self {
+        self.respond(R::METHOD, R::PATH, move |request| {
+            HttpResponse::ok(respond(request).to_string())
+        })
+    }
+
+    /// Answers requests of type `R` with an error response.
+    #[must_use]
+    pub fn fail<R: Request>(self, status: StatusCode, error: &str) -> Self {
+        let body = format!(r#"{{"success":false,"error":{}}}"#, json!(error));
+        let response = HttpResponse {
+            status,
+            retry_after: None,
+            body: body.into(),
+        };
+        self.respond(R::METHOD, R::PATH, move |_| response.clone())
+    }
+
+    /// Limits the route added last to the next `times` requests, after which
+    /// earlier routes answer them, e.g. to fail only the first attempts.
+    #[must_use]
+    pub fn times(self, times: usize) -> Self {
+        if let Some(route) = self.routes.lock().unwrap().last_mut() {
+            route.remaining = Some(times);
+        }
+        self
+    }
+
+    /// Sends `Retry-After` with the responses of the route added last.
+    #[must_use]
+    pub fn retry_after(self, retry_after: Duration) -> Self {
+        if let Some(route) = self.routes.lock().unwrap().last_mut() {
+            let respond = route.respond.clone();
+            route.respond = Arc::new(move |request| HttpResponse {
+                retry_after: Some(retry_after),
+                ..respond(request)
+            });
+        }
+        self
+    }
+
+    /// Delays every response by `delay`, e.g. to test concurrent requests.
+    #[must_use]
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    fn respond(
+        self,
+        method: Method,
+        path: &str,
+        respond: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    ) -> Self {
+        self.routes.lock().unwrap().push(Route {
+            method,
+            path: path.to_owned(),
+            respond: Arc::new(respond),
+            remaining: None,
+        });
+        self
+    }
+
+    /// Requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Transport for MockTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let path = request.path.split('?').next().unwrap_or_default();
+        let respond = self
+            .routes
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .rev()
+            .filter(|route| route.remaining != Some(0))
+            .find(|route| route.method == request.method && path_matches(&route.path, path))
+            .map(|route| {
+                if let Some(remaining) = &mut route.remaining {
+                    *remaining -= 1;
+                }
+                route.respond.clone()
+            });
+        let response = match respond {
+            Some(respond) => respond(&request),
+            None => HttpResponse {
+                status: StatusCode::NOT_FOUND,
+                retry_after: None,
+                body: json!({ "success": false, "error": "Not found" })
+                    .to_string()
+                    .into(),
+            },
+        };
+        self.requests.lock().unwrap().push(request);
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+        Ok(response)
+    }
+}
+
+impl Rest {
+    /// Sends requests with `transport` instead of over HTTP, e.g. a
+    /// `MockTransport` in tests.
+    #[must_use]
+    pub fn with_transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+}
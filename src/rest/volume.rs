@@ -0,0 +1,91 @@
+use super::{GetMarkets, Market, Resolution, Rest, Result, Symbol};
+use chrono::{DateTime, Utc};
+use futures::future::try_join_all;
+use rust_decimal::Decimal;
+
+/// USD volume traded in a market.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarketVolume {
+    pub market: Symbol,
+    pub volume_usd: Decimal,
+}
+
+/// Ranks markets by their 24 hour USD volume, most active first.
+pub fn rank_by_volume_24h<'a>(markets: impl IntoIterator<Item = &'a Market>) -> Vec<MarketVolume> {
+    rank(
+        markets
+            .into_iter()
+            .map(|market| MarketVolume {
+                market: market.name.clone(),
+                volume_usd: market.volume_usd24h,
+            })
+            .collect(),
+    )
+}
+
+fn rank(mut volumes: Vec<MarketVolume>) -> Vec<MarketVolume> {
+    volumes.sort_by(|a, b| {
+        b.volume_usd
+            .cmp(&a.volume_usd)
+            .then_with(|| a.market.cmp(&b.market))
+    });
+    volumes
+}
+
+impl Rest {
+    /// Ranks all markets matching `filter` by their 24 hour USD volume, most
+    /// active first, e.g. `|market| market.name.ends_with("-PERP")` for
+    /// perpetual futures.
+    pub async fn get_volume_ranking(
+        &self,
+        filter: impl Fn(&Market) -> bool,
+    ) -> Result<Vec<MarketVolume>> {
+        let markets = self.request(GetMarkets {}).await?;
+        Ok(rank_by_volume_24h(
+            markets.iter().filter(|market| filter(market)),
+        ))
+    }
+
+    /// Returns the USD volume traded in `market` between `start_time` and
+    /// `end_time`, summed from candles of the given resolution. Candles are
+    /// included if they start within the window.
+    pub async fn get_volume(
+        &self,
+        market: &str,
+        resolution: Resolution,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Decimal> {
+        let candles = self
+            .get_candle_history(market, resolution, start_time, end_time)
+            .await?;
+
+        Ok(candles
+            .iter()
+            .filter(|candle| candle.start_time >= start_time && candle.start_time <= end_time)
+            .map(|candle| candle.volume)
+            .sum())
+    }
+
+    /// Ranks `markets` by the USD volume traded between `start_time` and
+    /// `end_time`, most active first. See `get_volume`.
+    pub async fn get_volume_ranking_window(
+        &self,
+        markets: &[&str],
+        resolution: Resolution,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<MarketVolume>> {
+        let volumes = try_join_all(markets.iter().map(|market| async move {
+            Ok::<_, super::Error>(MarketVolume {
+                market: (*market).to_owned(),
+                volume_usd: self
+                    .get_volume(market, resolution, start_time, end_time)
+                    .await?,
+            })
+        }))
+        .await?;
+
+        Ok(rank(volumes))
+    }
+}
@@ -0,0 +1,135 @@
+use super::{Error, GetOrder, Id, OrderInfo, OrderStatus, Rest, Result};
+#[cfg(feature = "ws")]
+use crate::options::Options;
+#[cfg(feature = "ws")]
+use crate::ws::{Channel, Data, Ws};
+#[cfg(feature = "ws")]
+use std::fmt;
+use std::time::Duration;
+use tokio::time;
+
+/// Interval at which the order status is polled over REST.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Interval at which the order status is polled over REST while also
+/// listening for websocket updates, in case an update is missed.
+#[cfg(feature = "ws")]
+const WS_FALLBACK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Options the websockets of a `Rest` client connect with, without the
+/// secret in their debug output.
+#[cfg(feature = "ws")]
+#[derive(Clone)]
+pub(super) struct WsOptions(pub(super) Options);
+
+#[cfg(feature = "ws")]
+impl fmt::Debug for WsOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WsOptions")
+            .field("endpoint", &self.0.endpoint)
+            .field("subaccount", &self.0.subaccount)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Rest {
+    /// Waits until the order reaches a terminal state (filled, cancelled or
+    /// rejected), returning `Error::Timeout` if that does not happen within
+    /// `timeout`.
+    ///
+    /// With the `ws` feature, this opens a websocket with the credentials of
+    /// the client and waits for the update of the order on the `orders`
+    /// channel, polling its status occasionally in case an update is missed.
+    /// Without it, or if the websocket cannot be opened or subscribed, e.g.
+    /// for a client with an external `Signer`, the status is only polled.
+    pub async fn await_order_final(&self, id: Id, timeout: Duration) -> Result<OrderInfo> {
+        let wait = async {
+            #[cfg(feature = "ws")]
+            match self.connect_orders_ws().await {
+                Ok(mut ws) => return self.wait_with_ws(&mut ws, id).await,
+                Err(error) => log::debug!("polling order {} without a websocket: {}", id, error),
+            }
+            self.poll_until_closed(id).await
+        };
+
+        time::timeout(timeout, wait)
+            .await
+            .map_err(|_| Error::Timeout)?
+    }
+
+    /// Like `await_order_final`, but driven by the updates received on `ws`,
+    /// which must be subscribed to `Channel::Orders`. The order status is
+    /// still polled occasionally in case an update is missed, and only
+    /// polled once `ws` fails or closes.
+    ///
+    /// Other data received on `ws` while waiting is discarded, so a
+    /// dedicated connection should be used.
+    #[cfg(feature = "ws")]
+    pub async fn await_order_final_with_ws(
+        &self,
+        ws: &mut Ws,
+        id: Id,
+        timeout: Duration,
+    ) -> Result<OrderInfo> {
+        time::timeout(timeout, self.wait_with_ws(ws, id))
+            .await
+            .map_err(|_| Error::Timeout)?
+    }
+
+    /// Gives up on the websocket after `WS_FALLBACK_POLL_INTERVAL`, so that
+    /// polling is not delayed by an endpoint that does not respond.
+    #[cfg(feature = "ws")]
+    async fn connect_orders_ws(&self) -> Result<Ws> {
+        let connect = async {
+            let mut ws = Ws::connect(self.ws_options.0.clone()).await?;
+            ws.subscribe(&[Channel::Orders]).await?;
+            Ok(ws)
+        };
+        time::timeout(WS_FALLBACK_POLL_INTERVAL, connect)
+            .await
+            .map_err(|_| Error::Timeout)?
+            .map_err(|error: crate::ws::Error| Box::new(error).into())
+    }
+
+    #[cfg(feature = "ws")]
+    async fn wait_with_ws(&self, ws: &mut Ws, id: Id) -> Result<OrderInfo> {
+        use futures::StreamExt;
+
+        // The first tick completes immediately, so an order that is
+        // already closed is detected without waiting for an update.
+        let mut poll = time::interval(WS_FALLBACK_POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = poll.tick() => {
+                    let order = self.request(GetOrder::new(id)).await?;
+                    if order.status == OrderStatus::Closed {
+                        return Ok(order);
+                    }
+                }
+                data = ws.next() => match data {
+                    Some(Ok((_, Data::Order(order))))
+                        if order.id == id && order.status == OrderStatus::Closed =>
+                    {
+                        return Ok(order);
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(error)) => {
+                        log::warn!("polling order {} after a websocket error: {}", id, error);
+                        return self.poll_until_closed(id).await;
+                    }
+                    None => return self.poll_until_closed(id).await,
+                }
+            }
+        }
+    }
+
+    async fn poll_until_closed(&self, id: Id) -> Result<OrderInfo> {
+        loop {
+            let order = self.request(GetOrder::new(id)).await?;
+            if order.status == OrderStatus::Closed {
+                return Ok(order);
+            }
+            time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
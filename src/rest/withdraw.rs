@@ -0,0 +1,74 @@
+use super::{
+    address::is_valid_address, CoinInfo, Error, GetCoins, GetSavedAddresses, RequestWithdrawal,
+    Rest, Result, SavedAddress,
+};
+
+impl Rest {
+    /// Runs the client-side checks enabled in `Options` on `destination`,
+    /// see `Request::withdrawal`.
+    pub(crate) async fn check_withdrawal(&self, destination: &RequestWithdrawal) -> Result<()> {
+        if self.validate_withdrawals {
+            let coins = Box::pin(self.request(GetCoins {})).await?;
+            // Unknown coins are left for FTX to reject
+            if let Some(coin) = coins.iter().find(|coin| coin.id == destination.coin) {
+                validate_withdrawal(
+                    coin,
+                    &destination.address,
+                    destination.tag.as_deref(),
+                    destination.method.as_deref(),
+                )?;
+            }
+        }
+
+        if self.enforce_address_book {
+            let saved = Box::pin(self.request(GetSavedAddresses {
+                coin: destination.coin.clone(),
+            }))
+            .await?;
+            if !is_saved_address(&saved, &destination.address, destination.tag.as_deref()) {
+                return Err(Error::AddressNotSaved(destination.address.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that `address` matches the format of the withdrawal `method`, or
+/// of any method supported by `coin` if no method is given, and that a tag
+/// is given for coins that require one.
+pub fn validate_withdrawal(
+    coin: &CoinInfo,
+    address: &str,
+    tag: Option<&str>,
+    method: Option<&str>,
+) -> Result<()> {
+    if coin.has_tag && tag.is_none_or(str::is_empty) {
+        return Err(Error::TagRequired(coin.id.clone()));
+    }
+
+    let default_method = coin.id.to_lowercase();
+    let methods: Vec<&str> = match method {
+        Some(method) => vec![method],
+        None if coin.methods.is_empty() => vec![&default_method],
+        None => coin.methods.iter().map(String::as_str).collect(),
+    };
+
+    let checks: Vec<bool> = methods
+        .iter()
+        .filter_map(|method| is_valid_address(method, address))
+        .collect();
+    if checks.is_empty() || checks.contains(&true) {
+        Ok(())
+    } else {
+        Err(Error::InvalidAddress(address.to_owned(), methods.join("/")))
+    }
+}
+
+/// Returns whether `address` with `tag` is one of the `saved` addresses.
+pub(crate) fn is_saved_address(saved: &[SavedAddress], address: &str, tag: Option<&str>) -> bool {
+    let tag = tag.filter(|tag| !tag.is_empty());
+    saved.iter().any(|saved| {
+        saved.address == address && saved.tag.as_deref().filter(|tag| !tag.is_empty()) == tag
+    })
+}
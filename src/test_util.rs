@@ -0,0 +1,929 @@
+//! Generators of FTX-like JSON for property based tests, built with the
+//! `test-util` feature.
+//!
+//! Responses are described by templates, JSON values in which strings
+//! starting with `$` stand for generated values:
+//!
+//! - `$decimal`, `$id`, `$u32`, `$bool`, `$string`
+//! - `$time`: RFC 3339 timestamp with any offset and precision, as in REST
+//!   responses
+//! - `$epoch`: fractional seconds since the epoch, as in websocket messages
+//! - `$enum:a|b|c`: one of the listed strings
+//! - `$levels`: list of `[price, size]` pairs
+//!
+//! Keys ending in `?` are optional and are generated as missing, `null` or
+//! a value. `["$vec", template]` is a list of values of the template. All
+//! other values are kept as they are.
+//!
+//! `valid` generates responses the models must accept, with unknown fields
+//! added. `adversarial` breaks one field of a valid response; the models may
+//! reject those, but must do so with an error rather than a panic.
+//!
+//! ```
+//! use ftx::rest::Market;
+//! use ftx::test_util::{templates, valid};
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(json in valid(&templates::market()))| {
+//!     serde_json::from_value::<Market>(json).unwrap();
+//! });
+//! ```
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use proptest::prelude::*;
+use serde_json::{json, Map, Number, Value};
+
+/// Responses the models must accept.
+pub fn valid(template: &Value) -> BoxedStrategy<Value> {
+    match template {
+        Value::String(s) if s.starts_with('$') => generator(s),
+        Value::Array(items) if items.first() == Some(&json!("$vec")) => {
+            let item = items.get(1).cloned().unwrap_or(Value::Null);
+            prop::collection::vec(valid(&item), 0..4)
+                .prop_map(Value::Array)
+                .boxed()
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(valid)
+            .collect::<Vec<_>>()
+            .prop_map(Value::Array)
+            .boxed(),
+        Value::Object(fields) => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|(key, template)| match key.strip_suffix('?') {
+                    Some(key) => {
+                        let key = key.to_owned();
+                        prop_oneof![
+                            Just(None),
+                            Just(Some(Value::Null)),
+                            valid(template).prop_map(Some),
+                        ]
+                        .prop_map(move |value| (key.clone(), value))
+                        .boxed()
+                    }
+                    None => {
+                        let key = key.clone();
+                        valid(template)
+                            .prop_map(move |value| (key.clone(), Some(value)))
+                            .boxed()
+                    }
+                })
+                .collect();
+            let unknown = prop::collection::vec(("x-[a-z]{1,8}", any_value()), 0..3);
+            (fields, unknown)
+                .prop_map(|(fields, unknown)| {
+                    let mut object: Map<String, Value> = fields
+                        .into_iter()
+                        .filter_map(|(key, value)| Some((key, value?)))
+                        .collect();
+                    object.extend(unknown);
+                    Value::Object(object)
+                })
+                .boxed()
+        }
+        value => Just(value.clone()).boxed(),
+    }
+}
+
+/// Valid responses with one top level field removed or replaced by an
+/// `adversarial_value`.
+pub fn adversarial(template: &Value) -> BoxedStrategy<Value> {
+    (
+        valid(template),
+        any::<prop::sample::Index>(),
+        prop::option::of(adversarial_value()),
+    )
+        .prop_map(|(mut value, index, replacement)| {
+            if let Value::Object(object) = &mut value {
+                if !object.is_empty() {
+                    let key = object.keys().nth(index.index(object.len())).unwrap();
+                    let key = key.clone();
+                    match replacement {
+                        Some(replacement) => object.insert(key, replacement),
+                        None => object.remove(&key),
+                    };
+                }
+            }
+            value
+        })
+        .boxed()
+}
+
+fn generator(name: &str) -> BoxedStrategy<Value> {
+    if let Some(variants) = name.strip_prefix("$enum:") {
+        let variants: Vec<Value> = variants.split('|').map(|v| json!(v)).collect();
+        return prop::sample::select(variants).boxed();
+    }
+    match name {
+        "$decimal" => decimal(),
+        "$id" => (0..i64::MAX as u64).prop_map(|id| json!(id)).boxed(),
+        "$u32" => any::<u32>().prop_map(|n| json!(n)).boxed(),
+        "$bool" => any::<bool>().prop_map(Value::Bool).boxed(),
+        "$string" => "[ -~]{0,16}".prop_map(Value::String).boxed(),
+        "$time" => timestamp(),
+        "$epoch" => epoch_seconds(),
+        "$levels" => prop::collection::vec((decimal(), decimal()), 0..20)
+            .prop_map(|levels| json!(levels))
+            .boxed(),
+        _ => panic!("unknown template generator {}", name),
+    }
+}
+
+/// Numbers in the range of `Decimal`, from dust to very large values.
+pub fn decimal() -> BoxedStrategy<Value> {
+    prop_oneof![
+        any::<i64>().prop_map(|n| json!(n)),
+        any::<u64>().prop_map(|n| json!(n)),
+        (-1e6..1e6f64).prop_map(|n| json!(n)),
+        (0u32..12, 0..1_000_000i64)
+            .prop_map(|(scale, n)| json!(n as f64 / 10f64.powi(scale as i32))),
+        (-1e20..1e20f64).prop_map(|n| json!(n)),
+        Just(json!(0.0)),
+        Just(json!(-0.0)),
+        Just(json!(5e-324)),
+    ]
+    .boxed()
+}
+
+/// RFC 3339 timestamps between 1970 and 2100, with offsets from -12 to +14
+/// hours and zero to nine fractional digits.
+pub fn timestamp() -> BoxedStrategy<Value> {
+    let formats = prop::sample::select(vec![
+        SecondsFormat::Secs,
+        SecondsFormat::Millis,
+        SecondsFormat::Micros,
+        SecondsFormat::Nanos,
+        SecondsFormat::AutoSi,
+    ]);
+    (
+        0..4_102_444_800i64,
+        0..1_000_000_000u32,
+        -12 * 60..=14 * 60i32,
+        formats,
+        any::<bool>(),
+    )
+        .prop_map(|(secs, nanos, offset, format, use_z)| {
+            let time = Utc.timestamp_opt(secs, nanos).unwrap();
+            let offset = chrono::FixedOffset::east_opt(offset * 60).unwrap();
+            json!(time.with_timezone(&offset).to_rfc3339_opts(format, use_z))
+        })
+        .boxed()
+}
+
+/// Fractional seconds since the epoch between 1970 and 2100.
+pub fn epoch_seconds() -> BoxedStrategy<Value> {
+    prop_oneof![
+        (0..4_102_444_800i64).prop_map(|secs| json!(secs)),
+        (0.0..4_102_444_800f64).prop_map(|secs| json!(secs)),
+    ]
+    .boxed()
+}
+
+/// Values out of range or of the wrong type for any field.
+pub fn adversarial_value() -> BoxedStrategy<Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        Just(json!(1e30)),
+        Just(json!(-1e300)),
+        Just(json!(f64::MAX)),
+        Just(json!(u64::MAX)),
+        Just(json!(i64::MIN)),
+        any::<f64>()
+            .prop_filter_map("finite", Number::from_f64)
+            .prop_map(Value::Number),
+        prop::sample::select(vec![
+            "",
+            "NaN",
+            "inf",
+            "1e999",
+            "0x10",
+            "2020-13-45T99:99:99Z",
+            "2020-05-22T15:12:29",
+            "+275760-09-13T00:00:00Z",
+        ])
+        .prop_map(|s| json!(s)),
+        "\\PC{0,32}".prop_map(Value::String),
+        Just(json!([])),
+        Just(json!({})),
+        Just(json!([[1, 2, 3]])),
+    ]
+    .boxed()
+}
+
+/// Arbitrary JSON values, for unknown fields.
+pub fn any_value() -> BoxedStrategy<Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(|n| json!(n)),
+        "[ -~]{0,16}".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(3, 16, 4, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+            prop::collection::btree_map("[a-z]{1,8}", inner, 0..4)
+                .prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+    .boxed()
+}
+
+/// Templates of the responses of the REST and websocket APIs.
+pub mod templates {
+    use serde_json::{json, Value};
+
+    pub fn market() -> Value {
+        json!({
+            "type": "$enum:future|spot",
+            "name": "$string",
+            "underlying?": "$string",
+            "baseCurrency?": "$string",
+            "quoteCurrency?": "$string",
+            "enabled": "$bool",
+            "ask?": "$decimal",
+            "bid?": "$decimal",
+            "last?": "$decimal",
+            "postOnly": "$bool",
+            "priceIncrement": "$decimal",
+            "sizeIncrement": "$decimal",
+            "restricted": "$bool",
+            "minProvideSize": "$decimal",
+            "price?": "$decimal",
+            "highLeverageFeeExempt": "$bool",
+            "change1h": "$decimal",
+            "change24h": "$decimal",
+            "changeBod": "$decimal",
+            "quoteVolume24h": "$decimal",
+            "volumeUsd24h": "$decimal",
+        })
+    }
+
+    pub fn future() -> Value {
+        json!({
+            "ask?": "$decimal",
+            "bid?": "$decimal",
+            "change1h?": "$decimal",
+            "change24h?": "$decimal",
+            "changeBod?": "$decimal",
+            "volumeUsd24h?": "$decimal",
+            "volume?": "$decimal",
+            "description": "$string",
+            "enabled": "$bool",
+            "expired": "$bool",
+            "expiry?": "$time",
+            "index?": "$decimal",
+            "imfFactor": "$decimal",
+            "last?": "$decimal",
+            "lowerBound": "$decimal",
+            "mark?": "$decimal",
+            "name": "$string",
+            "openInterest?": "$decimal",
+            "openInterestUsd?": "$decimal",
+            "perpetual": "$bool",
+            "positionLimitWeight": "$decimal",
+            "postOnly": "$bool",
+            "priceIncrement": "$decimal",
+            "sizeIncrement": "$decimal",
+            "underlying": "$string",
+            "upperBound": "$decimal",
+            "type": "$enum:future|perpetual|prediction|move",
+        })
+    }
+
+    pub fn orderbook() -> Value {
+        json!({ "asks": "$levels", "bids": "$levels" })
+    }
+
+    pub fn trade() -> Value {
+        json!({
+            "id": "$id",
+            "liquidation": "$bool",
+            "price": "$decimal",
+            "side": "$enum:buy|sell",
+            "size": "$decimal",
+            "time": "$time",
+        })
+    }
+
+    pub fn candle() -> Value {
+        json!({
+            "close": "$decimal",
+            "high": "$decimal",
+            "low": "$decimal",
+            "open": "$decimal",
+            "volume": "$decimal",
+            "startTime": "$time",
+        })
+    }
+
+    pub fn order() -> Value {
+        json!({
+            "id": "$id",
+            "market": "$string",
+            "future?": "$string",
+            "type": "$enum:market|limit|stop|trailingStop|takeProfit",
+            "side": "$enum:buy|sell",
+            "price?": "$decimal",
+            "size": "$decimal",
+            "reduceOnly?": "$bool",
+            "ioc?": "$bool",
+            "postOnly?": "$bool",
+            "status": "$enum:new|open|closed|cancelled|triggered",
+            "filledSize?": "$decimal",
+            "remainingSize?": "$decimal",
+            "avgFillPrice?": "$decimal",
+            "liquidation?": "$bool",
+            "createdAt": "$time",
+            "clientId?": "$string",
+            "retryUntilFilled?": "$bool",
+            "triggerPrice?": "$decimal",
+            "orderPrice?": "$decimal",
+            "triggeredAt?": "$time",
+            "orderType?": "$enum:market|limit",
+            "orderId?": "$id",
+            "trailValue?": "$decimal",
+            "trailStart?": "$decimal",
+            "error?": "$string",
+        })
+    }
+
+    pub fn position() -> Value {
+        json!({
+            "cost": "$decimal",
+            "entryPrice?": "$decimal",
+            "estimatedLiquidationPrice?": "$decimal",
+            "future": "$string",
+            "initialMarginRequirement": "$decimal",
+            "longOrderSize": "$decimal",
+            "maintenanceMarginRequirement": "$decimal",
+            "netSize": "$decimal",
+            "openSize": "$decimal",
+            "realizedPnl": "$decimal",
+            "shortOrderSize": "$decimal",
+            "side": "$enum:buy|sell",
+            "size": "$decimal",
+            "unrealizedPnl": "$decimal",
+            "collateralUsed": "$decimal",
+        })
+    }
+
+    pub fn account() -> Value {
+        json!({
+            "backstopProvider": "$bool",
+            "chargeInterestOnNegativeUsd": "$bool",
+            "collateral": "$decimal",
+            "freeCollateral": "$decimal",
+            "initialMarginRequirement": "$decimal",
+            "liquidating": "$bool",
+            "maintenanceMarginRequirement": "$decimal",
+            "makerFee": "$decimal",
+            "marginFraction?": "$decimal",
+            "openMarginFraction?": "$decimal",
+            "positionLimit?": "$decimal",
+            "positionLimitUsed?": "$decimal",
+            "takerFee": "$decimal",
+            "totalAccountValue": "$decimal",
+            "totalPositionSize": "$decimal",
+            "useFttCollateral": "$bool",
+            "username": "$string",
+            "leverage": "$decimal",
+            "positions": ["$vec", position()],
+            "spotLendingEnabled": "$bool",
+            "spotMarginEnabled": "$bool",
+        })
+    }
+
+    pub fn balance() -> Value {
+        json!({
+            "coin": "$string",
+            "free": "$decimal",
+            "total": "$decimal",
+            "usdValue?": "$decimal",
+            "spotBorrow": "$decimal",
+            "availableWithoutBorrow": "$decimal",
+        })
+    }
+
+    pub fn fill() -> Value {
+        json!({
+            "id": "$id",
+            "market?": "$string",
+            "future?": "$string",
+            "baseCurrency?": "$string",
+            "quoteCurrency?": "$string",
+            "type": "$string",
+            "side": "$enum:buy|sell",
+            "price": "$decimal",
+            "size": "$decimal",
+            "orderId?": "$id",
+            "tradeId?": "$id",
+            "time": "$time",
+            "fee": "$decimal",
+            "feeRate": "$decimal",
+            "feeCurrency": "$string",
+            "liquidity": "$enum:maker|taker",
+        })
+    }
+
+    pub fn funding_payment() -> Value {
+        json!({
+            "id": "$id",
+            "future": "$string",
+            "payment": "$decimal",
+            "time": "$time",
+            "rate?": "$decimal",
+        })
+    }
+
+    pub fn funding_rate() -> Value {
+        json!({
+            "future": "$string",
+            "rate": "$decimal",
+            "time": "$time",
+        })
+    }
+
+    pub fn future_stats() -> Value {
+        json!({
+            "volume": "$decimal",
+            "nextFundingRate?": "$decimal",
+            "nextFundingTime?": "$time",
+            "expirationPrice?": "$decimal",
+            "predictedExpirationPrice?": "$decimal",
+            "strikePrice?": "$decimal",
+            "openInterest": "$decimal",
+        })
+    }
+
+    pub fn historical_candle() -> Value {
+        json!({
+            "open": "$decimal",
+            "high": "$decimal",
+            "low": "$decimal",
+            "close": "$decimal",
+            "startTime": "$time",
+            "volume?": "$decimal",
+        })
+    }
+
+    pub fn quote_id() -> Value {
+        json!({ "quoteId": "$id" })
+    }
+
+    pub fn quote() -> Value {
+        json!({
+            "id": "$id",
+            "baseCoin": "$string",
+            "quoteCoin": "$string",
+            "fromCoin": "$string",
+            "toCoin": "$string",
+            "side": "$enum:buy|sell",
+            "price": "$decimal",
+            "cost": "$decimal",
+            "proceeds": "$decimal",
+            "expiry": "$epoch",
+            "expired": "$bool",
+            "filled": "$bool",
+        })
+    }
+
+    pub fn fiat_deposit_instructions() -> Value {
+        json!({
+            "coin": "$string",
+            "bankName?": "$string",
+            "bankAddress?": "$string",
+            "accountName?": "$string",
+            "accountNumber?": "$string",
+            "routingNumber?": "$string",
+            "swiftCode?": "$string",
+            "iban?": "$string",
+            "reference?": "$string",
+        })
+    }
+
+    pub fn historical_balances() -> Value {
+        json!({
+            "id": "$id",
+            "accounts": ["$vec", "$string"],
+            "time": "$epoch",
+            "endTime": "$epoch",
+            "status": "$enum:requested|processing|complete",
+            "error": "$bool",
+            "results?": ["$vec", {
+                "account": "$string",
+                "ticker": "$string",
+                "size": "$decimal",
+                "price?": "$decimal",
+            }],
+        })
+    }
+
+    pub fn leveraged_token() -> Value {
+        json!({
+            "name": "$string",
+            "description": "$string",
+            "underlying": "$string",
+            "leverage": "$decimal",
+            "outstanding": "$decimal",
+            "pricePerShare": "$decimal",
+            "positionPerShare": "$decimal",
+            "underlyingMark": "$decimal",
+            "contractAddress?": "$string",
+            "change1h": "$decimal",
+            "change24h": "$decimal",
+        })
+    }
+
+    pub fn leveraged_token_balance() -> Value {
+        json!({ "token": "$string", "balance": "$decimal" })
+    }
+
+    pub fn leveraged_token_creation() -> Value {
+        json!({
+            "id": "$id",
+            "token": "$string",
+            "requestedSize": "$decimal",
+            "pending": "$bool",
+            "createdSize?": "$decimal",
+            "price?": "$decimal",
+            "cost": "$decimal",
+            "fee?": "$decimal",
+            "requestedAt": "$time",
+            "fulfilledAt?": "$time",
+        })
+    }
+
+    pub fn leveraged_token_redemption() -> Value {
+        json!({
+            "id": "$id",
+            "token": "$string",
+            "size": "$decimal",
+            "pending": "$bool",
+            "price?": "$decimal",
+            "proceeds?": "$decimal",
+            "projectedProceeds?": "$decimal",
+            "fee?": "$decimal",
+            "requestedAt": "$time",
+            "fulfilledAt?": "$time",
+        })
+    }
+
+    fn option_contract() -> Value {
+        json!({
+            "underlying": "$string",
+            "type": "$enum:call|put",
+            "strike": "$decimal",
+            "expiry": "$time",
+        })
+    }
+
+    pub fn option_quote_request() -> Value {
+        json!({
+            "id": "$id",
+            "option": option_contract(),
+            "side": "$enum:buy|sell",
+            "size": "$decimal",
+            "time": "$time",
+            "requestExpiry": "$time",
+            "status": "$enum:open|filled|cancelled",
+            "limitPrice?": "$decimal",
+        })
+    }
+
+    pub fn my_option_quote_request() -> Value {
+        let mut request = option_quote_request();
+        request["hideLimitPrice"] = json!("$bool");
+        request["quotes"] = json!(["$vec", option_quote()]);
+        request
+    }
+
+    pub fn option_quote() -> Value {
+        json!({
+            "id": "$id",
+            "requestId": "$id",
+            "option?": option_contract(),
+            "price": "$decimal",
+            "size?": "$decimal",
+            "collateral?": "$decimal",
+            "quoterSide?": "$enum:buy|sell",
+            "requestSide?": "$enum:buy|sell",
+            "quoteExpiry?": "$time",
+            "status": "$enum:open|filled|cancelled",
+            "time": "$time",
+        })
+    }
+
+    pub fn options_account_info() -> Value {
+        json!({
+            "usdBalance": "$decimal",
+            "liquidationPrice?": "$decimal",
+            "liquidating": "$bool",
+        })
+    }
+
+    pub fn option_position() -> Value {
+        json!({
+            "option": option_contract(),
+            "side": "$enum:buy|sell",
+            "size": "$decimal",
+            "netSize": "$decimal",
+            "entryPrice": "$decimal",
+        })
+    }
+
+    pub fn option_fill() -> Value {
+        json!({
+            "id": "$id",
+            "option": option_contract(),
+            "side": "$enum:buy|sell",
+            "price": "$decimal",
+            "size": "$decimal",
+            "fee": "$decimal",
+            "feeRate": "$decimal",
+            "liquidity": "$enum:maker|taker",
+            "time": "$time",
+        })
+    }
+
+    pub fn option_trade() -> Value {
+        json!({
+            "id": "$id",
+            "option": option_contract(),
+            "price": "$decimal",
+            "size": "$decimal",
+            "time": "$time",
+        })
+    }
+
+    pub fn trigger_order() -> Value {
+        json!({
+            "id": "$id",
+            "market": "$string",
+            "future?": "$string",
+            "type": "$enum:stop|trailingStop|takeProfit",
+            "orderType": "$enum:market|limit",
+            "side": "$enum:buy|sell",
+            "size": "$decimal",
+            "status": "$enum:open|cancelled|triggered",
+            "triggerPrice?": "$decimal",
+            "orderPrice?": "$decimal",
+            "trailValue?": "$decimal",
+            "trailStart?": "$decimal",
+            "filledSize?": "$decimal",
+            "avgFillPrice?": "$decimal",
+            "reduceOnly": "$bool",
+            "retryUntilFilled": "$bool",
+            "createdAt": "$time",
+            "triggeredAt?": "$time",
+            "orderId?": "$id",
+            "error?": "$string",
+        })
+    }
+
+    pub fn trigger_event() -> Value {
+        json!({
+            "time": "$time",
+            "orderSize?": "$decimal",
+            "filledSize?": "$decimal",
+            "orderId?": "$id",
+            "error?": "$string",
+        })
+    }
+
+    /// Spot margin lending or borrow rate
+    pub fn spot_margin_rate() -> Value {
+        json!({
+            "coin": "$string",
+            "estimate": "$decimal",
+            "previous?": "$decimal",
+        })
+    }
+
+    pub fn lending_history() -> Value {
+        json!({
+            "coin": "$string",
+            "time": "$time",
+            "rate": "$decimal",
+            "size": "$decimal",
+        })
+    }
+
+    pub fn my_lending_history() -> Value {
+        json!({
+            "coin": "$string",
+            "proceeds": "$decimal",
+            "rate": "$decimal",
+            "size": "$decimal",
+            "time": "$time",
+        })
+    }
+
+    pub fn my_borrow_history() -> Value {
+        json!({
+            "coin": "$string",
+            "cost": "$decimal",
+            "rate": "$decimal",
+            "size": "$decimal",
+            "time": "$time",
+        })
+    }
+
+    pub fn lending_info() -> Value {
+        json!({
+            "coin": "$string",
+            "lendable": "$decimal",
+            "locked": "$decimal",
+            "minRate?": "$decimal",
+            "offered": "$decimal",
+        })
+    }
+
+    pub fn stake() -> Value {
+        json!({
+            "id": "$id",
+            "coin": "$string",
+            "size": "$decimal",
+            "createdAt": "$time",
+        })
+    }
+
+    pub fn stake_balance() -> Value {
+        json!({
+            "coin": "$string",
+            "staked": "$decimal",
+            "scheduledToUnstake": "$decimal",
+            "lifetimeRewards": "$decimal",
+        })
+    }
+
+    pub fn unstake_request() -> Value {
+        json!({
+            "id": "$id",
+            "coin": "$string",
+            "size": "$decimal",
+            "status": "$enum:pending|cancelled|processed",
+            "createdAt": "$time",
+            "unlockAt": "$time",
+        })
+    }
+
+    pub fn staking_reward() -> Value {
+        json!({
+            "id": "$id",
+            "coin": "$string",
+            "size": "$decimal",
+            "status": "$string",
+            "time": "$time",
+        })
+    }
+
+    pub fn subaccount() -> Value {
+        json!({
+            "nickname": "$string",
+            "deletable": "$bool",
+            "editable": "$bool",
+            "competition": "$bool",
+        })
+    }
+
+    pub fn created_subaccount() -> Value {
+        json!({
+            "nickname": "$string",
+            "deletable": "$bool",
+            "editable": "$bool",
+        })
+    }
+
+    pub fn subaccount_balance() -> Value {
+        json!({
+            "coin": "$string",
+            "free": "$decimal",
+            "total": "$decimal",
+            "spotBorrow": "$decimal",
+            "availableWithoutBorrow": "$decimal",
+        })
+    }
+
+    pub fn transfer() -> Value {
+        json!({
+            "id": "$id",
+            "coin": "$string",
+            "size": "$decimal",
+            "time": "$time",
+            "notes": "$string",
+        })
+    }
+
+    pub fn deposit_address() -> Value {
+        json!({ "address": "$string", "tag?": "$string" })
+    }
+
+    pub fn deposit() -> Value {
+        json!({
+            "id": "$id",
+            "coin": "$string",
+            "size?": "$decimal",
+            "time": "$string",
+            "status": "$enum:confirmed|unconfirmed|cancelled|complete|initiated",
+            "confirmations?": "$u32",
+            "confirmedTime?": "$string",
+            "fee?": "$decimal",
+            "txid?": "$string",
+            "notes?": "$string",
+        })
+    }
+
+    pub fn withdrawal() -> Value {
+        json!({
+            "coin": "$string",
+            "size": "$decimal",
+            "time": "$string",
+            "address?": "$string",
+            "status": "$enum:requested|processing|sent|complete|cancelled",
+            "fee?": "$decimal",
+            "txid?": "$string",
+            "tag?": "$string",
+            "notes?": "$string",
+        })
+    }
+
+    pub fn airdrop() -> Value {
+        json!({
+            "id": "$id",
+            "coin": "$string",
+            "size": "$decimal",
+            "time": "$time",
+            "status": "$enum:confirmed|unconfirmed|cancelled|complete|initiated",
+        })
+    }
+
+    pub fn coin_info() -> Value {
+        json!({
+            "id": "$string",
+            "name": "$string",
+            "collateral": "$bool",
+            "usdFungible": "$bool",
+            "isEtf": "$bool",
+            "isToken": "$bool",
+            "hidden": "$bool",
+            "canDeposit": "$bool",
+            "canWithdraw": "$bool",
+            "canConvert": "$bool",
+            "hasTag": "$bool",
+            "collateralWeight": "$decimal",
+            "fiat": "$bool",
+            "methods": ["$vec", "$string"],
+            "erc20Contract?": "$string",
+            "bep2Asset?": "$string",
+            "trc20Contract?": "$string",
+            "splMint?": "$string",
+            "creditTo?": "$string",
+            "spotMargin": "$bool",
+            "tokenizedEquity?": "$bool",
+            "indexPrice": "$decimal",
+        })
+    }
+
+    pub fn saved_address() -> Value {
+        json!({
+            "address": "$string",
+            "coin": "$string",
+            "fiat": "$bool",
+            "id": "$id",
+            "isPrimetrust": "$bool",
+            "isSwipeCard": "$bool",
+            "lastUsedAt": "$string",
+            "name": "$string",
+            "tag?": "$string",
+            "wallet": "$string",
+            "whitelisted?": "$bool",
+            "whitelistedAfter?": "$string",
+        })
+    }
+
+    /// Websocket ticker
+    pub fn ticker() -> Value {
+        json!({
+            "bid": "$decimal",
+            "ask": "$decimal",
+            "bidSize": "$decimal",
+            "askSize": "$decimal",
+            "last": "$decimal",
+            "time": "$epoch",
+        })
+    }
+
+    /// Websocket order book partial or update
+    pub fn orderbook_data() -> Value {
+        json!({
+            "action": "$enum:partial|update",
+            "bids": "$levels",
+            "asks": "$levels",
+            "checksum": "$u32",
+            "time": "$epoch",
+        })
+    }
+}
@@ -0,0 +1,232 @@
+//! Webhook notifications of account events, built with the `ws` feature, so
+//! alerting systems can consume fills, order rejections, liquidations and
+//! margin warnings without embedding Rust.
+//!
+//! Events are POSTed as JSON to every configured URL. If a webhook has a
+//! secret, the body is signed like FTX signs requests: the `X-Signature`
+//! header holds the hex encoded HMAC-SHA256 of the `X-Timestamp` header
+//! followed by the body. Deliveries that still fail after all retries are
+//! kept in a dead letter queue.
+
+use crate::rest::{Account, OrderInfo, PlaceOrder};
+use crate::ws::{self, Channel, Data, Fill, Ws};
+use futures::future::join_all;
+use futures::StreamExt;
+use hmac_sha256::HMAC;
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time;
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "camelCase")]
+pub enum WebhookEvent {
+    Fill(Fill),
+    #[serde(rename_all = "camelCase")]
+    OrderRejected {
+        market: String,
+        reason: String,
+    },
+    /// An order placed by the liquidation engine
+    Liquidation(OrderInfo),
+    #[serde(rename_all = "camelCase")]
+    MarginWarning {
+        margin_fraction: Decimal,
+        maintenance_margin_requirement: Decimal,
+    },
+}
+
+impl WebhookEvent {
+    /// Returns the event for websocket data from the `fills` and `orders`
+    /// channels, if any. Orders are reported as rejected if they were closed
+    /// with an error, e.g. trigger orders that failed to trigger.
+    pub fn from_data(data: &Data) -> Option<Self> {
+        match data {
+            Data::Fill(fill) => Some(WebhookEvent::Fill(fill.clone())),
+            Data::Order(order) if order.liquidation == Some(true) => {
+                Some(WebhookEvent::Liquidation(order.clone()))
+            }
+            Data::Order(OrderInfo {
+                market,
+                error: Some(reason),
+                ..
+            }) => Some(WebhookEvent::OrderRejected {
+                market: market.clone(),
+                reason: reason.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns a rejection event for an order that FTX refused to place.
+    pub fn rejected(order: &PlaceOrder, error: &crate::rest::Error) -> Self {
+        WebhookEvent::OrderRejected {
+            market: order.market.to_owned(),
+            reason: error.to_string(),
+        }
+    }
+
+    /// Returns a margin warning if the margin fraction of `account` is below
+    /// `threshold`.
+    pub fn margin_warning(account: &Account, threshold: Decimal) -> Option<Self> {
+        let margin_fraction = account.margin_fraction?;
+        (margin_fraction < threshold).then_some(WebhookEvent::MarginWarning {
+            margin_fraction,
+            maintenance_margin_requirement: account.maintenance_margin_requirement,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Webhook {
+    pub url: String,
+    pub secret: Option<String>,
+}
+
+impl Webhook {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            secret: None,
+        }
+    }
+
+    #[must_use]
+    pub fn secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+}
+
+/// A delivery that failed after all retries.
+#[derive(Clone, Debug)]
+pub struct DeadLetter {
+    pub url: String,
+    pub body: String,
+    /// Error of the last attempt
+    pub error: String,
+}
+
+pub struct Notifier {
+    client: Client,
+    webhooks: Vec<Webhook>,
+    retries: u32,
+    retry_delay: Duration,
+    dead_letters: Mutex<Vec<DeadLetter>>,
+}
+
+impl Notifier {
+    pub fn new(webhooks: Vec<Webhook>) -> Self {
+        Self {
+            client: Client::new(),
+            webhooks,
+            retries: 3,
+            retry_delay: Duration::from_secs(1),
+            dead_letters: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Number of retries after a failed delivery, 3 by default. The delay
+    /// between attempts starts at `retry_delay` and doubles every retry.
+    #[must_use]
+    pub fn retries(mut self, retries: u32, retry_delay: Duration) -> Self {
+        self.retries = retries;
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Delivers `event` to all webhooks concurrently. Deliveries that fail
+    /// are added to the dead letter queue.
+    pub async fn notify(&self, event: &WebhookEvent) {
+        let body = serde_json::to_string(event).expect("events serialize to JSON");
+        join_all(
+            self.webhooks
+                .iter()
+                .map(|webhook| self.deliver(webhook, body.clone())),
+        )
+        .await;
+    }
+
+    /// Removes and returns the deliveries that failed.
+    pub fn take_dead_letters(&self) -> Vec<DeadLetter> {
+        std::mem::take(&mut *self.dead_letters.lock().unwrap())
+    }
+
+    /// Attempts to deliver the dead letters again. Deliveries that fail
+    /// again are returned to the queue.
+    pub async fn redeliver_dead_letters(&self) {
+        let dead_letters = self.take_dead_letters();
+        join_all(dead_letters.into_iter().filter_map(|dead_letter| {
+            let webhook = self.webhooks.iter().find(|w| w.url == dead_letter.url)?;
+            Some(self.deliver(webhook, dead_letter.body))
+        }))
+        .await;
+    }
+
+    /// Subscribes `ws` to fills and orders and notifies of every event until
+    /// the websocket closes or fails.
+    pub async fn watch(&self, ws: &mut Ws) -> ws::Result<()> {
+        ws.subscribe(&[Channel::Fills, Channel::Orders]).await?;
+        while let Some(data) = ws.next().await {
+            if let Some(event) = WebhookEvent::from_data(&data?.1) {
+                self.notify(&event).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn deliver(&self, webhook: &Webhook, body: String) {
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            let error = match self.send(webhook, &body).await {
+                Ok(()) => return,
+                Err(error) => error,
+            };
+            if attempt == self.retries {
+                log::warn!("webhook delivery to {} failed: {}", webhook.url, error);
+                self.dead_letters.lock().unwrap().push(DeadLetter {
+                    url: webhook.url.clone(),
+                    body,
+                    error: error.to_string(),
+                });
+                return;
+            }
+            attempt += 1;
+            time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+
+    async fn send(&self, webhook: &Webhook, body: &str) -> reqwest::Result<()> {
+        let mut request = self
+            .client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = &webhook.secret {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis();
+            request = request
+                .header("X-Timestamp", timestamp.to_string())
+                .header("X-Signature", sign(timestamp, body, secret));
+        }
+        request
+            .body(body.to_owned())
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Signature sent in the `X-Signature` header.
+pub fn sign(timestamp: u128, body: &str, secret: &str) -> String {
+    hex::encode(HMAC::mac(
+        format!("{}{}", timestamp, body).as_bytes(),
+        secret.as_bytes(),
+    ))
+}
@@ -21,6 +21,9 @@ pub enum Error {
     #[error("Socket is not authenticated")]
     SocketNotAuthenticated,
 
+    #[error("unknown channel {0}")]
+    UnknownChannel(String),
+
     #[error(transparent)]
     Tungstenite(#[from] tungstenite::Error),
 
@@ -2,11 +2,14 @@
 
 mod error;
 mod model;
+mod proxy;
+mod reconnect;
 #[cfg(test)]
 mod tests;
 
 pub use error::*;
 pub use model::*;
+pub use reconnect::*;
 
 use crate::options::Options;
 use futures::{
@@ -22,7 +25,7 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::net::TcpStream;
 use tokio::time; // 1.3.0
 use tokio::time::Interval;
-use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
 
 pub struct Ws {
     channels: Vec<Channel>,
@@ -38,7 +41,25 @@ impl Ws {
     pub const ENDPOINT_US: &'static str = "wss://ftx.us/ws";
 
     pub async fn connect(options: Options) -> Result<Self> {
-        let (mut stream, _) = connect_async(options.endpoint.ws()).await?;
+        let mut endpoints = vec![options.endpoint.clone()];
+        if let Some(failover) = &options.failover {
+            endpoints.extend(failover.endpoints().iter().cloned());
+        }
+        let count = endpoints.len();
+        let mut connected = None;
+        for (index, endpoint) in endpoints.into_iter().enumerate() {
+            match proxy::connect(endpoint.ws(), &options).await {
+                Ok(stream) => {
+                    connected = Some(stream);
+                    break;
+                }
+                Err(error) if index + 1 < count => {
+                    log::warn!("failed to connect to {}: {}", endpoint.ws(), error);
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+        let mut stream = connected.expect("connected or returned the last error");
         let is_authenticated = if let (Some(key), Some(secret)) = (options.key, options.secret) {
             let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
             let sign_payload = format!("{}websocket_login", timestamp);
@@ -72,6 +93,66 @@ impl Ws {
         })
     }
 
+    /// Connects with the credentials of `options`, the same as for the REST
+    /// client, and subscribes to the `orders` and `fills` channels.
+    ///
+    /// Returns `Error::SocketNotAuthenticated` without credentials.
+    pub async fn connect_order_updates(
+        options: Options,
+    ) -> Result<impl Stream<Item = Result<OrderUpdate>> + Unpin> {
+        let mut ws = Self::connect(options).await?;
+        ws.subscribe(&[Channel::Orders, Channel::Fills]).await?;
+        Ok(ws.order_updates())
+    }
+
+    /// Updates of orders and fills, from a socket subscribed to
+    /// `Channel::Orders` and `Channel::Fills`. Other data is skipped.
+    pub fn order_updates(self) -> impl Stream<Item = Result<OrderUpdate>> + Unpin {
+        self.filter_map(|item| {
+            futures::future::ready(match item {
+                Ok((_, Data::Order(order))) => Some(Ok(OrderUpdate::Order(order))),
+                Ok((_, Data::Fill(fill))) => Some(Ok(OrderUpdate::Fill(fill))),
+                Ok(_) => None,
+                Err(error) => Some(Err(error)),
+            })
+        })
+    }
+
+    /// Subscribes to the `trades` and `ticker` channels of all `markets`
+    /// on this one connection.
+    pub async fn subscribe_markets(&mut self, markets: &[&str]) -> Result<()> {
+        let channels: Vec<Channel> = markets
+            .iter()
+            .flat_map(|market| {
+                [
+                    Channel::Trades((*market).to_owned()),
+                    Channel::Ticker((*market).to_owned()),
+                ]
+            })
+            .collect();
+        self.subscribe(&channels).await
+    }
+
+    /// Trades and tickers of the subscribed markets, one trade at a time.
+    /// Other data is skipped.
+    ///
+    /// Borrows the socket, so markets can be subscribed and unsubscribed
+    /// between reads.
+    pub fn market_updates(&mut self) -> impl Stream<Item = Result<MarketUpdate>> + Unpin + '_ {
+        self.filter_map(|item| {
+            futures::future::ready(match item {
+                Ok((market, Data::Trade(trade))) => {
+                    Some(Ok(MarketUpdate::Trade(market.unwrap_or_default(), trade)))
+                }
+                Ok((market, Data::Ticker(ticker))) => {
+                    Some(Ok(MarketUpdate::Ticker(market.unwrap_or_default(), ticker)))
+                }
+                Ok(_) => None,
+                Err(error) => Some(Err(error)),
+            })
+        })
+    }
+
     async fn ping(&mut self) -> Result<()> {
         self.stream
             .send(Message::Text(
@@ -1,4 +1,4 @@
-pub use crate::rest::{Coin, Id, MarketType, OrderInfo, Side, Symbol, Trade};
+pub use crate::rest::{Coin, Fill, Id, Liquidity, MarketType, OrderInfo, Side, Symbol, Trade};
 use chrono::{DateTime, Utc};
 use crc32fast::Hasher;
 use rust_decimal::Decimal;
@@ -9,7 +9,7 @@ use std::{collections::BTreeMap, ops::Not};
 
 use super::Error;
 
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum Channel {
     Orderbook(Symbol),
@@ -19,6 +19,23 @@ pub enum Channel {
     Orders,
 }
 
+impl std::str::FromStr for Channel {
+    type Err = Error;
+
+    /// Parses a channel, e.g. `"trades:BTC-PERP"`, `"orderbook:BTC-PERP"`
+    /// or `"fills"`, for language bindings and tools.
+    fn from_str(channel: &str) -> Result<Self, Error> {
+        match channel.split_once(':') {
+            Some(("orderbook", market)) => Ok(Channel::Orderbook(market.to_owned())),
+            Some(("trades", market)) => Ok(Channel::Trades(market.to_owned())),
+            Some(("ticker", market)) => Ok(Channel::Ticker(market.to_owned())),
+            None if channel == "fills" => Ok(Channel::Fills),
+            None if channel == "orders" => Ok(Channel::Orders),
+            _ => Err(Error::UnknownChannel(channel.to_owned())),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Response {
@@ -52,8 +69,32 @@ pub enum ResponseData {
     Order(OrderInfo),
 }
 
+/// Update of the orders of the account, received on the private `orders`
+/// and `fills` channels, see `Ws::order_updates`.
+#[derive(Clone, Debug)]
+pub enum OrderUpdate {
+    Order(OrderInfo),
+    Fill(Fill),
+}
+
+/// Public market data, received on the `trades` and `ticker` channels, see
+/// `Ws::market_updates`.
+#[derive(Clone, Debug)]
+pub enum MarketUpdate {
+    Trade(Symbol, Trade),
+    Ticker(Symbol, Ticker),
+}
+
+impl MarketUpdate {
+    pub fn market(&self) -> &Symbol {
+        match self {
+            MarketUpdate::Trade(market, _) | MarketUpdate::Ticker(market, _) => market,
+        }
+    }
+}
+
 /// Represents the data we return to the user
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum Data {
     Ticker(Ticker),
     Trade(Trade),
@@ -91,7 +132,7 @@ pub struct OrderbookData {
     pub time: DateTime<Utc>, // API returns 1621740952.5079553
 }
 
-type Checksum = u32;
+pub type Checksum = u32;
 
 #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -168,6 +209,12 @@ impl Orderbook {
     }
 
     pub fn verify_checksum(&self, checksum: &Checksum) -> bool {
+        self.checksum() == *checksum
+    }
+
+    /// CRC32 checksum of the best 100 levels, as sent by FTX with every
+    /// update.
+    pub fn checksum(&self) -> Checksum {
         let input = (0..100)
             .into_iter()
             .zip(self.bids.iter().rev().zip(self.asks.iter()))
@@ -187,10 +234,7 @@ impl Orderbook {
 
         let mut hasher = Hasher::new();
         hasher.update(input);
-        let output = hasher.finalize();
-
-        // println!("Output: {}, Checksum: {}", output, checksum);
-        output == *checksum
+        hasher.finalize()
     }
 
     /// Returns the price of the best bid
@@ -263,34 +307,6 @@ impl Orderbook {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Fill {
-    pub id: Id,
-    pub market: Option<Symbol>,
-    pub future: Option<Symbol>,
-    pub base_currency: Option<Coin>,
-    pub quote_currency: Option<Coin>,
-    pub r#type: String, // e.g. "order"
-    pub side: Side,
-    pub price: Decimal,
-    pub size: Decimal,
-    pub order_id: Option<Id>,
-    pub trade_id: Option<Id>,
-    pub time: DateTime<Utc>,
-    pub fee: Decimal,
-    pub fee_rate: Decimal,
-    pub fee_currency: Coin,
-    pub liquidity: Liquidity,
-}
-
-#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
-#[serde(rename_all = "camelCase")]
-pub enum Liquidity {
-    Maker,
-    Taker,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -301,4 +317,17 @@ mod tests {
         assert_eq!(&format_value(&dec!(0.000075)), "7.5e-05");
         assert_eq!(&format_value(&dec!(0.1)), "0.1");
     }
+
+    #[test]
+    fn parse_channel() {
+        assert_eq!(
+            "orderbook:BTC-PERP".parse::<Channel>().unwrap(),
+            Channel::Orderbook("BTC-PERP".to_owned())
+        );
+        assert_eq!("fills".parse::<Channel>().unwrap(), Channel::Fills);
+        assert!(matches!(
+            "candles:BTC-PERP".parse::<Channel>(),
+            Err(Error::UnknownChannel(_))
+        ));
+    }
 }
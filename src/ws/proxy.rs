@@ -0,0 +1,97 @@
+use crate::options::Options;
+use async_http_proxy::{http_connect_tokio, http_connect_tokio_with_basic_auth};
+use reqwest::Url;
+use std::io::{self, ErrorKind};
+use tokio::net::{lookup_host, TcpStream};
+use tokio::time;
+use tokio_socks::{tcp::Socks5Stream, TargetAddr};
+use tokio_tungstenite::{
+    client_async_tls, connect_async, tungstenite, MaybeTlsStream, WebSocketStream,
+};
+
+/// Opens a websocket to `url`, through `Options::proxy` if set and within
+/// `Options::connect_timeout`.
+pub(super) async fn connect(
+    url: &str,
+    options: &Options,
+) -> tungstenite::Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    let connecting = async {
+        match &options.proxy {
+            Some(proxy) => {
+                let stream = tunnel(proxy, url).await?;
+                Ok(client_async_tls(url, stream).await?.0)
+            }
+            None => Ok(connect_async(url).await?.0),
+        }
+    };
+    match options.connect_timeout {
+        Some(timeout) => match time::timeout(timeout, connecting).await {
+            Ok(result) => result,
+            Err(_) => Err(io::Error::from(ErrorKind::TimedOut).into()),
+        },
+        None => connecting.await,
+    }
+}
+
+/// Connects to the host of `url` through an HTTP `CONNECT` tunnel or a
+/// SOCKS5 proxy. `socks5h` proxies resolve the host themselves.
+async fn tunnel(proxy: &Url, url: &str) -> io::Result<TcpStream> {
+    let invalid = |message: String| io::Error::new(ErrorKind::InvalidInput, message);
+    let target = Url::parse(url).map_err(|e| invalid(format!("invalid url {}: {}", url, e)))?;
+    let host = target
+        .host_str()
+        .ok_or_else(|| invalid(format!("no host in {}", url)))?;
+    let port = target
+        .port_or_known_default()
+        .ok_or_else(|| invalid(format!("no port in {}", url)))?;
+    let proxy_host = proxy
+        .host_str()
+        .ok_or_else(|| invalid(format!("no host in proxy {}", proxy)))?;
+    let username = proxy.username();
+    let password = proxy.password().unwrap_or("");
+
+    match proxy.scheme() {
+        "http" => {
+            let mut stream = TcpStream::connect((proxy_host, proxy.port().unwrap_or(80))).await?;
+            let connected = if username.is_empty() {
+                http_connect_tokio(&mut stream, host, port).await
+            } else {
+                http_connect_tokio_with_basic_auth(&mut stream, host, port, username, password)
+                    .await
+            };
+            connected.map_err(|e| {
+                io::Error::new(
+                    ErrorKind::ConnectionRefused,
+                    format!("proxy refused to connect to {}: {}", host, e),
+                )
+            })?;
+            Ok(stream)
+        }
+        scheme @ ("socks5" | "socks5h") => {
+            let proxy_addr = (proxy_host, proxy.port().unwrap_or(1080));
+            let target = if scheme == "socks5" {
+                TargetAddr::Ip(
+                    lookup_host((host, port))
+                        .await?
+                        .next()
+                        .ok_or_else(|| invalid(format!("no address for {}", host)))?,
+                )
+            } else {
+                TargetAddr::Domain(host.into(), port)
+            };
+            let stream = if username.is_empty() {
+                Socks5Stream::connect(proxy_addr, target).await
+            } else {
+                Socks5Stream::connect_with_password(proxy_addr, target, username, password).await
+            };
+            let stream = stream.map_err(|e| {
+                io::Error::new(
+                    ErrorKind::ConnectionRefused,
+                    format!("proxy refused to connect to {}: {}", host, e),
+                )
+            })?;
+            Ok(stream.into_inner())
+        }
+        scheme => Err(invalid(format!("unsupported proxy scheme {}", scheme))),
+    }
+}
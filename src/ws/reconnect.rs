@@ -0,0 +1,215 @@
+use super::{Channel, Data, Error, Orderbook, Result, Symbol, Ws};
+use crate::options::Options;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite;
+
+/// Received from a `ReconnectingWs`.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum WsEvent {
+    Data(Option<Symbol>, Data),
+    /// The connection was lost. Data sent until `Resubscribed` is missed.
+    Disconnected(Error),
+    /// Connected and logged in again, and subscribed to all channels. Order
+    /// books start over from a new partial.
+    Resubscribed,
+    /// An update of the order book of the market did not apply, e.g. after
+    /// a missed message, and its channel was subscribed again. The book
+    /// starts over from a new partial.
+    OrderbookReset(Symbol),
+}
+
+/// Websocket connection that reconnects after it is lost, logs in again and
+/// subscribes to the channels it was subscribed to.
+///
+/// Keeps the order books of subscribed `orderbook` channels, verifying the
+/// checksum of every update. Updates are only returned once applied.
+pub struct ReconnectingWs {
+    options: Options,
+    ws: Option<Ws>,
+    channels: Vec<Channel>,
+    books: HashMap<Symbol, Orderbook>,
+    /// Order books to subscribe again, kept until done so that cancelling
+    /// `next_event` does not lose them
+    resyncs: Vec<Symbol>,
+    /// Failed attempts to reconnect since the connection was lost
+    attempts: u32,
+}
+
+impl ReconnectingWs {
+    const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+    const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+    /// Fails if the first connection cannot be established.
+    pub async fn connect(options: Options) -> Result<Self> {
+        let ws = Ws::connect(options.clone()).await?;
+        Ok(Self {
+            options,
+            ws: Some(ws),
+            channels: Vec::new(),
+            books: HashMap::new(),
+            resyncs: Vec::new(),
+            attempts: 0,
+        })
+    }
+
+    /// Channels subscribed to, also after reconnecting.
+    pub fn channels(&self) -> &[Channel] {
+        &self.channels
+    }
+
+    /// Order book of a market whose `orderbook` channel is subscribed.
+    /// Empty until its partial arrives.
+    pub fn orderbook(&self, market: &str) -> Option<&Orderbook> {
+        self.books.get(market)
+    }
+
+    /// See `Ws::subscribe`. While disconnected, the channels are subscribed
+    /// once reconnected.
+    pub async fn subscribe(&mut self, channels: &[Channel]) -> Result<()> {
+        if let Some(ws) = &mut self.ws {
+            ws.subscribe(channels).await?;
+        }
+        for channel in channels {
+            if let Channel::Orderbook(market) = channel {
+                self.books
+                    .insert(market.clone(), Orderbook::new(market.clone()));
+            }
+        }
+        self.channels.extend(channels.iter().cloned());
+        Ok(())
+    }
+
+    /// See `Ws::unsubscribe`.
+    pub async fn unsubscribe(&mut self, channels: &[Channel]) -> Result<()> {
+        for channel in channels {
+            if !self.channels.contains(channel) {
+                return Err(Error::NotSubscribedToThisChannel(channel.clone()));
+            }
+        }
+        if let Some(ws) = &mut self.ws {
+            ws.unsubscribe(channels).await?;
+        }
+        for channel in channels {
+            if let Channel::Orderbook(market) = channel {
+                self.books.remove(market);
+                self.resyncs.retain(|m| m != market);
+            }
+        }
+        self.channels.retain(|c| !channels.contains(c));
+        Ok(())
+    }
+
+    /// Waits for the next data or change of the connection, reconnecting
+    /// with growing delays while it is lost.
+    ///
+    /// Errors other than lost connections, e.g. messages that cannot be
+    /// parsed, are returned without reconnecting.
+    ///
+    /// Can be cancelled, e.g. in `tokio::select!`, without losing state.
+    pub async fn next_event(&mut self) -> Result<WsEvent> {
+        loop {
+            let ws = match &mut self.ws {
+                Some(ws) => ws,
+                None => {
+                    if self.reconnect().await {
+                        return Ok(WsEvent::Resubscribed);
+                    }
+                    continue;
+                }
+            };
+            if let Some(market) = self.resyncs.last().cloned() {
+                let channel = Channel::Orderbook(market.clone());
+                // Already unsubscribed if cancelled before
+                let resubscribed = match ws.unsubscribe(std::slice::from_ref(&channel)).await {
+                    Ok(()) | Err(Error::NotSubscribedToThisChannel(_)) => {
+                        ws.subscribe(&[channel]).await
+                    }
+                    Err(error) => Err(error),
+                };
+                self.resyncs.pop();
+                return match resubscribed {
+                    Ok(()) => Ok(WsEvent::OrderbookReset(market)),
+                    Err(error @ Error::Tungstenite(_)) => Ok(self.disconnected(error)),
+                    Err(error) => Err(error),
+                };
+            }
+            match ws.next().await {
+                Some(Ok((Some(market), Data::OrderbookData(data)))) => {
+                    let book = match self.books.get_mut(&market) {
+                        Some(book) => book,
+                        None => return Ok(WsEvent::Data(Some(market), Data::OrderbookData(data))),
+                    };
+                    match book.update(&data) {
+                        Ok(()) => {
+                            return Ok(WsEvent::Data(Some(market), Data::OrderbookData(data)))
+                        }
+                        // Updates still in flight from before the partial
+                        Err(Error::MissingPartial) => continue,
+                        Err(error) => {
+                            log::warn!(
+                                "order book of {} out of sync ({}), resubscribing",
+                                market,
+                                error
+                            );
+                            *book = Orderbook::new(market.clone());
+                            if !self.resyncs.contains(&market) {
+                                self.resyncs.push(market);
+                            }
+                        }
+                    }
+                }
+                Some(Ok((market, data))) => return Ok(WsEvent::Data(market, data)),
+                Some(Err(error @ Error::Tungstenite(_))) => return Ok(self.disconnected(error)),
+                Some(Err(error)) => return Err(error),
+                None => {
+                    self.ws = None;
+                    return Ok(WsEvent::Disconnected(
+                        tungstenite::Error::ConnectionClosed.into(),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn disconnected(&mut self, error: Error) -> WsEvent {
+        log::warn!("websocket disconnected: {}", error);
+        self.ws = None;
+        WsEvent::Disconnected(error)
+    }
+
+    /// Waits out the backoff and tries to connect and subscribe once.
+    async fn reconnect(&mut self) -> bool {
+        let backoff = Self::INITIAL_BACKOFF
+            .checked_mul(1 << self.attempts.min(16))
+            .map_or(Self::MAX_BACKOFF, |backoff| backoff.min(Self::MAX_BACKOFF));
+        tokio::time::sleep(backoff).await;
+
+        let connected = match Ws::connect(self.options.clone()).await {
+            Ok(mut ws) => ws.subscribe(&self.channels).await.map(|()| ws),
+            Err(error) => Err(error),
+        };
+        match connected {
+            Ok(ws) => {
+                self.ws = Some(ws);
+                self.attempts = 0;
+                self.resyncs.clear();
+                for (market, book) in &mut self.books {
+                    *book = Orderbook::new(market.clone());
+                }
+                true
+            }
+            Err(error) => {
+                self.attempts += 1;
+                log::warn!(
+                    "failed to reconnect websocket (attempt {}): {}",
+                    self.attempts,
+                    error
+                );
+                false
+            }
+        }
+    }
+}
@@ -345,3 +345,447 @@ async fn orders() {
 
     ws.unsubscribe_all().await.expect("Unsubscribe failed");
 }
+
+#[test]
+fn webhook_events() {
+    use crate::webhook::{sign, WebhookEvent};
+
+    let order = |liquidation: bool, error: Option<&str>| -> Data {
+        Data::Order(
+            serde_json::from_value(json!({
+                "id": 1,
+                "market": "BTC-PERP",
+                "future": "BTC-PERP",
+                "type": "market",
+                "side": "sell",
+                "price": null,
+                "size": 0.5,
+                "status": "closed",
+                "filledSize": 0.5,
+                "remainingSize": 0.0,
+                "avgFillPrice": 40000.0,
+                "liquidation": liquidation,
+                "createdAt": "2022-01-01T00:00:00.000000+00:00",
+                "clientId": null,
+                "error": error,
+            }))
+            .unwrap(),
+        )
+    };
+
+    assert!(WebhookEvent::from_data(&order(false, None)).is_none());
+    assert!(matches!(
+        WebhookEvent::from_data(&order(true, None)),
+        Some(WebhookEvent::Liquidation(_))
+    ));
+    let rejected = WebhookEvent::from_data(&order(false, Some("Trigger price too low")));
+    assert_eq!(
+        serde_json::to_value(rejected.unwrap()).unwrap(),
+        json!({
+            "event": "orderRejected",
+            "data": {"market": "BTC-PERP", "reason": "Trigger price too low"},
+        })
+    );
+
+    assert_eq!(
+        sign(1_640_995_200_000, "{}", "secret"),
+        hex::encode(HMAC::mac(b"1640995200000{}", b"secret"))
+    );
+}
+
+#[tokio::test]
+async fn reconnect_and_resubscribe() {
+    use crate::options::Endpoint;
+    use tokio_tungstenite::accept_async;
+
+    // Confirms every subscription and sends one trade, closing the first
+    // connection afterwards
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        for id in 1.. {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+            let subscribe = ws.next().await.unwrap().unwrap().into_text().unwrap();
+            assert!(subscribe.contains(r#""op":"subscribe""#));
+            for message in [
+                json!({"type": "subscribed", "channel": "trades", "market": "BTC-PERP"}),
+                json!({"type": "update", "channel": "trades", "market": "BTC-PERP", "data": [{
+                    "id": id, "price": 40000, "size": 1, "side": "buy", "liquidation": false,
+                    "time": "2022-01-01T00:00:00.000000+00:00",
+                }]}),
+            ] {
+                ws.send(Message::Text(message.to_string())).await.unwrap();
+            }
+            if id == 1 {
+                ws.close(None).await.unwrap();
+            } else {
+                // Keeps the connection open
+                while ws.next().await.is_some() {}
+            }
+        }
+    });
+
+    let mut ws = ReconnectingWs::connect(Options {
+        endpoint: Endpoint::Custom {
+            rest: format!("http://{}/api", addr),
+            ws: format!("ws://{}/ws", addr),
+        },
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let channel = Channel::Trades("BTC-PERP".to_owned());
+    ws.subscribe(std::slice::from_ref(&channel)).await.unwrap();
+
+    let trade_id = |event| match event {
+        WsEvent::Data(Some(market), Data::Trade(trade)) if market == "BTC-PERP" => trade.id,
+        event => panic!("unexpected event {:?}", event),
+    };
+    assert_eq!(trade_id(ws.next_event().await.unwrap()), 1);
+    assert!(matches!(
+        ws.next_event().await.unwrap(),
+        WsEvent::Disconnected(_)
+    ));
+    assert!(matches!(
+        ws.next_event().await.unwrap(),
+        WsEvent::Resubscribed
+    ));
+    assert_eq!(ws.channels(), &[channel]);
+    assert_eq!(trade_id(ws.next_event().await.unwrap()), 2);
+}
+
+#[tokio::test]
+async fn resubscribe_orderbook_out_of_sync() {
+    use crate::options::Endpoint;
+    use rust_decimal::Decimal;
+    use tokio_tungstenite::accept_async;
+
+    let checksum = |bid: (Decimal, Decimal), ask: (Decimal, Decimal)| {
+        let mut book = Orderbook::new("BTC-PERP".to_owned());
+        book.bids.insert(bid.0, bid.1);
+        book.asks.insert(ask.0, ask.1);
+        book.checksum()
+    };
+    let partial = move |bid: (Decimal, Decimal), ask: (Decimal, Decimal)| {
+        json!({"type": "partial", "channel": "orderbook", "market": "BTC-PERP", "data": {
+            "action": "partial", "bids": [bid], "asks": [ask],
+            "checksum": checksum(bid, ask), "time": 1640995200.0,
+        }})
+    };
+
+    // Sends an update with a wrong checksum after the partial, and a new
+    // partial once subscribed again
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = accept_async(stream).await.unwrap();
+        for (i, op) in ["subscribe", "unsubscribe", "subscribe"].iter().enumerate() {
+            let request = loop {
+                let request = ws.next().await.unwrap().unwrap().into_text().unwrap();
+                if !request.contains(r#""op":"ping""#) {
+                    break request;
+                }
+            };
+            assert!(request.contains(&format!(r#""op":"{}""#, op)));
+            let mut messages = vec![json!({
+                "type": format!("{}d", op), "channel": "orderbook", "market": "BTC-PERP",
+            })];
+            match i {
+                0 => messages.extend([
+                    partial((dec!(100), dec!(1)), (dec!(101), dec!(1))),
+                    json!({"type": "update", "channel": "orderbook", "market": "BTC-PERP",
+                    "data": {
+                        "action": "update", "bids": [[dec!(100), dec!(2)]], "asks": [],
+                        "checksum": 0, "time": 1640995201.0,
+                    }}),
+                ]),
+                2 => messages.push(partial((dec!(99), dec!(2)), (dec!(102), dec!(2)))),
+                _ => {}
+            }
+            for message in messages {
+                ws.send(Message::Text(message.to_string())).await.unwrap();
+            }
+        }
+        while ws.next().await.is_some() {}
+    });
+
+    let mut ws = ReconnectingWs::connect(Options {
+        endpoint: Endpoint::Custom {
+            rest: format!("http://{}/api", addr),
+            ws: format!("ws://{}/ws", addr),
+        },
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    ws.subscribe(&[Channel::Orderbook("BTC-PERP".to_owned())])
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        ws.next_event().await.unwrap(),
+        WsEvent::Data(_, Data::OrderbookData(_))
+    ));
+    assert_eq!(
+        ws.orderbook("BTC-PERP").unwrap().best_bid(),
+        Some((&dec!(100), &dec!(1)))
+    );
+    assert!(matches!(
+        ws.next_event().await.unwrap(),
+        WsEvent::OrderbookReset(market) if market == "BTC-PERP"
+    ));
+    assert!(!ws.orderbook("BTC-PERP").unwrap().is_initialized());
+    assert!(matches!(
+        ws.next_event().await.unwrap(),
+        WsEvent::Data(_, Data::OrderbookData(_))
+    ));
+    assert_eq!(
+        ws.orderbook("BTC-PERP").unwrap().best_bid(),
+        Some((&dec!(99), &dec!(2)))
+    );
+}
+
+#[tokio::test]
+async fn connect_through_proxy() {
+    use crate::options::Endpoint;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_tungstenite::accept_async;
+
+    // Accepts the tunnel and serves the websocket itself
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        while !request.ends_with(b"\r\n\r\n") {
+            request.push(stream.read_u8().await.unwrap());
+        }
+        let request = String::from_utf8(request).unwrap();
+        assert!(request.starts_with("CONNECT ftx.invalid:80 HTTP/1.1\r\n"));
+        assert!(request.contains("Proxy-Authorization: Basic dXNlcjpwYXNz\r\n"));
+        stream
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+            .await
+            .unwrap();
+
+        let mut ws = accept_async(stream).await.unwrap();
+        let subscribe = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        assert!(subscribe.contains(r#""op":"subscribe""#));
+        let subscribed = json!({"type": "subscribed", "channel": "trades", "market": "BTC-PERP"});
+        ws.send(Message::Text(subscribed.to_string()))
+            .await
+            .unwrap();
+    });
+
+    let options = Options::default()
+        .endpoint(Endpoint::custom(
+            "http://ftx.invalid/api",
+            "ws://ftx.invalid/ws",
+        ))
+        .proxy(&format!("http://user:pass@{}", addr))
+        .unwrap()
+        .connect_timeout(Duration::from_secs(5));
+    let mut ws = Ws::connect(options).await.unwrap();
+    ws.subscribe(&[Channel::Trades("BTC-PERP".to_owned())])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn connect_through_socks_proxy() {
+    use crate::options::Endpoint;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_tungstenite::accept_async;
+
+    // Accepts the SOCKS5 connection and serves the websocket itself
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut greeting = [0; 2];
+        stream.read_exact(&mut greeting).await.unwrap();
+        let mut methods = vec![0; greeting[1] as usize];
+        stream.read_exact(&mut methods).await.unwrap();
+        assert!(methods.contains(&0));
+        stream.write_all(&[5, 0]).await.unwrap();
+
+        // The host name is resolved by the proxy
+        let mut request = [0; 5];
+        stream.read_exact(&mut request).await.unwrap();
+        assert_eq!(request[..4], [5, 1, 0, 3]);
+        let mut target = vec![0; request[4] as usize + 2];
+        stream.read_exact(&mut target).await.unwrap();
+        assert_eq!(&target[..target.len() - 2], b"ftx.invalid");
+        stream
+            .write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0])
+            .await
+            .unwrap();
+
+        let mut ws = accept_async(stream).await.unwrap();
+        let subscribe = ws.next().await.unwrap().unwrap().into_text().unwrap();
+        assert!(subscribe.contains(r#""op":"subscribe""#));
+        let subscribed = json!({"type": "subscribed", "channel": "trades", "market": "BTC-PERP"});
+        ws.send(Message::Text(subscribed.to_string()))
+            .await
+            .unwrap();
+    });
+
+    let options = Options::default()
+        .endpoint(Endpoint::custom(
+            "http://ftx.invalid/api",
+            "ws://ftx.invalid/ws",
+        ))
+        .proxy(&format!("socks5h://{}", addr))
+        .unwrap()
+        .connect_timeout(Duration::from_secs(5));
+    let mut ws = Ws::connect(options).await.unwrap();
+    ws.subscribe(&[Channel::Trades("BTC-PERP".to_owned())])
+        .await
+        .unwrap();
+}
+
+#[cfg(feature = "mock-server")]
+#[tokio::test]
+async fn order_updates() {
+    use crate::mock::MockServer;
+    use crate::rest::PlaceOrder;
+
+    let handle = MockServer::new("key", "secret")
+        .with_default_markets()
+        .start()
+        .await
+        .unwrap();
+    let mut updates = Ws::connect_order_updates(handle.options()).await.unwrap();
+    let order = Rest::new(handle.options())
+        .request(PlaceOrder::market("BTC-PERP", Side::Buy, dec!(0.1)))
+        .await
+        .unwrap();
+
+    let (mut order_update, mut fill) = (None, None);
+    while order_update.is_none() || fill.is_none() {
+        match time::timeout(Duration::from_secs(5), updates.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap()
+        {
+            OrderUpdate::Order(update) => order_update = Some(update),
+            OrderUpdate::Fill(update) => fill = Some(update),
+        }
+    }
+    assert_eq!(order_update.unwrap().id, order.id);
+    assert_eq!(fill.unwrap().order_id, Some(order.id));
+
+    // The private channels need credentials
+    let unauthenticated = Options {
+        key: None,
+        secret: None,
+        ..handle.options()
+    };
+    assert!(matches!(
+        Ws::connect_order_updates(unauthenticated).await,
+        Err(Error::SocketNotAuthenticated)
+    ));
+}
+
+#[cfg(feature = "mock-server")]
+#[tokio::test]
+async fn market_updates() {
+    use crate::mock::MockServer;
+    use crate::rest::PlaceOrder;
+
+    let handle = MockServer::new("key", "secret")
+        .with_default_markets()
+        .start()
+        .await
+        .unwrap();
+    let mut ws = Ws::connect(handle.options()).await.unwrap();
+    ws.subscribe_markets(&["BTC-PERP", "ETH-PERP"])
+        .await
+        .unwrap();
+    let rest = Rest::new(handle.options());
+    rest.request(PlaceOrder::market("BTC-PERP", Side::Buy, dec!(0.1)))
+        .await
+        .unwrap();
+
+    let (mut trade, mut ticker) = (None, None);
+    {
+        let mut updates = ws.market_updates();
+        while trade.is_none() || ticker.is_none() {
+            let update = time::timeout(Duration::from_secs(5), updates.next())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            assert_eq!(update.market(), "BTC-PERP");
+            match update {
+                MarketUpdate::Trade(_, update) => trade = Some(update),
+                MarketUpdate::Ticker(_, update) => ticker = Some(update),
+            }
+        }
+    }
+    let trade = trade.unwrap();
+    assert_eq!((trade.side, trade.size), (Side::Buy, dec!(0.1)));
+    assert!(!trade.liquidation);
+    assert!(ticker.unwrap().ask >= trade.price);
+
+    // Markets are managed on the same connection
+    ws.unsubscribe(&[
+        Channel::Trades("BTC-PERP".to_owned()),
+        Channel::Ticker("BTC-PERP".to_owned()),
+    ])
+    .await
+    .unwrap();
+    rest.request(PlaceOrder::market("ETH-PERP", Side::Sell, dec!(1)))
+        .await
+        .unwrap();
+    let update = time::timeout(Duration::from_secs(5), ws.market_updates().next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(update.market(), "ETH-PERP");
+}
+
+#[cfg(feature = "mock-server")]
+#[tokio::test]
+async fn local_order_book() {
+    use crate::book::{Level, OrderBook};
+    use crate::mock::MockServer;
+    use crate::rest::PlaceOrder;
+
+    let handle = MockServer::new("key", "secret")
+        .with_default_markets()
+        .start()
+        .await
+        .unwrap();
+    let mut book = OrderBook::subscribe(handle.options(), "BTC-PERP")
+        .await
+        .unwrap();
+    assert_eq!(
+        book.best_ask(),
+        Some(Level {
+            price: dec!(40001),
+            size: dec!(0.5)
+        })
+    );
+    let bid = book.best_bid().unwrap();
+
+    Rest::new(handle.options())
+        .request(PlaceOrder::market("BTC-PERP", Side::Buy, dec!(1)))
+        .await
+        .unwrap();
+    book.next_update().await.unwrap();
+    let depth = book.depth(1);
+    assert_eq!(
+        depth.asks,
+        vec![Level {
+            price: dec!(40002),
+            size: dec!(0.5)
+        }]
+    );
+    assert_eq!(depth.bids, vec![bid]);
+    assert!(depth.time.is_some());
+}